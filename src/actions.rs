@@ -1,7 +1,13 @@
 // Recall - 文件操作和同步动作定义
 // 定义了备份过程中的各种操作类型和相关数据结构
 
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// [`BackupStats::largest`] 保留的文件数量
+pub const LARGEST_FILES: usize = 10;
 
 /// 同步动作枚举
 /// 定义了在备份过程中可以对文件执行的各种操作
@@ -16,6 +22,10 @@ pub enum SyncAction {
     /// 创建硬链接（文件未变化，使用 NTFS 硬链接节省空间）
     Link(PathBuf),
 
+    /// 从上一次备份克隆（reflink）已修改的文件，再写入变化的数据块
+    /// 仅在目标文件系统支持块克隆时使用（ReFS、Btrfs、XFS、APFS）
+    Clone(PathBuf),
+
     /// 创建符号链接（源文件是符号链接）
     MakeSymlink(PathBuf),
 
@@ -24,6 +34,25 @@ pub enum SyncAction {
 
     /// 跳过（不需要处理）
     Skip,
+
+    /// 续传时目标中已存在完整文件，无需处理
+    Resumed,
+}
+
+impl SyncAction {
+    /// 动作名称，用于进度事件等机器可读输出
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyncAction::CopyNew => "copy_new",
+            SyncAction::CopyModified => "copy_modified",
+            SyncAction::Link(_) => "link",
+            SyncAction::Clone(_) => "clone",
+            SyncAction::MakeSymlink(_) => "symlink",
+            SyncAction::CreateDir => "create_dir",
+            SyncAction::Skip => "skip",
+            SyncAction::Resumed => "resumed",
+        }
+    }
 }
 
 /// 文件任务结构体
@@ -41,6 +70,9 @@ pub struct FileTask {
 
     /// 上一次备份的路径（用于增量备份和硬链接）
     pub old_path: Option<PathBuf>,
+
+    /// 源文件大小（目录为 0）
+    pub size: u64,
 }
 
 impl FileTask {
@@ -51,24 +83,27 @@ impl FileTask {
     /// * `src_path` - 源文件的完整路径
     /// * `dest_path` - 目标备份路径
     /// * `old_path` - 上一次备份的路径（可选）
+    /// * `size` - 源文件大小
     pub fn new(
         rel_path: PathBuf,
         src_path: PathBuf,
         dest_path: PathBuf,
         old_path: Option<PathBuf>,
+        size: u64,
     ) -> Self {
         Self {
             rel_path,
             src_path,
             dest_path,
             old_path,
+            size,
         }
     }
 }
 
 /// 备份统计信息结构体
 /// 记录备份操作的各项统计数据
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct BackupStats {
     /// 处理的文件总数
     pub total_files: u64,
@@ -82,14 +117,157 @@ pub struct BackupStats {
     /// 硬链接的文件数量
     pub linked: u64,
 
+    /// 硬链接的文件的总大小（逻辑大小，不占用新空间）
+    pub bytes_linked: u64,
+
+    /// 通过块克隆更新的已修改文件数量
+    pub cloned: u64,
+
+    /// 无法创建硬链接而改为复制的文件数量
+    pub link_fallbacks: u64,
+
     /// 跳过的文件数量
     pub skipped: u64,
 
-    /// 失败的文件数量
+    /// 创建的目录数量
+    pub dirs_created: u64,
+
+    /// 重新创建的符号链接数量
+    pub symlinks: u64,
+
+    /// 被排除模式跳过的条目数量（排除的目录只计一次，不包括其中的内容）
+    pub excluded: u64,
+
+    /// 续传时已完整存在而保留的文件数量
+    pub resumed: u64,
+
+    /// 失败的文件数量（启用 `fail_on_locked` 时包括被锁定的文件）
     pub failed: u64,
 
+    /// 被其他进程锁定而无法读取的文件数量（使用 VSS 重试可备份）
+    pub locked: u64,
+
+    /// 扫描之后、处理之前被删除的源文件数量（繁忙的源目录中很常见，不计为失败）
+    pub vanished: u64,
+
+    /// 被锁定文件的相对路径
+    pub locked_files: Vec<PathBuf>,
+
+    /// 失败的文件及原因（不包括被锁定的文件）
+    pub errors: Vec<FileError>,
+
+    /// 写入数据最多的文件（复制、克隆和链接降级，按写入字节数从大到小，最多 [`LARGEST_FILES`] 个）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub largest: Vec<FileSize>,
+
+    /// 每个目录（包括所有上级目录，不包括源目录本身）中写入和硬链接的字节数，见 [`BackupStats::top_directories`]
+    #[serde(skip)]
+    pub directories: HashMap<PathBuf, DirBytes>,
+
+    /// 按扩展名（小写，没有扩展名时为空字符串）汇总的写入文件数和字节数，见 [`BackupStats::top_extensions`]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, ExtensionBytes>,
+
     /// 传输的总字节数
     pub bytes_copied: u64,
+
+    /// 平均传输速度（字节/秒，按执行阶段的实际耗时计算）
+    pub bytes_per_sec: u64,
+
+    /// 平均处理速度（文件/秒，按执行阶段的实际耗时计算）
+    pub files_per_sec: f64,
+
+    /// 执行阶段的实际耗时（秒，与扫描同时进行）
+    pub execute_secs: f64,
+
+    /// 各类操作的累计耗时
+    pub durations: ActionDurations,
+}
+
+/// 失败的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct FileError {
+    /// 相对于源目录的路径
+    pub path: PathBuf,
+
+    /// 失败原因
+    pub error: String,
+}
+
+/// 文件及其写入的字节数
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSize {
+    /// 相对于源目录的路径
+    pub path: PathBuf,
+
+    /// 写入的字节数
+    pub bytes: u64,
+}
+
+/// 目录中写入和硬链接的字节数
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirBytes {
+    /// 复制、克隆和链接降级写入的字节数
+    pub copied: u64,
+
+    /// 硬链接的文件的总大小
+    pub linked: u64,
+}
+
+/// 某个扩展名的文件写入的数量和字节数
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ExtensionBytes {
+    /// 复制、克隆和链接降级的文件数量
+    pub files: u64,
+
+    /// 写入的字节数
+    pub bytes: u64,
+}
+
+/// 各类操作的累计耗时（秒）
+///
+/// 除扫描外为所有工作线程耗时之和，多线程执行时可能超过实际耗时。
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ActionDurations {
+    /// 扫描源目录的实际耗时（与执行同时进行，包括等待执行器接收任务的时间）
+    pub scan: f64,
+
+    /// 启用内容检查时比较哈希（扫描阶段，所有哈希线程之和）
+    pub hash: f64,
+
+    /// 复制新文件和已修改文件（包括无法链接而改为复制的文件）
+    pub copy: f64,
+
+    /// 块克隆已修改文件
+    pub clone: f64,
+
+    /// 创建硬链接
+    pub link: f64,
+
+    /// 创建符号链接
+    pub symlink: f64,
+
+    /// 创建目录
+    pub create_dir: f64,
+}
+
+impl ActionDurations {
+    /// 累加一次操作的耗时
+    ///
+    /// # 参数
+    /// * `action` - 执行的同步动作（跳过和续传的文件不计入）
+    /// * `elapsed` - 耗时
+    pub fn add(&mut self, action: &SyncAction, elapsed: Duration) {
+        let slot = match action {
+            SyncAction::CopyNew | SyncAction::CopyModified => &mut self.copy,
+            SyncAction::Clone(_) => &mut self.clone,
+            SyncAction::Link(_) => &mut self.link,
+            SyncAction::MakeSymlink(_) => &mut self.symlink,
+            SyncAction::CreateDir => &mut self.create_dir,
+            SyncAction::Skip | SyncAction::Resumed => return,
+        };
+        *slot += elapsed.as_secs_f64();
+    }
 }
 
 impl BackupStats {
@@ -97,4 +275,154 @@ impl BackupStats {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// 硬链接节省的空间比例，见 [`saved_percent`]
+    pub fn saved_percent(&self) -> Option<f64> {
+        saved_percent(self.bytes_linked, self.bytes_copied)
+    }
+
+    /// 记录一个写入了数据的文件，只保留写入最多的 [`LARGEST_FILES`] 个
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::actions::{BackupStats, LARGEST_FILES};
+    /// use std::path::Path;
+    ///
+    /// let mut stats = BackupStats::new();
+    /// for size in 0..20 {
+    ///     stats.record_written(Path::new(&format!("f{}", size)), size);
+    /// }
+    /// assert_eq!(stats.largest.len(), LARGEST_FILES);
+    /// assert_eq!(stats.largest[0].bytes, 19);
+    /// ```
+    pub fn record_written(&mut self, path: &Path, bytes: u64) {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let entry = self.extensions.entry(extension).or_default();
+        entry.files += 1;
+        entry.bytes += bytes;
+        if bytes == 0 {
+            return;
+        }
+        for dir in parent_dirs(path) {
+            self.directories.entry(dir.to_path_buf()).or_default().copied += bytes;
+        }
+        if self.largest.len() == LARGEST_FILES && self.largest[LARGEST_FILES - 1].bytes >= bytes {
+            return;
+        }
+        let pos = self.largest.partition_point(|f| f.bytes >= bytes);
+        self.largest.insert(pos, FileSize { path: path.to_path_buf(), bytes });
+        self.largest.truncate(LARGEST_FILES);
+    }
+
+    /// 记录一个硬链接的文件，计入其所在的各级目录
+    pub fn record_linked(&mut self, path: &Path, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        for dir in parent_dirs(path) {
+            self.directories.entry(dir.to_path_buf()).or_default().linked += bytes;
+        }
+    }
+
+    /// 写入字节数最多的扩展名
+    ///
+    /// # 参数
+    /// * `count` - 最多返回的扩展名数量
+    ///
+    /// # 返回
+    /// 扩展名（没有扩展名时为空字符串）及其文件数和字节数，按字节数从大到小排列
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::actions::BackupStats;
+    /// use std::path::Path;
+    ///
+    /// let mut stats = BackupStats::new();
+    /// stats.record_written(Path::new("photos/a.JPG"), 300);
+    /// stats.record_written(Path::new("photos/b.jpg"), 200);
+    /// stats.record_written(Path::new("mail/archive.pst"), 400);
+    /// stats.record_written(Path::new("Makefile"), 10);
+    ///
+    /// let top = stats.top_extensions(2);
+    /// assert_eq!(top[0].0, "jpg");
+    /// assert_eq!((top[0].1.files, top[0].1.bytes), (2, 500));
+    /// assert_eq!(top[1].0, "pst");
+    /// assert_eq!(stats.extensions[""].bytes, 10);
+    /// ```
+    pub fn top_extensions(&self, count: usize) -> Vec<(&str, ExtensionBytes)> {
+        let mut extensions: Vec<(&str, ExtensionBytes)> =
+            self.extensions.iter().map(|(ext, bytes)| (ext.as_str(), *bytes)).collect();
+        extensions.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+        extensions.truncate(count);
+        extensions
+    }
+
+    /// 写入（或硬链接）字节数最多的目录
+    ///
+    /// 每个文件的字节数计入它的所有上级目录，因此结果中可能同时出现一个目录和它的子目录。
+    ///
+    /// # 参数
+    /// * `count` - 最多返回的目录数量
+    /// * `linked` - 按硬链接的字节数排序，否则按写入的字节数
+    ///
+    /// # 返回
+    /// 目录（相对于源目录）和字节数，从大到小排列，不包括字节数为 0 的目录
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::actions::BackupStats;
+    /// use std::path::Path;
+    ///
+    /// let mut stats = BackupStats::new();
+    /// stats.record_written(Path::new("photos/2024/a.jpg"), 300);
+    /// stats.record_written(Path::new("docs/b.txt"), 100);
+    /// stats.record_linked(Path::new("docs/c.txt"), 50);
+    ///
+    /// let top = stats.top_directories(2, false);
+    /// assert_eq!(top, vec![(Path::new("photos"), 300), (Path::new("photos/2024"), 300)]);
+    /// assert_eq!(stats.top_directories(5, true), vec![(Path::new("docs"), 50)]);
+    /// ```
+    pub fn top_directories(&self, count: usize, linked: bool) -> Vec<(&Path, u64)> {
+        let mut dirs: Vec<(&Path, u64)> = self
+            .directories
+            .iter()
+            .map(|(dir, bytes)| (dir.as_path(), if linked { bytes.linked } else { bytes.copied }))
+            .filter(|(_, bytes)| *bytes > 0)
+            .collect();
+        dirs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        dirs.truncate(count);
+        dirs
+    }
+}
+
+/// 硬链接节省的空间比例
+///
+/// 硬链接的文件在快照中完整可见（逻辑大小），但不占用新空间；节省比例为硬链接的字节数
+/// 占快照中全部新写入和硬链接字节数的百分比。
+///
+/// # 参数
+/// * `bytes_linked` - 硬链接的文件的总大小
+/// * `bytes_written` - 实际写入的字节数
+///
+/// # 返回
+/// * `Some(f64)` - 节省的百分比（0 到 100，向下取整到 0.1，写入了数据时不会显示为 100%）
+/// * `None` - 没有写入或链接任何数据
+///
+/// # 示例
+/// ```
+/// use recall::actions::saved_percent;
+/// assert_eq!(saved_percent(98, 2), Some(98.0));
+/// assert_eq!(saved_percent(0, 0), None);
+/// ```
+pub fn saved_percent(bytes_linked: u64, bytes_written: u64) -> Option<f64> {
+    let total = bytes_linked + bytes_written;
+    (total > 0).then(|| (bytes_linked as f64 * 1000.0 / total as f64).floor() / 10.0)
+}
+
+/// 文件的各级上级目录（不包括空的根路径）
+fn parent_dirs(path: &Path) -> impl Iterator<Item = &Path> {
+    path.ancestors().skip(1).filter(|dir| !dir.as_os_str().is_empty())
 }
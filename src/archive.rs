@@ -0,0 +1,359 @@
+// Recall - 快照归档模块
+// 将旧快照压缩为单个 tar.zst 归档（包含摘要和清单）并删除原目录，长期保留时不再占用大量硬链接；
+// list / restore 像普通快照一样读取归档中的快照
+
+use crate::backend::{RemoteSnapshots, RestoreStats};
+use crate::copy::{copy_file, CopyOptions};
+use crate::events::EventSink;
+use crate::export::{write_archive, ArchiveFormat, ExportStats};
+use crate::manifest::Manifest;
+use crate::naming::SnapshotNaming;
+use crate::replicate::remove_tree;
+use crate::scanner::current_snapshot;
+use crate::summary::{BackupSummary, MANIFEST_FILE, SUMMARY_FILE};
+use crate::utils::to_verbatim_path;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use filetime::FileTime;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+use xxhash_rust::xxh3::Xxh3;
+
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+/// 归档文件的扩展名（`<快照名称>.tar.zst`，位于备份目标目录中）
+pub const ARCHIVE_SUFFIX: &str = ".tar.zst";
+
+/// 列出备份目标目录中的归档快照，按名称中的时间排序（最旧的在前）
+///
+/// # 参数
+/// * `destination` - 备份目标根目录
+/// * `naming` - 快照命名规则
+///
+/// # 返回
+/// * `Ok(Vec<(NaiveDateTime, PathBuf)>)` - 快照时间和归档文件路径
+/// * `Err(anyhow::Error)` - 读取目录失败
+pub fn find_archives(destination: &Path, naming: &SnapshotNaming) -> Result<Vec<(NaiveDateTime, PathBuf)>> {
+    if !destination.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut archives: Vec<(NaiveDateTime, PathBuf)> = fs::read_dir(destination)
+        .context("Cannot read destination directory")?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.strip_suffix(ARCHIVE_SUFFIX)?;
+            Some((naming.parse(name)?, path))
+        })
+        .collect();
+
+    archives.sort();
+    Ok(archives)
+}
+
+/// 归档文件对应的快照名称
+pub fn archive_name(archive: &Path) -> String {
+    let name = archive.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    name.strip_suffix(ARCHIVE_SUFFIX).unwrap_or(&name).to_string()
+}
+
+/// 将快照压缩为归档并删除原目录
+///
+/// 快照没有清单时先生成清单；归档写入完成后逐个文件与清单核对，
+/// 核对通过才删除原目录，否则删除归档并返回错误。
+///
+/// # 参数
+/// * `snapshot` - 快照目录
+/// * `key` - 生成清单时使用的签名密钥
+///
+/// # 返回
+/// * `Ok((PathBuf, ExportStats))` - 归档文件路径和写入统计
+/// * `Err(anyhow::Error)` - 写入、核对或删除失败
+pub fn archive_snapshot(snapshot: &Path, key: Option<&[u8]>) -> Result<(PathBuf, ExportStats)> {
+    let name = snapshot.file_name().context("Invalid snapshot path")?.to_string_lossy().to_string();
+    let output = snapshot.with_file_name(format!("{}{}", name, ARCHIVE_SUFFIX));
+    if output.exists() {
+        anyhow::bail!("Archive {:?} already exists", output);
+    }
+
+    let manifest = match Manifest::load(snapshot)? {
+        Some(manifest) => manifest,
+        None => {
+            let manifest = Manifest::build(snapshot, &mut HashMap::new())?;
+            manifest.save(snapshot, key)?;
+            manifest
+        }
+    };
+
+    let stats = write_archive(snapshot, &output, ArchiveFormat::TarZst, true)?;
+    if let Err(e) = verify_archive(&output, &manifest) {
+        fs::remove_file(&output).ok();
+        return Err(e.context(format!("Archive of {} failed verification, the snapshot was kept", name)));
+    }
+
+    remove_tree(snapshot)?;
+    Ok((output, stats))
+}
+
+/// 打开归档，返回 tar 读取器
+fn open_archive(archive: &Path) -> Result<tar::Archive<zstd::Decoder<'static, BufReader<File>>>> {
+    let file = File::open(archive).with_context(|| format!("Failed to open {:?}", archive))?;
+    Ok(tar::Archive::new(zstd::Decoder::new(file)?))
+}
+
+/// 去掉归档条目路径的顶层目录（快照名称），拒绝包含 `..` 或绝对路径的条目
+fn entry_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    components.next()?;
+    let rel: PathBuf = components.collect();
+    rel.components().all(|c| matches!(c, Component::Normal(_))).then_some(rel)
+}
+
+/// 是否为快照根部的摘要或清单
+fn is_metadata(rel: &Path) -> bool {
+    rel == Path::new(SUMMARY_FILE) || rel == Path::new(MANIFEST_FILE)
+}
+
+/// 读取归档开头的摘要和清单（归档时作为最前面的条目写入，无需解压整个归档）
+///
+/// # 返回
+/// * `Ok((Option<BackupSummary>, Option<Manifest>))` - 摘要和清单（不存在时为 `None`）
+/// * `Err(anyhow::Error)` - 归档无法读取
+pub fn read_metadata(archive: &Path) -> Result<(Option<BackupSummary>, Option<Manifest>)> {
+    let mut reader = open_archive(archive)?;
+    let (mut summary, mut manifest) = (None, None);
+
+    for entry in reader.entries()? {
+        let mut entry = entry?;
+        let Some(rel) = entry_path(&entry.path()?) else { continue };
+        if !is_metadata(&rel) {
+            break;
+        }
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        if rel == Path::new(SUMMARY_FILE) {
+            summary = Some(toml::from_str(&content).with_context(|| format!("Invalid summary in {:?}", archive))?);
+        } else {
+            manifest = Some(Manifest::parse(&content).with_context(|| format!("Invalid manifest in {:?}", archive))?);
+        }
+    }
+
+    Ok((summary, manifest))
+}
+
+/// 逐个文件核对归档内容与清单（大小和 XXH3 哈希值）
+fn verify_archive(archive: &Path, manifest: &Manifest) -> Result<()> {
+    let mut expected: HashMap<&str, (u64, u64)> =
+        manifest.entries.iter().map(|e| (e.path.as_str(), (e.size, e.hash))).collect();
+
+    let mut reader = open_archive(archive)?;
+    for entry in reader.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(rel) = entry_path(&entry.path()?) else { continue };
+        if is_metadata(&rel) {
+            continue;
+        }
+        let path = rel.to_string_lossy().replace('\\', "/");
+        let (size, hash) = expected
+            .remove(path.as_str())
+            .with_context(|| format!("{} is in the archive but not in the manifest", path))?;
+
+        let mut hasher = Xxh3::new();
+        let mut buffer = [0u8; 64 * 1024];
+        let mut read = 0u64;
+        loop {
+            let count = entry.read(&mut buffer)?;
+            if count == 0 {
+                break;
+            }
+            hasher.update(&buffer[..count]);
+            read += count as u64;
+        }
+        if read != size || hasher.digest() != hash {
+            anyhow::bail!("{} does not match the manifest", path);
+        }
+    }
+
+    if let Some(missing) = expected.keys().next() {
+        anyhow::bail!("{} is missing from the archive", missing);
+    }
+    Ok(())
+}
+
+/// 将归档中的快照解压到目录（不包括摘要、清单和自描述信息）
+fn extract_archive(archive: &Path, target: &Path, events: &dyn EventSink) -> Result<RestoreStats> {
+    let target = &to_verbatim_path(target);
+    fs::create_dir_all(target).with_context(|| format!("Failed to create {:?}", target))?;
+    let mut stats = RestoreStats::default();
+    let (summary, _) = read_metadata(archive)?;
+
+    let mut reader = open_archive(archive)?;
+    for entry in reader.entries()? {
+        let mut entry = entry?;
+        let Some(rel) = entry_path(&entry.path()?) else { continue };
+        if rel.as_os_str().is_empty() || BackupSummary::is_metadata(&rel, summary.as_ref()) {
+            continue;
+        }
+        let dest = target.join(&rel);
+        let kind = entry.header().entry_type();
+        entry.unpack(&dest).with_context(|| format!("Failed to restore {:?}", dest))?;
+        if kind.is_dir() {
+            stats.dirs += 1;
+        } else if kind.is_symlink() {
+            stats.symlinks += 1;
+        } else {
+            stats.files += 1;
+            stats.bytes += entry.size();
+            events.file_restored(&rel, entry.size());
+        }
+    }
+    Ok(stats)
+}
+
+/// 将快照目录复制到恢复目录（不包括摘要、清单和自描述信息）
+///
+/// 快照和恢复目录都使用逐字路径，恢复很深的目录树时不受 Windows 路径长度限制。
+fn restore_folder(snapshot: &Path, target: &Path, events: &dyn EventSink) -> Result<RestoreStats> {
+    let (snapshot, target) = (&to_verbatim_path(snapshot), &to_verbatim_path(target));
+    // 只读快照中的文件去掉了写权限，恢复时为所有者补回
+    let summary = BackupSummary::load(snapshot).ok().flatten();
+    let read_only = summary.as_ref().and_then(|s| s.settings.as_ref()).is_some_and(|s| s.read_only);
+    fs::create_dir_all(target).with_context(|| format!("Failed to create {:?}", target))?;
+    let mut stats = RestoreStats::default();
+
+    for entry in WalkDir::new(snapshot).follow_links(false).min_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(snapshot)?;
+        if BackupSummary::is_metadata(rel, summary.as_ref()) {
+            continue;
+        }
+        let dest = target.join(rel);
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest).with_context(|| format!("Failed to create {:?}", dest))?;
+            stats.dirs += 1;
+        } else if entry.file_type().is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link, &dest)
+                .with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, link))?;
+            #[cfg(windows)]
+            {
+                let result = if fs::metadata(entry.path()).is_ok_and(|m| m.is_dir()) {
+                    std::os::windows::fs::symlink_dir(&link, &dest)
+                } else {
+                    std::os::windows::fs::symlink_file(&link, &dest)
+                };
+                result.with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, link))?;
+            }
+            stats.symlinks += 1;
+        } else {
+            let bytes = copy_file(entry.path(), &dest, &CopyOptions::default())?;
+            let meta = entry.metadata()?;
+            filetime::set_file_mtime(&dest, FileTime::from_last_modification_time(&meta))
+                .with_context(|| format!("Failed to set modification time of {:?}", dest))?;
+            let mut perms = meta.permissions();
+            if read_only {
+                #[cfg(unix)]
+                perms.set_mode(perms.mode() | 0o200);
+                #[cfg(not(unix))]
+                #[allow(clippy::permissions_set_readonly_false)]
+                perms.set_readonly(false);
+            }
+            fs::set_permissions(&dest, perms).ok();
+            stats.files += 1;
+            stats.bytes += bytes;
+            events.file_restored(rel, bytes);
+        }
+    }
+    Ok(stats)
+}
+
+/// 本地备份目标上的快照（快照目录和归档），供 list / restore 统一读取
+pub struct LocalSnapshots {
+    /// 备份目标根目录
+    destination: PathBuf,
+}
+
+impl LocalSnapshots {
+    /// 打开本地备份目标
+    pub fn new(destination: &Path) -> Self {
+        Self { destination: destination.to_path_buf() }
+    }
+
+    /// 快照对应的目录或归档文件
+    fn locate(&self, name: &str) -> Result<PathBuf> {
+        let folder = self.destination.join(name);
+        if folder.is_dir() {
+            return Ok(folder);
+        }
+        let archive = self.destination.join(format!("{}{}", name, ARCHIVE_SUFFIX));
+        if archive.is_file() {
+            return Ok(archive);
+        }
+        anyhow::bail!("Snapshot {} not found in {:?}", name, self.destination)
+    }
+}
+
+impl RemoteSnapshots for LocalSnapshots {
+    fn describe(&self) -> String {
+        format!("{:?}", self.destination)
+    }
+
+    fn current(&self) -> Option<String> {
+        current_snapshot(&self.destination).map(|path| path.file_name().unwrap_or_default().to_string_lossy().to_string())
+    }
+
+    fn snapshots(&self, naming: &SnapshotNaming) -> Result<Vec<(NaiveDateTime, String)>> {
+        let mut snapshots: Vec<(NaiveDateTime, String)> = naming
+            .list(&self.destination, false)?
+            .into_iter()
+            .map(|(time, path)| (time, path.file_name().unwrap_or_default().to_string_lossy().to_string()))
+            .chain(
+                find_archives(&self.destination, naming)?
+                    .into_iter()
+                    .map(|(time, path)| (time, archive_name(&path))),
+            )
+            .collect();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    fn totals(&self, name: &str) -> Result<(u64, u64)> {
+        let path = self.locate(name)?;
+        let manifest = if path.is_dir() { Manifest::load(&path)? } else { read_metadata(&path)?.1 };
+        if let Some(manifest) = manifest {
+            return Ok((manifest.entries.len() as u64, manifest.entries.iter().map(|e| e.size).sum()));
+        }
+
+        // 没有清单的快照目录（由旧版本创建）直接统计
+        let (mut count, mut size) = (0, 0);
+        let path = to_verbatim_path(&path);
+        for entry in WalkDir::new(&path).follow_links(false).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_file() && !is_metadata(entry.path().strip_prefix(&path)?) {
+                count += 1;
+                size += entry.metadata()?.len();
+            }
+        }
+        Ok((count, size))
+    }
+
+    fn restore(&self, name: &str, target: &Path, events: &dyn EventSink) -> Result<RestoreStats> {
+        let path = self.locate(name)?;
+        if path.is_dir() {
+            restore_folder(&path, target, events)
+        } else {
+            extract_archive(&path, target, events)
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// Recall - 存储后端模块
+// 定义备份执行器与存储层之间的接口：扫描和同步决策保持不变，只替换每个文件的实际存储方式
+
+use crate::actions::{FileTask, SyncAction};
+use crate::chunks::{is_repository, Repository, RepositoryWriter};
+use crate::config::BackupConfig;
+use crate::events::EventSink;
+use crate::naming::SnapshotNaming;
+use crate::webdav::{is_webdav_destination, WebDav, WebDavWriter};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 单个任务的执行结果
+pub enum TaskOutcome {
+    /// 按计划完成，附带写入的字节数
+    Done(u64),
+
+    /// 无法创建链接，已改为复制文件，附带复制的字节数和原因
+    LinkFallback(u64, String),
+
+    /// 内容已存在于存储中（如内容寻址仓库中已有的数据块），未写入新数据
+    Reused,
+}
+
+/// 存储后端
+///
+/// 备份执行器从多个工作线程并发调用 [`StorageBackend::apply`]，实现需要是线程安全的。
+pub trait StorageBackend: Send + Sync {
+    /// 执行单个文件任务的存储操作
+    ///
+    /// # 参数
+    /// * `task` - 文件任务
+    /// * `action` - 同步决策给出的动作
+    ///
+    /// # 返回
+    /// * `Ok(TaskOutcome)` - 执行结果
+    /// * `Err(anyhow::Error)` - 操作失败
+    fn apply(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome>;
+}
+
+/// 共享的存储后端（调用方在执行器完成后仍需访问后端，如提交快照索引）
+impl<T: StorageBackend + ?Sized> StorageBackend for Arc<T> {
+    fn apply(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome> {
+        (**self).apply(task, action)
+    }
+}
+
+/// 需要在所有文件处理完成后提交快照的远程存储后端（对象存储、WebDAV）
+pub trait SnapshotWriter: StorageBackend {
+    /// 作为增量基准的上一个快照名称（首次备份为 `None`）
+    fn previous(&self) -> Option<&str>;
+
+    /// 提交快照（写入索引等），之后快照才对 list / restore 可见
+    ///
+    /// # 参数
+    /// * `name` - 快照名称
+    fn commit(&self, name: &str) -> Result<()>;
+}
+
+/// 远程备份目标上的快照（用于 list / restore）
+pub trait RemoteSnapshots {
+    /// 备份目标的描述（用于输出）
+    fn describe(&self) -> String;
+
+    /// 列出所有快照（按时间从旧到新排序），名称无法按命名格式解析的会被忽略
+    fn snapshots(&self, naming: &SnapshotNaming) -> Result<Vec<(NaiveDateTime, String)>>;
+
+    /// 最后一次提交的快照（本地目标的 `current` 链接或标记文件），没有记录时返回 `None`
+    fn current(&self) -> Option<String> {
+        None
+    }
+
+    /// 快照中的文件数量和总大小
+    fn totals(&self, name: &str) -> Result<(u64, u64)>;
+
+    /// 将快照恢复到本地目录
+    ///
+    /// # 参数
+    /// * `name` - 快照名称
+    /// * `target` - 恢复到的目录
+    /// * `events` - 每恢复一个文件调用一次 [`EventSink::file_restored`]
+    fn restore(&self, name: &str, target: &Path, events: &dyn EventSink) -> Result<RestoreStats>;
+}
+
+/// 恢复结果统计
+#[derive(Debug, Default)]
+pub struct RestoreStats {
+    /// 恢复的文件数量
+    pub files: u64,
+
+    /// 恢复的目录数量
+    pub dirs: u64,
+
+    /// 恢复的符号链接数量
+    pub symlinks: u64,
+
+    /// 写入的字节数
+    pub bytes: u64,
+}
+
+/// 检查备份目标是否为远程目标（`s3://` 分块仓库、本地分块仓库或 `http(s)://` WebDAV）
+pub fn is_remote_destination(destination: &Path) -> bool {
+    is_repository(destination) || is_webdav_destination(destination)
+}
+
+/// 打开远程备份目标上的快照
+///
+/// # 参数
+/// * `destination` - 备份目标
+/// * `username` - 访问凭据的用户名
+/// * `region` - 对象存储区域
+/// * `endpoint` - 对象存储服务地址
+/// * `keyfile` - 加密仓库的密钥文件
+pub fn open_remote(
+    destination: &Path,
+    username: Option<&str>,
+    region: Option<&str>,
+    endpoint: Option<&str>,
+    keyfile: Option<&Path>,
+) -> Result<Box<dyn RemoteSnapshots>> {
+    if is_webdav_destination(destination) {
+        return Ok(Box::new(WebDav::open(destination, username)?));
+    }
+    Ok(Box::new(Repository::open(destination, username, region, endpoint, keyfile)?))
+}
+
+/// 为本次备份打开远程目标的写入后端，并找到作为增量基准的上一个快照
+///
+/// 启用分块仓库（`chunked`）或加密（`encrypt`）时，本地目录作为本地仓库使用；
+/// 加密的新仓库在首次备份时生成密钥。
+///
+/// # 参数
+/// * `config` - 备份配置（目标为远程地址，或启用了分块仓库或加密）
+/// * `snapshot` - 本次快照名称
+pub fn open_writer(config: &BackupConfig, snapshot: &str) -> Result<Arc<dyn SnapshotWriter>> {
+    if is_webdav_destination(&config.destination) {
+        if config.encrypt {
+            anyhow::bail!("Encryption is not supported for WebDAV destinations");
+        }
+        if config.chunked {
+            anyhow::bail!("Chunk repositories are not supported on WebDAV destinations");
+        }
+        return Ok(Arc::new(WebDavWriter::open(config, snapshot)?));
+    }
+    if !is_repository(&config.destination) && !config.naming.list(&config.destination, false)?.is_empty() {
+        anyhow::bail!(
+            "{:?} already contains hardlink snapshots, a chunk repository can only be created in a new destination",
+            config.destination
+        );
+    }
+
+    let mut repo = Repository::open(
+        &config.destination,
+        config.username.as_deref(),
+        config.region.as_deref(),
+        config.endpoint.as_deref(),
+        config.keyfile.as_deref(),
+    )?;
+    if !config.dry_run {
+        repo = repo.init(config.encrypt, config.keyfile.as_deref())?;
+    }
+    Ok(Arc::new(RepositoryWriter::open(repo, config)?))
+}
@@ -0,0 +1,1125 @@
+// Recall - 分块仓库模块
+// 将文件按内容切分为数据块，以 SHA-256 寻址存储并去重；每个快照对应一个索引对象，记录文件由哪些数据块组成
+// 加密仓库的数据块改用带密钥的 HMAC 寻址，对象内容在写入前加密
+
+use crate::actions::{FileTask, SyncAction};
+use crate::backend::{RemoteSnapshots, RestoreStats, SnapshotWriter, StorageBackend, TaskOutcome};
+use crate::config::BackupConfig;
+use crate::copy::open_for_backup;
+use crate::encryption::{passphrase, EncryptedStore, KeyEnvelope, Keys};
+use crate::events::EventSink;
+use crate::naming::SnapshotNaming;
+use crate::s3::{is_s3_destination, S3Store};
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// 数据块最小长度
+const MIN_CHUNK: usize = 256 * 1024;
+
+/// 数据块最大长度
+const MAX_CHUNK: usize = 4 * 1024 * 1024;
+
+/// 切分点掩码：哈希值低 20 位全为 0 时切分，平均块长约 1 MiB
+const CHUNK_MASK: u64 = (1 << 20) - 1;
+
+/// 快照索引格式版本
+pub(crate) const INDEX_VERSION: u32 = 1;
+
+/// 存放数据块的目录
+const CHUNKS_DIR: &str = "chunks";
+
+/// 存放快照索引的目录
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// 仓库配置对象的键（加密仓库保存密钥信封；本地仓库同时作为识别标记）
+pub const REPOSITORY_FILE: &str = "repository.json";
+
+/// 仓库配置
+#[derive(Debug, Serialize, Deserialize)]
+struct RepositoryConfig {
+    /// 配置格式版本
+    version: u32,
+
+    /// 加密仓库的密钥信封（未加密的仓库为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<KeyEnvelope>,
+}
+
+/// 对象存储
+///
+/// 分块仓库只需要按键读写完整对象，不依赖目录、重命名或链接，
+/// 因此可以放在 S3 等只提供对象接口的存储上。
+pub trait ObjectStore: Send + Sync {
+    /// 存储位置的描述（用于输出）
+    fn describe(&self) -> String;
+
+    /// 检查对象是否存在
+    fn exists(&self, key: &str) -> Result<bool>;
+
+    /// 写入对象（已存在时覆盖）
+    fn put(&self, key: &str, data: &[u8]) -> Result<()>;
+
+    /// 读取对象
+    fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// 列出以 `prefix` 开头的所有对象键
+    fn list(&self, prefix: &str) -> Result<Vec<String>>;
+}
+
+/// 快照索引
+///
+/// 记录快照中的每个条目及文件内容对应的数据块，恢复时按顺序拼接数据块即可还原文件。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotIndex {
+    /// 索引格式版本
+    pub version: u32,
+
+    /// 创建时间
+    pub created_at: String,
+
+    /// 备份源路径
+    pub source: PathBuf,
+
+    /// 所有条目（按路径排序）
+    pub entries: Vec<IndexEntry>,
+}
+
+/// 快照索引中的条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// 普通文件
+    File,
+
+    /// 目录
+    Dir,
+
+    /// 符号链接
+    Symlink,
+}
+
+/// 快照索引中的单个条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// 相对于源目录的路径（使用 `/` 分隔）
+    pub path: String,
+
+    /// 条目类型
+    pub kind: EntryKind,
+
+    /// 文件大小（字节）
+    #[serde(default)]
+    pub size: u64,
+
+    /// 修改时间（Unix 时间戳，秒）
+    pub mtime: i64,
+
+    /// 修改时间的纳秒部分
+    #[serde(default)]
+    pub mtime_nanos: u32,
+
+    /// 权限位（Unix 为 mode，Windows 只读文件为 0o444，其他为 0o644）
+    pub mode: u32,
+
+    /// 组成文件内容的数据块（按顺序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<String>,
+
+    /// 符号链接的目标
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<PathBuf>,
+
+    /// 文件内容的 XXH3 哈希值（十六进制，WebDAV 清单用于内容检查）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl IndexEntry {
+    /// 根据源文件的元数据创建条目（类型为普通文件，不含内容信息）
+    ///
+    /// # 参数
+    /// * `rel_path` - 相对于源目录的路径
+    /// * `meta` - 源文件元数据（不跟随符号链接）
+    pub fn from_metadata(rel_path: &Path, meta: &fs::Metadata) -> Self {
+        let mtime = FileTime::from_last_modification_time(meta);
+        Self {
+            path: index_path(rel_path),
+            kind: EntryKind::File,
+            size: 0,
+            mtime: mtime.unix_seconds(),
+            mtime_nanos: mtime.nanoseconds(),
+            mode: file_mode(meta),
+            chunks: Vec::new(),
+            target: None,
+            hash: None,
+        }
+    }
+
+    /// 检查源文件的大小和修改时间是否与本条目相同
+    pub fn same_metadata(&self, other: &IndexEntry) -> bool {
+        self.kind == EntryKind::File
+            && other.kind == EntryKind::File
+            && self.size == other.size
+            && self.mtime == other.mtime
+            && self.mtime_nanos == other.mtime_nanos
+    }
+}
+
+/// 检查备份目标是否为分块仓库（`s3://` 目标，或包含仓库配置的本地目录）
+pub fn is_repository(destination: &Path) -> bool {
+    is_s3_destination(destination) || destination.join(REPOSITORY_FILE).is_file()
+}
+
+/// 本地目录中的对象存储（对象键对应目录下的相对路径）
+pub struct LocalStore {
+    /// 仓库根目录
+    root: PathBuf,
+}
+
+impl LocalStore {
+    /// 使用指定目录作为对象存储（目录在首次写入时创建）
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf() }
+    }
+}
+
+impl ObjectStore for LocalStore {
+    fn describe(&self) -> String {
+        format!("{:?}", self.root)
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.root.join(key).is_file())
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+        // 先写入临时文件再重命名，中断时不会留下不完整的对象；
+        // 多个工作线程可能同时写入内容相同的数据块，每次写入使用各自的临时文件
+        let dir = path.parent().unwrap_or(&self.root);
+        let mut temp = tempfile::Builder::new()
+            .suffix(".tmp")
+            .tempfile_in(dir)
+            .with_context(|| format!("Failed to create temporary file in {:?}", dir))?;
+        temp.write_all(data)
+            .with_context(|| format!("Failed to write {:?}", temp.path()))?;
+        temp.persist(&path)
+            .with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let path = self.root.join(key);
+        fs::read(&path).with_context(|| format!("Failed to read {:?}", path))
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let dir = match prefix.rsplit_once('/') {
+            Some((dir, _)) => self.root.join(dir),
+            None => self.root.clone(),
+        };
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut keys = Vec::new();
+        for entry in walkdir::WalkDir::new(&dir).follow_links(false) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let key = index_path(entry.path().strip_prefix(&self.root)?);
+            if key.starts_with(prefix) && !key.ends_with(".tmp") {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// 分块仓库
+pub struct Repository {
+    /// 底层对象存储（加密仓库为解锁后的 [`EncryptedStore`]）
+    store: Box<dyn ObjectStore>,
+
+    /// 加密仓库的主密钥
+    keys: Option<Keys>,
+}
+
+impl Repository {
+    /// 使用指定的对象存储创建仓库
+    pub fn new(store: Box<dyn ObjectStore>) -> Self {
+        Self { store, keys: None }
+    }
+
+    /// 根据备份目标打开仓库
+    ///
+    /// 仓库已加密时用口令（密钥文件、`RECALL_PASSPHRASE` 或终端输入）解锁，
+    /// 之后读写的对象都会透明地解密和加密。
+    ///
+    /// # 参数
+    /// * `destination` - 备份目标（如 `s3://bucket/prefix`，或本地仓库目录）
+    /// * `username` - 访问凭据的用户名（S3 为访问密钥 ID）
+    /// * `region` - 对象存储区域
+    /// * `endpoint` - 对象存储服务地址
+    /// * `keyfile` - 加密仓库的密钥文件
+    ///
+    /// # 返回
+    /// * `Ok(Repository)` - 打开的仓库
+    /// * `Err(anyhow::Error)` - 缺少凭据，或口令错误
+    pub fn open(
+        destination: &Path,
+        username: Option<&str>,
+        region: Option<&str>,
+        endpoint: Option<&str>,
+        keyfile: Option<&Path>,
+    ) -> Result<Self> {
+        let store: Box<dyn ObjectStore> = if is_s3_destination(destination) {
+            Box::new(S3Store::open(destination, username, region, endpoint)?)
+        } else {
+            Box::new(LocalStore::new(destination))
+        };
+
+        if !store.exists(REPOSITORY_FILE)? {
+            return Ok(Self::new(store));
+        }
+        let config: RepositoryConfig = serde_json::from_slice(&store.get(REPOSITORY_FILE)?)
+            .with_context(|| format!("Repository configuration in {} is corrupt", store.describe()))?;
+        match config.encryption {
+            Some(envelope) => {
+                let keys = Keys::open(&envelope, &passphrase(keyfile, false)?)?;
+                Ok(Self::unlocked(store, keys))
+            }
+            None => Ok(Self::new(store)),
+        }
+    }
+
+    /// 使用主密钥包装对象存储
+    fn unlocked(store: Box<dyn ObjectStore>, keys: Keys) -> Self {
+        Self { store: Box::new(EncryptedStore::new(store, keys.clone())), keys: Some(keys) }
+    }
+
+    /// 仓库是否已加密
+    pub fn is_encrypted(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// 初始化仓库配置：本地目录写入配置后才会被识别为仓库；启用加密时生成主密钥，用口令加密后写入
+    ///
+    /// # 参数
+    /// * `encrypt` - 是否为新仓库启用加密
+    /// * `keyfile` - 密钥文件（未指定时使用 `RECALL_PASSPHRASE` 或终端输入的口令）
+    ///
+    /// # 返回
+    /// * `Ok(Repository)` - 仓库（启用加密时已解锁）
+    /// * `Err(anyhow::Error)` - 仓库中已有未加密的快照，或写入失败
+    pub fn init(self, encrypt: bool, keyfile: Option<&Path>) -> Result<Self> {
+        if !encrypt || self.is_encrypted() {
+            if !self.store.exists(REPOSITORY_FILE)? {
+                let config = RepositoryConfig { version: 1, encryption: None };
+                self.store.put(REPOSITORY_FILE, &serde_json::to_vec_pretty(&config)?)?;
+            }
+            return Ok(self);
+        }
+        if !self.store.list(&format!("{}/", SNAPSHOTS_DIR))?.is_empty() {
+            anyhow::bail!(
+                "{} already contains unencrypted snapshots, encryption can only be enabled for a new destination",
+                self.store.describe()
+            );
+        }
+
+        let keys = Keys::generate();
+        let config = RepositoryConfig { version: 1, encryption: Some(keys.seal(&passphrase(keyfile, true)?)?) };
+        self.store.put(REPOSITORY_FILE, &serde_json::to_vec_pretty(&config)?)?;
+        Ok(Self::unlocked(self.store, keys))
+    }
+
+    /// 计算数据块标识（未加密仓库为 SHA-256，加密仓库为 HMAC-SHA256）
+    fn chunk_id(&self, data: &[u8]) -> String {
+        match self.keys {
+            Some(ref keys) => keys.chunk_id(data),
+            None => hex::encode(Sha256::digest(data)),
+        }
+    }
+
+    /// 读取快照索引
+    pub fn load_index(&self, name: &str) -> Result<SnapshotIndex> {
+        let data = self.store.get(&index_key(name))?;
+        let index: SnapshotIndex = serde_json::from_slice(&data)
+            .with_context(|| format!("Snapshot index {} is corrupt", name))?;
+        if index.version > INDEX_VERSION {
+            anyhow::bail!(
+                "Snapshot {} was written by a newer version of recall (index version {})",
+                name,
+                index.version
+            );
+        }
+        Ok(index)
+    }
+
+    /// 写入快照索引
+    fn save_index(&self, name: &str, index: &SnapshotIndex) -> Result<()> {
+        self.store.put(&index_key(name), &serde_json::to_vec(index)?)
+    }
+
+    /// 读取数据块并校验其哈希值
+    fn read_chunk(&self, id: &str) -> Result<Vec<u8>> {
+        let data = self.store.get(&chunk_key(id))?;
+        if self.chunk_id(&data) != id {
+            anyhow::bail!("Chunk {} is corrupt (hash mismatch)", id);
+        }
+        Ok(data)
+    }
+}
+
+impl RemoteSnapshots for Repository {
+    fn describe(&self) -> String {
+        self.store.describe()
+    }
+
+    fn snapshots(&self, naming: &SnapshotNaming) -> Result<Vec<(NaiveDateTime, String)>> {
+        let prefix = format!("{}/", SNAPSHOTS_DIR);
+        let mut snapshots: Vec<(NaiveDateTime, String)> = self
+            .store
+            .list(&prefix)?
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.strip_suffix(".json"))
+            .filter_map(|name| Some((naming.parse(name)?, name.to_string())))
+            .collect();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    fn totals(&self, name: &str) -> Result<(u64, u64)> {
+        let index = self.load_index(name)?;
+        let files = index.entries.iter().filter(|e| e.kind == EntryKind::File);
+        Ok(files.fold((0, 0), |(count, size), e| (count + 1, size + e.size)))
+    }
+
+    fn restore(&self, name: &str, target: &Path, events: &dyn EventSink) -> Result<RestoreStats> {
+        let index = self.load_index(name)?;
+        restore_index(&index, target, events, |entry, file| {
+            let mut written = 0;
+            for id in &entry.chunks {
+                let data = self.read_chunk(id)?;
+                file.write_all(&data)?;
+                written += data.len() as u64;
+            }
+            Ok(written)
+        })
+    }
+}
+
+/// 按快照索引将快照恢复到本地目录
+///
+/// 重建目录、文件和符号链接，并还原修改时间和权限；目标中已存在的同名文件会被覆盖。
+///
+/// # 参数
+/// * `index` - 快照索引
+/// * `target` - 恢复到的目录
+/// * `events` - 每恢复一个文件调用一次 [`EventSink::file_restored`]
+/// * `write_content` - 将文件内容写入已创建的文件，返回写入的字节数
+///
+/// # 返回
+/// * `Ok(RestoreStats)` - 恢复统计
+/// * `Err(anyhow::Error)` - 索引中有指向恢复目录之外的路径或符号链接，下载或写入失败，或写入的大小与索引不符
+pub(crate) fn restore_index(
+    index: &SnapshotIndex,
+    target: &Path,
+    events: &dyn EventSink,
+    write_content: impl Fn(&IndexEntry, &mut File) -> Result<u64>,
+) -> Result<RestoreStats> {
+    // 索引来自仓库，不能信任其中的路径：先全部检查，再写入任何文件
+    for entry in &index.entries {
+        if !is_contained(Path::new(&entry.path)) {
+            anyhow::bail!("Refusing to restore {:?}: path leaves the restore target", entry.path);
+        }
+        if entry.kind == EntryKind::Symlink {
+            let link_target = entry.target.as_deref().unwrap_or(Path::new(""));
+            if !link_stays_inside(Path::new(&entry.path), link_target) {
+                anyhow::bail!(
+                    "Refusing to restore symlink {} -> {:?}: target leaves the restore target",
+                    entry.path,
+                    link_target
+                );
+            }
+        }
+    }
+
+    let mut stats = RestoreStats::default();
+    fs::create_dir_all(target).with_context(|| format!("Failed to create {:?}", target))?;
+
+    for entry in &index.entries {
+        let dest = target.join(&entry.path);
+        match entry.kind {
+            EntryKind::Dir => {
+                fs::create_dir_all(&dest).with_context(|| format!("Failed to create {:?}", dest))?;
+                stats.dirs += 1;
+            }
+            EntryKind::Symlink => {
+                let link_target = entry.target.as_deref().unwrap_or(Path::new(""));
+                restore_symlink(link_target, &dest)?;
+                stats.symlinks += 1;
+            }
+            EntryKind::File => {
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                if dest.exists() {
+                    let mut perms = fs::metadata(&dest)?.permissions();
+                    #[allow(clippy::permissions_set_readonly_false)]
+                    perms.set_readonly(false);
+                    fs::set_permissions(&dest, perms).ok();
+                }
+                let mut file = File::create(&dest).with_context(|| format!("Failed to create {:?}", dest))?;
+                let written = write_content(entry, &mut file)
+                    .with_context(|| format!("Failed to restore {}", entry.path))?;
+                if written != entry.size {
+                    anyhow::bail!(
+                        "Restored size of {} ({} bytes) does not match the index ({} bytes)",
+                        entry.path,
+                        written,
+                        entry.size
+                    );
+                }
+                drop(file);
+                set_mode(&dest, entry.mode)?;
+                stats.files += 1;
+                stats.bytes += entry.size;
+                events.file_restored(Path::new(&entry.path), entry.size);
+            }
+        }
+    }
+
+    // 目录的修改时间在写入子项后才能设置，统一放在最后
+    for entry in &index.entries {
+        if entry.kind != EntryKind::Symlink {
+            let mtime = FileTime::from_unix_time(entry.mtime, entry.mtime_nanos);
+            filetime::set_file_mtime(target.join(&entry.path), mtime).ok();
+        }
+    }
+
+    Ok(stats)
+}
+
+/// 分块仓库写入后端
+///
+/// 作为 [`StorageBackend`] 接入备份执行器：文件按内容切分为数据块，仓库中没有的数据块才上传。
+/// 大小和修改时间与上一个快照相同的文件（未启用内容检查时）直接沿用上一个快照的数据块，不读取文件。
+/// 所有文件处理完成后调用 [`SnapshotWriter::commit`] 写入快照索引。
+pub struct RepositoryWriter {
+    /// 目标仓库
+    repo: Repository,
+
+    /// 备份源路径
+    source: PathBuf,
+
+    /// 是否为试运行模式（不上传任何数据）
+    dry_run: bool,
+
+    /// 是否总是读取文件内容（不沿用上一个快照的数据块）
+    check_content: bool,
+
+    /// 上一个快照的名称
+    previous_name: Option<String>,
+
+    /// 上一个快照的条目（按路径索引）
+    previous: HashMap<String, IndexEntry>,
+
+    /// 已确认存在于仓库中的数据块
+    known_chunks: Mutex<HashSet<String>>,
+
+    /// 仓库中已有的数据块是否已全部列出并加入 `known_chunks`
+    chunks_listed: Mutex<bool>,
+
+    /// 本次快照的条目
+    entries: Mutex<Vec<IndexEntry>>,
+}
+
+impl RepositoryWriter {
+    /// 创建仓库写入后端，读取最新快照的索引作为增量基准
+    ///
+    /// # 参数
+    /// * `repo` - 目标仓库
+    /// * `config` - 备份配置
+    ///
+    /// # 返回
+    /// * `Ok(RepositoryWriter)` - 写入后端
+    /// * `Err(anyhow::Error)` - 列出快照或读取索引失败
+    pub fn open(repo: Repository, config: &BackupConfig) -> Result<Self> {
+        let previous_name = repo.snapshots(&config.naming)?.pop().map(|(_, name)| name);
+        let previous: HashMap<String, IndexEntry> = match previous_name {
+            Some(ref name) => repo
+                .load_index(name)?
+                .entries
+                .into_iter()
+                .map(|e| (e.path.clone(), e))
+                .collect(),
+            None => HashMap::new(),
+        };
+        let known_chunks = previous.values().flat_map(|e| e.chunks.iter().cloned()).collect();
+        Ok(Self {
+            repo,
+            source: config.source.clone(),
+            dry_run: config.dry_run,
+            check_content: config.check_content,
+            previous_name,
+            previous,
+            known_chunks: Mutex::new(known_chunks),
+            chunks_listed: Mutex::new(false),
+            entries: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 切分并上传文件内容
+    ///
+    /// # 返回
+    /// * `Ok((chunks, uploaded))` - 数据块列表和实际上传的字节数
+    /// * `Err(anyhow::Error)` - 读取或上传失败
+    fn store_file(&self, path: &Path) -> Result<(Vec<String>, u64)> {
+        let file = open_for_backup(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut chunker = Chunker::new(file);
+        let mut chunks = Vec::new();
+        let mut uploaded = 0;
+
+        while let Some(data) = chunker.next_chunk().with_context(|| format!("Failed to read {:?}", path))? {
+            let id = self.repo.chunk_id(&data);
+            let known = self.is_known_chunk(&id)?;
+            if !known && !self.dry_run {
+                self.repo.store.put(&chunk_key(&id), &data)?;
+                uploaded += data.len() as u64;
+                self.known_chunks.lock().unwrap().insert(id.clone());
+            } else if !known {
+                // 试运行时按需要上传计算
+                uploaded += data.len() as u64;
+            }
+            chunks.push(id);
+        }
+
+        Ok((chunks, uploaded))
+    }
+
+    /// 检查数据块是否已在仓库中
+    ///
+    /// 先查上一个快照用到的数据块；遇到第一个不在其中的数据块时列出一次仓库中的全部数据块
+    /// （其他快照或中断的备份上传的），而不是对每个新数据块单独查询（远程存储每次查询都是一次请求）。
+    fn is_known_chunk(&self, id: &str) -> Result<bool> {
+        if self.known_chunks.lock().unwrap().contains(id) {
+            return Ok(true);
+        }
+
+        let mut listed = self.chunks_listed.lock().unwrap();
+        if !*listed {
+            let keys = self.repo.store.list(&format!("{}/", CHUNKS_DIR))?;
+            let mut known = self.known_chunks.lock().unwrap();
+            known.extend(keys.iter().filter_map(|key| key.rsplit('/').next()).map(str::to_string));
+            *listed = true;
+        }
+        Ok(self.known_chunks.lock().unwrap().contains(id))
+    }
+}
+
+impl SnapshotWriter for RepositoryWriter {
+    fn previous(&self) -> Option<&str> {
+        self.previous_name.as_deref()
+    }
+
+    fn commit(&self, name: &str) -> Result<()> {
+        let mut entries = std::mem::take(&mut *self.entries.lock().unwrap());
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let index = SnapshotIndex {
+            version: INDEX_VERSION,
+            created_at: Local::now().to_rfc3339(),
+            source: self.source.clone(),
+            entries,
+        };
+        self.repo.save_index(name, &index)
+    }
+}
+
+impl StorageBackend for RepositoryWriter {
+    fn apply(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome> {
+        if matches!(action, SyncAction::Skip | SyncAction::Resumed) {
+            return Ok(TaskOutcome::Done(0));
+        }
+
+        let meta = fs::symlink_metadata(&task.src_path)
+            .with_context(|| format!("Failed to read metadata of {:?}", task.src_path))?;
+        let mut entry = IndexEntry::from_metadata(&task.rel_path, &meta);
+
+        let outcome = match action {
+            SyncAction::CreateDir => {
+                entry.kind = EntryKind::Dir;
+                TaskOutcome::Done(0)
+            }
+            SyncAction::MakeSymlink(target) => {
+                entry.kind = EntryKind::Symlink;
+                entry.target = Some(target.clone());
+                TaskOutcome::Done(0)
+            }
+            _ => {
+                entry.size = meta.len();
+                let unchanged = self
+                    .previous
+                    .get(&entry.path)
+                    .filter(|old| !self.check_content && old.same_metadata(&entry));
+                match unchanged {
+                    Some(old) => {
+                        entry.chunks = old.chunks.clone();
+                        TaskOutcome::Reused
+                    }
+                    None => {
+                        let (chunks, uploaded) = self.store_file(&task.src_path)?;
+                        entry.chunks = chunks;
+                        if uploaded == 0 {
+                            TaskOutcome::Reused
+                        } else {
+                            TaskOutcome::Done(uploaded)
+                        }
+                    }
+                }
+            }
+        };
+
+        // 根目录本身不记录
+        if !entry.path.is_empty() {
+            self.entries.lock().unwrap().push(entry);
+        }
+        Ok(outcome)
+    }
+}
+
+/// 内容定义分块器（Gear 哈希）
+///
+/// 切分点只取决于附近的内容，文件中间插入或删除数据时，其他位置的数据块保持不变。
+struct Chunker<R: Read> {
+    /// 数据来源
+    reader: R,
+
+    /// 已读取但尚未切分的数据
+    buf: Vec<u8>,
+
+    /// 是否已读到末尾
+    eof: bool,
+}
+
+impl<R: Read> Chunker<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, buf: Vec::with_capacity(MAX_CHUNK), eof: false }
+    }
+
+    /// 读取下一个数据块
+    ///
+    /// # 返回
+    /// * `Ok(Some(Vec<u8>))` - 数据块
+    /// * `Ok(None)` - 已读完
+    /// * `Err(io::Error)` - 读取失败
+    fn next_chunk(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        while !self.eof && self.buf.len() < MAX_CHUNK {
+            let start = self.buf.len();
+            self.buf.resize(MAX_CHUNK, 0);
+            match self.reader.read(&mut self.buf[start..]) {
+                Ok(0) => {
+                    self.buf.truncate(start);
+                    self.eof = true;
+                }
+                Ok(n) => self.buf.truncate(start + n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => self.buf.truncate(start),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        let cut = cut_point(&self.buf);
+        let rest = self.buf.split_off(cut);
+        Ok(Some(std::mem::replace(&mut self.buf, rest)))
+    }
+}
+
+/// 在数据中查找切分点（不超过 `MAX_CHUNK`，数据不足 `MIN_CHUNK` 时取全部）
+fn cut_point(data: &[u8]) -> usize {
+    let limit = data.len().min(MAX_CHUNK);
+    if limit <= MIN_CHUNK {
+        return limit;
+    }
+    let mut hash: u64 = 0;
+    for (i, &byte) in data[..limit].iter().enumerate() {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if i + 1 >= MIN_CHUNK && hash & CHUNK_MASK == 0 {
+            return i + 1;
+        }
+    }
+    limit
+}
+
+/// Gear 哈希表：每个字节值对应一个固定的伪随机数（由 SplitMix64 生成，修改会改变所有切分点）
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x5265_6361_6c6c_4344; // "RecallCD"
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// 数据块的对象键（按哈希前两位分目录）
+fn chunk_key(id: &str) -> String {
+    format!("{}/{}/{}", CHUNKS_DIR, &id[..2], id)
+}
+
+/// 快照索引的对象键
+fn index_key(name: &str) -> String {
+    format!("{}/{}.json", SNAPSHOTS_DIR, name)
+}
+
+/// 将相对路径转换为索引中使用的 `/` 分隔形式
+pub(crate) fn index_path(rel_path: &Path) -> String {
+    rel_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 获取条目的权限位
+#[cfg(unix)]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+/// 获取条目的权限位
+#[cfg(not(unix))]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    if meta.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// 还原文件权限
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions of {:?}", path))
+}
+
+/// 还原文件权限（只还原只读属性）
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_readonly(mode & 0o200 == 0);
+    fs::set_permissions(path, perms).with_context(|| format!("Failed to set permissions of {:?}", path))
+}
+
+/// 路径是否只由普通的名称组成（不含 `..`、`.`、根目录或盘符）
+fn is_contained(path: &Path) -> bool {
+    path.components().next().is_some() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// 符号链接的目标是否留在恢复目录之内
+///
+/// 目标必须是相对路径；从链接所在目录出发逐级解析，`..` 不能越过恢复目录的根部。
+///
+/// # 参数
+/// * `link` - 链接在快照中的相对路径
+/// * `target` - 链接的目标
+fn link_stays_inside(link: &Path, target: &Path) -> bool {
+    let mut depth = link.components().count().saturating_sub(1);
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// 恢复符号链接（目标位置已存在时先删除）
+fn restore_symlink(target: &Path, link: &Path) -> Result<()> {
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::symlink_metadata(link).is_ok() {
+        fs::remove_file(link).with_context(|| format!("Failed to remove {:?}", link))?;
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, link)
+        .with_context(|| format!("Failed to symlink {:?} -> {:?}", link, target))?;
+
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(target, link)
+        .with_context(|| format!("Failed to symlink {:?} -> {:?}", link, target))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定种子的伪随机数据（xorshift），内容不可压缩且每次运行相同
+    fn sample(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed.max(1);
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect()
+    }
+
+    /// 在本地目录中创建未加密的仓库，以 `source` 为备份源
+    fn open_writer(source: &Path, repo: &Path) -> RepositoryWriter {
+        let repository = Repository::new(Box::new(LocalStore::new(repo))).init(false, None).unwrap();
+        let config = BackupConfig::builder(source, repo).read_ignore_files(false).finish().unwrap();
+        RepositoryWriter::open(repository, &config).unwrap()
+    }
+
+    /// 把源目录中的文件写入仓库
+    fn store(writer: &RepositoryWriter, source: &Path, name: &str) -> TaskOutcome {
+        let task = FileTask::new(PathBuf::from(name), source.join(name), PathBuf::new(), None, 0);
+        writer.apply(&task, &SyncAction::CopyNew).unwrap()
+    }
+
+    /// 按索引中的数据块还原文件内容
+    fn read_back(repo: &Repository, chunks: &[String]) -> Vec<u8> {
+        chunks.iter().flat_map(|id| repo.read_chunk(id).unwrap()).collect()
+    }
+
+    #[test]
+    fn identical_files_stored_in_parallel() {
+        const FILES: usize = 8;
+        let source = tempfile::tempdir().unwrap();
+        let repo_dir = tempfile::tempdir().unwrap();
+        let data = sample(3 * 1024 * 1024, 1);
+        for i in 0..FILES {
+            fs::write(source.path().join(format!("{}.bin", i)), &data).unwrap();
+        }
+
+        // 所有线程同时开始，写入相同的数据块
+        let writer = open_writer(source.path(), repo_dir.path());
+        let barrier = std::sync::Barrier::new(FILES);
+        std::thread::scope(|scope| {
+            for i in 0..FILES {
+                let (writer, barrier, source) = (&writer, &barrier, source.path());
+                scope.spawn(move || {
+                    barrier.wait();
+                    store(writer, source, &format!("{}.bin", i));
+                });
+            }
+        });
+        writer.commit("2024-01-15_10-30-00").unwrap();
+
+        let index = writer.repo.load_index("2024-01-15_10-30-00").unwrap();
+        assert_eq!(index.entries.len(), FILES);
+        for entry in &index.entries {
+            assert_eq!(entry.chunks, index.entries[0].chunks);
+        }
+        assert_eq!(read_back(&writer.repo, &index.entries[0].chunks), data);
+
+        // 没有遗留的临时文件
+        let leftovers = walkdir::WalkDir::new(repo_dir.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    /// 只含一个符号链接的索引
+    fn symlink_index(path: &str, target: &str) -> SnapshotIndex {
+        SnapshotIndex {
+            version: INDEX_VERSION,
+            created_at: String::new(),
+            source: PathBuf::new(),
+            entries: vec![IndexEntry {
+                path: path.to_string(),
+                kind: EntryKind::Symlink,
+                size: 0,
+                mtime: 0,
+                mtime_nanos: 0,
+                mode: 0o777,
+                chunks: Vec::new(),
+                target: Some(PathBuf::from(target)),
+                hash: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn restore_rejects_paths_outside_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("restore");
+        let no_content = |_: &IndexEntry, _: &mut File| -> Result<u64> { unreachable!() };
+
+        for (path, link) in [("../escape", "x"), ("/etc/escape", "x"), ("a/link", "../../x"), ("link", "/etc/passwd")] {
+            let index = symlink_index(path, link);
+            let result = restore_index(&index, &target, &crate::events::NullSink, no_content);
+            assert!(result.is_err(), "{} -> {}", path, link);
+        }
+        assert!(!dir.path().join("escape").exists());
+
+        #[cfg(unix)]
+        {
+            let index = symlink_index("a/b/link", "../c/./file");
+            restore_index(&index, &target, &crate::events::NullSink, no_content).unwrap();
+            assert_eq!(fs::read_link(target.join("a/b/link")).unwrap(), Path::new("../c/./file"));
+        }
+    }
+
+    /// 按内容切分数据
+    fn split(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut chunker = Chunker::new(data);
+        std::iter::from_fn(|| chunker.next_chunk().unwrap()).collect()
+    }
+
+    #[test]
+    fn chunk_boundaries_survive_insertion() {
+        let data = sample(12 * 1024 * 1024, 2);
+        let insert_at = 5 * 1024 * 1024;
+        let mut modified = data[..insert_at].to_vec();
+        modified.extend_from_slice(b"inserted in the middle of the file");
+        modified.extend_from_slice(&data[insert_at..]);
+
+        let before = split(&data);
+        let after = split(&modified);
+        assert!(before.len() >= 6, "too few chunks to compare: {}", before.len());
+        assert_eq!(before.concat(), data);
+        assert_eq!(after.concat(), modified);
+
+        // 插入位置之前的数据块不变，之后的数据块在下一个切分点重新对齐
+        let mut offset = 0;
+        for chunk in &before {
+            if offset + chunk.len() > insert_at {
+                break;
+            }
+            assert!(after.contains(chunk));
+            offset += chunk.len();
+        }
+        let changed = before.iter().filter(|chunk| !after.contains(chunk)).count();
+        assert!(changed <= 2, "{} of {} chunks changed", changed, before.len());
+    }
+
+    #[test]
+    fn unchanged_files_reuse_chunks() {
+        let source = tempfile::tempdir().unwrap();
+        let repo_dir = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("same.bin"), sample(2 * 1024 * 1024, 3)).unwrap();
+        fs::write(source.path().join("touched.bin"), sample(2 * 1024 * 1024, 4)).unwrap();
+        fs::write(source.path().join("changed.bin"), sample(2 * 1024 * 1024, 5)).unwrap();
+
+        let first = open_writer(source.path(), repo_dir.path());
+        for name in ["same.bin", "touched.bin", "changed.bin"] {
+            assert!(matches!(store(&first, source.path(), name), TaskOutcome::Done(n) if n > 0));
+        }
+        first.commit("2024-01-15_10-30-00").unwrap();
+        let chunk_files = |dir: &Path| {
+            walkdir::WalkDir::new(dir.join(CHUNKS_DIR))
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .count()
+        };
+        let stored = chunk_files(repo_dir.path());
+
+        // 只修改时间：内容相同，数据块已在仓库中；内容改变：上传新的数据块
+        let touched = FileTime::from_unix_time(1_700_000_000, 0);
+        filetime::set_file_mtime(source.path().join("touched.bin"), touched).unwrap();
+        fs::write(source.path().join("changed.bin"), sample(2 * 1024 * 1024, 6)).unwrap();
+
+        let second = open_writer(source.path(), repo_dir.path());
+        assert_eq!(second.previous(), Some("2024-01-15_10-30-00"));
+        assert!(matches!(store(&second, source.path(), "same.bin"), TaskOutcome::Reused));
+        assert!(matches!(store(&second, source.path(), "touched.bin"), TaskOutcome::Reused));
+        assert!(matches!(store(&second, source.path(), "changed.bin"), TaskOutcome::Done(n) if n > 0));
+        second.commit("2024-01-16_10-30-00").unwrap();
+
+        let old = first.repo.load_index("2024-01-15_10-30-00").unwrap();
+        let new = second.repo.load_index("2024-01-16_10-30-00").unwrap();
+        let chunks_of = |index: &SnapshotIndex, path: &str| {
+            index.entries.iter().find(|e| e.path == path).unwrap().chunks.clone()
+        };
+        assert_eq!(chunks_of(&old, "same.bin"), chunks_of(&new, "same.bin"));
+        assert_eq!(chunks_of(&old, "touched.bin"), chunks_of(&new, "touched.bin"));
+        let replaced = chunks_of(&old, "changed.bin");
+        assert!(chunks_of(&new, "changed.bin").iter().all(|id| !replaced.contains(id)));
+        assert_eq!(chunk_files(repo_dir.path()), stored + chunks_of(&new, "changed.bin").len());
+    }
+
+    #[test]
+    fn restore_round_trip() {
+        let source = tempfile::tempdir().unwrap();
+        let repo_dir = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let files = [
+            ("big.bin", sample(5 * 1024 * 1024, 7)),
+            ("docs/empty.txt", Vec::new()),
+            ("docs/deep/note.txt", b"note".to_vec()),
+        ];
+        let mtime = FileTime::from_unix_time(1_600_000_000, 500);
+        fs::create_dir_all(source.path().join("docs/deep")).unwrap();
+        for (name, data) in &files {
+            fs::write(source.path().join(name), data).unwrap();
+            filetime::set_file_mtime(source.path().join(name), mtime).unwrap();
+        }
+
+        let writer = open_writer(source.path(), repo_dir.path());
+        for dir in ["docs", "docs/deep"] {
+            let task = FileTask::new(PathBuf::from(dir), source.path().join(dir), PathBuf::new(), None, 0);
+            writer.apply(&task, &SyncAction::CreateDir).unwrap();
+        }
+        for (name, _) in &files {
+            store(&writer, source.path(), name);
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("deep/note.txt", source.path().join("docs/link")).unwrap();
+            let task =
+                FileTask::new(PathBuf::from("docs/link"), source.path().join("docs/link"), PathBuf::new(), None, 0);
+            writer.apply(&task, &SyncAction::MakeSymlink(PathBuf::from("deep/note.txt"))).unwrap();
+        }
+        writer.commit("2024-01-15_10-30-00").unwrap();
+
+        let stats = writer.repo.restore("2024-01-15_10-30-00", target.path(), &crate::events::NullSink).unwrap();
+        assert_eq!(stats.files, files.len() as u64);
+        assert_eq!(stats.bytes, files.iter().map(|(_, data)| data.len() as u64).sum::<u64>());
+        for (name, data) in &files {
+            let restored = target.path().join(name);
+            assert_eq!(&fs::read(&restored).unwrap(), data, "{}", name);
+            let restored_mtime = FileTime::from_last_modification_time(&fs::metadata(&restored).unwrap());
+            assert_eq!(restored_mtime, mtime, "{}", name);
+        }
+        #[cfg(unix)]
+        assert_eq!(fs::read_to_string(target.path().join("docs/link")).unwrap(), "note");
+    }
+}
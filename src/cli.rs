@@ -6,8 +6,22 @@ use console::style;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use std::path::PathBuf;
 
-use crate::config::BackupConfig;
-use crate::store::{AppConfig, Profile};
+use crate::events::ConsoleSink;
+use crate::config::{project_name, BackupConfig};
+use crate::history::{last_runs, RunRecord};
+use crate::i18n::tr;
+use crate::naming::SnapshotNaming;
+use crate::store::{AppConfig, Defaults, Profile};
+use crate::utils::format_bytes;
+
+/// 交互式菜单的选择结果
+pub enum Selection {
+    /// 用户选择退出
+    Exit,
+
+    /// 运行选中的配置文件（备份配置和配置文件名称）
+    Run(Box<BackupConfig>, String),
+}
 
 /// 运行交互式模式
 ///
@@ -15,15 +29,17 @@ use crate::store::{AppConfig, Profile};
 /// - 选择已保存的配置文件（Profile）
 /// - 创建新的配置文件
 /// - 删除配置文件
-/// - 退出程序
+/// - 退出
+///
+/// 不会结束进程，由调用方根据返回的选择决定下一步。
 ///
 /// # 参数
 /// * `dry_run` - 是否为试运行模式
 ///
 /// # 返回
-/// * `Ok((BackupConfig, String))` - 备份配置和项目名称
+/// * `Ok(Selection)` - 用户的选择（退出，或要运行的配置文件）
 /// * `Err(anyhow::Error)` - 操作失败
-pub fn run_interactive_mode(dry_run: bool) -> Result<(BackupConfig, String)> {
+pub fn run_interactive_mode(dry_run: bool) -> Result<Selection> {
     // 加载应用配置
     let mut app_config = AppConfig::load()?;
     let theme = ColorfulTheme::default();
@@ -45,7 +61,10 @@ pub fn run_interactive_mode(dry_run: bool) -> Result<(BackupConfig, String)> {
         let mut profiles: Vec<String> = app_config.profiles.keys().cloned().collect();
         profiles.sort();
 
-        // 构建菜单选项，显示 profile 详情
+        // 每个配置文件最近一次运行的结果（读取失败时不显示）
+        let last = last_runs().unwrap_or_default();
+
+        // 构建菜单选项，显示 profile 详情和最近一次运行的结果
         let mut choices: Vec<String> = profiles
             .iter()
             .map(|name| {
@@ -54,14 +73,20 @@ pub fn run_interactive_mode(dry_run: bool) -> Result<(BackupConfig, String)> {
                     let dst = profile.destination.to_string_lossy();
                     let check = if profile.check_content { "C" } else { "-" };
                     let vss = if profile.vss { "V" } else { "-" };
+                    let workers = if profile.workers == 0 {
+                        "auto".to_string()
+                    } else {
+                        profile.workers.to_string()
+                    };
                     format!(
-                        "{} ({} → {}) [{}{} W{}]",
+                        "{} ({} → {}) [{}{} W{}] — {}",
                         name,
                         src,
                         dst,
                         check,
                         vss,
-                        profile.workers
+                        workers,
+                        last_run_text(last.get(name))
                     )
                 } else {
                     name.clone()
@@ -69,39 +94,51 @@ pub fn run_interactive_mode(dry_run: bool) -> Result<(BackupConfig, String)> {
             })
             .collect();
 
-        choices.push(">> Create New Profile".to_string());
-        choices.push(">> Prune Backups".to_string());
+        let create = tr(">> Create New Profile", ">> 新建配置");
+        let prune = tr(">> Prune Backups", ">> 清理旧备份");
+        let edit = tr(">> Edit Profile", ">> 修改配置");
+        let rename = tr(">> Rename Profile", ">> 重命名配置");
+        let delete = tr(">> Delete Profile", ">> 删除配置");
+        let exit = tr(">> Exit", ">> 退出");
+
+        choices.push(create.to_string());
+        choices.push(prune.to_string());
         if !profiles.is_empty() {
-            choices.push(">> Edit Profile".to_string());
-            choices.push(">> Delete Profile".to_string());
+            choices.push(edit.to_string());
+            choices.push(rename.to_string());
+            choices.push(delete.to_string());
         }
-        choices.push(">> Exit".to_string());
+        choices.push(exit.to_string());
 
         // 显示选择菜单
         let selection = Select::with_theme(&theme)
-            .with_prompt("Select a backup profile")
+            .with_prompt(tr("Select a backup profile", "选择备份配置"))
             .default(0)
             .items(&choices)
             .interact()?;
 
         let choice = &choices[selection];
 
-        if choice == ">> Exit" {
+        if choice == exit {
             // 用户选择退出
-            std::process::exit(0);
-        } else if choice == ">> Create New Profile" {
+            return Ok(Selection::Exit);
+        } else if choice == create {
             // 创建新配置文件
             create_new_profile(&mut app_config)?;
             continue;
-        } else if choice == ">> Prune Backups" {
+        } else if choice == prune {
             // 清理旧备份
             prune_backups_interactive(&app_config)?;
             continue;
-        } else if choice == ">> Edit Profile" {
+        } else if choice == edit {
             // 修改配置文件
             edit_profile(&mut app_config)?;
             continue;
-        } else if choice == ">> Delete Profile" {
+        } else if choice == rename {
+            // 重命名配置文件
+            rename_profile_interactive(&mut app_config)?;
+            continue;
+        } else if choice == delete {
             // 删除配置文件
             delete_profile(&mut app_config)?;
             continue;
@@ -110,19 +147,99 @@ pub fn run_interactive_mode(dry_run: bool) -> Result<(BackupConfig, String)> {
             // selection 索引对应 profiles 数组
             let profile_name = &profiles[selection];
             let profile = app_config.profiles.get(profile_name).unwrap();
+            let (config, _) = profile_backup_config(profile, &app_config.defaults, dry_run)?;
+            return Ok(Selection::Run(Box::new(config), profile_name.clone()));
+        }
+    }
+}
 
-            // 获取源目录的绝对路径
-            let src_abs = std::fs::canonicalize(&profile.source)
-                .context("Source path in profile does not exist")?;
+/// 最近一次运行的简要说明，如“上次备份 2 天前，成功，1.20 MB”
+fn last_run_text(record: Option<&RunRecord>) -> String {
+    let Some(record) = record else {
+        return tr("never backed up", "从未备份").to_string();
+    };
 
-            // 生成项目名称
-            let project_name = get_project_name(&src_abs);
+    let age = record
+        .started()
+        .and_then(|started| started.and_local_timezone(chrono::Local).earliest())
+        .map(|started| (chrono::Local::now() - started).num_seconds().max(0) as u64)
+        .map(|secs| match secs {
+            0..60 => tr("just now", "刚刚").to_string(),
+            60..3600 => crate::trf!("{} min ago", "{} 分钟前", secs / 60),
+            3600..86400 => crate::trf!("{} h ago", "{} 小时前", secs / 3600),
+            _ => crate::trf!("{} day(s) ago", "{} 天前", secs / 86400),
+        })
+        .unwrap_or_else(|| record.started_at.clone());
+
+    let status = match record.status.as_str() {
+        "ok" => style(tr("OK", "成功")).green(),
+        "partial" => style(tr("partial", "部分失败")).yellow(),
+        _ => return crate::trf!("last backup {}, {}", "上次备份 {}，{}", age, style(tr("FAILED", "失败")).red()),
+    };
 
-            // 从配置文件创建备份配置
-            let config = BackupConfig::from_profile(profile, &project_name, dry_run);
-            return Ok((config?, project_name));
+    crate::trf!(
+        "last backup {}, {}, {}",
+        "上次备份 {}，{}，{}",
+        age,
+        status,
+        format_bytes(record.bytes_copied)
+    )
+}
+
+/// 按名称加载配置文件（Profile）并创建备份配置，不进行任何交互
+///
+/// # 参数
+/// * `name` - 配置文件名称
+/// * `dry_run` - 是否为试运行模式
+///
+/// # 返回
+/// * `Ok((BackupConfig, String))` - 备份配置和项目名称
+/// * `Err(anyhow::Error)` - 配置文件不存在或源路径无效
+pub fn load_profile(name: &str, dry_run: bool) -> Result<(BackupConfig, String)> {
+    let app_config = AppConfig::load()?;
+    let profile = match app_config.profiles.get(name) {
+        Some(profile) => profile,
+        None => {
+            let mut names: Vec<&String> = app_config.profiles.keys().collect();
+            names.sort();
+            anyhow::bail!(
+                "Profile '{}' not found. Available profiles: {}",
+                name,
+                if names.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", ")
+                }
+            );
         }
-    }
+    };
+    profile_backup_config(profile, &app_config.defaults, dry_run)
+}
+
+/// 按批量运行的顺序获取已保存配置文件的名称（优先级高的在前，相同时按名称排序）
+///
+/// # 参数
+/// * `group` - 只返回属于此分组的配置文件（`None` 表示全部）
+pub fn profile_names(group: Option<&str>) -> Result<Vec<String>> {
+    Ok(AppConfig::load()?.run_order(group))
+}
+
+/// 根据配置文件（Profile）创建备份配置
+fn profile_backup_config(
+    profile: &Profile,
+    defaults: &Defaults,
+    dry_run: bool,
+) -> Result<(BackupConfig, String)> {
+    // 获取源目录的绝对路径
+    let src_abs = std::fs::canonicalize(&profile.source)
+        .context("Source path in profile does not exist")?;
+
+    // 生成项目名称
+    let project_name = project_name(&src_abs)?;
+
+    // 从配置文件创建备份配置
+    let config = BackupConfig::from_profile(profile, defaults, &project_name, dry_run)?;
+    Ok((config, project_name))
 }
 
 /// 创建新的配置文件（Profile）
@@ -140,28 +257,57 @@ fn create_new_profile(config: &mut AppConfig) -> Result<()> {
 
     // 获取配置文件名称
     let name: String = Input::with_theme(&theme)
-        .with_prompt("Profile Name")
+        .with_prompt(tr("Profile Name", "配置名称"))
         .interact_text()?;
 
+    // 有配置模板时询问要继承的模板
+    let extends = if config.templates.is_empty() {
+        None
+    } else {
+        let mut items = vec![tr("(none)", "（不使用模板）").to_string()];
+        items.extend(config.templates.keys().cloned());
+        let selection = Select::with_theme(&theme)
+            .with_prompt(tr("Based on template", "继承模板"))
+            .items(&items)
+            .default(0)
+            .interact()?;
+        (selection > 0).then(|| items[selection].clone())
+    };
+
     // 获取源路径
     let source: String = Input::with_theme(&theme)
-        .with_prompt("Source Path")
+        .with_prompt(tr("Source Path", "源路径"))
         .interact_text()?;
 
     // 获取备份根路径
     let dest: String = Input::with_theme(&theme)
-        .with_prompt("Backup Root Path")
+        .with_prompt(tr("Backup Root Path", "备份根路径"))
         .interact_text()?;
 
     // 询问是否启用内容检查
     let check_content = Confirm::with_theme(&theme)
-        .with_prompt("Enable Content Check (Slower but safer)?")
+        .with_prompt(tr("Enable Content Check (Slower but safer)?", "启用内容校验（更慢但更可靠）？"))
         .default(false)
         .interact()?;
 
     let vss = if cfg!(windows) {
         Confirm::with_theme(&theme)
-            .with_prompt("Enable VSS snapshot (Windows only)?")
+            .with_prompt(tr("Enable VSS snapshot (Windows only)?", "启用 VSS 快照（仅 Windows）？"))
+            .default(false)
+            .interact()?
+    } else {
+        false
+    };
+
+    let (vss_fallback, vss_writers) = if vss {
+        prompt_vss_options(&theme, false, &[])?
+    } else {
+        (false, Vec::new())
+    };
+
+    let preserve_metadata = if cfg!(unix) {
+        Confirm::with_theme(&theme)
+            .with_prompt(tr("Preserve owner and extended attributes (Unix only)?", "保留所有者和扩展属性（仅 Unix）？"))
             .default(false)
             .interact()?
     } else {
@@ -169,7 +315,7 @@ fn create_new_profile(config: &mut AppConfig) -> Result<()> {
     };
 
     let exclude_input: String = Input::with_theme(&theme)
-        .with_prompt("Exclude patterns (comma-separated, optional)")
+        .with_prompt(tr("Exclude patterns (comma-separated, optional)", "排除模式（逗号分隔，可选）"))
         .default(String::new())
         .interact_text()?;
 
@@ -181,27 +327,103 @@ fn create_new_profile(config: &mut AppConfig) -> Result<()> {
         .collect();
 
     let workers: usize = Input::with_theme(&theme)
-        .with_prompt("Worker threads")
-        .default(4)
+        .with_prompt(tr("Worker threads (0 = auto)", "工作线程数（0 = 自动）"))
+        .default(0)
+        .interact_text()?;
+
+    let keep: usize = Input::with_theme(&theme)
+        .with_prompt(tr("Snapshots to keep after each backup (0 = keep all)", "每次备份后保留的快照数（0 = 全部保留）"))
+        .default(0)
         .interact_text()?;
 
     // 创建新的配置文件
     let profile = Profile {
+        extends,
         source: PathBuf::from(source),
         destination: PathBuf::from(dest),
+        username: None,
+        region: None,
+        endpoint: None,
+        replicate: None,
+        chunked: false,
+        encrypt: false,
+        keyfile: None,
         check_content,
         vss,
+        vss_fallback,
+        vss_writers,
+        preserve_metadata,
         workers,
         exclude,
+        ignore_file: None,
+        buffer_size: 0,
+        unbuffered: false,
+        bandwidth_limit: 0,
+        skip_space_check: false,
+        fail_on_locked: false,
+        on_failure: Default::default(),
+        max_failures: 0,
+        read_only: false,
+        manifest: false,
+        keep: (keep > 0).then_some(keep),
+        dry_run: false,
+        snapshot_format: None,
+        utc: false,
+        groups: Vec::new(),
+        priority: 0,
+        pre_hook: None,
+        post_hook: None,
+        hook_failure: Default::default(),
+        webhooks: Vec::new(),
+        webhook_on: Default::default(),
+        ping_url: None,
+        notify: false,
+        schedule: None,
     };
 
-    // 保存到配置文件
-    config.profiles.insert(name, profile);
-    config.save()?;
-    println!("Profile saved successfully!");
+    // 保存到配置文件（重新加载后合并，不覆盖其他 recall 实例同时保存的配置）
+    *config = AppConfig::update(|latest| {
+        latest.profiles.insert(name, profile);
+        Ok(latest.clone())
+    })?;
+    println!("{}", tr("Profile saved successfully!", "配置已保存！"));
     Ok(())
 }
 
+/// 询问 VSS 相关选项
+///
+/// # 参数
+/// * `theme` - 交互主题
+/// * `fallback` - “VSS 不可用时继续备份”的默认值
+/// * `writers` - 参与快照的写入器默认值
+///
+/// # 返回
+/// * `Ok((bool, Vec<String>))` - VSS 不可用时是否继续备份，以及参与快照的写入器
+fn prompt_vss_options(
+    theme: &ColorfulTheme,
+    fallback: bool,
+    writers: &[String],
+) -> Result<(bool, Vec<String>)> {
+    let fallback = Confirm::with_theme(theme)
+        .with_prompt(tr("Continue without snapshot if VSS is unavailable?", "VSS 不可用时不使用快照继续备份？"))
+        .default(fallback)
+        .interact()?;
+
+    let writers_input: String = Input::with_theme(theme)
+        .with_prompt(tr("VSS writers for application-consistent snapshots (comma-separated, optional)", "参与应用一致性快照的 VSS 写入器（逗号分隔，可选）"))
+        .default(writers.join(", "))
+        .interact_text()?;
+
+    let writers = writers_input
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+
+    Ok((fallback, writers))
+}
+
 /// 修改配置文件（Profile）
 ///
 /// 允许用户更新配置文件的各项参数。
@@ -210,13 +432,13 @@ fn edit_profile(config: &mut AppConfig) -> Result<()> {
 
     let mut profiles: Vec<String> = config.profiles.keys().cloned().collect();
     if profiles.is_empty() {
-        println!("{}", style("No profiles available to edit.").yellow());
+        println!("{}", style(tr("No profiles available to edit.", "没有可修改的配置。")).yellow());
         return Ok(());
     }
     profiles.sort();
 
     let selection = Select::with_theme(&theme)
-        .with_prompt("Select a profile to EDIT")
+        .with_prompt(tr("Select a profile to EDIT", "选择要修改的配置"))
         .items(&profiles)
         .interact()?;
 
@@ -224,32 +446,47 @@ fn edit_profile(config: &mut AppConfig) -> Result<()> {
     let mut profile = config.profiles.get(&profile_name).cloned().unwrap();
 
     let source: String = Input::with_theme(&theme)
-        .with_prompt("Source Path")
+        .with_prompt(tr("Source Path", "源路径"))
         .default(profile.source.to_string_lossy().to_string())
         .interact_text()?;
 
     let dest: String = Input::with_theme(&theme)
-        .with_prompt("Backup Root Path")
+        .with_prompt(tr("Backup Root Path", "备份根路径"))
         .default(profile.destination.to_string_lossy().to_string())
         .interact_text()?;
 
     let check_content = Confirm::with_theme(&theme)
-        .with_prompt("Enable Content Check (Slower but safer)?")
+        .with_prompt(tr("Enable Content Check (Slower but safer)?", "启用内容校验（更慢但更可靠）？"))
         .default(profile.check_content)
         .interact()?;
 
     let vss = if cfg!(windows) {
         Confirm::with_theme(&theme)
-            .with_prompt("Enable VSS snapshot (Windows only)?")
+            .with_prompt(tr("Enable VSS snapshot (Windows only)?", "启用 VSS 快照（仅 Windows）？"))
             .default(profile.vss)
             .interact()?
     } else {
         profile.vss
     };
 
+    let (vss_fallback, vss_writers) = if vss && cfg!(windows) {
+        prompt_vss_options(&theme, profile.vss_fallback, &profile.vss_writers)?
+    } else {
+        (profile.vss_fallback, profile.vss_writers.clone())
+    };
+
+    let preserve_metadata = if cfg!(unix) {
+        Confirm::with_theme(&theme)
+            .with_prompt(tr("Preserve owner and extended attributes (Unix only)?", "保留所有者和扩展属性（仅 Unix）？"))
+            .default(profile.preserve_metadata)
+            .interact()?
+    } else {
+        profile.preserve_metadata
+    };
+
     let current_exclude = profile.exclude.join(", ");
     let exclude_input: String = Input::with_theme(&theme)
-        .with_prompt("Exclude patterns (comma-separated, optional)")
+        .with_prompt(tr("Exclude patterns (comma-separated, optional)", "排除模式（逗号分隔，可选）"))
         .default(current_exclude)
         .interact_text()?;
 
@@ -261,20 +498,31 @@ fn edit_profile(config: &mut AppConfig) -> Result<()> {
         .collect();
 
     let workers: usize = Input::with_theme(&theme)
-        .with_prompt("Worker threads")
+        .with_prompt(tr("Worker threads (0 = auto)", "工作线程数（0 = 自动）"))
         .default(profile.workers)
         .interact_text()?;
 
+    let keep: usize = Input::with_theme(&theme)
+        .with_prompt(tr("Snapshots to keep after each backup (0 = keep all)", "每次备份后保留的快照数（0 = 全部保留）"))
+        .default(profile.keep.unwrap_or(0))
+        .interact_text()?;
+
     profile.source = PathBuf::from(source);
     profile.destination = PathBuf::from(dest);
     profile.check_content = check_content;
     profile.vss = vss;
+    profile.vss_fallback = vss_fallback;
+    profile.vss_writers = vss_writers;
+    profile.preserve_metadata = preserve_metadata;
     profile.exclude = exclude;
     profile.workers = workers;
+    profile.keep = (keep > 0).then_some(keep);
 
-    config.profiles.insert(profile_name, profile);
-    config.save()?;
-    println!("Profile updated successfully!");
+    *config = AppConfig::update(|latest| {
+        latest.profiles.insert(profile_name, profile);
+        Ok(latest.clone())
+    })?;
+    println!("{}", tr("Profile updated successfully!", "配置已更新！"));
     Ok(())
 }
 
@@ -286,61 +534,124 @@ fn prune_backups_interactive(config: &AppConfig) -> Result<()> {
 
     let mut choices: Vec<String> = Vec::new();
     let mut targets: Vec<Option<PathBuf>> = Vec::new();
+    // 各配置文件设置的保留数量（用作默认值）和快照命名规则
+    let mut keeps: Vec<Option<usize>> = Vec::new();
+    let mut namings: Vec<SnapshotNaming> = Vec::new();
 
     for name in &profiles {
         if let Some(profile) = config.profiles.get(name) {
-            let project_name = get_project_name(&profile.source);
+            let project_name = project_name(&profile.source)?;
             let dest = profile.destination.join(project_name);
             choices.push(format!("{} ({})", name, dest.to_string_lossy()));
             targets.push(Some(dest));
+            keeps.push(profile.keep.or(config.defaults.keep));
+            namings.push(SnapshotNaming::new(
+                profile.snapshot_format.as_deref().or(config.defaults.snapshot_format.as_deref()),
+                profile.utc || config.defaults.utc,
+            )?);
         }
     }
 
-    choices.push(">> Enter path manually".to_string());
+    let manual = tr(">> Enter path manually", ">> 手动输入路径");
+    let back = tr(">> Back", ">> 返回");
+
+    choices.push(manual.to_string());
     targets.push(None);
 
     if profiles.is_empty() {
-        choices.push(">> Back".to_string());
+        choices.push(back.to_string());
         targets.push(None);
     }
 
     let selection = Select::with_theme(&theme)
-        .with_prompt("Select a backup target to prune")
+        .with_prompt(tr("Select a backup target to prune", "选择要清理的备份目标"))
         .items(&choices)
         .interact()?;
 
     let selected = choices[selection].as_str();
-    if selected == ">> Back" {
+    if selected == back {
         return Ok(());
     }
 
-    let destination = if selected == ">> Enter path manually" {
+    let destination = if selected == manual {
         let dest: String = Input::with_theme(&theme)
-            .with_prompt("Backup Target Path")
+            .with_prompt(tr("Backup Target Path", "备份目标路径"))
             .interact_text()?;
         PathBuf::from(dest)
     } else {
         targets[selection]
             .clone()
-            .unwrap_or_else(PathBuf::new)
+            .unwrap_or_default()
     };
 
     if destination.as_os_str().is_empty() {
-        println!("{}", style("No destination selected.").yellow());
+        println!("{}", style(tr("No destination selected.", "未选择目标路径。")).yellow());
         return Ok(());
     }
 
     let keep: usize = Input::with_theme(&theme)
-        .with_prompt("Keep latest N backups")
-        .default(5)
+        .with_prompt(tr("Keep latest N backups", "保留最新的 N 个备份"))
+        .default(keeps.get(selection).copied().flatten().unwrap_or(5))
         .interact_text()?;
 
     let dry_run = Confirm::with_theme(&theme)
-        .with_prompt("Dry run (no delete)?")
+        .with_prompt(tr("Dry run (no delete)?", "试运行（不删除）？"))
         .default(false)
         .interact()?;
 
-    crate::prune::prune_backups(&destination, keep, dry_run)?;
+    let naming = match namings.get(selection) {
+        Some(naming) => naming.clone(),
+        None => SnapshotNaming::new(config.defaults.snapshot_format.as_deref(), config.defaults.utc)?,
+    };
+    crate::prune::prune_backups(&destination, keep, dry_run, &[], &naming, &ConsoleSink::new())?;
+    Ok(())
+}
+
+/// 重命名配置文件（Profile）
+///
+/// 选择一个配置文件并输入新名称，所有设置保持不变。
+fn rename_profile_interactive(config: &mut AppConfig) -> Result<()> {
+    let theme = ColorfulTheme::default();
+
+    let mut profiles: Vec<String> = config.profiles.keys().cloned().collect();
+    if profiles.is_empty() {
+        println!("{}", style(tr("No profiles available to rename.", "没有可重命名的配置。")).yellow());
+        return Ok(());
+    }
+    profiles.sort();
+
+    let selection = Select::with_theme(&theme)
+        .with_prompt(tr("Select a profile to RENAME", "选择要重命名的配置"))
+        .items(&profiles)
+        .interact()?;
+    let old_name = &profiles[selection];
+
+    let new_name: String = Input::with_theme(&theme)
+        .with_prompt(tr("New profile name", "新的配置名称"))
+        .default(old_name.clone())
+        .interact_text()?;
+
+    let renamed = AppConfig::update(|latest| {
+        latest.rename_profile(old_name, &new_name)?;
+        Ok(latest.clone())
+    });
+    match renamed {
+        Ok(latest) => {
+            *config = latest;
+            println!(
+                "{} {}",
+                style(tr("Success:", "成功：")).green(),
+                crate::trf!(
+                    "'{}' has been renamed to '{}'.",
+                    "'{}' 已重命名为 '{}'。",
+                    old_name,
+                    new_name.trim()
+                )
+            );
+        }
+        Err(e) => println!("{} {}", style(tr("Error:", "错误：")).red(), e),
+    }
+
     Ok(())
 }
 
@@ -358,14 +669,14 @@ fn delete_profile(config: &mut AppConfig) -> Result<()> {
     // 获取并排序所有配置文件名称
     let mut profiles: Vec<String> = config.profiles.keys().cloned().collect();
     if profiles.is_empty() {
-        println!("{}", style("No profiles available to delete.").yellow());
+        println!("{}", style(tr("No profiles available to delete.", "没有可删除的配置。")).yellow());
         return Ok(());
     }
     profiles.sort();
 
     // 显示选择菜单
     let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a profile to DELETE")
+        .with_prompt(tr("Select a profile to DELETE", "选择要删除的配置"))
         .items(&profiles)
         .interact()?;
 
@@ -373,55 +684,27 @@ fn delete_profile(config: &mut AppConfig) -> Result<()> {
 
     // 确认删除
     if Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!(
+        .with_prompt(crate::trf!(
             "Are you sure you want to delete profile '{}'?",
+            "确定要删除配置 '{}' 吗？",
             style(profile_name).red().bold()
         ))
         .default(false)
         .interact()?
     {
         // 删除配置文件
-        config.profiles.remove(profile_name);
-        config.save()?;
+        *config = AppConfig::update(|latest| {
+            latest.profiles.remove(profile_name);
+            Ok(latest.clone())
+        })?;
         println!(
-            "{} '{}' has been deleted.",
-            style("Success:").green(),
-            profile_name
+            "{} {}",
+            style(tr("Success:", "成功：")).green(),
+            crate::trf!("'{}' has been deleted.", "'{}' 已删除。", profile_name)
         );
     } else {
-        println!("Operation cancelled.");
+        println!("{}", tr("Operation cancelled.", "操作已取消。"));
     }
 
     Ok(())
 }
-
-/// 从路径获取项目名称
-///
-/// 使用路径的最后一部分作为项目名称。
-/// 例如：`C:\Users\Data` -> `Data`
-/// 对于驱动器根路径：`D:\` -> `D_Drive`
-///
-/// # 参数
-/// * `path` - 源路径
-///
-/// # 返回
-/// 项目名称字符串
-fn get_project_name(path: &std::path::Path) -> String {
-    if let Some(name) = path.file_name() {
-        name.to_string_lossy().to_string()
-    } else {
-        // 如果是驱动器根目录，生成特殊名称
-        let path_str = path.to_string_lossy();
-        if let Some(colon_idx) = path_str.find(':') {
-            if colon_idx > 0 {
-                let drive = &path_str[colon_idx - 1..colon_idx];
-                format!("{}_Drive", drive.to_uppercase())
-            } else {
-                "Unknown_Drive".to_string()
-            }
-        } else {
-            "Root_Backup".to_string()
-        }
-    }
-}
-
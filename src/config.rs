@@ -1,11 +1,110 @@
 // Recall - 备份配置管理模块
 // 负责创建和管理单次备份任务的配置
 
-use crate::store::Profile;
-use anyhow::{Context, Result};
+use crate::copy::CopyOptions;
+use crate::hooks::HookFailure;
+use crate::session::FailurePolicy;
+use crate::webhook::WebhookOn;
+use crate::naming::SnapshotNaming;
+use crate::store::{AppConfig, Defaults, Profile};
+use crate::error::{Classify, RecallError, Result};
+use anyhow::Context;
+use serde::Deserialize;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// 源目录根部的项目配置文件名
+pub const PROJECT_FILE: &str = ".recall.toml";
+
+/// 源目录根部的排除文件名
+pub const IGNORE_FILE: &str = ".recallignore";
+
+/// 项目配置文件（源目录根部的 `.recall.toml`）
+///
+/// 由项目自身声明备份偏好，可与代码一起提交到版本库。
+/// 命令行临时运行和配置文件运行都会自动读取。
+#[derive(Debug, Deserialize, Default)]
+pub struct ProjectFile {
+    /// 目标目录中的项目名称（默认使用源目录名）
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// 额外的排除模式（Glob 风格）
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// 是否总是启用内容检查
+    #[serde(default)]
+    pub check_content: bool,
+}
+
+impl ProjectFile {
+    /// 读取源目录根部的项目配置文件
+    ///
+    /// # 参数
+    /// * `source` - 源目录
+    ///
+    /// # 返回
+    /// * `Ok(Some(ProjectFile))` - 读取的项目配置
+    /// * `Ok(None)` - 源目录中没有项目配置文件
+    /// * `Err(RecallError)` - 读取或解析失败
+    pub fn load(source: &Path) -> Result<Option<Self>> {
+        let path = source.join(PROJECT_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {:?}", path))
+            .classify(RecallError::Config)?;
+        let project: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {:?}", path))
+            .classify(RecallError::Config)?;
+        Ok(Some(project))
+    }
+}
+
+/// 确定源目录在备份目标中的项目名称
+///
+/// 优先使用项目配置文件中的 `name`，否则使用源目录名；
+/// 源路径为驱动器根目录时生成 `C_Drive` 形式的名称。
+///
+/// # 参数
+/// * `source` - 源目录
+///
+/// # 返回
+/// * `Ok(String)` - 项目名称
+/// * `Err(RecallError)` - 项目配置文件无效，或其中的名称不是合法的目录名
+pub fn project_name(source: &Path) -> Result<String> {
+    if let Some(name) = ProjectFile::load(source)?.and_then(|p| p.name) {
+        let name = name.trim();
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', ':']) {
+            return Err(RecallError::Config(anyhow::anyhow!(
+                "Invalid project name {:?} in {}",
+                name,
+                PROJECT_FILE
+            )));
+        }
+        return Ok(name.to_string());
+    }
+
+    if let Some(name) = source.file_name() {
+        Ok(name.to_string_lossy().to_string())
+    } else {
+        // 如果是驱动器根目录，生成特殊名称
+        let path_str = source.to_string_lossy();
+        if let Some(colon_idx) = path_str.find(':') {
+            if colon_idx > 0 {
+                let drive = &path_str[colon_idx - 1..colon_idx];
+                Ok(format!("{}_Drive", drive.to_uppercase()))
+            } else {
+                Ok("Unknown_Drive".to_string())
+            }
+        } else {
+            Ok("Root_Backup".to_string())
+        }
+    }
+}
 
 /// 备份配置结构体
 ///
@@ -19,55 +118,248 @@ pub struct BackupConfig {
     /// 目标路径（备份存储位置）
     pub destination: PathBuf,
 
+    /// 连接备份目标所用的用户名（密码从系统凭据管理器读取）
+    pub username: Option<String>,
+
+    /// 对象存储目标的区域
+    pub region: Option<String>,
+
+    /// S3 兼容服务的地址
+    pub endpoint: Option<String>,
+
+    /// 备份完成后用 rclone 复制新快照的远程目标
+    pub replicate: Option<String>,
+
+    /// 是否以分块去重仓库存储，而不是硬链接快照目录
+    pub chunked: bool,
+
+    /// 是否加密备份（以分块仓库存储，数据块和索引在写入前加密）
+    pub encrypt: bool,
+
+    /// 加密口令的密钥文件（未设置时使用 `RECALL_PASSPHRASE` 或在终端中输入）
+    pub keyfile: Option<PathBuf>,
+
     /// 是否启用内容检查（使用哈希值比较文件，更准确但更慢）
     pub check_content: bool,
 
     /// 是否启用 VSS 快照（仅 Windows）
     pub vss: bool,
 
-    /// 工作线程数量
+    /// VSS 不可用时是否继续备份（不使用快照）
+    pub vss_fallback: bool,
+
+    /// 参与 VSS 快照的写入器名称（为空时只创建崩溃一致的快照）
+    pub vss_writers: Vec<String>,
+
+    /// 是否保留所有者和扩展属性（仅 Unix）
+    pub preserve_metadata: bool,
+
+    /// 目标文件系统是否支持块克隆（运行时检测，不由用户配置）
+    pub reflink: bool,
+
+    /// 是否正在续传未完成的备份（运行时检测，不由用户配置）
+    pub resume: bool,
+
+    /// 工作线程数量（0 表示根据 CPU 数量和目标设备类型自动确定）
     pub workers: usize,
 
     /// 排除模式列表（Glob 风格）
     pub exclude_patterns: Vec<String>,
 
+    /// 额外读取的排除文件（`.recallignore` 格式，可放在配置目录等源目录以外的位置）
+    pub ignore_file: Option<PathBuf>,
+
+    /// 复制缓冲区大小（字节），0 表示使用系统默认的复制方式
+    pub buffer_size: usize,
+
+    /// 是否对大文件使用无缓冲 I/O（绕过系统页缓存）
+    pub unbuffered: bool,
+
+    /// 复制时的带宽上限（字节/秒），0 表示不限制
+    pub bandwidth_limit: u64,
+
+    /// 是否跳过备份前的可用空间检查（省去预先扫描源目录）
+    pub skip_space_check: bool,
+
+    /// 被其他进程锁定而无法读取的文件是否计为失败（否则只单独统计，不影响备份结果）
+    pub fail_on_locked: bool,
+
+    /// 失败的文件超过 `max_failures` 时如何处理快照
+    pub on_failure: FailurePolicy,
+
+    /// 允许的失败文件数量，超过时按 `on_failure` 处理
+    pub max_failures: u64,
+
+    /// 提交后是否把快照设为只读（见 [`set_snapshot_read_only`](crate::executor::set_snapshot_read_only)）
+    pub read_only: bool,
+
+    /// 提交时是否写入完整性清单（见 [`Manifest`](crate::manifest::Manifest)），供 `recall verify` 按哈希值校验
+    pub manifest: bool,
+
+    /// 备份成功后保留的快照数量，超出的旧快照会被自动清理（`None` 表示不自动清理）
+    pub keep: Option<usize>,
+
+    /// 快照目录的命名规则
+    pub naming: SnapshotNaming,
+
+    /// 备份开始前运行的命令
+    pub pre_hook: Option<String>,
+
+    /// 备份结束后运行的命令
+    pub post_hook: Option<String>,
+
+    /// 钩子失败时的处理方式
+    pub hook_failure: HookFailure,
+
+    /// 运行结束后通知的 Webhook URL
+    pub webhooks: Vec<String>,
+
+    /// 发送 Webhook 的时机
+    pub webhook_on: WebhookOn,
+
+    /// 运行开始和结束时请求的监控 URL
+    pub ping_url: Option<String>,
+
+    /// 是否在备份结束或失败时显示桌面通知
+    pub notify: bool,
+
     /// 是否为试运行模式（不实际复制文件）
     pub dry_run: bool,
 }
 
 impl BackupConfig {
+    /// 开始构建备份配置，未设置的选项使用默认值
+    ///
+    /// # 参数
+    /// * `source` - 源路径
+    /// * `destination` - 目标路径（快照目录直接创建在其中）
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::config::BackupConfig;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let config = BackupConfig::builder(dir.path(), dir.path().join("backups"))
+    ///     .exclude("*.tmp")
+    ///     .check_content(true)
+    ///     .workers(4)
+    ///     .read_ignore_files(false)
+    ///     .finish()
+    ///     .unwrap();
+    /// assert_eq!(config.exclude_patterns, ["*.tmp"]);
+    /// ```
+    pub fn builder(source: impl Into<PathBuf>, destination: impl Into<PathBuf>) -> BackupConfigBuilder {
+        BackupConfigBuilder {
+            config: Self::bare(source.into(), destination.into()),
+            read_ignore_files: true,
+        }
+    }
+
+    /// 所有选项为默认值的备份配置（不读取项目配置文件和排除文件）
+    fn bare(source: PathBuf, destination: PathBuf) -> Self {
+        Self {
+            source,
+            destination,
+            username: None,
+            region: None,
+            endpoint: None,
+            replicate: None,
+            chunked: false,
+            encrypt: false,
+            keyfile: None,
+            check_content: false,
+            vss: false,
+            vss_fallback: false,
+            vss_writers: Vec::new(),
+            preserve_metadata: false,
+            reflink: false,
+            resume: false,
+            workers: 0,
+            exclude_patterns: Vec::new(),
+            ignore_file: None,
+            buffer_size: 0,
+            unbuffered: false,
+            bandwidth_limit: 0,
+            skip_space_check: false,
+            fail_on_locked: false,
+            on_failure: FailurePolicy::Commit,
+            max_failures: 0,
+            read_only: false,
+            manifest: false,
+            keep: None,
+            naming: SnapshotNaming::default(),
+            pre_hook: None,
+            post_hook: None,
+            hook_failure: HookFailure::Abort,
+            webhooks: Vec::new(),
+            webhook_on: WebhookOn::Always,
+            ping_url: None,
+            notify: false,
+            dry_run: false,
+        }
+    }
+
     /// 创建新的备份配置
     ///
+    /// 参数较多，新代码建议使用 [`BackupConfig::builder`]。
+    ///
     /// # 参数
     /// * `source` - 源路径
     /// * `destination` - 目标路径
     /// * `check_content` - 是否启用内容检查
+    /// * `vss` - 是否启用 VSS 快照
+    /// * `vss_fallback` - VSS 不可用时是否继续备份
+    /// * `vss_writers` - 参与 VSS 快照的写入器名称
+    /// * `preserve_metadata` - 是否保留所有者和扩展属性
+    /// * `workers` - 工作线程数量
     /// * `exclude_patterns` - 排除模式列表
+    /// * `ignore_file` - 额外读取的排除文件
+    /// * `buffer_size` - 复制缓冲区大小（0 表示系统默认）
+    /// * `unbuffered` - 是否对大文件使用无缓冲 I/O
+    /// * `bandwidth_limit` - 带宽上限（字节/秒，0 表示不限制）
+    /// * `naming` - 快照目录的命名规则
     /// * `dry_run` - 是否为试运行模式
     ///
     /// # 返回
     /// * `Ok(BackupConfig)` - 创建的备份配置
-    /// * `Err(anyhow::Error)` - 处理 `.recallignore` 失败
+    /// * `Err(RecallError)` - 处理 `.recallignore` 失败
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         source: PathBuf,
         destination: PathBuf,
         check_content: bool,
         vss: bool,
+        vss_fallback: bool,
+        vss_writers: Vec<String>,
+        preserve_metadata: bool,
         workers: usize,
         exclude_patterns: Vec<String>,
+        ignore_file: Option<PathBuf>,
+        buffer_size: usize,
+        unbuffered: bool,
+        bandwidth_limit: u64,
+        naming: SnapshotNaming,
         dry_run: bool,
     ) -> Result<Self> {
         let mut config = Self {
-            source,
-            destination,
             check_content,
             vss,
+            vss_fallback,
+            vss_writers,
+            preserve_metadata,
             workers,
             exclude_patterns,
+            ignore_file,
+            buffer_size,
+            unbuffered,
+            bandwidth_limit,
+            naming,
             dry_run,
+            ..Self::bare(source, destination)
         };
 
-        // 处理 .recallignore 文件，保持与 from_profile 一致
+        // 处理项目配置文件和 .recallignore 文件，保持与 from_profile 一致
+        config.process_project_file()?;
         config.process_recallignore()?;
 
         Ok(config)
@@ -76,55 +368,202 @@ impl BackupConfig {
     /// 从配置文件（Profile）创建备份配置
     ///
     /// 此方法会处理 `.recallignore` 文件，将其中的排除模式添加到配置中。
+    /// 配置文件未设置的工作线程数和保留数量使用全局默认值，全局默认排除模式排在最前。
     ///
     /// # 参数
     /// * `profile` - 保存的配置文件
+    /// * `defaults` - 全局默认设置
     /// * `project_name` - 项目名称（用于构建目标路径）
-    /// * `dry_run` - 是否为试运行模式
+    /// * `dry_run` - 是否为试运行模式（配置文件中设置了默认试运行时总是试运行）
     ///
     /// # 返回
     /// * `Ok(BackupConfig)` - 创建的备份配置
-    /// * `Err(anyhow::Error)` - 处理失败
-    pub fn from_profile(profile: &Profile, project_name: &str, dry_run: bool) -> Result<Self> {
+    /// * `Err(RecallError)` - 处理失败
+    pub fn from_profile(
+        profile: &Profile,
+        defaults: &Defaults,
+        project_name: &str,
+        dry_run: bool,
+    ) -> Result<Self> {
         // 将项目名称附加到目标路径
         let final_dest = profile.destination.join(project_name);
 
         let mut config = Self {
             source: profile.source.clone(),
             destination: final_dest,
+            username: profile.username.clone(),
+            region: profile.region.clone(),
+            endpoint: profile.endpoint.clone(),
+            replicate: profile.replicate.clone(),
+            chunked: profile.chunked,
+            encrypt: profile.encrypt,
+            keyfile: profile.keyfile.clone(),
             check_content: profile.check_content,
             vss: profile.vss,
-            workers: profile.workers,
-            exclude_patterns: profile.exclude.clone(),
-            dry_run,
+            vss_fallback: profile.vss_fallback,
+            vss_writers: profile.vss_writers.clone(),
+            preserve_metadata: profile.preserve_metadata,
+            reflink: false,
+            resume: false,
+            workers: match profile.workers {
+                0 => defaults.workers.unwrap_or(0),
+                n => n,
+            },
+            exclude_patterns: defaults.exclude.iter().chain(&profile.exclude).cloned().collect(),
+            ignore_file: match profile.ignore_file {
+                // 相对路径相对于配置文件所在目录
+                Some(ref path) if path.is_relative() => AppConfig::get_config_path()
+                    .classify(RecallError::Config)?
+                    .parent()
+                    .map(|dir| dir.join(path)),
+                ref path => path.clone(),
+            },
+            buffer_size: profile.buffer_size,
+            unbuffered: profile.unbuffered,
+            bandwidth_limit: profile.bandwidth_limit,
+            skip_space_check: profile.skip_space_check,
+            fail_on_locked: profile.fail_on_locked,
+            on_failure: profile.on_failure,
+            max_failures: profile.max_failures,
+            read_only: profile.read_only,
+            manifest: profile.manifest,
+            keep: profile.keep.or(defaults.keep),
+            naming: SnapshotNaming::new(
+                profile.snapshot_format.as_deref().or(defaults.snapshot_format.as_deref()),
+                profile.utc || defaults.utc,
+            )
+            .classify(RecallError::Config)?,
+            pre_hook: profile.pre_hook.clone(),
+            post_hook: profile.post_hook.clone(),
+            hook_failure: profile.hook_failure,
+            webhooks: defaults.webhooks.iter().chain(&profile.webhooks).cloned().collect(),
+            webhook_on: profile.webhook_on,
+            ping_url: profile.ping_url.clone(),
+            notify: profile.notify,
+            dry_run: dry_run || profile.dry_run,
         };
 
-        // 处理 .recallignore 文件
+        // 处理项目配置文件和 .recallignore 文件
+        config.process_project_file()?;
         config.process_recallignore()?;
 
         Ok(config)
     }
 
-    /// 处理 `.recallignore` 文件
+    /// 获取本次备份的文件复制选项
+    pub fn copy_options(&self) -> CopyOptions {
+        CopyOptions {
+            buffer_size: self.buffer_size,
+            unbuffered: self.unbuffered,
+            bandwidth_limit: self.bandwidth_limit,
+        }
+    }
+
+    /// 检查源目录和目标目录的位置关系
+    ///
+    /// 源目录和目标相同或源目录位于目标中时，快照会写入正在备份的目录树；
+    /// 目标位于源目录中（包括使用卷影副本时，快照中的源目录同样包含目标）且没有被排除时，
+    /// 每次备份都会把之前的快照再备份一遍。这些情况都拒绝运行。远程目标不检查。
+    ///
+    /// # 返回
+    /// * `Ok(())` - 两个目录互不包含，或源目录中的目标已被排除
+    /// * `Err(RecallError::Config)` - 路径重叠，目标位于源目录中时错误信息给出需要添加的排除模式
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::config::BackupConfig;
+    ///
+    /// let source = tempfile::tempdir().unwrap();
+    /// let config = BackupConfig::builder(source.path(), source.path().join("backups"))
+    ///     .read_ignore_files(false)
+    ///     .finish()
+    ///     .unwrap();
+    /// assert!(config.check_paths().is_err());
+    ///
+    /// let mut config = config;
+    /// config.exclude_patterns.push("backups".to_string());
+    /// assert!(config.check_paths().is_ok());
+    ///
+    /// let config = BackupConfig::builder(source.path(), source.path()).finish().unwrap();
+    /// assert!(config.check_paths().is_err());
+    /// ```
+    pub fn check_paths(&self) -> Result<()> {
+        if crate::backend::is_remote_destination(&self.destination) {
+            return Ok(());
+        }
+        check_path_overlap(&self.source, &self.destination, &self.exclude_patterns)
+            .classify(RecallError::Config)
+    }
+
+    /// 处理源目录中的项目配置文件（`.recall.toml`）
+    ///
+    /// 将其中的排除模式添加到配置中；声明了内容检查时启用内容检查。
+    ///
+    /// # 返回
+    /// * `Ok(())` - 处理成功（文件不存在时不做任何修改）
+    /// * `Err(RecallError)` - 读取或解析失败
+    fn process_project_file(&mut self) -> Result<()> {
+        let Some(project) = ProjectFile::load(&self.source)? else {
+            return Ok(());
+        };
+        self.check_content |= project.check_content;
+        for pattern in project.exclude {
+            if !self.exclude_patterns.contains(&pattern) {
+                self.exclude_patterns.push(pattern);
+            }
+        }
+        Ok(())
+    }
+
+    /// 处理排除文件
     ///
-    /// 如果 `.recallignore` 文件不存在，会自动创建一个默认的排除文件。
-    /// 然后读取该文件并将所有排除模式添加到配置中。
+    /// 依次读取源目录中的 `.recallignore` 和配置指定的排除文件（`ignore_file`），
+    /// 将其中的排除模式添加到配置中。两者都不存在时使用内置的默认排除模式。
+    /// 不会向源目录写入任何文件（需要时使用 [`write_default_ignore_file`] 显式创建）。
     ///
     /// # 返回
     /// * `Ok(())` - 处理成功
-    /// * `Err(anyhow::Error)` - 处理失败
+    /// * `Err(RecallError)` - 读取失败，或指定的排除文件不存在
     fn process_recallignore(&mut self) -> Result<()> {
-        let ignore_file_path = self.source.join(".recallignore");
+        let source_file = self.source.join(IGNORE_FILE);
+        let mut found = false;
 
-        // 如果文件不存在，创建默认的排除文件
-        if !ignore_file_path.exists() {
-            self.create_default_ignore_file(&ignore_file_path)?;
+        if source_file.is_file() {
+            self.read_ignore_file(&source_file)?;
+            found = true;
+        }
+        if let Some(path) = self.ignore_file.clone() {
+            if !path.is_file() {
+                return Err(RecallError::Config(anyhow::anyhow!("Ignore file {:?} does not exist", path)));
+            }
+            self.read_ignore_file(&path)?;
+            found = true;
         }
 
-        // 读取并解析排除文件
-        let content =
-            fs::read_to_string(&ignore_file_path).context("Failed to read .recallignore")?;
+        if !found {
+            self.add_ignore_patterns(&default_ignore_content());
+        }
+        Ok(())
+    }
 
+    /// 读取排除文件（`.recallignore` 格式），将其中的排除模式添加到配置中
+    ///
+    /// # 参数
+    /// * `path` - 排除文件路径
+    ///
+    /// # 返回
+    /// * `Ok(())` - 读取成功
+    /// * `Err(RecallError)` - 读取失败
+    pub fn read_ignore_file(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))
+            .classify(RecallError::Config)?;
+        self.add_ignore_patterns(&content);
+        Ok(())
+    }
+
+    /// 将排除文件内容中的每一行模式添加到配置中（跳过空行、注释和重复项）
+    fn add_ignore_patterns(&mut self, content: &str) {
         for line in content.lines() {
             let line = line.trim();
             // 跳过空行和注释行
@@ -136,46 +575,300 @@ impl BackupConfig {
                 self.exclude_patterns.push(line.to_string());
             }
         }
+    }
+}
 
-        Ok(())
+/// 备份配置的构建器（由 [`BackupConfig::builder`] 创建）
+///
+/// 只设置需要的选项，最后调用 [`finish`](BackupConfigBuilder::finish) 读取排除文件并检查配置。
+/// 以后新增的选项只会增加新的设置方法，不影响已有代码。
+#[derive(Debug, Clone)]
+pub struct BackupConfigBuilder {
+    /// 正在构建的配置
+    config: BackupConfig,
+
+    /// 是否读取源目录中的 `.recall.toml` 和 `.recallignore`
+    read_ignore_files: bool,
+}
+
+impl BackupConfigBuilder {
+    /// 添加一个排除模式（Glob 风格）
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.config.exclude_patterns.push(pattern.into());
+        self
     }
 
-    /// 创建默认的 `.recallignore` 文件
-    ///
-    /// 根据操作系统创建不同的默认排除内容。
+    /// 添加多个排除模式（Glob 风格）
+    pub fn excludes<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.exclude_patterns.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// 额外读取的排除文件（`.recallignore` 格式，不存在时 `finish` 返回错误）
+    pub fn ignore_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.ignore_file = Some(path.into());
+        self
+    }
+
+    /// 是否读取源目录中的项目配置文件和 `.recallignore`（默认读取）
     ///
-    /// # 参数
-    /// * `path` - 要创建的文件路径
+    /// 关闭后只使用显式设置的排除模式和排除文件，也不添加内置的默认排除模式。
+    pub fn read_ignore_files(mut self, read: bool) -> Self {
+        self.read_ignore_files = read;
+        self
+    }
+
+    /// 是否启用内容检查（比较哈希值，而不只是大小和修改时间）
+    pub fn check_content(mut self, check_content: bool) -> Self {
+        self.config.check_content = check_content;
+        self
+    }
+
+    /// 工作线程数量（0 表示根据目标自动选择）
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.config.workers = workers;
+        self
+    }
+
+    /// 是否启用 VSS 快照，以及 VSS 不可用时是否继续备份（仅 Windows）
+    pub fn vss(mut self, vss: bool, fallback: bool) -> Self {
+        self.config.vss = vss;
+        self.config.vss_fallback = fallback;
+        self
+    }
+
+    /// 参与 VSS 快照的写入器名称
+    pub fn vss_writers(mut self, writers: Vec<String>) -> Self {
+        self.config.vss_writers = writers;
+        self
+    }
+
+    /// 是否保留所有者和扩展属性
+    pub fn preserve_metadata(mut self, preserve: bool) -> Self {
+        self.config.preserve_metadata = preserve;
+        self
+    }
+
+    /// 复制缓冲区大小（0 表示系统默认）和是否对大文件使用无缓冲 I/O
+    pub fn buffer(mut self, buffer_size: usize, unbuffered: bool) -> Self {
+        self.config.buffer_size = buffer_size;
+        self.config.unbuffered = unbuffered;
+        self
+    }
+
+    /// 带宽上限（字节/秒，0 表示不限制）
+    pub fn bandwidth_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.config.bandwidth_limit = bytes_per_sec;
+        self
+    }
+
+    /// 是否跳过备份前的可用空间检查
+    pub fn skip_space_check(mut self, skip: bool) -> Self {
+        self.config.skip_space_check = skip;
+        self
+    }
+
+    /// 被锁定的文件是否计为失败
+    pub fn fail_on_locked(mut self, fail: bool) -> Self {
+        self.config.fail_on_locked = fail;
+        self
+    }
+
+    /// 失败的文件超过 `max_failures` 个时如何处理快照
+    pub fn on_failure(mut self, policy: FailurePolicy, max_failures: u64) -> Self {
+        self.config.on_failure = policy;
+        self.config.max_failures = max_failures;
+        self
+    }
+
+    /// 提交后是否把快照设为只读
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.config.read_only = read_only;
+        self
+    }
+
+    /// 提交时是否写入完整性清单
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.config.manifest = manifest;
+        self
+    }
+
+    /// 快照目录的命名规则
+    pub fn naming(mut self, naming: SnapshotNaming) -> Self {
+        self.config.naming = naming;
+        self
+    }
+
+    /// 备份后保留的快照数量
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.config.keep = Some(keep);
+        self
+    }
+
+    /// 是否为试运行模式（不实际复制文件）
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.config.dry_run = dry_run;
+        self
+    }
+
+    /// 完成构建：读取项目配置文件和排除文件，并检查配置
     ///
     /// # 返回
-    /// * `Ok(())` - 创建成功
-    /// * `Err(anyhow::Error)` - 创建失败
-    fn create_default_ignore_file(&self, path: &PathBuf) -> Result<()> {
-        let mut default_content = String::from(
-            "# Recall Ignore File\n# Add patterns to exclude from backup (Glob style)\n\n# --- Common ---\n.git\n.svn\n.DS_Store\nThumbs.db\n\n"
-        );
+    /// * `Ok(BackupConfig)` - 创建的备份配置
+    /// * `Err(RecallError::Config)` - 源路径不是目录、排除模式无效、`keep` 为 0，
+    ///   或读取项目配置文件、排除文件失败
+    pub fn finish(self) -> Result<BackupConfig> {
+        let mut config = self.config;
+        if !config.source.is_dir() {
+            return Err(RecallError::Config(anyhow::anyhow!(
+                "Source {:?} is not a directory",
+                config.source
+            )));
+        }
+        if config.keep == Some(0) {
+            return Err(RecallError::Config(anyhow::anyhow!(
+                "keep = 0 would delete every snapshot, use at least 1"
+            )));
+        }
 
-        // Windows 特定的排除项
-        #[cfg(windows)]
-        {
-            default_content.push_str(
-                "# --- Windows System ---\nSystem Volume Information\n$RECYCLE.BIN\nRecovery\npagefile.sys\nhiberfil.sys\nswapfile.sys\nDumpStack.log.tmp\n"
-            );
+        if self.read_ignore_files {
+            config.process_project_file()?;
+            config.process_recallignore()?;
+        } else if let Some(path) = config.ignore_file.clone() {
+            config.read_ignore_file(&path)?;
         }
 
-        // Linux/macOS 特定的排除项
-        #[cfg(not(windows))]
-        {
-            default_content.push_str("# --- Linux/macOS ---\n/proc\n/sys\n/dev\n");
+        for pattern in &config.exclude_patterns {
+            if let Err(e) = glob::Pattern::new(pattern) {
+                return Err(RecallError::Config(anyhow::anyhow!(
+                    "Invalid exclude pattern {:?}: {}",
+                    pattern,
+                    e
+                )));
+            }
         }
+        Ok(config)
+    }
+}
+
+/// 生成默认的排除文件内容
+///
+/// 根据操作系统包含不同的默认排除项。
+fn default_ignore_content() -> String {
+    let mut default_content = String::from(
+        "# Recall Ignore File\n# Add patterns to exclude from backup (Glob style)\n\n# --- Common ---\n.git\n.svn\n.DS_Store\nThumbs.db\n\n"
+    );
 
-        let mut file = fs::File::create(path).context("Failed to create .recallignore")?;
-        file.write_all(default_content.as_bytes())?;
+    // Windows 特定的排除项
+    #[cfg(windows)]
+    {
+        default_content.push_str(
+            "# --- Windows System ---\nSystem Volume Information\n$RECYCLE.BIN\nRecovery\npagefile.sys\nhiberfil.sys\nswapfile.sys\nDumpStack.log.tmp\n"
+        );
+    }
 
-        if !self.dry_run {
-            println!("Created default ignore file at: {:?}", path);
+    // Linux/macOS 特定的排除项
+    #[cfg(not(windows))]
+    {
+        default_content.push_str("# --- Linux/macOS ---\n/proc\n/sys\n/dev\n");
+    }
+
+    default_content
+}
+
+/// 在源目录中创建包含默认排除项的 `.recallignore` 文件（`--init-ignore`）
+///
+/// # 参数
+/// * `source` - 源目录
+///
+/// # 返回
+/// * `Ok(Some(PathBuf))` - 创建的文件路径
+/// * `Ok(None)` - 文件已存在，未做修改
+/// * `Err(RecallError)` - 创建失败（如源目录只读）
+pub fn write_default_ignore_file(source: &Path) -> Result<Option<PathBuf>> {
+    let path = source.join(IGNORE_FILE);
+    if path.exists() {
+        return Ok(None);
+    }
+    let mut file = fs::File::create(&path)
+        .with_context(|| format!("Failed to create {:?}", path))
+        .classify(RecallError::Config)?;
+    file.write_all(default_ignore_content().as_bytes())
+        .with_context(|| format!("Failed to write {:?}", path))
+        .classify(RecallError::Config)?;
+    Ok(Some(path))
+}
+
+/// 检查源目录和目标目录是否重叠
+///
+/// 两个路径都先解析为规范路径（目标不存在时解析其最近的已存在上级目录），
+/// 避免相对路径、符号链接或 `..` 绕过检查。
+///
+/// # 参数
+/// * `source` - 源目录
+/// * `destination` - 目标目录
+/// * `exclude_patterns` - 排除模式（目标位于源目录中时，目标或其所在的某一级目录被排除即视为安全）
+///
+/// # 返回
+/// * `Ok(())` - 两个目录互不包含，或源目录中的目标已被排除
+/// * `Err(anyhow::Error)` - 源目录和目标相同、源目录位于目标中，或目标位于源目录中且未被排除
+pub fn check_path_overlap(source: &Path, destination: &Path, exclude_patterns: &[String]) -> anyhow::Result<()> {
+    let source = resolve_path(source);
+    let destination = resolve_path(destination);
+
+    if source == destination {
+        anyhow::bail!("Source and destination are the same directory ({:?})", source);
+    }
+    if source.starts_with(&destination) {
+        anyhow::bail!(
+            "Source {:?} is inside the destination {:?}, snapshots would be written next to the files being backed up. \
+             Choose a destination that does not contain the source",
+            source,
+            destination
+        );
+    }
+    let Ok(rel) = destination.strip_prefix(&source) else {
+        return Ok(());
+    };
+
+    // 扫描时被排除的目录不会再进入，因此目标的任一级上级目录被排除即可
+    let patterns: Vec<glob::Pattern> = exclude_patterns.iter().filter_map(|p| glob::Pattern::new(p).ok()).collect();
+    let mut prefix = PathBuf::new();
+    for component in rel.components() {
+        prefix.push(component);
+        if crate::utils::matches_exclude_pattern(&prefix, &patterns) {
+            return Ok(());
         }
+    }
 
-        Ok(())
+    anyhow::bail!(
+        "Destination {:?} is inside the source {:?}, every backup would include the previous backups. \
+         Choose a destination outside the source, or exclude it with --exclude \"{}\"",
+        destination,
+        source,
+        // 排除源目录下最上一级的目录即可，同一目标中的其他项目也不会被备份
+        Path::new(rel.components().next().unwrap().as_os_str()).display()
+    )
+}
+
+/// 解析为规范路径；路径不存在时解析最近的已存在上级目录，再接上其余部分
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            return rest.iter().rev().fold(resolved, |acc, name| acc.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            }
+            _ => return path.to_path_buf(),
+        }
     }
 }
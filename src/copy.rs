@@ -0,0 +1,619 @@
+// Recall - 文件复制模块
+// 负责将源文件复制到备份目录，并尽量保留稀疏文件中的空洞、利用块克隆减少写入量
+
+use crate::error::{Classify, RecallError};
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 启用无缓冲 I/O 时，只有达到此大小的文件才绕过页缓存（1 GB）
+pub const UNBUFFERED_THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// 无缓冲 I/O 要求的缓冲区地址和写入长度对齐（覆盖常见的 512 / 4K 扇区）
+const DIRECT_IO_ALIGN: usize = 4096;
+
+/// 无缓冲 I/O 未指定缓冲区大小时使用的默认值（8 MB）
+const DEFAULT_DIRECT_BUFFER: usize = 8 * 1024 * 1024;
+
+/// 系统复制方式因权限不足失败时，改用逐块复制所用的缓冲区大小（1 MB）
+#[cfg(windows)]
+const BACKUP_READ_BUFFER: usize = 1024 * 1024;
+
+/// 限制带宽且未指定缓冲区大小时使用的缓冲区大小（1 MB）
+const THROTTLED_BUFFER: usize = 1024 * 1024;
+
+/// 带宽限制下一次允许写入的时间点（所有工作线程共享）
+static THROTTLE_NEXT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 文件复制选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyOptions {
+    /// 复制缓冲区大小（字节），0 表示使用系统默认的复制方式
+    pub buffer_size: usize,
+
+    /// 对大文件使用无缓冲 I/O（Windows `FILE_FLAG_NO_BUFFERING` / Linux `O_DIRECT`）
+    pub unbuffered: bool,
+
+    /// 所有工作线程合计的写入速率上限（字节/秒），0 表示不限制
+    pub bandwidth_limit: u64,
+}
+
+/// 复制单个文件
+///
+/// 对于稀疏文件（虚拟机磁盘、数据库文件等），仅复制已分配的数据区域，
+/// 并在目标文件中重新创建空洞，避免备份后文件被完全展开。
+/// 启用无缓冲 I/O 时，超过 [`UNBUFFERED_THRESHOLD`] 的文件绕过系统页缓存，
+/// 避免备份挤占用户工作集；指定了缓冲区大小或带宽限制时逐块复制；
+/// 其他文件直接使用 `fs::copy`。稀疏文件不受带宽限制。
+///
+/// # 参数
+/// * `src` - 源文件路径
+/// * `dest` - 目标文件路径
+/// * `options` - 复制选项
+///
+/// # 返回
+/// * `Ok(u64)` - 实际写入的字节数
+/// * `Err(RecallError)` - 复制失败
+pub fn copy_file(src: &Path, dest: &Path, options: &CopyOptions) -> crate::error::Result<u64> {
+    copy_contents(src, dest, options).classify(RecallError::Copy)
+}
+
+/// 按文件类型和复制选项选择复制方式（见 [`copy_file`]）
+fn copy_contents(src: &Path, dest: &Path, options: &CopyOptions) -> Result<u64> {
+    let src_meta = fs::metadata(src).with_context(|| format!("Failed to read metadata of {:?}", src))?;
+
+    if is_sparse(&src_meta) {
+        return copy_sparse(src, dest)
+            .with_context(|| format!("Failed to sparse-copy {:?} to {:?}", src, dest));
+    }
+
+    if options.unbuffered && src_meta.len() >= UNBUFFERED_THRESHOLD {
+        let buffer_size = match options.buffer_size {
+            0 => DEFAULT_DIRECT_BUFFER,
+            n => n,
+        };
+        match copy_with_buffer(src, dest, buffer_size, true, options.bandwidth_limit) {
+            Ok(bytes) => return Ok(bytes),
+            // 文件系统不支持无缓冲 I/O（tmpfs、部分网络共享）时回退为普通复制，其他错误照常报告
+            Err(e) if is_direct_io_unsupported(&e) => {
+                tracing::debug!(path = %dest.display(), error = %e, "unbuffered I/O unsupported, copying with the page cache");
+                fs::remove_file(dest).ok();
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to copy {:?} to {:?}", src, dest)),
+        }
+    }
+
+    if options.buffer_size > 0 || options.bandwidth_limit > 0 {
+        let buffer_size = match options.buffer_size {
+            0 => THROTTLED_BUFFER,
+            n => n,
+        };
+        return copy_with_buffer(src, dest, buffer_size, false, options.bandwidth_limit)
+            .with_context(|| format!("Failed to copy {:?} to {:?}", src, dest));
+    }
+
+    match fs::copy(src, dest) {
+        Ok(bytes) => Ok(bytes),
+        // `CopyFileExW` 不使用备份语义；受 ACL 保护的文件改为以备份语义打开后逐块复制
+        #[cfg(windows)]
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            copy_with_buffer(src, dest, BACKUP_READ_BUFFER, false, options.bandwidth_limit)
+                .with_context(|| format!("Failed to copy {:?} to {:?}", src, dest))
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to copy {:?} to {:?}", src, dest)),
+    }
+}
+
+/// 检查错误是否表示文件系统不支持无缓冲 I/O
+///
+/// - Unix: 打开时不支持 `O_DIRECT`，或读写未按设备扇区对齐（`EINVAL`、`ENOTSUP`、`EOPNOTSUPP`）
+/// - Windows: `ERROR_INVALID_PARAMETER`、`ERROR_NOT_SUPPORTED`
+fn is_direct_io_unsupported(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    const UNSUPPORTED: &[i32] = &[libc::EINVAL, libc::ENOTSUP, libc::EOPNOTSUPP];
+    #[cfg(windows)]
+    const UNSUPPORTED: &[i32] = &[
+        winapi::shared::winerror::ERROR_INVALID_PARAMETER as i32,
+        winapi::shared::winerror::ERROR_NOT_SUPPORTED as i32,
+    ];
+
+    err.raw_os_error().is_some_and(|code| UNSUPPORTED.contains(&code))
+}
+
+/// 以只读方式打开源文件
+///
+/// 在 Windows 上使用 `FILE_FLAG_BACKUP_SEMANTICS` 打开，
+/// 进程启用了备份特权时可以读取受 ACL 保护的文件。
+///
+/// # 参数
+/// * `path` - 源文件路径
+///
+/// # 返回
+/// * `Ok(File)` - 打开的文件
+/// * `Err(io::Error)` - 打开失败
+pub fn open_for_backup(path: &Path) -> io::Result<File> {
+    open_source(path, false)
+}
+
+/// 使用指定大小的缓冲区逐块复制文件
+///
+/// `direct` 为 `true` 时目标文件以无缓冲方式打开：缓冲区按 [`DIRECT_IO_ALIGN`] 对齐，
+/// 最后一个不完整的块补零写入后再截断到实际长度。
+/// `bandwidth_limit` 大于 0 时每写入一块都按速率上限等待。
+fn copy_with_buffer(
+    src: &Path,
+    dest: &Path,
+    buffer_size: usize,
+    direct: bool,
+    bandwidth_limit: u64,
+) -> io::Result<u64> {
+    let mut src_file = open_source(src, direct)?;
+    let src_meta = src_file.metadata()?;
+    let mut dest_file = open_dest(dest, direct)?;
+
+    let size = if direct {
+        buffer_size.max(DIRECT_IO_ALIGN).next_multiple_of(DIRECT_IO_ALIGN)
+    } else {
+        buffer_size
+    };
+    let mut storage = vec![0u8; size + DIRECT_IO_ALIGN];
+    let start = storage.as_ptr().align_offset(DIRECT_IO_ALIGN);
+    let buf = &mut storage[start..start + size];
+
+    let mut total = 0u64;
+    loop {
+        let n = read_full(&mut src_file, buf)?;
+        if n == 0 {
+            break;
+        }
+
+        if direct && n % DIRECT_IO_ALIGN != 0 {
+            let padded = n.next_multiple_of(DIRECT_IO_ALIGN);
+            buf[n..padded].fill(0);
+            dest_file.write_all(&buf[..padded])?;
+        } else {
+            dest_file.write_all(&buf[..n])?;
+        }
+        total += n as u64;
+        if bandwidth_limit > 0 {
+            throttle(n as u64, bandwidth_limit);
+        }
+
+        // 已读取的源数据不再需要，通知内核释放对应的页缓存
+        #[cfg(target_os = "linux")]
+        if direct {
+            use std::os::unix::io::AsRawFd;
+            unsafe { libc::posix_fadvise(src_file.as_raw_fd(), 0, total as i64, libc::POSIX_FADV_DONTNEED) };
+        }
+
+        if n < size {
+            break;
+        }
+    }
+
+    if direct {
+        dest_file.set_len(total)?;
+    }
+    drop(dest_file);
+
+    fs::set_permissions(dest, src_meta.permissions())?;
+    Ok(total)
+}
+
+/// 按带宽上限等待，使所有工作线程的合计写入速率不超过 `limit` 字节/秒
+fn throttle(bytes: u64, limit: u64) {
+    let wait = {
+        let mut next = THROTTLE_NEXT.lock().unwrap();
+        let now = Instant::now();
+        let start = next.filter(|t| *t > now).unwrap_or(now);
+        let end = start + Duration::from_secs_f64(bytes as f64 / limit as f64);
+        *next = Some(end);
+        end - now
+    };
+    std::thread::sleep(wait);
+}
+
+/// 打开源文件（Windows 上使用备份语义，无缓冲模式下提示顺序读取）
+fn open_source(path: &Path, direct: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_SEQUENTIAL_SCAN};
+        let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+        if direct {
+            flags |= FILE_FLAG_SEQUENTIAL_SCAN;
+        }
+        options.custom_flags(flags);
+    }
+    #[cfg(not(windows))]
+    let _ = direct;
+
+    options.open(path)
+}
+
+/// 创建目标文件（无缓冲模式下绕过系统页缓存）
+fn open_dest(path: &Path, direct: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    if direct {
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            options.custom_flags(winapi::um::winbase::FILE_FLAG_NO_BUFFERING);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_DIRECT);
+        }
+    }
+
+    options.open(path)
+}
+
+/// 检测目录所在的文件系统是否支持块克隆（reflink）
+///
+/// 在目录中创建一个探测文件并尝试克隆，完成后删除两个临时文件。
+///
+/// # 参数
+/// * `dir` - 要检测的目录（通常为备份目标根目录）
+///
+/// # 返回
+/// * `true` - 支持块克隆（ReFS、Btrfs、XFS、APFS 等）
+/// * `false` - 不支持或检测失败
+pub fn supports_reflink(dir: &Path) -> bool {
+    let probe = dir.join(".recall-reflink-probe");
+    let probe_clone = dir.join(".recall-reflink-probe.clone");
+
+    let supported = fs::write(&probe, b"recall").is_ok()
+        && reflink_copy::reflink(&probe, &probe_clone).is_ok();
+
+    fs::remove_file(&probe).ok();
+    fs::remove_file(&probe_clone).ok();
+    supported
+}
+
+/// 检测目录所在的文件系统是否支持硬链接
+///
+/// 在目录中创建一个探测文件并尝试为其创建硬链接，完成后删除临时文件。
+/// 只有明确表示不支持的错误（exFAT、FAT32、部分 SMB 共享等）才返回 `false`；
+/// 无法创建探测文件等其他失败视为支持，仍由复制时逐个文件处理。
+///
+/// # 参数
+/// * `dir` - 要检测的目录（通常为备份目标根目录）
+///
+/// # 返回
+/// * `true` - 支持硬链接或无法确定
+/// * `false` - 文件系统不支持硬链接
+pub fn supports_hard_links(dir: &Path) -> bool {
+    let probe = dir.join(".recall-link-probe");
+    let probe_link = dir.join(".recall-link-probe.link");
+
+    let supported = match fs::write(&probe, b"recall") {
+        Ok(()) => match fs::hard_link(&probe, &probe_link) {
+            Ok(()) => true,
+            // 刚创建的探测文件不会受保护，这里的 EPERM 表示文件系统本身不支持（如 Linux 上的 FAT32）
+            Err(e) => !crate::utils::is_link_unsupported(&e) && !crate::utils::is_link_denied(&e),
+        },
+        Err(_) => true,
+    };
+
+    fs::remove_file(&probe).ok();
+    fs::remove_file(&probe_link).ok();
+    supported
+}
+
+/// 检测目录所在的文件系统是否允许创建符号链接
+///
+/// 在目录中尝试创建一个指向不存在文件的符号链接，完成后删除。Windows 上没有开发者模式
+/// 或管理员权限时同样视为不支持。其他失败视为支持，仍由复制时逐个文件处理。
+///
+/// # 参数
+/// * `dir` - 要检测的目录（通常为备份目标根目录）
+///
+/// # 返回
+/// * `true` - 支持符号链接或无法确定
+/// * `false` - 文件系统不支持或没有权限创建符号链接
+pub fn supports_symlinks(dir: &Path) -> bool {
+    let probe_link = dir.join(".recall-symlink-probe");
+    fs::remove_file(&probe_link).ok();
+
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(".recall-symlink-probe.target", &probe_link);
+    #[cfg(windows)]
+    let result = std::os::windows::fs::symlink_file(".recall-symlink-probe.target", &probe_link);
+
+    let supported = match result {
+        Ok(()) => true,
+        Err(e) => !crate::utils::is_symlink_unsupported(&anyhow::Error::from(e)),
+    };
+
+    fs::remove_file(&probe_link).ok();
+    supported
+}
+
+/// 从上一次备份克隆文件，并只写入与源文件不同的数据块
+///
+/// 克隆后的文件与旧文件共享物理块，写入时才会触发写时复制，
+/// 因此对大文件的局部修改只会产生少量实际写入。
+///
+/// # 参数
+/// * `old` - 上一次备份中的文件（克隆来源）
+/// * `src` - 源文件路径
+/// * `dest` - 目标文件路径
+///
+/// # 返回
+/// * `Ok(u64)` - 实际写入的字节数
+/// * `Err(RecallError)` - 克隆或写入失败（调用方应回退为普通复制）
+pub fn clone_and_patch(old: &Path, src: &Path, dest: &Path) -> crate::error::Result<u64> {
+    reflink_copy::reflink(old, dest)
+        .with_context(|| format!("Failed to clone {:?} to {:?}", old, dest))
+        .classify(RecallError::Copy)?;
+
+    let result = patch_clone(src, dest)
+        .with_context(|| format!("Failed to patch {:?} from {:?}", dest, src))
+        .classify(RecallError::Copy);
+    if result.is_err() {
+        fs::remove_file(dest).ok();
+    }
+    result
+}
+
+/// 将克隆文件中与源文件不同的块覆盖为源文件内容，并调整文件长度
+fn patch_clone(src: &Path, dest: &Path) -> Result<u64> {
+    const BLOCK_SIZE: usize = 1024 * 1024;
+
+    let src_meta = fs::metadata(src)?;
+
+    // 克隆继承了旧文件的权限，可能是只读的
+    let mut perms = fs::metadata(dest)?.permissions();
+    if perms.readonly() {
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        fs::set_permissions(dest, perms)?;
+    }
+
+    let mut src_file = open_for_backup(src)?;
+    let mut dest_file = OpenOptions::new().read(true).write(true).open(dest)?;
+
+    let mut src_buf = vec![0u8; BLOCK_SIZE];
+    let mut dest_buf = vec![0u8; BLOCK_SIZE];
+    let mut offset = 0u64;
+    let mut written = 0u64;
+
+    loop {
+        let n = read_full(&mut src_file, &mut src_buf)?;
+        if n == 0 {
+            break;
+        }
+        let m = read_full(&mut dest_file, &mut dest_buf[..n])?;
+
+        if m != n || src_buf[..n] != dest_buf[..n] {
+            dest_file.seek(SeekFrom::Start(offset))?;
+            dest_file.write_all(&src_buf[..n])?;
+            written += n as u64;
+        }
+
+        offset += n as u64;
+        dest_file.seek(SeekFrom::Start(offset))?;
+    }
+
+    dest_file.set_len(src_meta.len())?;
+    drop(dest_file);
+
+    fs::set_permissions(dest, src_meta.permissions())?;
+    Ok(written)
+}
+
+/// 尽量读满缓冲区，返回实际读取的字节数（到达文件末尾时可能小于缓冲区长度）
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// 检查文件是否为稀疏文件
+///
+/// - Linux：已分配的块数小于文件逻辑大小
+/// - Windows：带有 `FILE_ATTRIBUTE_SPARSE_FILE` 属性
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn is_sparse(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    meta.is_file() && meta.blocks() * 512 < meta.len()
+}
+
+#[cfg(windows)]
+fn is_sparse(meta: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use winapi::um::winnt::FILE_ATTRIBUTE_SPARSE_FILE;
+    meta.is_file() && meta.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0
+}
+
+/// 其他平台不支持稀疏文件检测
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn is_sparse(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+/// 使用 `SEEK_DATA` / `SEEK_HOLE` 复制稀疏文件（Linux）
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn copy_sparse(src: &Path, dest: &Path) -> Result<u64> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut src_file = open_for_backup(src)?;
+    let src_meta = src_file.metadata()?;
+    let len = src_meta.len() as i64;
+
+    let mut dest_file = File::create(dest)?;
+    // 先设置逻辑大小，未写入的区域即为空洞
+    dest_file.set_len(src_meta.len())?;
+
+    let fd = src_file.as_raw_fd();
+    let mut pos: i64 = 0;
+    let mut copied = 0u64;
+
+    while pos < len {
+        let data = unsafe { libc::lseek(fd, pos, libc::SEEK_DATA) };
+        if data < 0 {
+            let err = io::Error::last_os_error();
+            // ENXIO 表示 pos 之后只剩空洞
+            if err.raw_os_error() == Some(libc::ENXIO) {
+                break;
+            }
+            return Err(err.into());
+        }
+
+        let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) };
+        if hole < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        copied += copy_range(&mut src_file, &mut dest_file, data as u64, (hole - data) as u64)?;
+        pos = hole;
+    }
+
+    fs::set_permissions(dest, src_meta.permissions())?;
+    Ok(copied)
+}
+
+/// 使用 `FSCTL_QUERY_ALLOCATED_RANGES` / `FSCTL_SET_SPARSE` 复制稀疏文件（Windows）
+#[cfg(windows)]
+fn copy_sparse(src: &Path, dest: &Path) -> Result<u64> {
+    let mut src_file = open_for_backup(src)?;
+    let src_meta = src_file.metadata()?;
+
+    let mut dest_file = File::create(dest)?;
+    win::set_sparse(&dest_file)?;
+    dest_file.set_len(src_meta.len())?;
+
+    let mut copied = 0u64;
+    for (offset, length) in win::query_allocated_ranges(&src_file, src_meta.len())? {
+        copied += copy_range(&mut src_file, &mut dest_file, offset, length)?;
+    }
+
+    fs::set_permissions(dest, src_meta.permissions())?;
+    Ok(copied)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn copy_sparse(src: &Path, dest: &Path) -> Result<u64> {
+    Ok(fs::copy(src, dest)?)
+}
+
+/// 将源文件中指定区域复制到目标文件的相同偏移处
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+fn copy_range(src: &mut File, dest: &mut File, offset: u64, length: u64) -> Result<u64> {
+    src.seek(SeekFrom::Start(offset))?;
+    dest.seek(SeekFrom::Start(offset))?;
+    let copied = io::copy(&mut src.take(length), dest)?;
+    Ok(copied)
+}
+
+/// Windows 稀疏文件相关的 DeviceIoControl 封装
+#[cfg(windows)]
+mod win {
+    use std::fs::File;
+    use std::io;
+    use std::mem::size_of;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr::null_mut;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::winerror::ERROR_MORE_DATA;
+    use winapi::um::ioapiset::DeviceIoControl;
+    use winapi::um::winioctl::{FSCTL_QUERY_ALLOCATED_RANGES, FSCTL_SET_SPARSE};
+
+    /// `FILE_ALLOCATED_RANGE_BUFFER`（winapi 未导出）
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct AllocatedRange {
+        file_offset: i64,
+        length: i64,
+    }
+
+    /// 将文件标记为稀疏文件
+    pub fn set_sparse(file: &File) -> io::Result<()> {
+        let mut returned: DWORD = 0;
+        let ok = unsafe {
+            DeviceIoControl(
+                file.as_raw_handle() as _,
+                FSCTL_SET_SPARSE,
+                null_mut(),
+                0,
+                null_mut(),
+                0,
+                &mut returned,
+                null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// 查询文件中已分配（包含数据）的区域列表
+    pub fn query_allocated_ranges(file: &File, len: u64) -> io::Result<Vec<(u64, u64)>> {
+        let mut ranges = Vec::new();
+        let mut query = AllocatedRange {
+            file_offset: 0,
+            length: len as i64,
+        };
+        let mut buffer = [AllocatedRange::default(); 64];
+
+        loop {
+            let mut returned: DWORD = 0;
+            let ok = unsafe {
+                DeviceIoControl(
+                    file.as_raw_handle() as _,
+                    FSCTL_QUERY_ALLOCATED_RANGES,
+                    &mut query as *mut _ as *mut _,
+                    size_of::<AllocatedRange>() as DWORD,
+                    buffer.as_mut_ptr() as *mut _,
+                    (buffer.len() * size_of::<AllocatedRange>()) as DWORD,
+                    &mut returned,
+                    null_mut(),
+                )
+            };
+            let more = if ok == 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(ERROR_MORE_DATA as i32) {
+                    return Err(err);
+                }
+                true
+            } else {
+                false
+            };
+
+            let count = returned as usize / size_of::<AllocatedRange>();
+            for range in &buffer[..count] {
+                ranges.push((range.file_offset as u64, range.length as u64));
+            }
+
+            if !more || count == 0 {
+                break;
+            }
+
+            // 从最后一个区域之后继续查询
+            let last = buffer[count - 1];
+            let next = last.file_offset + last.length;
+            query.length -= next - query.file_offset;
+            query.file_offset = next;
+        }
+
+        Ok(ranges)
+    }
+}
@@ -0,0 +1,153 @@
+// Recall - 凭据模块
+// 将访问备份目标所需的密码保存在系统凭据管理器中（Windows 凭据管理器、macOS 钥匙串、Linux Secret Service），不写入 config.toml
+
+use anyhow::{Context, Result};
+use keyring::Entry;
+use std::path::Path;
+
+/// 系统凭据管理器中的服务名前缀
+const SERVICE_PREFIX: &str = "recall";
+
+/// 获取备份目标对应的凭据名称
+///
+/// UNC 路径（`\\server\share\...`）使用共享根路径，URL 形式的目标（如 `s3://bucket/prefix`）
+/// 使用协议和主机部分，同一共享或存储桶下的多个目标共用一份凭据；其他路径使用路径本身。
+///
+/// # 参数
+/// * `destination` - 备份目标路径
+///
+/// # 返回
+/// 凭据名称
+pub fn credential_target(destination: &Path) -> String {
+    share_root(destination)
+        .or_else(|| url_root(destination))
+        .unwrap_or_else(|| destination.to_string_lossy().into_owned())
+}
+
+/// 获取 URL 形式目标的协议和主机部分（`scheme://authority`）
+fn url_root(path: &Path) -> Option<String> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let (scheme, rest) = path.split_once("://")?;
+    let authority = rest.split('/').next().filter(|a| !a.is_empty())?;
+    Some(format!("{}://{}", scheme, authority))
+}
+
+/// 获取 UNC 路径的共享根路径（`\\server\share`）
+///
+/// # 返回
+/// * `Some(String)` - 共享根路径
+/// * `None` - 不是 UNC 路径
+pub fn share_root(path: &Path) -> Option<String> {
+    let path = path.to_string_lossy();
+    let rest = path
+        .strip_prefix(r"\\?\UNC\")
+        .or_else(|| path.strip_prefix(r"\\").filter(|rest| !rest.starts_with(r"?\")))?;
+    let mut parts = rest.split(['\\', '/']).filter(|part| !part.is_empty());
+    let server = parts.next()?;
+    let share = parts.next()?;
+    Some(format!(r"\\{}\{}", server, share))
+}
+
+/// 打开系统凭据管理器中的条目
+fn entry(target: &str, user: &str) -> Result<Entry> {
+    Entry::new(&format!("{}:{}", SERVICE_PREFIX, target), user)
+        .context("Cannot access the system credential store")
+}
+
+/// 在系统凭据管理器中保存密码（已存在时覆盖）
+///
+/// # 参数
+/// * `target` - 凭据名称（见 [`credential_target`]）
+/// * `user` - 用户名
+/// * `password` - 密码
+pub fn set_password(target: &str, user: &str, password: &str) -> Result<()> {
+    entry(target, user)?
+        .set_password(password)
+        .with_context(|| format!("Failed to store the password for {}@{}", user, target))
+}
+
+/// 从系统凭据管理器中读取密码
+///
+/// # 返回
+/// * `Ok(String)` - 保存的密码
+/// * `Err(anyhow::Error)` - 没有保存密码或无法访问凭据管理器
+pub fn get_password(target: &str, user: &str) -> Result<String> {
+    match entry(target, user)?.get_password() {
+        Ok(password) => Ok(password),
+        Err(keyring::Error::NoEntry) => anyhow::bail!(
+            "No password stored for {}@{}. Run `recall credential set` first",
+            user,
+            target
+        ),
+        Err(e) => Err(e).with_context(|| format!("Failed to read the password for {}@{}", user, target)),
+    }
+}
+
+/// 从系统凭据管理器中删除密码
+///
+/// # 返回
+/// * `Ok(true)` - 已删除
+/// * `Ok(false)` - 没有保存密码
+/// * `Err(anyhow::Error)` - 无法访问凭据管理器
+pub fn delete_password(target: &str, user: &str) -> Result<bool> {
+    match entry(target, user)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to delete the password for {}@{}", user, target)),
+    }
+}
+
+/// 使用保存的凭据连接备份目标所在的网络共享
+///
+/// 仅 Windows 下连接 UNC 共享（`WNetAddConnection2`）；目标不是 UNC 路径时不做任何操作。
+/// 其他平台上网络共享由系统挂载，无需连接。
+///
+/// # 参数
+/// * `destination` - 备份目标路径
+/// * `user` - 用户名
+///
+/// # 返回
+/// * `Ok(())` - 已连接（或无需连接）
+/// * `Err(anyhow::Error)` - 读取密码或连接失败
+#[cfg(windows)]
+pub fn connect(destination: &Path, user: &str) -> Result<()> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::winerror::{ERROR_SESSION_CREDENTIAL_CONFLICT, NO_ERROR};
+    use winapi::um::winnetwk::{WNetAddConnection2W, NETRESOURCEW, RESOURCETYPE_DISK};
+
+    let Some(share) = share_root(destination) else {
+        return Ok(());
+    };
+    let password = get_password(&share, user)?;
+
+    let wide = |s: &str| -> Vec<u16> { OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect() };
+    let mut remote = wide(&share);
+    let user_w = wide(user);
+    let password_w = wide(&password);
+
+    // SAFETY: NETRESOURCEW 是纯数据结构，全零是有效的初始值；所有字符串在调用期间有效
+    let result = unsafe {
+        let mut resource: NETRESOURCEW = std::mem::zeroed();
+        resource.dwType = RESOURCETYPE_DISK;
+        resource.lpRemoteName = remote.as_mut_ptr();
+        WNetAddConnection2W(&mut resource, password_w.as_ptr(), user_w.as_ptr(), 0)
+    };
+
+    match result {
+        NO_ERROR => Ok(()),
+        // 已用其他凭据连接到同一服务器，沿用现有连接
+        ERROR_SESSION_CREDENTIAL_CONFLICT => Ok(()),
+        code => Err(std::io::Error::from_raw_os_error(code as i32))
+            .with_context(|| format!("Failed to connect to {} as {}", share, user)),
+    }
+}
+
+/// 使用保存的凭据连接备份目标所在的网络共享
+///
+/// 仅 Windows 下连接 UNC 共享（`WNetAddConnection2`）；目标不是 UNC 路径时不做任何操作。
+/// 其他平台上网络共享由系统挂载，无需连接。
+#[cfg(not(windows))]
+pub fn connect(_destination: &Path, _user: &str) -> Result<()> {
+    Ok(())
+}
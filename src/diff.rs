@@ -0,0 +1,215 @@
+// Recall - 快照比较模块
+// 比较两个快照的文件列表，或比较源目录与最新快照，找出新增、删除和修改的文件
+
+use crate::actions::SyncAction;
+use crate::config::BackupConfig;
+use crate::events::ConsoleSink;
+use crate::scanner::{find_latest_backup, scan_source};
+use crate::summary::BackupSummary;
+use crate::utils::file_identity;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// 文件变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// 只存在于新快照中
+    Added,
+
+    /// 只存在于旧快照中
+    Removed,
+
+    /// 两个快照中内容不同
+    Modified,
+
+    /// 未变化（仅在与源目录比较时报告）
+    Unchanged,
+}
+
+/// 单个文件的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    /// 相对于快照根目录的路径
+    pub path: PathBuf,
+
+    /// 变化类型
+    pub kind: ChangeKind,
+
+    /// 旧快照中的文件大小
+    pub old_size: Option<u64>,
+
+    /// 新快照中的文件大小
+    pub new_size: Option<u64>,
+}
+
+/// 快照中单个文件的信息
+struct Entry {
+    size: u64,
+    modified: Option<SystemTime>,
+    identity: Option<(u64, u64)>,
+}
+
+/// 比较两个快照
+///
+/// 两个快照中硬链接到同一数据的文件视为未修改；
+/// 否则大小或修改时间不同的文件视为已修改。
+///
+/// # 参数
+/// * `old` - 旧快照目录
+/// * `new` - 新快照目录
+///
+/// # 返回
+/// * `Ok(Vec<FileChange>)` - 按路径排序的变化列表
+/// * `Err(anyhow::Error)` - 快照不存在或读取失败
+pub fn diff_snapshots(old: &Path, new: &Path) -> Result<Vec<FileChange>> {
+    let old_entries = snapshot_entries(old)?;
+    let mut new_entries = snapshot_entries(new)?;
+    let mut changes = Vec::new();
+
+    for (path, old_entry) in old_entries {
+        match new_entries.remove(&path) {
+            None => changes.push(FileChange {
+                path,
+                kind: ChangeKind::Removed,
+                old_size: Some(old_entry.size),
+                new_size: None,
+            }),
+            Some(new_entry) => {
+                let same_file = old_entry.identity.is_some() && old_entry.identity == new_entry.identity;
+                if !same_file
+                    && (old_entry.size != new_entry.size || old_entry.modified != new_entry.modified)
+                {
+                    changes.push(FileChange {
+                        path,
+                        kind: ChangeKind::Modified,
+                        old_size: Some(old_entry.size),
+                        new_size: Some(new_entry.size),
+                    });
+                }
+            }
+        }
+    }
+
+    changes.extend(new_entries.into_iter().map(|(path, entry)| FileChange {
+        path,
+        kind: ChangeKind::Added,
+        old_size: None,
+        new_size: Some(entry.size),
+    }));
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// 比较源目录与最新快照，报告下一次备份将执行的变化
+///
+/// 复用扫描器和 `decide_action` 的决策逻辑，但不执行任何操作：
+/// 复制新文件记为新增，复制或克隆已修改文件记为修改，硬链接记为未变化；
+/// 最新快照中存在而源目录中已不存在（或已被排除）的文件记为删除。
+///
+/// # 参数
+/// * `config` - 备份配置（`destination` 为项目目录）
+///
+/// # 返回
+/// * `Ok(Vec<FileChange>)` - 按路径排序的变化列表（包含未变化的文件）
+/// * `Err(anyhow::Error)` - 扫描失败
+pub fn diff_against_source(config: &BackupConfig) -> Result<Vec<FileChange>> {
+    let latest = find_latest_backup(&config.destination, &config.naming)?;
+
+    let mut scan_config = config.clone();
+    scan_config.resume = false;
+    scan_config.reflink = false;
+
+    // 扫描器只需要一个目标目录来构造任务路径，不会写入
+    let placeholder = latest.clone().unwrap_or_else(|| config.destination.clone());
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let scanner = {
+        let latest = latest.clone();
+        std::thread::spawn(move || scan_source(&scan_config, &placeholder, latest.as_deref(), tx, &ConsoleSink::new()))
+    };
+
+    let mut changes = Vec::new();
+    let mut seen = HashSet::new();
+    for (task, action) in rx {
+        let src_size = std::fs::symlink_metadata(&task.src_path).map(|m| m.len()).ok();
+        let old_size = task
+            .old_path
+            .as_ref()
+            .and_then(|p| std::fs::symlink_metadata(p).ok())
+            .map(|m| m.len());
+
+        let kind = match action {
+            SyncAction::CreateDir | SyncAction::Skip | SyncAction::Resumed => continue,
+            SyncAction::CopyNew => ChangeKind::Added,
+            SyncAction::MakeSymlink(_) if old_size.is_none() => ChangeKind::Added,
+            SyncAction::MakeSymlink(_) | SyncAction::CopyModified | SyncAction::Clone(_) => {
+                ChangeKind::Modified
+            }
+            SyncAction::Link(_) => ChangeKind::Unchanged,
+        };
+        seen.insert(task.rel_path.clone());
+        changes.push(FileChange {
+            path: task.rel_path,
+            kind,
+            old_size: if kind == ChangeKind::Added { None } else { old_size },
+            new_size: src_size,
+        });
+    }
+    scanner.join().map_err(|_| anyhow::anyhow!("Scanner thread panicked"))??;
+
+    if let Some(ref latest) = latest {
+        for (path, entry) in snapshot_entries(latest)? {
+            if !seen.contains(&path) {
+                changes.push(FileChange {
+                    path,
+                    kind: ChangeKind::Removed,
+                    old_size: Some(entry.size),
+                    new_size: None,
+                });
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// 列出快照中的所有文件（不含 recall 自身写入的摘要、清单和自描述信息）
+fn snapshot_entries(dir: &Path) -> Result<BTreeMap<PathBuf, Entry>> {
+    if !dir.is_dir() {
+        bail!("Backup directory {:?} does not exist", dir);
+    }
+
+    let summary = BackupSummary::load(dir).ok().flatten();
+    let mut entries = BTreeMap::new();
+    for entry in WalkDir::new(dir).follow_links(false).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = match entry.path().strip_prefix(dir) {
+            Ok(rel) => rel.to_path_buf(),
+            Err(_) => continue,
+        };
+        if BackupSummary::is_metadata(&rel, summary.as_ref()) {
+            continue;
+        }
+        let meta = match entry.metadata() {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+        entries.insert(
+            rel,
+            Entry {
+                size: meta.len(),
+                modified: meta.modified().ok(),
+                identity: file_identity(entry.path()),
+            },
+        );
+    }
+    Ok(entries)
+}
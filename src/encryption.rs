@@ -0,0 +1,344 @@
+// Recall - 加密模块
+// 分块仓库的静态加密：数据块和快照索引在写入存储前用 XChaCha20-Poly1305 加密，
+// 密钥由口令或密钥文件经 Argon2id 派生，备份盘或远程存储不可信时使用
+
+use crate::chunks::ObjectStore;
+use anyhow::{Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+
+/// 加密对象的格式标识
+const MAGIC: &[u8; 4] = b"RCE1";
+
+/// XChaCha20-Poly1305 的随机数长度
+const NONCE_LEN: usize = 24;
+
+/// 口令的环境变量
+pub const PASSPHRASE_ENV: &str = "RECALL_PASSPHRASE";
+
+/// 保存在仓库中的密钥信封：用口令派生的密钥加密的随机主密钥
+///
+/// 更换口令只需重新加密信封，不需要重新加密已有数据。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyEnvelope {
+    /// 密钥派生算法（`argon2id`）
+    pub kdf: String,
+
+    /// Argon2 内存开销（KiB）
+    pub m_cost: u32,
+
+    /// Argon2 迭代次数
+    pub t_cost: u32,
+
+    /// Argon2 并行度
+    pub p_cost: u32,
+
+    /// 盐（十六进制）
+    pub salt: String,
+
+    /// 加密后的主密钥（随机数 + 密文，十六进制）
+    pub keys: String,
+}
+
+/// 仓库主密钥
+#[derive(Clone)]
+pub struct Keys {
+    /// 加密对象内容的密钥
+    data: [u8; 32],
+
+    /// 计算数据块标识（HMAC-SHA256）的密钥，避免标识泄露明文内容的哈希值
+    id: [u8; 32],
+}
+
+impl Keys {
+    /// 生成新的随机主密钥
+    pub fn generate() -> Self {
+        let mut keys = Self { data: [0; 32], id: [0; 32] };
+        OsRng.fill_bytes(&mut keys.data);
+        OsRng.fill_bytes(&mut keys.id);
+        keys
+    }
+
+    /// 用口令加密主密钥，生成可保存在仓库中的信封
+    pub fn seal(&self, passphrase: &[u8]) -> Result<KeyEnvelope> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let mut envelope = KeyEnvelope {
+            kdf: "argon2id".to_string(),
+            m_cost: Params::DEFAULT_M_COST * 4,
+            t_cost: Params::DEFAULT_T_COST + 1,
+            p_cost: Params::DEFAULT_P_COST,
+            salt: hex::encode(salt),
+            keys: String::new(),
+        };
+
+        let mut plain = [0u8; 64];
+        plain[..32].copy_from_slice(&self.data);
+        plain[32..].copy_from_slice(&self.id);
+        let sealed = seal(&derive_key(&envelope, passphrase)?, b"recall-keys", &plain)?;
+        envelope.keys = hex::encode(sealed);
+        Ok(envelope)
+    }
+
+    /// 用口令解开信封中的主密钥
+    ///
+    /// # 返回
+    /// * `Ok(Keys)` - 主密钥
+    /// * `Err(anyhow::Error)` - 口令或密钥文件错误，或信封已损坏
+    pub fn open(envelope: &KeyEnvelope, passphrase: &[u8]) -> Result<Self> {
+        if envelope.kdf != "argon2id" {
+            anyhow::bail!("Unsupported key derivation '{}'", envelope.kdf);
+        }
+        let sealed = hex::decode(&envelope.keys).context("Key envelope is corrupt")?;
+        let plain = open(&derive_key(envelope, passphrase)?, b"recall-keys", &sealed)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase or key file"))?;
+        if plain.len() != 64 {
+            anyhow::bail!("Key envelope is corrupt");
+        }
+
+        let mut keys = Self { data: [0; 32], id: [0; 32] };
+        keys.data.copy_from_slice(&plain[..32]);
+        keys.id.copy_from_slice(&plain[32..]);
+        Ok(keys)
+    }
+
+    /// 计算数据块标识（HMAC-SHA256，十六进制）
+    pub fn chunk_id(&self, data: &[u8]) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.id).expect("HMAC accepts any key length");
+        mac.update(data);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 加密对象内容，对象键作为附加数据，防止对象被互相替换
+    pub fn encrypt(&self, key: &str, data: &[u8]) -> Result<Vec<u8>> {
+        let mut out = MAGIC.to_vec();
+        out.extend(seal(&self.data, key.as_bytes(), data)?);
+        Ok(out)
+    }
+
+    /// 解密对象内容并校验其完整性
+    pub fn decrypt(&self, key: &str, data: &[u8]) -> Result<Vec<u8>> {
+        let sealed = data
+            .strip_prefix(MAGIC.as_slice())
+            .with_context(|| format!("Object {} is not encrypted", key))?;
+        open(&self.data, key.as_bytes(), sealed)
+            .map_err(|_| anyhow::anyhow!("Object {} failed authentication (corrupt or modified)", key))
+    }
+}
+
+/// 由口令和信封中的参数派生加密主密钥所用的密钥
+fn derive_key(envelope: &KeyEnvelope, passphrase: &[u8]) -> Result<[u8; 32]> {
+    let salt = hex::decode(&envelope.salt).context("Key envelope is corrupt")?;
+    let params = Params::new(envelope.m_cost, envelope.t_cost, envelope.p_cost, Some(32))
+        .map_err(|e| anyhow::anyhow!("Invalid key derivation parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, &salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// 加密数据，返回随机数 + 密文
+fn seal(key: &[u8; 32], aad: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(&nonce, Payload { msg: data, aad })
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+    let mut out = nonce.to_vec();
+    out.extend(sealed);
+    Ok(out)
+}
+
+/// 解密 [`seal`] 的输出
+fn open(key: &[u8; 32], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, chacha20poly1305::Error> {
+    if data.len() < NONCE_LEN {
+        return Err(chacha20poly1305::Error);
+    }
+    let (nonce, sealed) = data.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into()).decrypt(XNonce::from_slice(nonce), Payload { msg: sealed, aad })
+}
+
+/// 获取加密口令
+///
+/// 依次使用密钥文件的内容、环境变量 `RECALL_PASSPHRASE`，在终端中运行时提示输入。
+///
+/// # 参数
+/// * `keyfile` - 密钥文件
+/// * `confirm` - 提示输入时是否要求再次输入确认（创建加密仓库时）
+///
+/// # 返回
+/// * `Ok(Vec<u8>)` - 口令
+/// * `Err(anyhow::Error)` - 无法读取密钥文件，或无人值守运行时没有可用的口令
+pub fn passphrase(keyfile: Option<&Path>, confirm: bool) -> Result<Vec<u8>> {
+    if let Some(path) = keyfile {
+        let key = fs::read(path).with_context(|| format!("Failed to read key file {:?}", path))?;
+        if key.is_empty() {
+            anyhow::bail!("Key file {:?} is empty", path);
+        }
+        return Ok(key);
+    }
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase.into_bytes());
+        }
+    }
+    if !std::io::stdout().is_terminal() || cfg!(not(feature = "cli")) {
+        anyhow::bail!(
+            "The destination is encrypted: set {} or use a key file (--keyfile, or keyfile in the profile)",
+            PASSPHRASE_ENV
+        );
+    }
+    prompt_passphrase(confirm)
+}
+
+/// 在终端中输入口令
+#[cfg(feature = "cli")]
+fn prompt_passphrase(confirm: bool) -> Result<Vec<u8>> {
+    use crate::i18n::tr;
+
+    let mut prompt = dialoguer::Password::new().with_prompt(tr("Encryption passphrase", "加密口令"));
+    if confirm {
+        prompt = prompt.with_confirmation(
+            tr("Confirm passphrase", "确认口令"),
+            tr("Passphrases do not match", "两次输入的口令不一致"),
+        );
+    }
+    Ok(prompt.interact()?.into_bytes())
+}
+
+/// 没有终端界面时无法输入口令
+#[cfg(not(feature = "cli"))]
+fn prompt_passphrase(_confirm: bool) -> Result<Vec<u8>> {
+    anyhow::bail!("Passphrase prompts require the `cli` feature")
+}
+
+/// 加密对象存储
+///
+/// 包装另一个对象存储，写入前加密、读取后解密并校验对象内容；对象键（数据块标识、快照名称）不加密。
+pub struct EncryptedStore {
+    /// 底层对象存储
+    inner: Box<dyn ObjectStore>,
+
+    /// 仓库主密钥
+    keys: Keys,
+}
+
+impl EncryptedStore {
+    /// 使用主密钥包装对象存储
+    pub fn new(inner: Box<dyn ObjectStore>, keys: Keys) -> Self {
+        Self { inner, keys }
+    }
+}
+
+impl ObjectStore for EncryptedStore {
+    fn describe(&self) -> String {
+        self.inner.describe()
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key)
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.inner.put(key, &self.keys.encrypt(key, data)?)
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        self.keys.decrypt(key, &self.inner.get(key)?)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        self.inner.list(prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 使用较小开销的密钥派生参数，避免测试耗时
+    fn envelope(salt: &str) -> KeyEnvelope {
+        KeyEnvelope {
+            kdf: "argon2id".to_string(),
+            m_cost: Params::MIN_M_COST,
+            t_cost: 1,
+            p_cost: 1,
+            salt: salt.to_string(),
+            keys: String::new(),
+        }
+    }
+
+    #[test]
+    fn derive_key_depends_on_passphrase_and_salt() {
+        let salt = hex::encode([1u8; 16]);
+        let key = derive_key(&envelope(&salt), b"secret").unwrap();
+        assert_eq!(key, derive_key(&envelope(&salt), b"secret").unwrap());
+        assert_ne!(key, derive_key(&envelope(&salt), b"Secret").unwrap());
+        assert_ne!(key, derive_key(&envelope(&hex::encode([2u8; 16])), b"secret").unwrap());
+    }
+
+    #[test]
+    fn derive_key_rejects_invalid_envelopes() {
+        assert!(derive_key(&envelope("not hex"), b"secret").is_err());
+        let mut invalid = envelope(&hex::encode([1u8; 16]));
+        invalid.t_cost = 0;
+        assert!(derive_key(&invalid, b"secret").is_err());
+    }
+
+    #[test]
+    fn sealed_keys_open_only_with_the_same_passphrase() {
+        let keys = Keys::generate();
+        let envelope = keys.seal(b"correct horse").unwrap();
+        assert_eq!(envelope.kdf, "argon2id");
+
+        let opened = Keys::open(&envelope, b"correct horse").unwrap();
+        assert_eq!(opened.data, keys.data);
+        assert_eq!(opened.id, keys.id);
+
+        let err = Keys::open(&envelope, b"battery staple").err().unwrap();
+        assert!(err.to_string().contains("Wrong passphrase"));
+
+        let mut unsupported = envelope.clone();
+        unsupported.kdf = "scrypt".to_string();
+        assert!(Keys::open(&unsupported, b"correct horse").is_err());
+    }
+
+    #[test]
+    fn encrypted_objects_are_bound_to_their_key() {
+        let keys = Keys::generate();
+        let sealed = keys.encrypt("chunks/ab/abcd", b"hello").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert_ne!(sealed, keys.encrypt("chunks/ab/abcd", b"hello").unwrap());
+        assert_eq!(keys.decrypt("chunks/ab/abcd", &sealed).unwrap(), b"hello");
+
+        // 对象被替换为另一个对象，或内容被修改
+        assert!(keys.decrypt("chunks/cd/cdef", &sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(keys.decrypt("chunks/ab/abcd", &tampered).is_err());
+
+        // 其他密钥、未加密或被截断的对象
+        assert!(Keys::generate().decrypt("chunks/ab/abcd", &sealed).is_err());
+        assert!(keys.decrypt("chunks/ab/abcd", b"hello").is_err());
+        assert!(keys.decrypt("chunks/ab/abcd", MAGIC).is_err());
+    }
+
+    #[test]
+    fn chunk_ids_are_keyed() {
+        let keys = Keys::generate();
+        let id = keys.chunk_id(b"data");
+        assert_eq!(id.len(), 64);
+        assert_eq!(id, keys.chunk_id(b"data"));
+        assert_ne!(id, keys.chunk_id(b"other"));
+        assert_ne!(id, Keys::generate().chunk_id(b"data"));
+    }
+}
@@ -0,0 +1,75 @@
+// Recall - 错误类型模块
+// 库的公开接口返回 RecallError，嵌入 recall 的程序可以按错误类别处理，而不必解析错误信息
+
+/// recall 库的错误类型
+///
+/// 每个变体包含完整的错误信息和原因链（`anyhow::Error`），显示和 [`std::error::Error::source`]
+/// 都直接转发给它，因此错误信息与命令行中看到的相同；变体本身表示错误的类别。
+///
+/// # 示例
+/// ```
+/// use recall::config::BackupConfig;
+/// use recall::error::RecallError;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let config = BackupConfig::builder(dir.path(), dir.path().join("backups"))
+///     .ignore_file(dir.path().join("missing.ignore"))
+///     .finish();
+/// assert!(matches!(config, Err(RecallError::Config(_))));
+/// ```
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RecallError {
+    /// 配置无效（配置文件、项目配置文件、排除文件或快照命名规则）
+    #[error(transparent)]
+    Config(anyhow::Error),
+
+    /// 扫描源目录失败
+    #[error(transparent)]
+    Scan(anyhow::Error),
+
+    /// 复制文件失败
+    #[error(transparent)]
+    Copy(anyhow::Error),
+
+    /// 读写备份目标失败（查找、提交或清理快照）
+    #[error(transparent)]
+    Destination(anyhow::Error),
+
+    /// 创建或访问卷影副本（VSS）失败
+    #[error(transparent)]
+    Vss(anyhow::Error),
+
+    /// 其他错误
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl RecallError {
+    /// 错误信息和原因链，可以用 `downcast_ref` 取得底层错误（如 [`std::io::Error`]）
+    pub fn inner(&self) -> &anyhow::Error {
+        match self {
+            RecallError::Config(e)
+            | RecallError::Scan(e)
+            | RecallError::Copy(e)
+            | RecallError::Destination(e)
+            | RecallError::Vss(e)
+            | RecallError::Other(e) => e,
+        }
+    }
+}
+
+/// recall 库的结果类型
+pub type Result<T, E = RecallError> = std::result::Result<T, E>;
+
+/// 为错误标记类别
+pub(crate) trait Classify<T> {
+    /// 将错误转换为指定类别的 [`RecallError`]
+    fn classify(self, kind: fn(anyhow::Error) -> RecallError) -> Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> Classify<T> for std::result::Result<T, E> {
+    fn classify(self, kind: fn(anyhow::Error) -> RecallError) -> Result<T> {
+        self.map_err(|e| kind(e.into()))
+    }
+}
@@ -0,0 +1,147 @@
+// Recall - 备份预估模块
+// 扫描源目录并按 `decide_action` 的决策统计将要复制和硬链接的文件，不执行任何操作
+
+use crate::actions::SyncAction;
+use crate::config::BackupConfig;
+use crate::events::{ConsoleSink, EventSink};
+use crate::scanner::{find_latest_backup, scan_source};
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+/// 备份预估结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Estimate {
+    /// 是否为首次完整备份（目标中没有可链接的快照）
+    pub full_backup: bool,
+
+    /// 源目录中的文件总数（不含目录）
+    pub total_files: u64,
+
+    /// 源目录中的文件总大小
+    pub total_bytes: u64,
+
+    /// 将复制的新文件数量
+    pub new_files: u64,
+
+    /// 将复制的新文件大小
+    pub new_bytes: u64,
+
+    /// 将复制的已修改文件数量
+    pub modified_files: u64,
+
+    /// 将复制的已修改文件大小
+    pub modified_bytes: u64,
+
+    /// 将硬链接的未修改文件数量
+    pub linked_files: u64,
+
+    /// 将硬链接的未修改文件大小（不占用新空间）
+    pub linked_bytes: u64,
+
+    /// 将重新创建的符号链接数量
+    pub symlinks: u64,
+
+    /// 将跳过的文件数量（无法读取元数据）
+    pub skipped: u64,
+
+    /// 预计目标目录增长的字节数（新文件与已修改文件的大小之和）
+    pub projected_growth: u64,
+}
+
+/// 预估下一次备份的工作量
+///
+/// 复用扫描器和 `decide_action` 的决策逻辑，但不复制或链接任何文件。
+/// 不检测块克隆，已修改文件按完整复制计算，因此 `projected_growth` 是上限。
+///
+/// # 参数
+/// * `config` - 备份配置（`destination` 为项目目录）
+///
+/// # 返回
+/// * `Ok(Estimate)` - 预估结果
+/// * `Err(anyhow::Error)` - 扫描失败
+pub fn estimate_backup(config: &BackupConfig) -> Result<Estimate> {
+    let latest = find_latest_backup(&config.destination, &config.naming)?;
+
+    let mut scan_config = config.clone();
+    scan_config.resume = false;
+    scan_config.reflink = false;
+
+    // 扫描器只需要一个目标目录来构造任务路径，不会写入
+    let placeholder = latest.clone().unwrap_or_else(|| config.destination.clone());
+    estimate_with(&scan_config, &placeholder, latest.as_deref(), &ConsoleSink::new())
+}
+
+/// 按给定的扫描设置预估备份的工作量（不复制或链接任何文件）
+///
+/// # 参数
+/// * `config` - 扫描使用的配置（`resume` 为 `true` 时目标中已完整存在的文件计为跳过）
+/// * `current_backup_dir` - 当前备份的目标目录（只用于构造任务路径）
+/// * `latest_backup` - 上一个快照
+/// * `events` - 事件接收者（无法访问的路径、无效的排除模式）
+///
+/// # 返回
+/// * `Ok(Estimate)` - 预估结果
+/// * `Err(anyhow::Error)` - 扫描失败
+pub fn estimate_with(
+    config: &BackupConfig,
+    current_backup_dir: &Path,
+    latest_backup: Option<&Path>,
+    events: &dyn EventSink,
+) -> Result<Estimate> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let mut estimate = Estimate {
+        full_backup: latest_backup.is_none(),
+        ..Default::default()
+    };
+    std::thread::scope(|scope| {
+        let scanner = scope.spawn(|| scan_source(config, current_backup_dir, latest_backup, tx, events));
+        for (task, action) in rx {
+            if matches!(action, SyncAction::CreateDir) {
+                continue;
+            }
+            let size = task.size;
+            estimate.total_files += 1;
+            estimate.total_bytes += size;
+
+            match action {
+                SyncAction::CopyNew => {
+                    estimate.new_files += 1;
+                    estimate.new_bytes += size;
+                }
+                SyncAction::CopyModified | SyncAction::Clone(_) => {
+                    estimate.modified_files += 1;
+                    estimate.modified_bytes += size;
+                }
+                SyncAction::Link(_) => {
+                    estimate.linked_files += 1;
+                    estimate.linked_bytes += size;
+                }
+                SyncAction::MakeSymlink(_) => estimate.symlinks += 1,
+                SyncAction::Skip | SyncAction::Resumed => estimate.skipped += 1,
+                SyncAction::CreateDir => {}
+            }
+        }
+        scanner.join().map_err(|_| anyhow::anyhow!("Scanner thread panicked"))?
+    })?;
+
+    estimate.projected_growth = estimate.new_bytes + estimate.modified_bytes;
+    Ok(estimate)
+}
+
+/// 备份前的可用空间检查结果
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SpaceCheck {
+    /// 预计需要写入的字节数（新文件与已修改文件的大小之和）
+    pub required: u64,
+
+    /// 目标所在卷的可用空间
+    pub available: u64,
+}
+
+impl SpaceCheck {
+    /// 可用空间是否足够
+    pub fn sufficient(&self) -> bool {
+        self.required <= self.available
+    }
+}
@@ -0,0 +1,416 @@
+// Recall - 事件模块
+// 扫描、执行和清理过程中的事件通过 EventSink 报告，命令行输出到控制台，嵌入 recall 的程序可以自行处理
+
+use crate::actions::{BackupStats, FileTask, SyncAction};
+use crate::i18n::tr;
+use crate::utils::{format_bytes, style, verbosity, Verbosity};
+use crate::verify::VerifyIssue;
+#[cfg(feature = "cli")]
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 纯文本输出模式下两条进度行之间的间隔
+const PLAIN_PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 进度条上的传输速度的更新间隔（即时速度按这段时间内传输的字节数计算）
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 备份过程中的事件接收者
+///
+/// 扫描器、执行器和清理过程不直接输出，而是调用这里的方法；所有方法都有空的默认实现，
+/// 只需实现关心的事件。执行器在多个工作线程中同时调用，实现需要自行处理同步。
+///
+/// # 示例
+/// ```
+/// use recall::actions::FileTask;
+/// use recall::events::EventSink;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct FailureCounter(AtomicUsize);
+///
+/// impl EventSink for FailureCounter {
+///     fn file_failed(&self, _task: &FileTask, _error: &anyhow::Error, _locked: bool) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// ```
+pub trait EventSink: Send + Sync {
+    /// 扫描器为文件决定了同步操作
+    fn action_decided(&self, _task: &FileTask, _action: &SyncAction) {}
+
+    /// 执行器开始处理文件
+    fn file_started(&self, _task: &FileTask, _action: &SyncAction) {}
+
+    /// 文件处理完成（目录不报告）
+    ///
+    /// `action` 为 [`SyncAction::name`]，无法链接而改为复制时为 `copy_fallback`，
+    /// 内容已在存储中时为 `reuse`。
+    fn file_done(&self, _task: &FileTask, _action: &str, _bytes: u64) {}
+
+    /// 无法创建硬链接，改为复制
+    fn link_fallback(&self, _task: &FileTask, _reason: &str) {}
+
+    /// 文件处理失败（`locked` 表示文件被其他程序锁定）
+    fn file_failed(&self, _task: &FileTask, _error: &anyhow::Error, _locked: bool) {}
+
+    /// 源文件在扫描之后被删除，已跳过（不计为失败）
+    fn file_vanished(&self, _task: &FileTask) {}
+
+    /// 网络中断，等待后重试（`attempt` 从 1 开始）
+    fn retrying(&self, _task: &FileTask, _error: &anyhow::Error, _delay: Duration, _attempt: u32, _max: u32) {}
+
+    /// 处理完一个任务后的累计统计
+    fn progress(&self, _stats: &BackupStats, _elapsed: Duration) {}
+
+    /// 所有任务处理完毕
+    fn finished(&self, _stats: &BackupStats, _elapsed: Duration) {}
+
+    /// 扫描时无法访问的路径（跳过，不计入失败）
+    fn path_inaccessible(&self, _path: Option<&Path>, _error: &str) {}
+
+    /// 被排除模式跳过的条目（相对路径；排除的目录只报告一次，不包括其中的内容）
+    fn path_excluded(&self, _path: &Path, _pattern: &str) {}
+
+    /// 不影响继续运行的问题（无效的排除模式、无法创建 current 链接等）
+    fn warning(&self, _message: &str) {}
+
+    /// 快照带有受保护的标签，不参与清理
+    fn snapshot_protected(&self, _name: &str) {}
+
+    /// 清理计划：参与清理的快照数量、将删除和保留的数量
+    fn prune_planned(&self, _found: usize, _delete: usize, _keep: usize) {}
+
+    /// 即将删除快照（试运行时不实际删除）
+    fn snapshot_deleting(&self, _name: &str, _dry_run: bool) {}
+
+    /// 快照被保留
+    fn snapshot_kept(&self, _name: &str) {}
+
+    /// 清理完成
+    fn prune_finished(&self, _deleted: usize, _dry_run: bool) {}
+
+    /// 恢复了一个文件（`path` 为相对于快照根目录的路径）
+    fn file_restored(&self, _path: &Path, _bytes: u64) {}
+
+    /// 校验了一个文件，`issue` 为发现的问题（文件完好时为空）
+    fn file_verified(&self, _path: &str, _issue: Option<&VerifyIssue>) {}
+}
+
+/// 忽略所有事件
+pub struct NullSink;
+
+impl EventSink for NullSink {}
+
+/// 备份进度的显示方式
+///
+/// [`ConsoleSink`] 把处理的文件数和需要用户注意的信息交给它显示；命令行使用 indicatif 的进度条，
+/// 图形界面、TUI 或批处理程序可以提供自己的实现（[`SilentProgress`] 不显示任何内容）。
+pub trait ProgressReporter: Send + Sync {
+    /// 已处理的文件数增加
+    fn inc(&self, delta: u64);
+
+    /// 设置进度旁显示的状态信息
+    fn set_message(&self, message: String);
+
+    /// 在进度上方显示一行信息（失败的文件、警告等）
+    fn println(&self, line: String);
+
+    /// 进度结束，显示最终信息
+    fn finish(&self, message: String);
+}
+
+/// 不显示任何进度
+pub struct SilentProgress;
+
+impl ProgressReporter for SilentProgress {
+    fn inc(&self, _delta: u64) {}
+
+    fn set_message(&self, _message: String) {}
+
+    fn println(&self, _line: String) {}
+
+    fn finish(&self, _message: String) {}
+}
+
+/// 不显示进度，只直接输出信息行（纯文本或安静模式）
+struct LineProgress;
+
+impl ProgressReporter for LineProgress {
+    fn inc(&self, _delta: u64) {}
+
+    fn set_message(&self, _message: String) {}
+
+    fn println(&self, line: String) {
+        crate::status!("{}", line);
+    }
+
+    fn finish(&self, _message: String) {}
+}
+
+#[cfg(feature = "cli")]
+impl ProgressReporter for ProgressBar {
+    fn inc(&self, delta: u64) {
+        ProgressBar::inc(self, delta);
+    }
+
+    fn set_message(&self, message: String) {
+        ProgressBar::set_message(self, message);
+    }
+
+    /// 暂时清除进度条后输出（同样经过订阅者，写入日志文件）
+    fn println(&self, line: String) {
+        self.suspend(|| crate::status!("{}", line));
+    }
+
+    fn finish(&self, message: String) {
+        self.finish_with_message(message);
+    }
+}
+
+/// 命令行使用的事件接收者：按当前的输出级别输出到控制台（和运行日志）
+///
+/// 执行阶段显示进度条；纯文本模式下改为定期输出进度行，安静模式下只输出警告。
+pub struct ConsoleSink {
+    /// 进度的显示方式
+    progress: Box<dyn ProgressReporter>,
+
+    /// 输出级别
+    level: Verbosity,
+
+    /// 是否定期输出纯文本进度行
+    plain_progress: bool,
+
+    /// 上一次输出纯文本进度行的时间
+    last_plain_report: Mutex<Instant>,
+
+    /// 是否已输出过无法链接的警告
+    fallback_reported: AtomicBool,
+
+    /// 上一次更新传输速度的时间和当时已传输的字节数
+    last_rate_sample: Mutex<(Instant, u64)>,
+}
+
+impl Default for ConsoleSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsoleSink {
+    /// 创建不显示进度条的控制台输出（扫描、清理）
+    pub fn new() -> Self {
+        Self {
+            progress: Box::new(LineProgress),
+            level: verbosity(),
+            plain_progress: false,
+            last_plain_report: Mutex::new(Instant::now()),
+            fallback_reported: AtomicBool::new(false),
+            last_rate_sample: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// 创建显示备份进度的控制台输出（执行阶段，需要 `cli` 功能）
+    #[cfg(feature = "cli")]
+    pub fn with_progress() -> Self {
+        let mut sink = Self::new();
+        // 安静模式下只输出最终汇总，不显示进度条；纯文本模式下改为定期输出进度行
+        let plain = crate::utils::plain_output();
+        sink.plain_progress = plain && sink.level >= Verbosity::Normal;
+        if sink.level != Verbosity::Quiet && !plain {
+            let style = ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {pos} files processed ({eta}) {msg}")
+                .expect("valid progress template")
+                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏");
+            sink.progress = Box::new(ProgressBar::new_spinner().with_style(style));
+        }
+        sink.progress.set_message(tr("Backup in progress...", "正在备份...").to_string());
+        sink
+    }
+
+    /// 创建通过指定方式显示进度的输出（信息的内容和输出级别与控制台相同）
+    ///
+    /// # 参数
+    /// * `progress` - 进度的显示方式
+    pub fn with_reporter(progress: Box<dyn ProgressReporter>) -> Self {
+        let mut sink = Self::new();
+        sink.progress = progress;
+        sink.progress.set_message(tr("Backup in progress...", "正在备份...").to_string());
+        sink
+    }
+
+    /// 在进度上方输出一行
+    fn line(&self, line: String) {
+        self.progress.println(line);
+    }
+}
+
+impl EventSink for ConsoleSink {
+    fn file_done(&self, task: &FileTask, action: &str, _bytes: u64) {
+        // 详细模式下记录对每个文件执行的操作
+        if self.level >= Verbosity::Verbose {
+            self.line(format!("{}: {:?}", action, task.rel_path));
+        }
+    }
+
+    fn link_fallback(&self, task: &FileTask, reason: &str) {
+        // 仅记录第一次降级的原因，避免整个目标不支持硬链接时刷屏
+        if self.level >= Verbosity::Normal && !self.fallback_reported.swap(true, Ordering::Relaxed) {
+            self.line(crate::trf!(
+                "Warning: cannot link {:?} ({}), copying instead",
+                "警告：无法为 {:?} 创建链接（{}），改为复制",
+                task.rel_path, reason
+            ));
+        }
+    }
+
+    fn file_failed(&self, task: &FileTask, error: &anyhow::Error, locked: bool) {
+        if self.level < Verbosity::Normal {
+            return;
+        }
+        if locked {
+            self.line(crate::trf!(
+                "Locked: {:?} (retry with VSS)",
+                "已锁定：{:?}（可使用 VSS 重试）",
+                task.rel_path
+            ));
+        } else {
+            self.line(crate::trf!("Failed: {:?} - {}", "失败：{:?} - {}", task.rel_path, error));
+        }
+    }
+
+    fn file_vanished(&self, task: &FileTask) {
+        if self.level >= Verbosity::Verbose {
+            self.line(crate::trf!("Vanished: {:?}", "已消失：{:?}", task.rel_path));
+        }
+    }
+
+    fn retrying(&self, task: &FileTask, error: &anyhow::Error, delay: Duration, attempt: u32, max: u32) {
+        if self.level >= Verbosity::Normal {
+            self.line(crate::trf!(
+                "Network error on {:?} ({:#}), retrying in {}s ({}/{})",
+                "处理 {:?} 时网络中断（{:#}），{} 秒后重试（{}/{}）",
+                task.rel_path, error, delay.as_secs(), attempt, max
+            ));
+        }
+    }
+
+    fn progress(&self, stats: &BackupStats, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64().max(0.001);
+        if self.plain_progress {
+            let mut last = self.last_plain_report.lock().unwrap();
+            if last.elapsed() >= PLAIN_PROGRESS_INTERVAL {
+                *last = Instant::now();
+                crate::status!(
+                    "{}",
+                    crate::trf!(
+                        "Progress: {} files processed, {} copied ({}s elapsed, {}/s, {:.0} files/s)",
+                        "进度：已处理 {} 个文件，已复制 {}（已用 {} 秒，{}/s，每秒 {:.0} 个文件）",
+                        stats.total_files,
+                        format_bytes(stats.bytes_copied),
+                        elapsed.as_secs(),
+                        format_bytes((stats.bytes_copied as f64 / secs) as u64),
+                        stats.total_files as f64 / secs
+                    )
+                );
+            }
+        }
+
+        // 每秒更新一次进度条上的即时速度、平均速度和每秒处理的文件数
+        if let Ok(mut sample) = self.last_rate_sample.try_lock() {
+            let since = sample.0.elapsed();
+            if since >= RATE_INTERVAL {
+                let current = stats.bytes_copied.saturating_sub(sample.1) as f64 / since.as_secs_f64();
+                *sample = (Instant::now(), stats.bytes_copied);
+                self.progress.set_message(crate::trf!(
+                    "{}/s (avg {}/s), {:.0} files/s",
+                    "{}/s（平均 {}/s），每秒 {:.0} 个文件",
+                    format_bytes(current as u64),
+                    format_bytes((stats.bytes_copied as f64 / secs) as u64),
+                    stats.total_files as f64 / secs
+                ));
+            }
+        }
+        self.progress.inc(1);
+    }
+
+    fn finished(&self, _stats: &BackupStats, elapsed: Duration) {
+        self.progress.finish(crate::trf!(
+            "Backup completed in {:.2}s",
+            "备份完成，用时 {:.2} 秒",
+            elapsed.as_secs_f64()
+        ));
+    }
+
+    fn path_inaccessible(&self, path: Option<&Path>, error: &str) {
+        if self.level >= Verbosity::Normal {
+            crate::warning!(
+                "{}",
+                crate::trf!("Warning: Cannot access {:?}: {}", "警告：无法访问 {:?}：{}", path, error)
+            );
+        }
+    }
+
+    fn warning(&self, message: &str) {
+        crate::warning!("{}", message);
+    }
+
+    fn snapshot_protected(&self, name: &str) {
+        crate::note!("{}", crate::trf!("Protected by tag: {:?}", "受标签保护：{:?}", style(name).green()));
+    }
+
+    fn prune_planned(&self, found: usize, delete: usize, keep: usize) {
+        if delete == 0 {
+            crate::status!(
+                "{}",
+                crate::trf!(
+                    "Found {} backup(s), keeping {}. Nothing to prune.",
+                    "找到 {} 个备份，保留 {} 个，无需清理。",
+                    found,
+                    keep
+                )
+            );
+        } else {
+            crate::note!(
+                "{}",
+                crate::trf!(
+                    "Found {} backup(s). Will delete {} oldest, keeping {} newest.",
+                    "找到 {} 个备份，将删除最旧的 {} 个，保留最新的 {} 个。",
+                    found,
+                    delete,
+                    keep
+                )
+            );
+        }
+    }
+
+    fn snapshot_deleting(&self, name: &str, dry_run: bool) {
+        if dry_run {
+            crate::status!(
+                "{} {}",
+                style(tr("Dry run:", "试运行：")).yellow(),
+                crate::trf!("Would delete: {:?}", "将删除：{:?}", name)
+            );
+        } else {
+            crate::note!("{}", crate::trf!("Deleting: {:?}", "正在删除：{:?}", style(name).red()));
+        }
+    }
+
+    fn snapshot_kept(&self, name: &str) {
+        crate::verbose!("{}", crate::trf!("Keeping: {:?}", "保留：{:?}", style(name).green()));
+    }
+
+    fn prune_finished(&self, deleted: usize, dry_run: bool) {
+        if !dry_run {
+            crate::status!(
+                "{}",
+                style(crate::trf!("Pruned {} old backup(s).", "已清理 {} 个旧备份。", deleted))
+                    .green()
+                    .bold()
+            );
+        }
+    }
+}
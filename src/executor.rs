@@ -1,112 +1,436 @@
 // Recall - 备份执行器模块
 // 负责执行实际的文件操作（复制、硬链接、创建目录等）
 
-use crate::actions::{BackupStats, FileTask, SyncAction};
+use crate::actions::{BackupStats, FileError, FileTask, SyncAction};
+use crate::backend::{StorageBackend, TaskOutcome};
+use crate::copy::{clone_and_patch, copy_file, CopyOptions};
+use crate::events::{ConsoleSink, EventSink, NullSink, ProgressReporter};
+use crate::metadata::preserve_metadata;
+use crate::progress::{self, ProgressEvent, ProgressTracker};
+use crate::utils::{
+    is_link_denied, is_link_unsupported, is_network_error, is_sharing_violation, is_symlink_unsupported,
+    is_not_found, is_too_many_links, to_verbatim_path,
+};
+use crate::error::{Classify, RecallError};
 use anyhow::{Context, Result};
 use filetime::FileTime;
 use crossbeam_channel::Receiver;
-use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use std::fs;
-use std::path::Path;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 无法创建 `current` 符号链接时，记录最新快照名称的标记文件（位于目标根目录）
+pub const CURRENT_MARKER_FILE: &str = "current.txt";
+
+/// 网络中断时单个文件的最大重试次数
+const NETWORK_RETRIES: u32 = 3;
+
+/// 网络中断后第一次重试前的等待时间（之后每次加倍）
+const NETWORK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 源文件是否在扫描之后被删除（处理时报告不存在，源路径也确实已不存在）
+fn vanished(task: &FileTask, err: &anyhow::Error) -> bool {
+    is_not_found(err) && fs::symlink_metadata(&task.src_path).is_err()
+}
+
+/// 为单个已处理的任务输出进度事件（目录不计入进度）
+fn report_task(
+    task: &FileTask,
+    action: &SyncAction,
+    res: &Result<TaskOutcome>,
+    tracker: &Mutex<ProgressTracker>,
+) {
+    if matches!(action, SyncAction::CreateDir) {
+        return;
+    }
+    match res {
+        Ok(TaskOutcome::Done(bytes)) => progress::emit(&ProgressEvent::File {
+            path: &task.rel_path,
+            action: action.name(),
+            bytes: *bytes,
+        }),
+        Ok(TaskOutcome::LinkFallback(bytes, _)) => progress::emit(&ProgressEvent::File {
+            path: &task.rel_path,
+            action: "copy_fallback",
+            bytes: *bytes,
+        }),
+        Ok(TaskOutcome::Reused) => progress::emit(&ProgressEvent::File {
+            path: &task.rel_path,
+            action: "reuse",
+            bytes: 0,
+        }),
+        Err(e) if vanished(task, e) => progress::emit(&ProgressEvent::File {
+            path: &task.rel_path,
+            action: "vanished",
+            bytes: 0,
+        }),
+        Err(e) => progress::emit(&ProgressEvent::Failed {
+            path: &task.rel_path,
+            error: format!("{:#}", e),
+            locked: is_sharing_violation(e),
+        }),
+    }
+    tracker.lock().unwrap().file_done(task.size);
+}
 
 /// 备份执行器
 ///
-/// 负责执行文件同步操作，支持多线程并行处理。
+/// 负责调度文件同步任务，支持多线程并行处理；每个任务的实际存储操作交给 [`StorageBackend`]，
+/// 处理过程报告给 [`EventSink`]（默认忽略）。
 pub struct BackupExecutor {
-    /// 是否为试运行模式
-    dry_run: bool,
+    /// 执行存储操作的后端
+    backend: Box<dyn StorageBackend>,
+
+    /// 事件接收者
+    events: Arc<dyn EventSink>,
+
+    /// 被锁定的文件是否同时计为失败
+    fail_on_locked: bool,
 }
 
 impl BackupExecutor {
-    /// 创建新的备份执行器
+    /// 创建使用硬链接快照目录（[`LinkTreeBackend`]）的备份执行器
     ///
     /// # 参数
     /// * `dry_run` - 是否为试运行模式
-    pub fn new(dry_run: bool) -> Self {
-        Self { dry_run }
+    /// * `preserve_metadata` - 是否保留所有者和扩展属性
+    /// * `copy_options` - 文件复制选项
+    pub fn new(dry_run: bool, preserve_metadata: bool, copy_options: CopyOptions) -> Self {
+        Self::with_backend(Box::new(LinkTreeBackend::new(dry_run, preserve_metadata, copy_options)))
+    }
+
+    /// 创建使用指定存储后端的备份执行器
+    ///
+    /// # 参数
+    /// * `backend` - 存储后端
+    pub fn with_backend(backend: Box<dyn StorageBackend>) -> Self {
+        Self { backend, events: Arc::new(NullSink), fail_on_locked: false }
+    }
+
+    /// 设置接收处理事件的对象
+    ///
+    /// # 参数
+    /// * `events` - 事件接收者（命令行使用 [`ConsoleSink`](crate::events::ConsoleSink)）
+    pub fn with_events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 设置被其他进程锁定的文件是否同时计为失败
+    ///
+    /// 默认只记入 [`BackupStats::locked`]，不影响备份结果；启用后还计入 [`BackupStats::failed`]。
+    pub fn with_fail_on_locked(mut self, fail: bool) -> Self {
+        self.fail_on_locked = fail;
+        self
+    }
+
+    /// 通过指定方式显示处理进度和需要注意的信息（与命令行输出的内容相同）
+    ///
+    /// # 参数
+    /// * `progress` - 进度的显示方式
+    pub fn with_progress(self, progress: Box<dyn ProgressReporter>) -> Self {
+        self.with_events(Arc::new(ConsoleSink::with_reporter(progress)))
     }
 
     /// 执行备份任务
     ///
-    /// 从通道接收任务并使用线程池并行处理。
-    /// 显示进度条并在完成后返回统计信息。
+    /// 从通道接收任务并使用线程池并行处理，每个任务的结果报告给事件接收者，完成后返回统计信息。
     ///
     /// # 参数
     /// * `rx` - 任务接收通道
     /// * `workers` - 工作线程数量
     ///
     /// # 返回
-    /// * `Ok(BackupStats)` - 备份统计信息
-    /// * `Err(anyhow::Error)` - 执行失败
+    /// * `Ok(BackupStats)` - 备份统计信息（单个文件的失败计入统计，不作为错误返回）
+    /// * `Err(RecallError)` - 无法启动工作线程
     pub fn execute(
         &self,
         rx: Receiver<(FileTask, SyncAction)>,
         workers: usize,
-    ) -> Result<BackupStats> {
+    ) -> crate::error::Result<BackupStats> {
         // 线程安全的统计信息
         let stats = Mutex::new(BackupStats::new());
-
-        // 创建进度条样式
-        let style = ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed_precise}] {pos} files processed ({eta}) {msg}")?
-            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏");
-
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(style);
-        pb.set_message("Backup in progress...");
+        let tracker = Mutex::new(ProgressTracker::new());
+        let report_progress = progress::enabled();
+        let events = &*self.events;
 
         let start_time = Instant::now();
+        let span = tracing::info_span!("execute", workers);
 
         // 创建线程池
         let pool = rayon::ThreadPoolBuilder::new()
             .num_threads(workers)
             .build()
-            .context("Failed to build thread pool")?;
+            .context("Failed to build thread pool")
+            .classify(RecallError::Other)?;
 
         // 使用线程池并行处理任务
         pool.install(|| {
             rx.into_iter().par_bridge().for_each(|(task, action)| {
-                let res = self.process_task(&task, &action);
+                let _span = tracing::debug_span!(
+                    parent: &span,
+                    "file",
+                    path = %task.rel_path.display(),
+                    action = action.name()
+                )
+                .entered();
+                events.file_started(&task, &action);
+                let started = Instant::now();
+                let res = self.process_with_retry(&task, &action);
+                let elapsed = started.elapsed();
+                match &res {
+                    Ok(TaskOutcome::Done(bytes)) => tracing::debug!(bytes, ?elapsed, "done"),
+                    Ok(TaskOutcome::LinkFallback(bytes, reason)) => {
+                        tracing::debug!(bytes, reason = %reason, ?elapsed, "copied instead of linking")
+                    }
+                    Ok(TaskOutcome::Reused) => tracing::debug!(?elapsed, "reused stored content"),
+                    Err(e) if vanished(&task, e) => tracing::debug!(?elapsed, "source vanished"),
+                    Err(e) => tracing::warn!(error = %format!("{:#}", e), "failed"),
+                }
+
+                if report_progress {
+                    report_task(&task, &action, &res, &tracker);
+                }
+
+                if !matches!(action, SyncAction::CreateDir) {
+                    match res {
+                        Ok(TaskOutcome::Done(bytes)) => events.file_done(&task, action.name(), bytes),
+                        Ok(TaskOutcome::LinkFallback(bytes, _)) => events.file_done(&task, "copy_fallback", bytes),
+                        Ok(TaskOutcome::Reused) => events.file_done(&task, "reuse", 0),
+                        Err(_) => {}
+                    }
+                }
 
                 let mut s = stats.lock().unwrap();
                 s.total_files += 1;
 
                 // 根据操作类型和结果更新统计信息
                 match res {
-                    Ok(bytes) => match action {
-                        SyncAction::CopyNew => {
-                            s.copied_new += 1;
-                            s.bytes_copied += bytes;
+                    Ok(TaskOutcome::LinkFallback(bytes, reason)) => {
+                        events.link_fallback(&task, &reason);
+                        s.link_fallbacks += 1;
+                        s.bytes_copied += bytes;
+                        s.record_written(&task.rel_path, bytes);
+                        s.durations.copy += elapsed.as_secs_f64();
+                    }
+                    // 内容已在存储中，与硬链接一样不占用新空间
+                    Ok(TaskOutcome::Reused) => {
+                        s.linked += 1;
+                        s.bytes_linked += task.size;
+                        s.record_linked(&task.rel_path, task.size);
+                        s.durations.link += elapsed.as_secs_f64();
+                    }
+                    Ok(TaskOutcome::Done(bytes)) => {
+                        s.durations.add(&action, elapsed);
+                        match action {
+                            SyncAction::CopyNew => {
+                                s.copied_new += 1;
+                                s.bytes_copied += bytes;
+                                s.record_written(&task.rel_path, bytes);
+                            }
+                            SyncAction::CopyModified => {
+                                s.copied_modified += 1;
+                                s.bytes_copied += bytes;
+                                s.record_written(&task.rel_path, bytes);
+                            }
+                            SyncAction::Clone(_) => {
+                                s.cloned += 1;
+                                s.bytes_copied += bytes;
+                                s.record_written(&task.rel_path, bytes);
+                            }
+                            SyncAction::Link(_) => {
+                                s.linked += 1;
+                                s.bytes_linked += task.size;
+                                s.record_linked(&task.rel_path, task.size);
+                            }
+                            SyncAction::MakeSymlink(_) => s.symlinks += 1,
+                            SyncAction::CreateDir => {
+                                s.total_files -= 1; // 目录不计入文件数
+                                s.dirs_created += 1;
+                            }
+                            SyncAction::Skip => s.skipped += 1,
+                            SyncAction::Resumed => s.resumed += 1,
                         }
-                        SyncAction::CopyModified => {
-                            s.copied_modified += 1;
-                            s.bytes_copied += bytes;
+                    }
+                    Err(e) if vanished(&task, &e) => {
+                        events.file_vanished(&task);
+                        s.vanished += 1;
+                    }
+                    Err(e) if is_sharing_violation(&e) => {
+                        events.file_failed(&task, &e, true);
+                        s.locked += 1;
+                        s.locked_files.push(task.rel_path.clone());
+                        if self.fail_on_locked {
+                            s.failed += 1;
                         }
-                        SyncAction::Link(_) => s.linked += 1,
-                        SyncAction::MakeSymlink(_) => s.linked += 1,
-                        SyncAction::CreateDir => s.total_files -= 1, // 目录不计入文件数
-                        SyncAction::Skip => s.skipped += 1,
-                    },
+                    }
                     Err(e) => {
-                        pb.println(format!("Failed: {:?} - {}", task.rel_path, e));
+                        events.file_failed(&task, &e, false);
                         s.failed += 1;
+                        s.errors.push(FileError { path: task.rel_path.clone(), error: format!("{:#}", e) });
                     }
                 }
-                pb.inc(1);
+
+                events.progress(&s, start_time.elapsed());
             });
         });
 
-        pb.finish_with_message(format!(
-            "Backup completed in {:.2}s",
-            start_time.elapsed().as_secs_f64()
-        ));
+        if report_progress {
+            tracker.lock().unwrap().emit();
+        }
+
+        let mut stats = stats.into_inner().unwrap();
+        let elapsed = start_time.elapsed();
+        stats.bytes_per_sec = (stats.bytes_copied as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+        stats.files_per_sec = stats.total_files as f64 / elapsed.as_secs_f64().max(0.001);
+        stats.execute_secs = elapsed.as_secs_f64();
+        events.finished(&stats, elapsed);
+        Ok(stats)
+    }
+
+    /// 处理单个文件任务，网络中断时等待后重试
+    ///
+    /// 每次重试前的等待时间加倍；所有操作都先清理目标路径上的遗留文件，重复执行是安全的。
+    fn process_with_retry(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome> {
+        let mut attempt = 0;
+        loop {
+            match self.backend.apply(task, action) {
+                Err(e) if attempt < NETWORK_RETRIES && is_network_error(&e) => {
+                    let delay = NETWORK_RETRY_DELAY * 2u32.pow(attempt);
+                    attempt += 1;
+                    tracing::warn!(attempt, ?delay, error = %format!("{:#}", e), "network error, retrying");
+                    self.events.retrying(task, &e, delay, attempt, NETWORK_RETRIES);
+                    std::thread::sleep(delay);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// 提交备份（重命名临时目录并更新 current 符号链接）
+    ///
+    /// 备份过程中使用 `.partial` 后缀的临时目录，
+    /// 完成后重命名为最终目录名，并更新 `current` 符号链接（无法创建符号链接时写入 [`CURRENT_MARKER_FILE`]）。
+    ///
+    /// # 参数
+    /// * `temp_path` - 临时备份目录（带 .partial 后缀）
+    /// * `final_path` - 最终备份目录名
+    /// * `link_path` - current 符号链接路径
+    /// * `events` - 事件接收者（无法创建 current 链接时报告警告）
+    ///
+    /// # 返回
+    /// * `Ok(())` - 提交成功
+    /// * `Err(RecallError)` - 提交失败
+    pub fn commit_backup(
+        temp_path: &Path,
+        final_path: &Path,
+        link_path: &Path,
+        events: &dyn EventSink,
+    ) -> crate::error::Result<()> {
+        // 确保目标目录不存在
+        if final_path.exists() {
+            return Err(RecallError::Destination(anyhow::anyhow!(
+                "Destination backup folder already exists: {:?}",
+                final_path
+            )));
+        }
+
+        // 重命名临时目录为最终目录名
+        // 使用逐字路径，目录树很深时（超过 Windows 的 260 字符限制）重命名也不会失败
+        fs::rename(to_verbatim_path(temp_path), to_verbatim_path(final_path))
+            .with_context(|| format!("Failed to rename {:?} to {:?}", temp_path, final_path))
+            .classify(RecallError::Destination)?;
+
+        // 删除旧的 current 链接/目录（如果无法作为链接删除，则直接移除目录）
+        if link_path.exists() || fs::symlink_metadata(link_path).is_ok() {
+            let removed = match fs::symlink_metadata(link_path) {
+                Ok(meta) => {
+                    if meta.is_dir() {
+                        fs::remove_dir(link_path).ok().is_some()
+                    } else {
+                        fs::remove_file(link_path).ok().is_some()
+                    }
+                }
+                Err(_) => false,
+            };
+
+            if !removed {
+                if link_path.is_dir() {
+                    fs::remove_dir_all(to_verbatim_path(link_path)).ok();
+                } else {
+                    fs::remove_file(link_path).ok();
+                }
+            }
+        }
+
+        // 快照与 current 位于同一目录时链接到快照名称（相对路径）：以相对路径指定目标、
+        // 或备份盘挂载到其他位置时链接仍然有效；否则链接到绝对路径
+        let target = match final_path.file_name() {
+            Some(name) if final_path.parent() == link_path.parent() => PathBuf::from(name),
+            _ => std::path::absolute(final_path).unwrap_or_else(|_| final_path.to_path_buf()),
+        };
+
+        // 创建新的 current 符号链接（Windows 上为目录符号链接，没有权限时改为目录联接，联接只能指向绝对路径）
+        #[cfg(windows)]
+        let linked = std::os::windows::fs::symlink_dir(&target, link_path)
+            .or_else(|_| std::path::absolute(final_path).and_then(|absolute| create_junction(&absolute, link_path)));
+        #[cfg(unix)]
+        let linked = std::os::unix::fs::symlink(&target, link_path);
+
+        // 目标不支持符号链接（exFAT、部分 SMB / CIFS 挂载，或 Windows 上目录符号链接和联接都无法创建）时
+        // 改为写入记录快照名称的标记文件；查找最新快照本身按名称中的时间排序，不依赖 current
+        let marker = link_path.with_file_name(CURRENT_MARKER_FILE);
+        match linked {
+            Ok(()) => {
+                fs::remove_file(&marker).ok();
+            }
+            Err(e) => {
+                let name = final_path.file_name().unwrap_or_default().to_string_lossy();
+                match fs::write(&marker, format!("{}\n", name)) {
+                    Ok(()) => events.warning(&crate::trf!(
+                        "Warning: Cannot create 'current' symlink ({}), recorded the latest snapshot in {} instead",
+                        "警告：无法创建 'current' 符号链接（{}），已改为在 {} 中记录最新快照",
+                        e,
+                        CURRENT_MARKER_FILE
+                    )),
+                    Err(marker_err) => events.warning(&crate::trf!(
+                        "Warning: Failed to create 'current' symlink: {} (and {}: {})",
+                        "警告：无法创建 'current' 符号链接：{}（{} 也无法写入：{}）",
+                        e,
+                        CURRENT_MARKER_FILE,
+                        marker_err
+                    )),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
 
-        Ok(stats.into_inner().unwrap())
+/// 创建指向 `target` 的目录联接（junction，Windows）
+///
+/// 与目录符号链接不同，创建联接不需要开发者模式或管理员权限，但只能指向本地卷上的目录（网络共享上会失败）。
+#[cfg(windows)]
+fn create_junction(target: &Path, link: &Path) -> std::io::Result<()> {
+    let status = std::process::Command::new("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(link)
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("mklink /J exited with {}", status)))
     }
+}
 
+impl StorageBackend for LinkTreeBackend {
     /// 处理单个文件任务
     ///
     /// 根据同步动作类型执行相应的文件操作。
@@ -116,162 +440,455 @@ impl BackupExecutor {
     /// * `action` - 要执行的同步动作
     ///
     /// # 返回
-    /// * `Ok(u64)` - 复制的字节数（仅复制操作返回非零值）
+    /// * `Ok(TaskOutcome)` - 执行结果及复制的字节数（仅复制操作返回非零值）
     /// * `Err(anyhow::Error)` - 操作失败
-    fn process_task(&self, task: &FileTask, action: &SyncAction) -> Result<u64> {
+    fn apply(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome> {
         // 试运行模式不执行实际操作
         if self.dry_run {
-            return Ok(0);
+            return Ok(TaskOutcome::Done(0));
         }
 
         match action {
             SyncAction::CopyNew | SyncAction::CopyModified => {
-                // 复制文件
+                self.copy_to_dest(task, None).map(TaskOutcome::Done)
+            }
+            SyncAction::Clone(old_path) => {
+                self.copy_to_dest(task, Some(old_path)).map(TaskOutcome::Done)
+            }
+            SyncAction::Link(old_path) => {
+                // 创建硬链接（节省空间）
                 if let Some(parent) = task.dest_path.parent() {
-                     fs::create_dir_all(parent).with_context(|| {
-                         format!("Failed to create parent dir for {:?}", task.dest_path)
-                     })?;
+                    fs::create_dir_all(parent)?;
                 }
-                let bytes = fs::copy(&task.src_path, &task.dest_path).with_context(|| {
-                    format!("Failed to copy {:?} to {:?}", task.src_path, task.dest_path)
-                })?;
+                remove_stale(&task.dest_path)?;
 
-                // 保留源文件的时间戳
-                let src_meta = fs::metadata(&task.src_path)?;
-                let mtime = FileTime::from_last_modification_time(&src_meta);
-                let atime = FileTime::from_last_access_time(&src_meta);
-
-                let dest_meta = fs::metadata(&task.dest_path)?;
-                let mut perms = dest_meta.permissions();
-                let original_readonly = perms.readonly();
-
-                // 如果文件是只读的，需要先取消只读才能设置时间戳
-                if original_readonly {
-                    perms.set_readonly(false);
-                    fs::set_permissions(&task.dest_path, perms.clone()).with_context(|| {
-                        format!("Failed to unset readonly for {:?}", task.dest_path)
-                    })?;
+                // 已确认目标不支持硬链接，直接复制
+                if self.hard_links_unsupported.load(Ordering::Relaxed) {
+                    let bytes = self.fallback_copy(task)?;
+                    return Ok(TaskOutcome::LinkFallback(bytes, "hard links not supported".to_string()));
                 }
 
-                filetime::set_file_times(&task.dest_path, atime, mtime)
-                    .with_context(|| format!("Failed to set time for {:?}", task.dest_path))?;
-
-                if original_readonly {
-                    perms.set_readonly(true);
-                    fs::set_permissions(&task.dest_path, perms)?;
+                // 旧文件的硬链接数已达 NTFS 上限，复制一份新文件，它将成为后续备份的链接锚点；
+                // Unix 上由下面的 EMLINK 分支处理
+                #[cfg(windows)]
+                if crate::utils::hard_link_count(old_path).is_some_and(|n| n >= crate::utils::MAX_HARD_LINKS) {
+                    let bytes = self.fallback_copy(task)?;
+                    return Ok(TaskOutcome::LinkFallback(bytes, "hard link limit reached".to_string()));
                 }
 
-                Ok(bytes)
-            }
-            SyncAction::Link(old_path) => {
-                // 创建硬链接（节省空间）
-                if let Some(parent) = task.dest_path.parent() {
-                     fs::create_dir_all(parent)?;
+                match fs::hard_link(old_path, &task.dest_path) {
+                    Ok(()) => Ok(TaskOutcome::Done(0)),
+                    // 链接数超限、跨卷或目标文件系统不支持硬链接（exFAT、SMB 等）时改为复制
+                    Err(e) if is_too_many_links(&e) => {
+                        let bytes = self.fallback_copy(task)?;
+                        Ok(TaskOutcome::LinkFallback(bytes, e.to_string()))
+                    }
+                    Err(e) if is_link_unsupported(&e) => {
+                        self.hard_links_unsupported.store(true, Ordering::Relaxed);
+                        let bytes = self.fallback_copy(task)?;
+                        Ok(TaskOutcome::LinkFallback(bytes, e.to_string()))
+                    }
+                    // 只有这个文件不允许链接（受保护、不可变等），复制它，其余文件继续尝试链接
+                    Err(e) if is_link_denied(&e) => {
+                        let bytes = self.fallback_copy(task)?;
+                        Ok(TaskOutcome::LinkFallback(bytes, e.to_string()))
+                    }
+                    Err(e) => Err(e).with_context(|| {
+                        format!("Failed to link {:?} to {:?}", old_path, task.dest_path)
+                    }),
                 }
-                fs::hard_link(old_path, &task.dest_path).with_context(|| {
-                    format!("Failed to link {:?} to {:?}", old_path, task.dest_path)
-                })?;
-                Ok(0)
-            }
-            SyncAction::MakeSymlink(target) => {
-                // 创建符号链接
-                 if let Some(parent) = task.dest_path.parent() {
-                     fs::create_dir_all(parent)?;
-                 }
-                 #[cfg(unix)]
-                 std::os::unix::fs::symlink(target, &task.dest_path)
-                    .with_context(|| format!("Failed to symlink {:?} -> {:?}", task.dest_path, target))?;
-
-                 #[cfg(windows)]
-                 {
-                     // Windows 需要区分目录符号链接和文件符号链接
-                     // 由于目标可能是相对路径或不存在的文件，我们检查源路径来判断
-                     let is_dir = fs::metadata(&task.src_path).map(|m| m.is_dir()).unwrap_or(false);
-                     if is_dir {
-                         std::os::windows::fs::symlink_dir(target, &task.dest_path)
-                            .with_context(|| format!("Failed to symlink_dir {:?} -> {:?}", task.dest_path, target))?;
-                     } else {
-                         std::os::windows::fs::symlink_file(target, &task.dest_path)
-                            .with_context(|| format!("Failed to symlink_file {:?} -> {:?}", task.dest_path, target))?;
-                     }
-                 }
-                 Ok(0)
             }
+            SyncAction::MakeSymlink(target) => self.symlink_or_copy(task, target),
             SyncAction::CreateDir => {
                 // 创建目录
                 fs::create_dir_all(&task.dest_path).with_context(|| {
                     format!("Failed to create dir {:?}", task.dest_path)
                 })?;
-                Ok(0)
+                Ok(TaskOutcome::Done(0))
             }
-            SyncAction::Skip => Ok(0),
+            SyncAction::Skip | SyncAction::Resumed => Ok(TaskOutcome::Done(0)),
         }
     }
+}
 
-    /// 提交备份（重命名临时目录并更新 current 符号链接）
+/// 硬链接快照目录后端
+///
+/// 每个快照是目标目录下的一个完整目录树，未变化的文件硬链接到上一个快照。
+pub struct LinkTreeBackend {
+    /// 是否为试运行模式
+    dry_run: bool,
+
+    /// 是否保留所有者和扩展属性（仅 Unix）
+    preserve_metadata: bool,
+
+    /// 文件复制选项（缓冲区大小、无缓冲 I/O）
+    copy_options: CopyOptions,
+
+    /// 目标已确认不支持硬链接（如部分 SMB 共享），之后的文件直接复制，不再逐个尝试
+    hard_links_unsupported: AtomicBool,
+
+    /// 目标已确认不支持符号链接，之后的符号链接直接复制其指向的文件
+    symlinks_unsupported: AtomicBool,
+}
+
+impl LinkTreeBackend {
+    /// 创建硬链接快照目录后端
     ///
-    /// 备份过程中使用 `.partial` 后缀的临时目录，
-    /// 完成后重命名为最终目录名，并更新 `current` 符号链接。
+    /// # 参数
+    /// * `dry_run` - 是否为试运行模式
+    /// * `preserve_metadata` - 是否保留所有者和扩展属性
+    /// * `copy_options` - 文件复制选项
+    pub fn new(dry_run: bool, preserve_metadata: bool, copy_options: CopyOptions) -> Self {
+        Self {
+            dry_run,
+            preserve_metadata,
+            copy_options,
+            hard_links_unsupported: AtomicBool::new(false),
+            symlinks_unsupported: AtomicBool::new(false),
+        }
+    }
+
+    /// 设置目标是否支持硬链接和符号链接（见 [`supports_hard_links`](crate::copy::supports_hard_links)）
+    ///
+    /// 不支持硬链接时，未修改的文件直接完整复制；不支持符号链接时，符号链接复制为其指向的文件。
+    /// 不需要先逐个文件尝试失败后再降级。
     ///
     /// # 参数
-    /// * `temp_path` - 临时备份目录（带 .partial 后缀）
-    /// * `final_path` - 最终备份目录名
-    /// * `link_path` - current 符号链接路径
+    /// * `hard_links` - 是否支持硬链接
+    /// * `symlinks` - 是否支持符号链接
+    pub fn with_link_support(self, hard_links: bool, symlinks: bool) -> Self {
+        self.hard_links_unsupported.store(!hard_links, Ordering::Relaxed);
+        self.symlinks_unsupported.store(!symlinks, Ordering::Relaxed);
+        self
+    }
+
+    /// 将源文件复制到目标路径，并保留时间戳和元数据
+    ///
+    /// 先写入同目录下的临时文件（见 [`temp_path_for`]），全部完成后再重命名到目标路径，
+    /// 保证目标目录中出现的每个文件都是完整的，中断后续传不会误用截断的文件。
+    ///
+    /// # 参数
+    /// * `task` - 文件任务
+    /// * `clone_from` - 若提供，先尝试从该旧备份文件块克隆，失败时回退为普通复制
     ///
     /// # 返回
-    /// * `Ok(())` - 提交成功
-    /// * `Err(anyhow::Error)` - 提交失败
-    pub fn commit_backup(temp_path: &Path, final_path: &Path, link_path: &Path) -> Result<()> {
-        // 确保目标目录不存在
-        if final_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Destination backup folder already exists: {:?}",
-                final_path
-            ));
+    /// * `Ok(u64)` - 实际写入的字节数
+    /// * `Err(anyhow::Error)` - 复制失败
+    fn copy_to_dest(&self, task: &FileTask, clone_from: Option<&Path>) -> Result<u64> {
+        if let Some(parent) = task.dest_path.parent() {
+             fs::create_dir_all(parent).with_context(|| {
+                 format!("Failed to create parent dir for {:?}", task.dest_path)
+             })?;
         }
 
-        // 重命名临时目录为最终目录名
-        fs::rename(temp_path, final_path)
-            .with_context(|| format!("Failed to rename {:?} to {:?}", temp_path, final_path))?;
+        let temp_path = temp_path_for(&task.dest_path);
+        remove_stale(&temp_path)?;
 
-        // 删除旧的 current 链接/目录（如果无法作为链接删除，则直接移除目录）
-        if link_path.exists() || fs::symlink_metadata(link_path).is_ok() {
-            let removed = match fs::symlink_metadata(link_path) {
-                Ok(meta) => {
-                    if meta.is_dir() {
-                        fs::remove_dir(link_path).ok().is_some()
-                    } else {
-                        fs::remove_file(link_path).ok().is_some()
-                    }
-                }
-                Err(_) => false,
+        let bytes = match self.write_file(task, clone_from, &temp_path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                remove_stale(&temp_path).ok();
+                return Err(e);
+            }
+        };
+
+        remove_stale(&task.dest_path)?;
+        fs::rename(&temp_path, &task.dest_path).with_context(|| {
+            format!("Failed to rename {:?} to {:?}", temp_path, task.dest_path)
+        })?;
+
+        Ok(bytes)
+    }
+
+    /// 将源文件内容、时间戳和元数据写入 `target`
+    fn write_file(&self, task: &FileTask, clone_from: Option<&Path>, target: &Path) -> Result<u64> {
+        let bytes = match clone_from {
+            // 克隆失败时回退为普通复制
+            Some(old_path) => match clone_and_patch(old_path, &task.src_path, target) {
+                Ok(bytes) => bytes,
+                Err(_) => copy_file(&task.src_path, target, &self.copy_options)?,
+            },
+            None => copy_file(&task.src_path, target, &self.copy_options)?,
+        };
+
+        // 保留源文件的时间戳
+        let src_meta = fs::metadata(&task.src_path)?;
+        let mtime = FileTime::from_last_modification_time(&src_meta);
+        let atime = FileTime::from_last_access_time(&src_meta);
+
+        let dest_meta = fs::metadata(target)?;
+        let mut perms = dest_meta.permissions();
+        let original_readonly = perms.readonly();
+
+        // 如果文件是只读的，需要先取消只读才能设置时间戳
+        #[allow(clippy::permissions_set_readonly_false)]
+        if original_readonly {
+            perms.set_readonly(false);
+            fs::set_permissions(target, perms.clone()).with_context(|| {
+                format!("Failed to unset readonly for {:?}", target)
+            })?;
+        }
+
+        filetime::set_file_times(target, atime, mtime)
+            .with_context(|| format!("Failed to set time for {:?}", target))?;
+
+        // 保留所有者和扩展属性（需在恢复只读之前，非 root 用户无法为只读文件设置扩展属性）
+        if self.preserve_metadata {
+            preserve_metadata(&task.src_path, target)?;
+        }
+
+        if original_readonly {
+            perms.set_readonly(true);
+            fs::set_permissions(target, perms)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// 无法创建硬链接时，改为从源重新复制文件（符号链接则重新创建）
+    fn fallback_copy(&self, task: &FileTask) -> Result<u64> {
+        let src_meta = fs::symlink_metadata(&task.src_path)?;
+        if src_meta.is_symlink() {
+            let target = fs::read_link(&task.src_path)?;
+            return match self.symlink_or_copy(task, &target)? {
+                TaskOutcome::Done(bytes) | TaskOutcome::LinkFallback(bytes, _) => Ok(bytes),
+                TaskOutcome::Reused => Ok(0),
             };
+        }
+        self.copy_to_dest(task, None)
+    }
 
-            if !removed {
-                if link_path.is_dir() {
-                    fs::remove_dir_all(link_path).ok();
-                } else {
-                    fs::remove_file(link_path).ok();
+    /// 创建符号链接；目标不支持符号链接时改为复制链接指向的文件
+    ///
+    /// 指向目录或无效目标的符号链接无法复制，仍按失败处理。
+    fn symlink_or_copy(&self, task: &FileTask, target: &Path) -> Result<TaskOutcome> {
+        let reason = if self.symlinks_unsupported.load(Ordering::Relaxed) {
+            "symbolic links not supported".to_string()
+        } else {
+            match self.make_symlink(task, target) {
+                Ok(()) => return Ok(TaskOutcome::Done(0)),
+                Err(e) if is_symlink_unsupported(&e) => {
+                    self.symlinks_unsupported.store(true, Ordering::Relaxed);
+                    format!("{:#}", e)
                 }
+                Err(e) => return Err(e),
             }
+        };
+
+        if !fs::metadata(&task.src_path).is_ok_and(|m| m.is_file()) {
+            anyhow::bail!(
+                "Cannot create symlink {:?} -> {:?} and the target is not a regular file ({})",
+                task.dest_path,
+                target,
+                reason
+            );
+        }
+        let bytes = self.copy_to_dest(task, None)?;
+        Ok(TaskOutcome::LinkFallback(bytes, reason))
+    }
+
+    /// 在目标路径创建指向 `target` 的符号链接
+    fn make_symlink(&self, task: &FileTask, target: &Path) -> Result<()> {
+        if let Some(parent) = task.dest_path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        remove_stale(&task.dest_path)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, &task.dest_path)
+            .with_context(|| format!("Failed to symlink {:?} -> {:?}", task.dest_path, target))?;
 
-        // 创建新的 current 符号链接
         #[cfg(windows)]
         {
-            // Windows 需要使用目录符号链接
-            if let Err(e) = std::os::windows::fs::symlink_dir(final_path, link_path) {
-                eprintln!("Warning: Failed to create 'current' symlink: {}", e);
-                eprintln!("(Note: Creating directory symlinks on Windows requires Developer Mode or Admin rights)");
+            // Windows 需要区分目录符号链接和文件符号链接
+            // 由于目标可能是相对路径或不存在的文件，我们检查源路径来判断
+            let is_dir = fs::metadata(&task.src_path).map(|m| m.is_dir()).unwrap_or(false);
+            if is_dir {
+                std::os::windows::fs::symlink_dir(target, &task.dest_path)
+                    .with_context(|| format!("Failed to symlink_dir {:?} -> {:?}", task.dest_path, target))?;
+            } else {
+                std::os::windows::fs::symlink_file(target, &task.dest_path)
+                    .with_context(|| format!("Failed to symlink_file {:?} -> {:?}", task.dest_path, target))?;
             }
         }
+        Ok(())
+    }
+}
 
-        #[cfg(unix)]
+/// 复制过程中使用的临时文件后缀
+///
+/// 没有直接使用 `.tmp`，避免与源目录中真实存在的 `name.tmp` 文件冲突。
+pub const TEMP_SUFFIX: &str = ".recall-tmp";
+
+/// 获取目标文件对应的临时文件路径（同一目录下，保证重命名是原子的）
+fn temp_path_for(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    dest.with_file_name(name)
+}
+
+/// 把已提交的快照设为只读，防止误操作修改与其他快照共享硬链接的文件
+///
+/// 文件设为只读（Unix 上去掉所有写权限位）；Unix 上子目录也去掉写权限，
+/// 防止在快照中新建、删除或重命名文件（Windows 上目录的只读属性没有这种作用，只处理文件）。
+/// 快照根目录和摘要文件保持可写，之后仍可添加标签或清单。
+/// 硬链接共享权限，只读属性对所有快照中的同一文件都生效。
+///
+/// # 参数
+/// * `snapshot` - 快照目录
+///
+/// # 返回
+/// * `Ok(u64)` - 设为只读的文件数量
+/// * `Err(RecallError)` - 无法修改权限
+pub fn set_snapshot_read_only(snapshot: &Path) -> crate::error::Result<u64> {
+    let snapshot = to_verbatim_path(snapshot);
+    let mut files = 0;
+    for entry in walkdir::WalkDir::new(&snapshot).follow_links(false).min_depth(1) {
+        let entry = entry.context("Failed to read snapshot").classify(RecallError::Destination)?;
+        let file_type = entry.file_type();
+        if file_type.is_symlink() || (entry.depth() == 1 && entry.file_name() == crate::summary::SUMMARY_FILE) {
+            continue;
+        }
+        if !file_type.is_file() && !cfg!(unix) {
+            continue;
+        }
+        let mut perms = entry.metadata().context("Failed to read snapshot").classify(RecallError::Destination)?.permissions();
+        if perms.readonly() {
+            continue;
+        }
+        perms.set_readonly(true);
+        fs::set_permissions(entry.path(), perms)
+            .with_context(|| format!("Failed to make {:?} read-only", entry.path()))
+            .classify(RecallError::Destination)?;
+        if file_type.is_file() {
+            files += 1;
+        }
+    }
+    Ok(files)
+}
+
+/// 恢复只读快照中各目录的写权限（仅 Unix），以便删除快照
+///
+/// 只修改目录（目录不共享硬链接），文件保持只读，不影响其他快照中的同一文件。
+/// Windows 上删除目录树时会忽略文件的只读属性，无需处理。
+///
+/// # 参数
+/// * `dir` - 要删除的目录
+pub fn make_dirs_writable(dir: &Path) {
+    #[cfg(unix)]
+    for entry in walkdir::WalkDir::new(dir).follow_links(false).into_iter().flatten() {
+        use std::os::unix::fs::PermissionsExt;
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        if let Ok(meta) = entry.metadata() {
+            let mut perms = meta.permissions();
+            if perms.mode() & 0o200 == 0 {
+                perms.set_mode(perms.mode() | 0o700);
+                fs::set_permissions(entry.path(), perms).ok();
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// 删除目录中遗留的临时文件
+///
+/// 备份中断时，正在复制的文件会以临时文件的形式留在 `.partial` 目录中；
+/// 续传前调用此函数清理，避免它们被带入最终的快照。
+///
+/// # 参数
+/// * `dir` - 要清理的目录（通常为 `.partial` 目录）
+///
+/// # 返回
+/// * `Ok(usize)` - 删除的临时文件数量
+/// * `Err(RecallError)` - 删除失败
+pub fn remove_temp_files(dir: &Path) -> crate::error::Result<usize> {
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(to_verbatim_path(dir)).follow_links(false) {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        if entry.file_type().is_file()
+            && entry.file_name().to_string_lossy().ends_with(TEMP_SUFFIX)
         {
-            std::os::unix::fs::symlink(final_path, link_path)
-                .with_context(|| "Failed to create symlink on Unix")?;
+            remove_stale(entry.path()).classify(RecallError::Destination)?;
+            removed += 1;
         }
+    }
+    Ok(removed)
+}
 
+/// 删除目标路径上遗留的文件（续传时可能存在不完整的文件）
+///
+/// 不能直接覆盖写入：遗留文件可能是指向旧备份的硬链接，
+/// 原地写入会同时修改旧备份中的内容。
+fn remove_stale(path: &Path) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if !meta.is_dir() => {
+            // Windows 无法删除只读文件（Unix 删除只需要目录的写权限）
+            #[cfg(windows)]
+            if meta.permissions().readonly() && !meta.is_symlink() {
+                // 只读属性由所有硬链接共享：文件还链接在只读快照中时不能清除它，改为忽略只读属性直接删除
+                if crate::utils::hard_link_count(path).is_none_or(|n| n > 1) {
+                    return remove_shared_read_only(path);
+                }
+                let mut perms = meta.permissions();
+                perms.set_readonly(false);
+                fs::set_permissions(path, perms).ok();
+            }
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale file {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 删除与其他快照共享的只读硬链接，不修改文件的只读属性
+///
+/// 先把链接重命名到一旁（带临时文件后缀，中断时由 [`remove_temp_files`] 清理），
+/// 再以 `FILE_DISPOSITION_FLAG_IGNORE_READONLY_ATTRIBUTE` 删除，其他快照中的文件保持只读。
+/// 需要 Windows 10 1809 及以上的 NTFS；不支持时把链接改回原名并返回错误。
+#[cfg(windows)]
+fn remove_shared_read_only(path: &Path) -> Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::fileapi::SetFileInformationByHandle;
+    use winapi::um::winnt::DELETE;
+
+    // winapi 0.3 未提供 FileDispositionInfoEx 相关的定义
+    const FILE_DISPOSITION_INFO_EX: u32 = 21;
+    const FILE_DISPOSITION_FLAG_DELETE: DWORD = 0x1;
+    const FILE_DISPOSITION_FLAG_POSIX_SEMANTICS: DWORD = 0x2;
+    const FILE_DISPOSITION_FLAG_IGNORE_READONLY_ATTRIBUTE: DWORD = 0x10;
+
+    let aside = temp_path_for(path);
+    fs::rename(path, &aside).with_context(|| format!("Failed to move stale file {:?} aside", path))?;
+
+    let result = fs::OpenOptions::new().access_mode(DELETE).open(&aside).and_then(|file| {
+        let mut flags: DWORD = FILE_DISPOSITION_FLAG_DELETE
+            | FILE_DISPOSITION_FLAG_POSIX_SEMANTICS
+            | FILE_DISPOSITION_FLAG_IGNORE_READONLY_ATTRIBUTE;
+        let ok = unsafe {
+            SetFileInformationByHandle(
+                file.as_raw_handle() as _,
+                FILE_DISPOSITION_INFO_EX,
+                &mut flags as *mut DWORD as _,
+                std::mem::size_of::<DWORD>() as DWORD,
+            )
+        };
+        if ok == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
         Ok(())
+    });
+
+    if let Err(e) = result {
+        fs::rename(&aside, path).ok();
+        return Err(e).with_context(|| format!("Failed to remove stale read-only file {:?}", path));
     }
+    tracing::debug!("Removed stale read-only hard link {:?} without clearing its attribute", path);
+    Ok(())
 }
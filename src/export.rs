@@ -0,0 +1,263 @@
+// Recall - 快照导出模块
+// 将单个快照打包为 zip / tar 归档，硬链接展开为普通文件，便于交给他人或写入一次性介质
+
+use crate::summary::{MANIFEST_FILE, SUMMARY_FILE};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use filetime::FileTime;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// 归档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// 未压缩的 tar
+    Tar,
+
+    /// gzip 压缩的 tar
+    TarGz,
+
+    /// zstd 压缩的 tar
+    TarZst,
+
+    /// zip（deflate 压缩）
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// 所有格式的名称及对应的文件扩展名
+    const NAMES: [(&'static str, ArchiveFormat); 5] = [
+        ("tar", ArchiveFormat::Tar),
+        ("tar.gz", ArchiveFormat::TarGz),
+        ("tgz", ArchiveFormat::TarGz),
+        ("tar.zst", ArchiveFormat::TarZst),
+        ("zip", ArchiveFormat::Zip),
+    ];
+
+    /// 根据输出文件的扩展名判断格式
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::export::ArchiveFormat;
+    /// use std::path::Path;
+    /// assert_eq!(ArchiveFormat::from_path(Path::new("out.tar.zst")), Some(ArchiveFormat::TarZst));
+    /// assert_eq!(ArchiveFormat::from_path(Path::new("out.ZIP")), Some(ArchiveFormat::Zip));
+    /// assert_eq!(ArchiveFormat::from_path(Path::new("out.7z")), None);
+    /// ```
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        // 较长的扩展名优先（`.tar.gz` 先于 `.tar`）
+        let mut names = Self::NAMES;
+        names.sort_by_key(|(ext, _)| std::cmp::Reverse(ext.len()));
+        names
+            .iter()
+            .find(|(ext, _)| name.ends_with(&format!(".{}", ext)))
+            .map(|(_, format)| *format)
+    }
+}
+
+/// 解析 `--format` 参数
+pub fn parse_format(s: &str) -> Result<ArchiveFormat, String> {
+    ArchiveFormat::NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        .map(|(_, format)| *format)
+        .ok_or_else(|| format!("unknown archive format '{}' (expected zip, tar, tar.gz or tar.zst)", s))
+}
+
+/// 导出结果统计
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ExportStats {
+    /// 写入的文件数量
+    pub files: u64,
+
+    /// 写入的目录数量
+    pub dirs: u64,
+
+    /// 写入的符号链接数量
+    pub symlinks: u64,
+
+    /// 文件内容的总大小（未压缩）
+    pub bytes: u64,
+
+    /// 归档文件的大小
+    pub archive_size: u64,
+}
+
+/// 将快照导出为归档文件
+///
+/// 归档中的条目位于以快照名称命名的顶层目录下；硬链接展开为普通文件，
+/// 符号链接保留为链接，recall 的摘要和清单文件不会写入。
+/// 归档先写入临时文件，完成后才重命名为输出文件名。
+///
+/// # 参数
+/// * `snapshot` - 快照目录
+/// * `output` - 输出文件路径
+/// * `format` - 归档格式
+///
+/// # 返回
+/// * `Ok(ExportStats)` - 导出结果统计
+/// * `Err(anyhow::Error)` - 读取快照或写入归档失败
+pub fn export_snapshot(snapshot: &Path, output: &Path, format: ArchiveFormat) -> Result<ExportStats> {
+    write_archive(snapshot, output, format, false)
+}
+
+/// 将快照写入归档文件
+///
+/// `metadata` 为 `true` 时（归档旧快照）摘要和清单作为最前面的条目写入，
+/// 读取它们时无需解压整个归档。
+pub(crate) fn write_archive(snapshot: &Path, output: &Path, format: ArchiveFormat, metadata: bool) -> Result<ExportStats> {
+    let snapshot = fs::canonicalize(snapshot).with_context(|| format!("Failed to access {:?}", snapshot))?;
+    if !snapshot.is_dir() {
+        anyhow::bail!("{:?} is not a snapshot directory", snapshot);
+    }
+    let root = snapshot.file_name().context("Invalid snapshot path")?.to_string_lossy().to_string();
+
+    let name = output.file_name().context("Invalid output path")?.to_string_lossy();
+    let temp = output.with_file_name(format!(".{}.partial", name));
+    let writer = BufWriter::new(File::create(&temp).with_context(|| format!("Failed to create {:?}", temp))?);
+
+    let mut stats = ExportStats::default();
+    let result = match format {
+        ArchiveFormat::Tar => write_tar(&snapshot, &root, metadata, writer, &mut stats).and_then(finish_writer),
+        ArchiveFormat::TarGz => {
+            let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            write_tar(&snapshot, &root, metadata, encoder, &mut stats).and_then(|e| finish_writer(e.finish()?))
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(writer, 0)?;
+            write_tar(&snapshot, &root, metadata, encoder, &mut stats).and_then(|e| finish_writer(e.finish()?))
+        }
+        ArchiveFormat::Zip => write_zip(&snapshot, &root, metadata, writer, &mut stats).and_then(finish_writer),
+    };
+    if let Err(e) = result {
+        fs::remove_file(&temp).ok();
+        return Err(e);
+    }
+
+    fs::rename(&temp, output).with_context(|| format!("Failed to rename {:?} to {:?}", temp, output))?;
+    stats.archive_size = fs::metadata(output)?.len();
+    Ok(stats)
+}
+
+/// 刷新写入缓冲并同步到磁盘
+fn finish_writer(writer: BufWriter<File>) -> Result<()> {
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// 遍历快照中要导出的条目，返回 (路径, 归档中的名称)
+///
+/// `metadata` 为 `true` 时先返回快照根部的摘要和清单，否则跳过它们。
+fn entries<'a>(
+    snapshot: &'a Path,
+    root: &str,
+    metadata: bool,
+) -> impl Iterator<Item = Result<(walkdir::DirEntry, PathBuf)>> + 'a {
+    let root = PathBuf::from(root);
+    let metadata_files = [SUMMARY_FILE, MANIFEST_FILE]
+        .into_iter()
+        .map(move |name| snapshot.join(name))
+        .filter(move |path| metadata && path.is_file())
+        .flat_map(WalkDir::new);
+    let tree = WalkDir::new(snapshot)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter(move |entry| {
+            // 跳过快照根部的摘要和清单
+            !entry.as_ref().is_ok_and(|e| {
+                e.depth() == 1 && (e.file_name() == SUMMARY_FILE || e.file_name() == MANIFEST_FILE)
+            })
+        });
+    metadata_files.chain(tree).map(move |entry| {
+        let entry = entry?;
+        let name = root.join(entry.path().strip_prefix(snapshot)?);
+        Ok((entry, name))
+    })
+}
+
+/// 写入 tar 归档
+fn write_tar<W: Write>(snapshot: &Path, root: &str, metadata: bool, writer: W, stats: &mut ExportStats) -> Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+
+    for item in entries(snapshot, root, metadata) {
+        let (entry, name) = item?;
+        builder
+            .append_path_with_name(entry.path(), &name)
+            .with_context(|| format!("Failed to add {:?}", entry.path()))?;
+        count(&entry, stats)?;
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+/// 写入 zip 归档
+fn write_zip(
+    snapshot: &Path,
+    root: &str,
+    metadata: bool,
+    writer: BufWriter<File>,
+    stats: &mut ExportStats,
+) -> Result<BufWriter<File>> {
+    let mut zip = ZipWriter::new(writer);
+
+    for item in entries(snapshot, root, metadata) {
+        let (entry, name) = item?;
+        // zip 中的路径总是使用 `/` 分隔
+        let name = name.to_string_lossy().replace('\\', "/");
+        let meta = entry.path().symlink_metadata()?;
+        let mut options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(meta.len() >= u32::MAX as u64);
+        let mtime = FileTime::from_last_modification_time(&meta);
+        if let Some(time) = DateTime::from_timestamp(mtime.unix_seconds(), 0) {
+            if let Ok(time) = zip::DateTime::try_from(time.with_timezone(&Local).naive_local()) {
+                options = options.last_modified_time(time);
+            }
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(meta.permissions().mode() & 0o7777);
+        }
+
+        if entry.file_type().is_dir() {
+            zip.add_directory(name, options)?;
+        } else if entry.file_type().is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            zip.add_symlink(name, target.to_string_lossy(), options)?;
+        } else {
+            zip.start_file(name, options)?;
+            let mut file = File::open(entry.path()).with_context(|| format!("Failed to open {:?}", entry.path()))?;
+            std::io::copy(&mut file, &mut zip).with_context(|| format!("Failed to add {:?}", entry.path()))?;
+        }
+        count(&entry, stats)?;
+    }
+
+    Ok(zip.finish()?)
+}
+
+/// 统计写入的条目
+fn count(entry: &walkdir::DirEntry, stats: &mut ExportStats) -> Result<()> {
+    // 单独写入的摘要和清单不计入统计
+    if entry.depth() == 0 && entry.file_type().is_file() {
+        return Ok(());
+    }
+    if entry.file_type().is_dir() {
+        stats.dirs += 1;
+    } else if entry.file_type().is_symlink() {
+        stats.symlinks += 1;
+    } else {
+        stats.files += 1;
+        stats.bytes += entry.metadata()?.len();
+    }
+    Ok(())
+}
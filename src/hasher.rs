@@ -1,11 +1,18 @@
 // Recall - 文件哈希计算模块
 // 使用 XXH3 算法计算文件内容的哈希值，用于检测文件是否发生变化
 
+use crate::copy::open_for_backup;
+use memmap2::Mmap;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
 use xxhash_rust::xxh3::Xxh3;
 
+/// 使用内存映射计算快照文件哈希的最小文件大小（64 MB），见 [`calculate_snapshot_hash`]
+///
+/// 较小的文件建立映射的开销超过节省的复制，仍使用缓冲读取。
+pub const MMAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+
 /// 计算文件内容的 XXH3 哈希值
 ///
 /// 此函数使用 XXH3 算法计算文件的哈希值，用于在启用内容检查时
@@ -22,10 +29,39 @@ use xxhash_rust::xxh3::Xxh3;
 /// - 使用 1MB 的缓冲区读取文件
 /// - 使用 64KB 的缓冲区进行哈希计算
 /// - XXH3 是一种非常快速的非加密哈希算法，适合文件比对
+/// - 源文件可能正被其他程序写入，始终使用缓冲读取；快照中的文件见 [`calculate_snapshot_hash`]
 pub fn calculate_hash(path: &Path) -> anyhow::Result<u64> {
     // 打开文件
-    let file = File::open(path)?;
+    let file = open_for_backup(path)?;
+    hash_buffered(file)
+}
 
+/// 计算快照中文件内容的 XXH3 哈希值
+///
+/// 快照中的文件由 recall 写入后不再修改，不小于 [`MMAP_THRESHOLD`] 的文件通过内存映射直接哈希，
+/// 省去从内核缓冲区到用户缓冲区的复制；无法映射时（网络文件系统、某些特殊文件）退回缓冲读取。
+/// 结果与 [`calculate_hash`] 相同。
+///
+/// 映射期间文件被截断会使进程收到总线错误，因此**不要**用于可能正被其他程序写入的源文件。
+///
+/// # 参数
+/// * `path` - 快照中的文件路径
+///
+/// # 返回
+/// * `Ok(u64)` - 文件的 64 位哈希值
+/// * `Err(anyhow::Error)` - 如果读取文件失败
+pub fn calculate_snapshot_hash(path: &Path) -> anyhow::Result<u64> {
+    let file = open_for_backup(path)?;
+    if file.metadata()?.len() >= MMAP_THRESHOLD {
+        if let Some(hash) = hash_mapped(&file) {
+            return Ok(hash);
+        }
+    }
+    hash_buffered(file)
+}
+
+/// 逐块读取文件并计算哈希值
+fn hash_buffered(file: File) -> anyhow::Result<u64> {
     // 创建带缓冲的读取器，1MB 缓冲区以提高性能
     let mut reader = BufReader::with_capacity(1024 * 1024, file);
 
@@ -47,3 +83,19 @@ pub fn calculate_hash(path: &Path) -> anyhow::Result<u64> {
     // 返回最终的哈希值
     Ok(hasher.digest())
 }
+
+/// 通过内存映射计算整个文件的哈希值
+///
+/// # 返回
+/// * `Some(u64)` - 文件的 64 位哈希值
+/// * `None` - 无法建立映射，调用方应改用缓冲读取
+fn hash_mapped(file: &File) -> Option<u64> {
+    // SAFETY: 映射只读且只在本函数内使用；调用方保证文件属于已写入完成的快照，不会被截断
+    let map = unsafe { Mmap::map(file) }.ok()?;
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+
+    let mut hasher = Xxh3::new();
+    hasher.update(&map);
+    Some(hasher.digest())
+}
@@ -0,0 +1,65 @@
+// Recall - 监控心跳模块
+// 运行开始和结束时请求 Healthchecks.io 风格的监控 URL，计划备份悄悄停止运行时由监控服务发出告警
+
+use crate::webhook::{host, post};
+use anyhow::Result;
+
+/// 监控服务接收的请求正文上限（Healthchecks.io 为 100 KB）
+const MAX_BODY: usize = 100 * 1024;
+
+/// 心跳事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingEvent {
+    /// 运行开始（监控服务据此计算运行时长）
+    Start,
+
+    /// 运行成功
+    Success,
+
+    /// 运行失败或部分失败
+    Failure,
+}
+
+impl PingEvent {
+    /// 事件对应的 URL
+    ///
+    /// 与 Healthchecks.io 的约定相同：开始时请求 `<url>/start`，成功时请求 `<url>`，失败时请求 `<url>/fail`。
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::healthcheck::PingEvent;
+    /// let url = "https://hc-ping.com/1234";
+    /// assert_eq!(PingEvent::Start.url(url), "https://hc-ping.com/1234/start");
+    /// assert_eq!(PingEvent::Success.url(url), "https://hc-ping.com/1234");
+    /// assert_eq!(PingEvent::Failure.url("https://hc-ping.com/1234/"), "https://hc-ping.com/1234/fail");
+    /// ```
+    pub fn url(&self, base: &str) -> String {
+        let base = base.trim_end_matches('/');
+        match self {
+            PingEvent::Start => format!("{}/start", base),
+            PingEvent::Success => base.to_string(),
+            PingEvent::Failure => format!("{}/fail", base),
+        }
+    }
+}
+
+/// 向监控服务发送心跳
+///
+/// 网络错误和服务端错误（5xx）时重试。`body` 作为运行日志显示在监控服务中，超过上限时截断。
+///
+/// # 参数
+/// * `base` - 配置文件中的监控 URL
+/// * `event` - 心跳事件
+/// * `body` - 请求正文（运行摘要或失败原因）
+///
+/// # 返回
+/// * `Ok(())` - 服务端返回 2xx
+/// * `Err(anyhow::Error)` - 请求失败
+pub fn send_ping(base: &str, event: PingEvent, body: &str) -> Result<()> {
+    let mut end = body.len().min(MAX_BODY);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    post(&event.url(base), "text/plain; charset=utf-8", &body[..end])
+        .map_err(|reason| anyhow::anyhow!("Health check ping to {} failed: {}", host(base), reason))
+}
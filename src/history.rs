@@ -0,0 +1,318 @@
+// Recall - 运行历史模块
+// 在配置目录中逐行追加每次备份的结果，即使快照已被清理也能查看历次运行的趋势；
+// 备份目标根目录中另有一份随硬盘保存的统计记录，用于按月查看数据增长和剩余空间
+
+use crate::store::AppConfig;
+use crate::utils::csv_field;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 历史记录文件名（位于配置文件所在目录，每行一条 JSON 记录）
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// 备份目标统计记录文件名（位于备份目标根目录，每行一条 JSON 记录）
+pub const STATS_FILE: &str = ".recall-stats.jsonl";
+
+/// 迷你折线图使用的字符（从低到高）
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 单次运行的记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunRecord {
+    /// 配置文件名称（命令行运行时为项目名称）
+    pub profile: String,
+
+    /// 开始时间（本地时间，`%Y-%m-%d %H:%M:%S`）
+    pub started_at: String,
+
+    /// 运行结果：`ok`、`partial`（有文件失败）或 `failed`
+    pub status: String,
+
+    /// 创建的快照名称（失败时为空）
+    pub snapshot: Option<String>,
+
+    /// 耗时（秒）
+    pub duration_secs: u64,
+
+    /// 处理的文件总数
+    pub total_files: u64,
+
+    /// 复制（含克隆）的文件数量
+    pub copied: u64,
+
+    /// 硬链接的文件数量
+    pub linked: u64,
+
+    /// 失败的文件数量
+    pub failed: u64,
+
+    /// 传输的总字节数
+    pub bytes_copied: u64,
+
+    /// 以硬链接引用的总字节数（未占用新空间）
+    pub bytes_linked: u64,
+
+    /// 备份完成后目标卷的可用空间（无法查询时为空）
+    pub free_bytes: Option<u64>,
+
+    /// 运行失败的原因
+    pub error: Option<String>,
+}
+
+impl RunRecord {
+    /// 开始时间（解析失败时为 `None`）
+    pub fn started(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.started_at, "%Y-%m-%d %H:%M:%S").ok()
+    }
+
+    /// 硬链接文件占全部文件的比例（0.0 ~ 1.0）
+    pub fn link_ratio(&self) -> f64 {
+        if self.total_files == 0 {
+            0.0
+        } else {
+            self.linked as f64 / self.total_files as f64
+        }
+    }
+}
+
+/// 历史记录文件路径（与配置文件位于同一目录）
+pub fn history_path() -> Result<PathBuf> {
+    Ok(AppConfig::get_config_path()?.with_file_name(HISTORY_FILE))
+}
+
+/// 追加一条运行记录
+///
+/// # 参数
+/// * `record` - 运行记录
+///
+/// # 返回
+/// * `Ok(())` - 写入成功
+/// * `Err(anyhow::Error)` - 无法写入历史记录文件
+pub fn record_run(record: &RunRecord) -> Result<()> {
+    append_record(&history_path()?, record)
+}
+
+/// 在备份目标根目录的统计记录中追加一条运行记录
+///
+/// 统计记录随备份硬盘保存，换一台电脑或重装系统后仍能查看这块硬盘的历次运行。
+///
+/// # 参数
+/// * `destination` - 备份目标目录
+/// * `record` - 运行记录
+///
+/// # 返回
+/// * `Ok(())` - 写入成功
+/// * `Err(anyhow::Error)` - 无法写入统计记录文件
+pub fn record_destination_run(destination: &Path, record: &RunRecord) -> Result<()> {
+    append_record(&destination.join(STATS_FILE), record)
+}
+
+/// 读取运行记录（按时间顺序，最旧的在前）
+///
+/// 无法解析的行会被跳过。
+///
+/// # 参数
+/// * `profile` - 只返回此配置文件的记录（`None` 表示全部）
+///
+/// # 返回
+/// * `Ok(Vec<RunRecord>)` - 运行记录
+/// * `Err(anyhow::Error)` - 历史记录文件存在但无法读取
+pub fn load_history(profile: Option<&str>) -> Result<Vec<RunRecord>> {
+    Ok(read_records(&history_path()?)?
+        .into_iter()
+        .filter(|record| profile.is_none_or(|p| record.profile == p))
+        .collect())
+}
+
+/// 读取备份目标根目录中的统计记录（按时间顺序，最旧的在前）
+///
+/// # 参数
+/// * `destination` - 备份目标目录
+///
+/// # 返回
+/// * `Ok(Vec<RunRecord>)` - 运行记录（还没有记录时为空）
+/// * `Err(anyhow::Error)` - 统计记录文件存在但无法读取
+pub fn load_destination_stats(destination: &Path) -> Result<Vec<RunRecord>> {
+    read_records(&destination.join(STATS_FILE))
+}
+
+/// 读取每个配置文件最近一次运行的记录
+///
+/// # 返回
+/// * `Ok(HashMap<String, RunRecord>)` - 配置文件名称到最近一次运行记录的映射
+/// * `Err(anyhow::Error)` - 历史记录文件存在但无法读取
+pub fn last_runs() -> Result<HashMap<String, RunRecord>> {
+    Ok(load_history(None)?
+        .into_iter()
+        .map(|record| (record.profile.clone(), record))
+        .collect())
+}
+
+/// 一个月内所有运行的汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MonthStats {
+    /// 月份（`%Y-%m`）
+    pub month: String,
+
+    /// 运行次数
+    pub runs: u64,
+
+    /// 本月传输的总字节数
+    pub bytes_copied: u64,
+
+    /// 本月最后一次运行的快照大小（传输与硬链接字节数之和）
+    pub snapshot_bytes: u64,
+
+    /// 平均耗时（秒）
+    pub duration_secs: u64,
+
+    /// 本月最后一次查询到的目标卷可用空间
+    pub free_bytes: Option<u64>,
+}
+
+/// 按月汇总运行记录（按时间顺序，没有运行的月份不出现）
+///
+/// 失败的运行没有传输数据，不计入汇总。
+///
+/// # 参数
+/// * `records` - 运行记录（最旧的在前）
+///
+/// # 返回
+/// 每月的汇总
+pub fn monthly_stats(records: &[RunRecord]) -> Vec<MonthStats> {
+    let mut months: Vec<MonthStats> = Vec::new();
+    let mut total_secs = 0;
+    for record in records.iter().filter(|r| r.status != "failed") {
+        let Some(month) = record.started_at.get(..7) else { continue };
+        if months.last().is_none_or(|m| m.month != month) {
+            total_secs = 0;
+            months.push(MonthStats { month: month.to_string(), ..Default::default() });
+        }
+        let current = months.last_mut().expect("month was just pushed");
+        current.runs += 1;
+        current.bytes_copied += record.bytes_copied;
+        current.snapshot_bytes = record.bytes_copied + record.bytes_linked;
+        total_secs += record.duration_secs;
+        current.duration_secs = total_secs / current.runs;
+        current.free_bytes = record.free_bytes.or(current.free_bytes);
+    }
+    months
+}
+
+/// 按目标卷可用空间的变化速度估算还有多少天写满
+///
+/// 取最早和最近一次记录了可用空间的运行做线性外推。
+///
+/// # 参数
+/// * `records` - 运行记录（最旧的在前）
+///
+/// # 返回
+/// * `Some(f64)` - 预计剩余天数
+/// * `None` - 记录不足一天，或可用空间没有减少
+pub fn days_until_full(records: &[RunRecord]) -> Option<f64> {
+    let mut samples = records
+        .iter()
+        .filter_map(|r| Some((r.started()?, r.free_bytes?)));
+    let (first_at, first_free) = samples.next()?;
+    let (last_at, last_free) = samples.next_back()?;
+    let days = (last_at - first_at).num_seconds() as f64 / 86_400.0;
+    if days < 1.0 || last_free >= first_free {
+        return None;
+    }
+    let per_day = (first_free - last_free) as f64 / days;
+    Some(last_free as f64 / per_day)
+}
+
+/// 把运行记录导出为 CSV（每次运行一行，带表头）
+///
+/// # 参数
+/// * `records` - 运行记录
+///
+/// # 返回
+/// CSV 文本
+pub fn to_csv(records: &[RunRecord]) -> String {
+    let mut csv = String::from(
+        "started_at,profile,status,snapshot,duration_secs,total_files,copied,linked,failed,bytes_copied,bytes_linked,free_bytes\n",
+    );
+    for r in records {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            r.started_at,
+            csv_field(&r.profile),
+            r.status,
+            csv_field(r.snapshot.as_deref().unwrap_or("")),
+            r.duration_secs,
+            r.total_files,
+            r.copied,
+            r.linked,
+            r.failed,
+            r.bytes_copied,
+            r.bytes_linked,
+            r.free_bytes.map(|b| b.to_string()).unwrap_or_default()
+        );
+    }
+    csv
+}
+
+/// 绘制迷你折线图（每个值一个字符，按最小值到最大值映射到八级高度）
+///
+/// # 参数
+/// * `values` - 数据序列
+///
+/// # 返回
+/// 由 `▁` 到 `█` 组成的字符串
+///
+/// # 示例
+/// ```
+/// use recall::history::sparkline;
+/// assert_eq!(sparkline(&[0, 2, 14]), "▁▂█");
+/// assert_eq!(sparkline(&[5, 5]), "▁▁");
+/// assert_eq!(sparkline(&[]), "");
+/// ```
+pub fn sparkline(values: &[u64]) -> String {
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let range = (max - min).max(1) as f64;
+    values
+        .iter()
+        .map(|&v| {
+            let level = ((v - min) as f64 / range * (SPARK_CHARS.len() - 1) as f64).round() as usize;
+            SPARK_CHARS[level]
+        })
+        .collect()
+}
+
+/// 在记录文件末尾追加一行 JSON
+fn append_record(path: &Path, record: &RunRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(record)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open history file {:?}", path))?;
+    writeln!(file, "{}", line).with_context(|| format!("Failed to write history file {:?}", path))
+}
+
+/// 读取记录文件中的全部记录（文件不存在时为空，无法解析的行会被跳过）
+fn read_records(path: &Path) -> Result<Vec<RunRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history file {:?}", path))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<RunRecord>(line).ok())
+        .collect())
+}
@@ -0,0 +1,102 @@
+// Recall - 钩子脚本模块
+// 在备份开始前（如导出数据库）和结束后（如卸载备份盘）运行配置文件中的命令，通过环境变量传递快照路径和统计信息
+
+use crate::note;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+
+/// 钩子失败时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// 备份前钩子失败时不执行备份，备份后钩子失败时本次运行记为失败（默认）
+    #[default]
+    Abort,
+
+    /// 只输出警告，备份照常进行，运行结果不受影响
+    Continue,
+}
+
+impl HookFailure {
+    /// 是否为默认的 `abort`（保存配置时省略）
+    pub fn is_abort(&self) -> bool {
+        *self == HookFailure::Abort
+    }
+}
+
+/// 钩子的运行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPhase {
+    /// 扫描源目录之前
+    Pre,
+
+    /// 快照提交之后（备份失败时同样运行）
+    Post,
+}
+
+impl HookPhase {
+    /// 阶段名称（`RECALL_HOOK` 环境变量的值）
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookPhase::Pre => "pre",
+            HookPhase::Post => "post",
+        }
+    }
+}
+
+/// 通过系统 shell 运行钩子命令
+///
+/// 命令在 Windows 上由 `cmd /C`、其他平台由 `sh -c` 执行，输出逐行转发到 recall 的输出（和运行日志）。
+/// 环境变量 `RECALL_HOOK` 为阶段名称，其余变量由调用方提供。
+///
+/// # 参数
+/// * `command` - 命令行
+/// * `phase` - 运行阶段
+/// * `env` - 额外的环境变量
+///
+/// # 返回
+/// * `Ok(())` - 命令以状态 0 退出
+/// * `Err(anyhow::Error)` - 命令无法启动或以非 0 状态退出
+pub fn run_hook(command: &str, phase: HookPhase, env: &[(&str, String)]) -> Result<()> {
+    #[cfg(windows)]
+    let mut child = Command::new("cmd");
+    #[cfg(windows)]
+    child.arg("/C").arg(command);
+    #[cfg(not(windows))]
+    let mut child = Command::new("sh");
+    #[cfg(not(windows))]
+    child.arg("-c").arg(command);
+
+    let mut child = child
+        .env("RECALL_HOOK", phase.name())
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}-backup hook '{}'", phase.name(), command))?;
+
+    // 同时读取标准输出和标准错误，避免其中一个管道写满后命令阻塞
+    let stderr = child.stderr.take().map(|stderr| std::thread::spawn(move || forward_lines(stderr)));
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout);
+    }
+    if let Some(handle) = stderr {
+        handle.join().ok();
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{}-backup hook '{}' failed ({})", phase.name(), command, status);
+    }
+    Ok(())
+}
+
+/// 将命令的输出逐行转发到 recall 的输出
+fn forward_lines(output: impl Read) {
+    for line in BufReader::new(output).lines().map_while(Result::ok) {
+        note!("  {} {}", crate::utils::style("|").dim(), line);
+    }
+}
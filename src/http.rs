@@ -0,0 +1,126 @@
+// Recall - HTTP 模块
+// 远程备份目标（S3、WebDAV）共用的 HTTP 客户端设置、重试和 URL 编码
+
+use std::time::Duration;
+
+/// 请求失败（网络错误或服务端 5xx）时的最大重试次数
+const RETRIES: u32 = 3;
+
+/// 第一次重试前的等待时间（之后每次加倍）
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// 创建 HTTP 客户端
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(30))
+        .timeout(Duration::from_secs(300))
+        .build()
+}
+
+/// 发送请求，网络错误和服务端错误（5xx）时等待后重试
+///
+/// 每次重试都会重新调用 `send`（签名、请求体等随之重新生成）。
+///
+/// # 返回
+/// 最后一次请求的结果；4xx 错误不重试，直接返回
+pub fn with_retry(
+    send: impl Fn() -> Result<ureq::Response, Box<ureq::Error>>,
+) -> Result<ureq::Response, Box<ureq::Error>> {
+    let mut attempt = 0;
+    loop {
+        match send() {
+            Err(e) if attempt < RETRIES && is_retryable(&e) => {
+                std::thread::sleep(RETRY_DELAY * 2u32.pow(attempt));
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// 检查请求错误是否值得重试
+fn is_retryable(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(code, _) => *code >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
+
+/// URL 编码（保留非保留字符；`encode_slash` 为 false 时保留 `/`）
+///
+/// # 示例
+/// ```
+/// use recall::http::percent_encode;
+/// assert_eq!(percent_encode("a b/c", false), "a%20b/c");
+/// assert_eq!(percent_encode("a b/c", true), "a%20b%2Fc");
+/// ```
+pub fn percent_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// URL 解码（无效的编码原样保留）
+///
+/// # 示例
+/// ```
+/// use recall::http::percent_decode;
+/// assert_eq!(percent_decode("a%20b/%E4%B8%AD"), "a b/中");
+/// ```
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 提取 XML 中所有指定元素的文本内容（忽略命名空间前缀，如 `<d:href>`）
+///
+/// 远程服务返回的 XML 结构简单，无需完整的 XML 解析。
+pub fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let name = rest[..end].split_whitespace().next().unwrap_or("");
+        let self_closing = rest[..end].ends_with('/');
+        rest = &rest[end + 1..];
+        // 去掉命名空间前缀后比较，跳过结束标签和自闭合标签
+        let local = name.rsplit(':').next().unwrap_or(name);
+        if local != tag || name.starts_with('/') || self_closing {
+            continue;
+        }
+        let Some(close) = rest.find('<') else { break };
+        values.push(xml_unescape(&rest[..close]));
+        rest = &rest[close..];
+    }
+    values
+}
+
+/// 还原 XML 实体
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
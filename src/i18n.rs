@@ -0,0 +1,142 @@
+// Recall - 国际化模块
+// 提供英文和中文两种界面语言，根据配置文件中的 `language` 或 LANG 等环境变量选择
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// 英文（默认）
+    English,
+    /// 简体中文
+    Chinese,
+}
+
+/// 当前界面语言
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+/// 设置界面语言
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// 当前界面语言
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+/// 解析语言名称（`en`、`zh`、`zh_CN.UTF-8` 等）
+///
+/// # 返回
+/// * `Some(Language)` - 可识别的语言
+/// * `None` - 空值、`auto` 或无法识别的语言
+pub fn parse_language(value: &str) -> Option<Language> {
+    let value = value.trim().to_ascii_lowercase();
+    if value.starts_with("zh") {
+        Some(Language::Chinese)
+    } else if value.starts_with("en") || value == "c" || value == "posix" {
+        Some(Language::English)
+    } else {
+        None
+    }
+}
+
+/// 确定界面语言
+///
+/// 优先使用配置文件中的 `language`（`auto` 或未设置时忽略），
+/// 其次依次检查 `RECALL_LANG`、`LC_ALL`、`LC_MESSAGES`、`LANG` 环境变量，都未设置时使用英文。
+///
+/// # 参数
+/// * `configured` - 配置文件中的 `language` 值
+pub fn detect_language(configured: Option<&str>) -> Language {
+    if let Some(language) = configured.and_then(parse_language) {
+        return language;
+    }
+    ["RECALL_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| parse_language(&value))
+        .unwrap_or(Language::English)
+}
+
+/// 按当前界面语言格式化文本
+///
+/// 两种语言的格式字符串使用相同的参数（按位置），只对选中的语言求值。
+///
+/// # 示例
+/// ```ignore
+/// let text = trf!("Found {} backup(s)", "找到 {} 个备份", count);
+/// ```
+#[macro_export]
+macro_rules! trf {
+    ($en:literal, $zh:literal $(, $arg:expr)* $(,)?) => {
+        match $crate::i18n::language() {
+            $crate::i18n::Language::English => format!($en $(, $arg)*),
+            $crate::i18n::Language::Chinese => format!($zh $(, $arg)*),
+        }
+    };
+}
+
+/// 按当前界面语言选择文本
+///
+/// # 参数
+/// * `en` - 英文文本
+/// * `zh` - 中文文本
+///
+/// # 示例
+/// ```ignore
+/// println!("{}", tr("Backup Completed Successfully!", "备份成功完成！"));
+/// ```
+pub fn tr<'a>(en: &'a str, zh: &'a str) -> &'a str {
+    match language() {
+        Language::English => en,
+        Language::Chinese => zh,
+    }
+}
+
+/// 按当前界面语言选择对齐的标签
+///
+/// 英文标签自带用于对齐的尾部空格；中文标签按显示宽度（中文字符占两列）补齐到相同宽度。
+///
+/// # 示例
+/// ```ignore
+/// println!("{}{}", label("Total Files:     ", "文件总数："), 42);
+/// ```
+pub fn label(en: &str, zh: &str) -> String {
+    match language() {
+        Language::English => en.to_string(),
+        Language::Chinese => align(zh, en.len(), false),
+    }
+}
+
+/// 按显示宽度对齐文本，用于包含中文的表头
+///
+/// # 参数
+/// * `text` - 文本
+/// * `width` - 显示宽度
+/// * `right` - 是否右对齐（否则左对齐）
+#[cfg(feature = "cli")]
+pub fn align(text: &str, width: usize, right: bool) -> String {
+    let alignment = if right {
+        console::Alignment::Right
+    } else {
+        console::Alignment::Left
+    };
+    console::pad_str(text, width, alignment, None).into_owned()
+}
+
+/// 按显示宽度对齐文本，用于包含中文的表头（中日韩文字和全角标点按两列计算）
+#[cfg(not(feature = "cli"))]
+pub fn align(text: &str, width: usize, right: bool) -> String {
+    let shown: usize = text.chars().map(|c| if c >= '\u{2E80}' { 2 } else { 1 }).sum();
+    let padding = " ".repeat(width.saturating_sub(shown));
+    if right {
+        format!("{}{}", padding, text)
+    } else {
+        format!("{}{}", text, padding)
+    }
+}
@@ -0,0 +1,412 @@
+// Recall - 导入模块
+// 识别其他工具留下的版本化备份目录（rsnapshot 的 daily.0、按日期命名的 robocopy 文件夹），
+// 按 recall 的快照命名导入到备份目标，并生成摘要和清单，切换工具后无需重新完整备份
+
+use crate::copy::{copy_file, CopyOptions};
+use crate::events::ConsoleSink;
+use crate::executor::BackupExecutor;
+use crate::manifest::{Manifest, MANIFEST_VERSION};
+use crate::naming::{SnapshotNaming, PARTIAL_SUFFIX};
+use crate::summary::BackupSummary;
+use crate::{trf, verbose, warning};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use filetime::FileTime;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 已有备份的目录布局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// rsnapshot：`hourly.0`、`daily.0`、`weekly.1` 等，时间取自目录的修改时间
+    Rsnapshot,
+
+    /// 名称中包含日期（和时间）的文件夹，如 `2024-01-15`、`Backup_20240115_1030`
+    Dated,
+}
+
+impl Layout {
+    /// 布局名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Rsnapshot => "rsnapshot",
+            Layout::Dated => "dated",
+        }
+    }
+}
+
+/// 解析 `--layout` 参数
+pub fn parse_layout(s: &str) -> Result<Layout, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "rsnapshot" => Ok(Layout::Rsnapshot),
+        "dated" | "robocopy" => Ok(Layout::Dated),
+        _ => Err(format!("unknown layout '{}' (expected rsnapshot or dated)", s)),
+    }
+}
+
+/// rsnapshot 的保留级别名称
+const RSNAPSHOT_INTERVALS: [&str; 6] = ["hourly", "daily", "weekly", "monthly", "yearly", "alpha"];
+
+/// 带时间的文件夹名称格式（去掉名称开头的非数字前缀后匹配）
+const DATETIME_FORMATS: [&str; 7] = [
+    "%Y-%m-%d_%H-%M-%S",
+    "%Y-%m-%d %H-%M-%S",
+    "%Y-%m-%d_%H-%M",
+    "%Y-%m-%d %H-%M",
+    "%Y%m%d_%H%M%S",
+    "%Y%m%d-%H%M%S",
+    "%Y%m%dT%H%M%S",
+];
+
+/// 只有日期的文件夹名称格式（时间取当天 00:00）
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y%m%d", "%Y.%m.%d", "%Y_%m_%d"];
+
+/// 判断是否为 rsnapshot 的快照目录名（如 `daily.0`）
+///
+/// # 示例
+/// ```
+/// use recall::import::is_rsnapshot_name;
+/// assert!(is_rsnapshot_name("daily.0"));
+/// assert!(is_rsnapshot_name("weekly.12"));
+/// assert!(!is_rsnapshot_name("daily.x"));
+/// assert!(!is_rsnapshot_name("_delete.1234"));
+/// ```
+pub fn is_rsnapshot_name(name: &str) -> bool {
+    name.split_once('.').is_some_and(|(interval, number)| {
+        RSNAPSHOT_INTERVALS.contains(&interval)
+            && !number.is_empty()
+            && number.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+/// 解析文件夹名称中的日期和时间
+///
+/// 名称开头的非数字前缀（如 `Backup_`）会被忽略。
+///
+/// # 示例
+/// ```
+/// use recall::import::parse_dated_name;
+/// assert_eq!(parse_dated_name("Backup_2024-01-15_10-30").unwrap().to_string(), "2024-01-15 10:30:00");
+/// assert_eq!(parse_dated_name("20240115").unwrap().to_string(), "2024-01-15 00:00:00");
+/// assert!(parse_dated_name("notes").is_none());
+/// ```
+pub fn parse_dated_name(name: &str) -> Option<NaiveDateTime> {
+    let stamp = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(stamp, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(stamp, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// 已有备份中的一个快照
+#[derive(Debug, Clone)]
+pub struct SourceSnapshot {
+    /// 快照目录
+    pub path: PathBuf,
+
+    /// 快照时间（本地时间）
+    pub time: DateTime<Local>,
+}
+
+/// 查找已有备份中的快照，按时间排序（最旧的在前）
+///
+/// # 参数
+/// * `source` - 已有备份的根目录（rsnapshot 的 `snapshot_root` 或存放日期文件夹的目录）
+/// * `layout` - 目录布局，`None` 时自动识别
+///
+/// # 返回
+/// * `Ok((Layout, Vec<SourceSnapshot>))` - 识别出的布局和快照
+/// * `Err(anyhow::Error)` - 无法读取目录，或没有找到可识别的快照
+pub fn find_snapshots(source: &Path, layout: Option<Layout>) -> Result<(Layout, Vec<SourceSnapshot>)> {
+    let mut dirs: Vec<(String, PathBuf)> = fs::read_dir(source)
+        .with_context(|| format!("Failed to read {:?}", source))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path()))
+        .collect();
+    dirs.sort();
+
+    let layout = match layout {
+        Some(layout) => layout,
+        None if dirs.iter().any(|(name, _)| is_rsnapshot_name(name)) => Layout::Rsnapshot,
+        None if dirs.iter().any(|(name, _)| parse_dated_name(name).is_some()) => Layout::Dated,
+        None => anyhow::bail!(
+            "No rsnapshot (daily.0) or dated backup folders found in {:?}; use --layout to choose one",
+            source
+        ),
+    };
+
+    let mut snapshots = Vec::new();
+    for (name, path) in dirs {
+        let time = match layout {
+            // rsnapshot 每次备份后会更新快照根目录的修改时间
+            Layout::Rsnapshot if is_rsnapshot_name(&name) => {
+                let meta = fs::metadata(&path).with_context(|| format!("Failed to read {:?}", path))?;
+                let mtime = FileTime::from_last_modification_time(&meta);
+                DateTime::from_timestamp(mtime.unix_seconds(), 0).map(|t| t.with_timezone(&Local))
+            }
+            Layout::Dated => parse_dated_name(&name).and_then(|t| Local.from_local_datetime(&t).earliest()),
+            _ => None,
+        };
+        match time {
+            Some(time) => snapshots.push(SourceSnapshot { path, time }),
+            None => verbose!("skip: {}", name),
+        }
+    }
+
+    if snapshots.is_empty() {
+        anyhow::bail!("No {} snapshots found in {:?}", layout.name(), source);
+    }
+    snapshots.sort_by_key(|s| s.time);
+    Ok((layout, snapshots))
+}
+
+/// 单个快照的导入结果
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportedSnapshot {
+    /// 原快照目录
+    pub source: PathBuf,
+
+    /// 导入后的快照名称
+    pub name: String,
+
+    /// 导入状态：`imported`、`exists`（同名快照已存在，跳过）或 `planned`（`--dry-run`）
+    pub status: &'static str,
+
+    /// 文件数量
+    pub files: u64,
+
+    /// 以硬链接导入的文件数量
+    pub linked: u64,
+
+    /// 复制的文件数量（无法创建硬链接时，如跨文件系统）
+    pub copied: u64,
+
+    /// 符号链接数量
+    pub symlinks: u64,
+
+    /// 导入失败的条目数量
+    pub failed: u64,
+
+    /// 复制的字节数
+    pub bytes_copied: u64,
+}
+
+/// 将已有备份中的快照导入到备份目标
+///
+/// 快照按时间从旧到新处理，每个快照写入 `<名称>.partial` 后再重命名。文件优先以硬链接指向原备份，
+/// 因此原备份中互为硬链接的文件导入后仍共享数据；无法链接时（如位于不同文件系统）
+/// 与上一个导入的快照比较大小和修改时间，未变化的文件链接到上一个快照，其余文件复制。
+/// 每个快照写入摘要（标签 `imported`）和清单。原备份不会被修改；同名快照已存在时跳过，
+/// 因此可以重复运行以导入新增的快照。
+///
+/// # 参数
+/// * `snapshots` - [`find_snapshots`] 找到的快照
+/// * `destination` - recall 的备份目标目录
+/// * `naming` - 快照命名
+/// * `subdir` - 只导入每个快照中的此子目录（如 rsnapshot 的 `localhost/home`）
+/// * `key` - 清单签名密钥
+/// * `dry_run` - 只列出将导入的快照，不写入任何内容
+///
+/// # 返回
+/// * `Ok(Vec<ImportedSnapshot>)` - 每个快照的导入结果
+/// * `Err(anyhow::Error)` - 无法创建目标目录或提交快照
+pub fn import_snapshots(
+    snapshots: &[SourceSnapshot],
+    destination: &Path,
+    naming: &SnapshotNaming,
+    subdir: Option<&Path>,
+    key: Option<&[u8]>,
+    dry_run: bool,
+) -> Result<Vec<ImportedSnapshot>> {
+    if !dry_run {
+        fs::create_dir_all(destination).with_context(|| format!("Failed to create {:?}", destination))?;
+    }
+
+    // 目标中已有的最新快照，导入的快照更新时才移动 current
+    let mut newest = naming.list(destination, false)?.last().map(|(time, _)| *time);
+    let mut previous: Option<PathBuf> = None;
+    let mut hashes = HashMap::new();
+    let mut results = Vec::new();
+
+    for snapshot in snapshots {
+        let name = naming.format_name(snapshot.time);
+        let final_path = destination.join(&name);
+        let mut result = ImportedSnapshot {
+            source: snapshot.path.clone(),
+            name: name.clone(),
+            status: "imported",
+            files: 0,
+            linked: 0,
+            copied: 0,
+            symlinks: 0,
+            failed: 0,
+            bytes_copied: 0,
+        };
+
+        if final_path.exists() {
+            if results.iter().any(|r: &ImportedSnapshot| r.name == name) {
+                warning!(
+                    "{}",
+                    trf!(
+                        "Warning: {:?} maps to the same snapshot name {} as an earlier folder; skipped",
+                        "警告：{:?} 与之前的文件夹对应同一个快照名称 {}，已跳过",
+                        snapshot.path,
+                        name
+                    )
+                );
+            }
+            result.status = "exists";
+            previous = Some(final_path);
+            results.push(result);
+            continue;
+        }
+        if dry_run {
+            result.status = "planned";
+            results.push(result);
+            continue;
+        }
+
+        let root = match subdir {
+            Some(subdir) => snapshot.path.join(subdir),
+            None => snapshot.path.clone(),
+        };
+        if !root.is_dir() {
+            anyhow::bail!("{:?} does not exist", root);
+        }
+
+        let temp_path = destination.join(format!("{}{}", name, PARTIAL_SUFFIX));
+        if temp_path.exists() {
+            fs::remove_dir_all(&temp_path).with_context(|| format!("Failed to remove {:?}", temp_path))?;
+        }
+        fs::create_dir(&temp_path).with_context(|| format!("Failed to create {:?}", temp_path))?;
+
+        import_tree(&root, &temp_path, previous.as_deref(), &mut result);
+
+        let summary = BackupSummary {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: snapshot.time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            source: root.clone(),
+            previous: previous
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+            total_files: result.files,
+            copied_new: result.copied,
+            linked: result.linked,
+            failed: result.failed,
+            bytes_copied: result.bytes_copied,
+            tags: vec!["imported".to_string()],
+            manifest: Some(MANIFEST_VERSION),
+            ..Default::default()
+        };
+        summary.save(&temp_path)?;
+        Manifest::build(&temp_path, &mut hashes)?.save(&temp_path, key)?;
+
+        let time = snapshot.time.naive_local();
+        if newest.is_none_or(|newest| time > newest) {
+            BackupExecutor::commit_backup(&temp_path, &final_path, &destination.join("current"), &ConsoleSink::new())?;
+            newest = Some(time);
+        } else {
+            fs::rename(&temp_path, &final_path)
+                .with_context(|| format!("Failed to rename {:?} to {:?}", temp_path, final_path))?;
+        }
+
+        previous = Some(final_path);
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// 将原快照目录的内容导入到临时快照目录（单个条目失败时记录在 `failed` 中）
+fn import_tree(root: &Path, target: &Path, previous: Option<&Path>, result: &mut ImportedSnapshot) {
+    for entry in WalkDir::new(root).follow_links(false).min_depth(1).sort_by_file_name() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                warning!(
+                    "{}",
+                    trf!("Warning: Failed to read {:?}: {}", "警告：无法读取 {:?}：{}", e.path().unwrap_or(root), e)
+                );
+                result.failed += 1;
+                continue;
+            }
+        };
+        let Ok(rel) = entry.path().strip_prefix(root) else { continue };
+        let dest = target.join(rel);
+
+        let imported = if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest).with_context(|| format!("Failed to create {:?}", dest))
+        } else if entry.file_type().is_symlink() {
+            import_symlink(entry.path(), &dest).map(|()| result.symlinks += 1)
+        } else {
+            result.files += 1;
+            import_file(entry.path(), &dest, previous.map(|p| p.join(rel)), result)
+        };
+
+        if let Err(e) = imported {
+            warning!(
+                "{}",
+                trf!("Warning: Failed to import {}: {:#}", "警告：无法导入 {}：{:#}", rel.display(), e)
+            );
+            result.failed += 1;
+        }
+    }
+}
+
+/// 导入单个文件：硬链接到原文件，失败时链接到上一个快照中未变化的同名文件，最后才复制
+fn import_file(src: &Path, dest: &Path, previous: Option<PathBuf>, result: &mut ImportedSnapshot) -> Result<()> {
+    if fs::hard_link(src, dest).is_ok() {
+        result.linked += 1;
+        return Ok(());
+    }
+
+    let src_meta = fs::metadata(src).with_context(|| format!("Failed to read {:?}", src))?;
+    let mtime = FileTime::from_last_modification_time(&src_meta);
+    if let Some(previous) = previous {
+        let unchanged = fs::symlink_metadata(&previous).is_ok_and(|meta| {
+            meta.is_file() && meta.len() == src_meta.len() && FileTime::from_last_modification_time(&meta) == mtime
+        });
+        if unchanged && fs::hard_link(&previous, dest).is_ok() {
+            result.linked += 1;
+            return Ok(());
+        }
+    }
+
+    verbose!("copy: {}", src.display());
+    result.bytes_copied += copy_file(src, dest, &CopyOptions::default())?;
+    filetime::set_file_mtime(dest, mtime).with_context(|| format!("Failed to set modification time of {:?}", dest))?;
+    fs::set_permissions(dest, src_meta.permissions()).ok();
+    result.copied += 1;
+    Ok(())
+}
+
+/// 按原样重建符号链接
+fn import_symlink(src: &Path, dest: &Path) -> Result<()> {
+    let target = fs::read_link(src).with_context(|| format!("Failed to read link {:?}", src))?;
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, dest).with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, target))?;
+
+    // Windows 区分文件链接和目录链接，按原链接指向的类型创建
+    #[cfg(windows)]
+    {
+        let result = if fs::metadata(src).is_ok_and(|m| m.is_dir()) {
+            std::os::windows::fs::symlink_dir(&target, dest)
+        } else {
+            std::os::windows::fs::symlink_file(&target, dest)
+        };
+        result.with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, target))?;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,24 @@
 // Recall - 基于 NTFS 硬链接的增量备份工具
 // 模块声明文件
 
+// 供输出宏（`status!` 等）使用
+#[doc(hidden)]
+pub use tracing;
+
 /// 文件操作和同步动作相关模块
 pub mod actions;
 
-/// 命令行交互界面模块
+/// 快照归档模块（`recall archive`，旧快照压缩为 tar.zst）
+pub mod archive;
+
+/// 存储后端接口模块
+pub mod backend;
+
+/// 分块仓库模块（内容寻址去重，快照索引）
+pub mod chunks;
+
+/// 命令行交互界面模块（需要 `cli` 功能）
+#[cfg(feature = "cli")]
 pub mod cli;
 
 /// 备份清理模块（删除旧备份）
@@ -17,17 +31,129 @@ pub mod vss;
 /// 备份配置管理模块
 pub mod config;
 
+/// 凭据模块（密码保存在系统凭据管理器中）
+pub mod credentials;
+
+/// 错误类型模块（供嵌入 recall 的程序区分错误类别）
+pub mod error;
+
+/// 事件模块（扫描、执行和清理过程的事件回调）
+pub mod events;
+
+/// 加密模块（分块仓库的静态加密）
+pub mod encryption;
+
+/// 文件复制模块（支持稀疏文件）
+pub mod copy;
+
+/// 快照比较模块
+pub mod diff;
+
+/// 备份预估模块
+pub mod estimate;
+
 /// 备份执行器模块
 pub mod executor;
 
+/// 快照导出模块（zip / tar 归档）
+pub mod export;
+
 /// 文件哈希计算模块
 pub mod hasher;
 
+/// 钩子脚本模块（备份前后运行的命令）
+pub mod hooks;
+
+/// HTTP 模块（远程目标共用的客户端、重试和编码）
+pub mod http;
+
+/// 运行历史模块（记录每次备份的结果）
+pub mod history;
+
+/// 国际化模块（英文 / 中文界面）
+pub mod i18n;
+
+/// 已有备份导入模块（rsnapshot / 按日期命名的文件夹）
+pub mod import;
+
+/// 运行日志模块（`--log-file`，按大小轮转）
+pub mod logfile;
+
+/// 日志订阅模块（控制台输出和运行日志的 tracing 订阅者，需要 `cli` 功能）
+#[cfg(feature = "cli")]
+pub mod logging;
+
+/// 快照清单模块（文件大小、修改时间和哈希值）
+pub mod manifest;
+
+/// 文件元数据保留模块（Unix 所有者和扩展属性）
+pub mod metadata;
+
+/// 快照命名模块（时间戳格式、本地时间或 UTC）
+pub mod naming;
+
+/// 备份计划模块（试运行时将要执行的操作和预计的统计信息）
+pub mod plan;
+
+/// 进度事件模块（`--progress-json`）
+pub mod progress;
+
+/// rclone 复制模块（备份后复制到异地）
+pub mod rclone;
+
+/// 备份集复制模块（`recall replicate` / `recall migrate`，保留硬链接）
+pub mod replicate;
+
+/// HTML 运行报告模块（`--report-html`）
+pub mod report;
+
+/// 快照恢复模块（选择快照并恢复到本地目录）
+pub mod restore;
+
+/// S3 对象存储模块
+pub mod s3;
+
 /// 源文件扫描模块
 pub mod scanner;
 
+/// 备份会话模块（一次完整的快照备份：准备、传输、提交）
+pub mod session;
+
+/// 计划任务模块（cron 表达式，`recall daemon` 状态）
+pub mod schedule;
+
+/// 系统计划任务模块（Windows 任务计划程序）
+pub mod scheduler;
+
+/// 存储设备检测模块
+pub mod storage;
+
+/// 备份摘要模块（记录每个快照的统计信息）
+pub mod summary;
+
 /// 配置文件存储模块
 pub mod store;
 
+/// 磁盘占用统计模块（识别硬链接）
+pub mod usage;
+
 /// 工具函数模块
 pub mod utils;
+
+/// 配置检查模块（`config validate`）
+pub mod validate;
+
+/// 快照校验模块（按清单检查文件是否缺失或损坏）
+pub mod verify;
+
+/// 桌面通知模块（备份结束或失败时显示系统通知）
+pub mod notify;
+
+/// 监控服务心跳模块（运行开始和结束时请求 Healthchecks.io 等服务）
+pub mod healthcheck;
+
+/// Webhook 通知模块（运行结果发送到监控或消息服务）
+pub mod webhook;
+
+/// WebDAV 备份目标模块
+pub mod webdav;
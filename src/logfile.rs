@@ -0,0 +1,119 @@
+// Recall - 运行日志模块
+// 将提示信息、警告和失败写入日志文件（`--log-file`），按大小轮转，便于查看无人值守的定时备份
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// 默认的日志文件大小上限（10 MB）
+pub const DEFAULT_MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 轮转时保留的旧日志文件数量（`recall.log.1` ~ `recall.log.5`）
+const ROTATED_LOGS: usize = 5;
+
+/// 当前打开的日志文件
+static LOG: OnceLock<Mutex<LogFile>> = OnceLock::new();
+
+/// 按大小轮转的日志文件
+struct LogFile {
+    /// 日志文件路径
+    path: PathBuf,
+    /// 打开的文件
+    file: File,
+    /// 当前文件大小
+    size: u64,
+    /// 文件大小上限，超过后轮转
+    max_size: u64,
+}
+
+impl LogFile {
+    /// 打开日志文件（追加写入），已超过大小上限时先轮转
+    fn open(path: &Path, max_size: u64) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory {:?}", parent))?;
+        }
+        if fs::metadata(path).is_ok_and(|m| m.len() >= max_size) {
+            rotate(path)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {:?}", path))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+        })
+    }
+
+    /// 写入一行，写入后超过大小上限时轮转并重新打开
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let entry = format!("[{}] {}\n", Local::now().format("%Y-%m-%d %H:%M:%S"), line);
+        self.file.write_all(entry.as_bytes())?;
+        self.size += entry.len() as u64;
+
+        if self.size >= self.max_size {
+            rotate(&self.path)?;
+            *self = Self::open(&self.path, self.max_size)?;
+        }
+        Ok(())
+    }
+}
+
+/// 轮转日志文件：`recall.log` → `recall.log.1` → … → `recall.log.5`，最旧的被删除
+fn rotate(path: &Path) -> Result<()> {
+    let numbered = |n: usize| {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    };
+
+    let _ = fs::remove_file(numbered(ROTATED_LOGS));
+    for n in (1..ROTATED_LOGS).rev() {
+        let from = numbered(n);
+        if from.exists() {
+            fs::rename(&from, numbered(n + 1))?;
+        }
+    }
+    fs::rename(path, numbered(1)).with_context(|| format!("Failed to rotate log file {:?}", path))
+}
+
+/// 启用日志文件
+///
+/// # 参数
+/// * `path` - 日志文件路径
+/// * `max_size` - 单个日志文件的大小上限（字节），超过后轮转
+///
+/// # 返回
+/// * `Ok(())` - 已启用
+/// * `Err(anyhow::Error)` - 无法打开或轮转日志文件
+pub fn init(path: &Path, max_size: u64) -> Result<()> {
+    let log = LogFile::open(path, max_size.max(1))?;
+    let _ = LOG.set(Mutex::new(log));
+    Ok(())
+}
+
+/// 向日志文件写入一行（去除终端颜色代码）
+///
+/// 未启用日志文件时不做任何操作；写入失败时静默忽略，不影响备份本身。
+pub fn write(line: &str) {
+    if let Some(log) = LOG.get() {
+        #[cfg(feature = "cli")]
+        let plain = console::strip_ansi_codes(line);
+        // 没有终端界面时不输出颜色代码
+        #[cfg(not(feature = "cli"))]
+        let plain = line;
+        let mut log = log.lock().unwrap();
+        for line in plain.lines() {
+            let _ = log.write_line(line);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Recall - 日志订阅模块
+// 命令行程序的 tracing 订阅者：控制台输出、详细程度和运行日志都由这里统一处理
+
+use crate::utils::{json_output, verbosity, Verbosity};
+use std::fmt::{self, Write as _};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// 面向用户的控制台输出（[`status!`](crate::status)、[`note!`](crate::note) 等宏）使用的目标
+///
+/// 级别对应输出的类别：`WARN` 为警告，`INFO` 为结果和汇总，`DEBUG` 为过程信息（安静模式下不输出），
+/// `TRACE` 为详细信息（仅详细模式下输出）。
+pub const CONSOLE_TARGET: &str = "recall::console";
+
+/// 把 recall 的事件输出到控制台和运行日志的 tracing 层
+///
+/// 控制台事件按当前的详细程度（`-q` / `-v`）过滤后输出：警告写入标准错误，
+/// 其他信息写入标准输出（启用 JSON 输出时改为标准错误），同时写入运行日志（`--log-file`）。
+/// 扫描、决策、复制、链接和 VSS 等内部事件不输出到控制台，详细模式下写入运行日志。
+pub struct ConsoleLayer;
+
+impl<S> Layer<S> for ConsoleLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    /// 保存 span 的字段，内部事件写入日志时一并输出
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = MessageVisitor::default();
+            attrs.record(&mut visitor);
+            span.extensions_mut().insert(SpanFields(visitor.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let level = *meta.level();
+
+        if meta.target() == CONSOLE_TARGET {
+            let shown = match level {
+                Level::ERROR | Level::WARN | Level::INFO => true,
+                Level::DEBUG => verbosity() >= Verbosity::Normal,
+                Level::TRACE => verbosity() >= Verbosity::Verbose,
+            };
+            if !shown {
+                return;
+            }
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            crate::logfile::write(&visitor.message);
+            if level <= Level::WARN || json_output() {
+                eprintln!("{}", visitor.message);
+            } else {
+                println!("{}", visitor.message);
+            }
+            return;
+        }
+
+        // 内部事件：详细模式下写入运行日志，附带所在的 span（如 `scan`、`vss`）
+        if verbosity() < Verbosity::Verbose || level > Level::DEBUG {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| match span.extensions().get::<SpanFields>() {
+                        Some(SpanFields(fields)) if !fields.is_empty() => {
+                            format!("{}{{{}}}", span.name(), fields.trim_start())
+                        }
+                        _ => span.name().to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(":")
+            })
+            .unwrap_or_default();
+        let mut line = format!("{} {}", level, meta.target());
+        if !spans.is_empty() {
+            let _ = write!(line, " [{}]", spans);
+        }
+        let _ = write!(line, ": {}{}", visitor.message, visitor.fields);
+        crate::logfile::write(&line);
+    }
+}
+
+/// span 的字段（` 名称=值` 形式）
+struct SpanFields(String);
+
+/// 收集事件的消息和其他字段
+#[derive(Default)]
+struct MessageVisitor {
+    /// `message` 字段
+    message: String,
+
+    /// 其他字段（` 名称=值` 形式）
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// 安装命令行程序使用的全局订阅者（只输出 recall 的事件）
+///
+/// 嵌入 recall 的程序不需要调用，可以安装自己的订阅者处理 recall 的事件：
+/// 控制台输出的目标为 [`CONSOLE_TARGET`]，内部事件的目标为所在的模块（如 `recall::scanner`）。
+///
+/// # 返回
+/// * `Ok(())` - 已安装
+/// * `Err(anyhow::Error)` - 已经安装过全局订阅者
+pub fn init() -> anyhow::Result<()> {
+    let subscriber = tracing_subscriber::registry().with(ConsoleLayer.with_filter(
+        tracing_subscriber::filter::filter_fn(|meta| meta.target().starts_with("recall")),
+    ));
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(())
+}
@@ -10,36 +10,580 @@
 // - 交互式配置管理：保存和管理备份配置
 
 use anyhow::{Context, Result};
-use chrono::Local;
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Local, NaiveTime, Weekday};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use console::style;
 use indicatif::ProgressBar;
-use recall::cli::run_interactive_mode;
-use recall::config::BackupConfig;
+use recall::actions::{saved_percent, BackupStats, SyncAction};
+use recall::archive::{archive_name, archive_snapshot, find_archives, read_metadata, LocalSnapshots};
+use recall::backend::{is_remote_destination, open_remote, open_writer, RemoteSnapshots};
+use recall::cli::{load_profile, profile_names, run_interactive_mode, Selection};
+use recall::config::{check_path_overlap, project_name, write_default_ignore_file, BackupConfig};
+use recall::credentials::{connect, credential_target, delete_password, set_password};
+use recall::diff::{diff_against_source, diff_snapshots, ChangeKind, FileChange};
+use recall::estimate::estimate_backup;
+use recall::export::{export_snapshot, parse_format, ArchiveFormat};
+use recall::events::{ConsoleSink, NullSink};
 use recall::executor::BackupExecutor;
-use recall::scanner::{find_latest_backup, scan_source};
-use recall::utils::{format_bytes, format_duration};
+use recall::store::AppConfig;
+use recall::history::{
+    days_until_full, load_destination_stats, load_history, monthly_stats, record_destination_run, record_run, sparkline, to_csv,
+    RunRecord,
+};
+use recall::healthcheck::{send_ping, PingEvent};
+use recall::hooks::{run_hook, HookPhase};
+use recall::import::{find_snapshots, import_snapshots, parse_layout, Layout};
+use recall::i18n::{align, detect_language, label, set_language, tr};
+use recall::logfile::{self, DEFAULT_MAX_LOG_SIZE};
+use recall::manifest::{self, Integrity};
+use recall::naming::SnapshotNaming;
+use recall::notify::{is_interactive_desktop, show_notification, MIN_DURATION_SECS};
+use recall::progress::{self, ProgressEvent};
+use recall::plan::BackupPlan;
+use recall::prune::prune_backups;
+use recall::rclone::{replicate_snapshot, ReplicationReport};
+use recall::replicate::{remove_tree, replicate_tree, verify_tree};
+use recall::report::RunReport;
+use recall::restore::{restore, RestoreOptions};
+use recall::usage::compute_usage;
+use recall::validate::validate_config;
+use recall::verify::{verify, VerifyOptions};
+use recall::webhook::{send_webhook, WebhookOn, WebhookPayload};
+use recall::summary::{tag_snapshot, BackupSummary, MANIFEST_FILE};
+use recall::scanner::{current_snapshot, find_latest_backup, scan_source};
+use recall::session::{parse_failure_policy, BackupSession, FailurePolicy, VssStatus};
+use recall::storage::free_space;
+use recall::schedule::{CronSchedule, DaemonStatus, ScheduledProfile};
+use recall::scheduler::{
+    install_systemd, install_task, parse_time, parse_weekday, remove_systemd, remove_task, systemd_units, task_name,
+    task_xml, unit_name, TaskOptions,
+};
+use recall::{note, status, trf, verbose, warning};
+use recall::utils::{
+    enter_background_mode, format_bytes, format_duration, init_plain_output, parse_age, parse_size,
+    plain_output, set_json_output, set_verbosity, verbosity, Verbosity,
+};
+use serde::Serialize;
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
 use std::time::Duration;
 use std::thread;
-use crossbeam_channel;
+
+/// 备份到分块仓库时自动模式下的上传并发数
+const REPOSITORY_WORKERS: usize = 8;
 
 /// 子命令枚举
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// 清理旧备份
     Prune {
-        /// 要保留的备份数量
-        #[arg(long, default_value_t = 5)]
-        keep: usize,
+        /// 要保留的备份数量（默认使用配置文件中的保留数量，未设置时为 5）
+        #[arg(long)]
+        keep: Option<usize>,
 
         /// 要清理的目标路径。如果未提供，将尝试从交互模式或配置文件推断
         /// 目前需要显式指定路径
         #[arg(value_name = "DESTINATION")]
         destination: Option<PathBuf>,
+
+        /// 清理指定配置文件的快照
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+
+        /// 始终保留带有此标签的备份（可多次指定），这些备份不计入 `--keep`
+        #[arg(long, value_name = "TAG")]
+        keep_tag: Vec<String>,
+    },
+
+    /// 将旧快照压缩为 tar.zst 归档并删除原目录（list / restore 仍可读取）
+    Archive {
+        /// 归档早于此时间的快照（如 `90d`、`6m`、`1y`）
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: u64,
+
+        /// 备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 归档指定配置文件的快照
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+
+        /// 清单签名密钥文件（未指定时使用 `RECALL_MANIFEST_KEY`）
+        #[arg(long, value_name = "FILE")]
+        manifest_key: Option<PathBuf>,
+    },
+
+    /// 列出目标目录中的所有快照
+    List {
+        /// 备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 列出指定配置文件的快照
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+
+        /// 加密仓库口令的密钥文件（未指定时使用配置中的 keyfile、`RECALL_PASSPHRASE` 或在终端中输入）
+        #[arg(long, value_name = "FILE")]
+        keyfile: Option<PathBuf>,
+    },
+
+    /// 统计各快照独占的空间和目标目录的实际占用（识别硬链接）
+    Du {
+        /// 备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 统计指定配置文件的快照
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+    },
+
+    /// 比较两个快照，列出新增、删除和修改的文件
+    ///
+    /// 使用 `--against-source` 时改为比较源目录与最新快照（下一次备份将做什么），
+    /// 此时两个参数分别为源路径和备份根路径，或使用 `--profile` 指定配置文件
+    Diff {
+        /// 旧快照目录（`--against-source` 时为源路径）
+        #[arg(value_name = "BACKUP_A", required_unless_present = "profile")]
+        old: Option<PathBuf>,
+
+        /// 新快照目录（`--against-source` 时为备份根路径）
+        #[arg(value_name = "BACKUP_B", required_unless_present = "profile")]
+        new: Option<PathBuf>,
+
+        /// 比较源目录与最新快照，而不是比较两个快照
+        #[arg(long)]
+        against_source: bool,
+
+        /// 与 `--against-source` 一起使用，比较指定配置文件的源目录
+        #[arg(long, requires = "against_source", conflicts_with_all = ["old", "new"])]
+        profile: Option<String>,
+    },
+
+    /// 预估下一次备份：将复制和硬链接的文件数量、大小以及目标目录的增长
+    Estimate {
+        /// 源路径
+        #[arg(value_name = "SOURCE", required_unless_present = "profile")]
+        source: Option<PathBuf>,
+
+        /// 备份根路径
+        #[arg(value_name = "DESTINATION", required_unless_present = "profile")]
+        destination: Option<PathBuf>,
+
+        /// 预估指定配置文件的备份
+        #[arg(long, conflicts_with_all = ["source", "destination"])]
+        profile: Option<String>,
+    },
+
+    /// 显示历次运行的统计（日期、耗时、传输量、硬链接比例、失败数）
+    History {
+        /// 只显示此配置文件的记录
+        #[arg(value_name = "PROFILE")]
+        profile: Option<String>,
+
+        /// 显示最近的 N 条记录 [默认: 20]
+        #[arg(long, default_value_t = 20, hide_default_value = true)]
+        limit: usize,
+    },
+
+    /// 显示备份目标上历次运行的按月趋势（传输量、快照大小、耗时、剩余空间）
+    ///
+    /// 统计记录保存在备份目标根目录的 `.recall-stats.jsonl` 中，随备份硬盘保存。
+    Stats {
+        /// 备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 显示指定配置文件的备份目标
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+
+        /// 以 CSV 输出每次运行的记录（便于导入电子表格）
+        #[arg(long, conflicts_with = "sparkline")]
+        csv: bool,
+
+        /// 以迷你折线图显示各项指标的按月趋势
+        #[arg(long)]
+        sparkline: bool,
+    },
+
+    /// 在文件管理器中打开最新的快照
+    Open {
+        /// 备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 打开指定配置文件的最新快照
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+    },
+
+    /// 为快照添加标签或备注
+    Tag {
+        /// 快照目录（如 `E:\Backups\Projects\2024-01-15_10-30-00`）
+        #[arg(value_name = "BACKUP")]
+        backup: PathBuf,
+
+        /// 标签或备注文本
+        #[arg(value_name = "TEXT")]
+        text: String,
+
+        /// 移除该标签，而不是添加
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// 显示单个快照的详细信息
+    Info {
+        /// 快照目录（如 `E:\Backups\Projects\2024-01-15_10-30-00`）
+        #[arg(value_name = "BACKUP")]
+        backup: PathBuf,
+    },
+
+    /// 按清单校验快照中的文件（缺失、大小不符或内容损坏）
+    Verify {
+        /// 快照目录（如 `E:\Backups\Projects\2024-01-15_10-30-00`）
+        #[arg(value_name = "BACKUP")]
+        backup: PathBuf,
+
+        /// 只比较文件大小，不读取文件内容
+        #[arg(long)]
+        quick: bool,
+
+        /// 清单签名密钥文件（未指定时使用 `RECALL_MANIFEST_KEY`）
+        #[arg(long, value_name = "FILE")]
+        manifest_key: Option<PathBuf>,
+    },
+
+    /// 将快照导出为 zip / tar 归档（硬链接展开为普通文件）
+    Export {
+        /// 快照目录（如 `E:\Backups\Projects\2024-01-15_10-30-00`）
+        #[arg(value_name = "BACKUP")]
+        backup: PathBuf,
+
+        /// 输出文件（如 `Projects.tar.zst`）
+        #[arg(value_name = "OUT")]
+        output: PathBuf,
+
+        /// 归档格式：zip、tar、tar.gz 或 tar.zst [默认: 根据输出文件扩展名判断]
+        #[arg(long, value_parser = parse_format)]
+        format: Option<ArchiveFormat>,
+    },
+
+    /// 将整个备份目标目录（所有快照）同步到另一个位置，保留硬链接
+    Replicate {
+        /// 备份目标目录（如 `E:\Backups`）
+        #[arg(value_name = "SOURCE")]
+        source: PathBuf,
+
+        /// 镜像目录（如 `F:\Mirror`），源中已不存在的条目会被删除
+        #[arg(value_name = "MIRROR")]
+        mirror: PathBuf,
+    },
+
+    /// 将备份目标目录（所有快照）迁移到新磁盘，保留硬链接并核对结果
+    Migrate {
+        /// 当前的备份目标目录（如 `E:\Backups`）
+        #[arg(value_name = "SOURCE")]
+        source: PathBuf,
+
+        /// 新的备份目标目录（如 `F:\Backups`）
+        #[arg(value_name = "TARGET")]
+        target: PathBuf,
+
+        /// 核对通过后删除原目录中的备份
+        #[arg(long)]
+        delete_source: bool,
+    },
+
+    /// 导入其他工具留下的版本化备份（rsnapshot 的 `daily.0`、按日期命名的文件夹）
+    Import {
+        /// 已有备份的根目录（如 rsnapshot 的 `snapshot_root`、`E:\Robocopy\Projects`）
+        #[arg(value_name = "SOURCE")]
+        source: PathBuf,
+
+        /// 导入到的备份目标目录（项目目录，如 `E:\Backups\Projects`）
+        #[arg(value_name = "DESTINATION")]
+        destination: Option<PathBuf>,
+
+        /// 导入到指定配置文件的目标目录
+        #[arg(long, conflicts_with = "destination")]
+        profile: Option<String>,
+
+        /// 已有备份的布局：rsnapshot 或 dated [默认: 自动识别]
+        #[arg(long, value_parser = parse_layout)]
+        layout: Option<Layout>,
+
+        /// 只导入每个快照中的此子目录（如 rsnapshot 的 `localhost/home`）
+        #[arg(long, value_name = "PATH")]
+        subdir: Option<PathBuf>,
+
+        /// 清单签名密钥文件（未指定时使用 `RECALL_MANIFEST_KEY`）
+        #[arg(long, value_name = "FILE")]
+        manifest_key: Option<PathBuf>,
+    },
+
+    /// 从备份目标恢复快照（本地快照目录或归档、S3 分块仓库、WebDAV）
+    Restore {
+        /// 恢复到的目录
+        #[arg(value_name = "TARGET")]
+        target: PathBuf,
+
+        /// 备份目标（如 `E:\Backups\Projects`、`s3://bucket/backups/Projects`、`https://nas/dav/Projects`）
+        #[arg(long, value_name = "DESTINATION", required_unless_present = "profile")]
+        from: Option<PathBuf>,
+
+        /// 从指定配置文件的备份目标恢复
+        #[arg(long, conflicts_with = "from")]
+        profile: Option<String>,
+
+        /// 要恢复的快照名称（默认为最新快照）
+        #[arg(long, value_name = "SNAPSHOT")]
+        snapshot: Option<String>,
+
+        /// 加密仓库口令的密钥文件（未指定时使用配置中的 keyfile、`RECALL_PASSPHRASE` 或在终端中输入）
+        #[arg(long, value_name = "FILE")]
+        keyfile: Option<PathBuf>,
+    },
+
+    /// 以非交互方式运行已保存的配置文件（适用于计划任务）
+    Run {
+        /// 配置文件名称
+        #[arg(value_name = "PROFILE", required_unless_present_any = ["all", "group"])]
+        profile: Option<String>,
+
+        /// 依次运行所有配置文件，单个失败不影响其余配置文件
+        #[arg(long, conflicts_with = "profile")]
+        all: bool,
+
+        /// 按优先级依次运行属于此分组的配置文件
+        #[arg(long, value_name = "GROUP", conflicts_with_all = ["profile", "all"])]
+        group: Option<String>,
+
+        /// 覆盖配置文件中设置的备份选项
+        #[command(flatten)]
+        options: BackupOptions,
+    },
+
+    /// 常驻运行，按配置文件中的 `schedule`（cron 表达式）定时备份，错过的运行在启动或唤醒后补上
+    Daemon {
+        /// 显示常驻进程的状态和各配置文件的下一次运行时间，而不是启动常驻进程
+        #[arg(long)]
+        status: bool,
+
+        /// 覆盖各配置文件中设置的备份选项
+        #[command(flatten)]
+        options: BackupOptions,
+    },
+
+    /// 在系统的计划任务中注册或删除定时运行配置文件的任务
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommands,
+    },
+
+    /// 管理已保存的配置文件
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommands,
+    },
+
+    /// 检查配置文件
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+
+    /// 在系统凭据管理器中保存或删除访问备份目标所需的密码
+    Credential {
+        #[command(subcommand)]
+        action: CredentialCommands,
+    },
+
+    /// 列出或删除 VSS 卷影副本（仅 Windows）
+    Snapshots {
+        /// 只显示此路径所在卷的快照
+        #[arg(value_name = "VOLUME")]
+        volume: Option<PathBuf>,
+
+        /// 删除指定 ID 的快照（可多次指定）
+        #[arg(long, value_name = "GUID")]
+        delete: Vec<String>,
+
+        /// 删除所有由 recall 创建的遗留快照（如进程崩溃后未清理的快照）
+        #[arg(long)]
+        clean: bool,
+    },
+}
+
+/// 配置文件管理子命令
+#[derive(Subcommand, Debug)]
+enum ProfileCommands {
+    /// 重命名配置文件，保留其所有设置
+    Rename {
+        /// 原名称
+        old: String,
+
+        /// 新名称
+        new: String,
+    },
+
+    /// 将配置文件导出为 TOML（输出到标准输出）
+    Export {
+        /// 要导出的配置文件名称，未指定时导出全部
+        names: Vec<String>,
+    },
+
+    /// 从 TOML 文件导入配置文件
+    Import {
+        /// 要导入的文件（`profile export` 的输出或其他机器上的 config.toml）
+        file: PathBuf,
+
+        /// 覆盖同名的现有配置文件（默认跳过）
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+/// 计划任务子命令
+#[derive(Subcommand, Debug)]
+enum ScheduleCommands {
+    /// 注册（或替换）运行 `recall run <PROFILE>` 的计划任务（Windows 任务计划程序或 systemd 定时器）
+    Install {
+        /// 配置文件名称
+        profile: String,
+
+        /// 运行时间（HH:MM）[默认: 02:00]
+        #[arg(long, value_name = "HH:MM", default_value = "02:00", hide_default_value = true, value_parser = parse_time)]
+        time: NaiveTime,
+
+        /// 只在每周的这些日期运行（逗号分隔，如 `mon,thu`），默认每天运行
+        #[arg(long, value_name = "DAYS", value_delimiter = ',', value_parser = parse_weekday)]
+        days: Vec<Weekday>,
+
+        /// 用户登录时也运行一次
+        #[arg(long, conflicts_with = "systemd")]
+        logon: bool,
+
+        /// 到时间时唤醒计算机
+        #[arg(long)]
+        wake: bool,
+
+        /// 错过计划时间（关机、睡眠）后不补运行（默认在开机或唤醒后尽快补上）
+        #[arg(long)]
+        no_catch_up: bool,
+
+        /// 以最高权限运行（配置启用了 VSS 时自动启用）
+        #[arg(long, conflicts_with = "systemd")]
+        elevated: bool,
+
+        /// 生成 systemd 服务和定时器单元（Linux），而不是 Windows 计划任务
+        #[arg(long)]
+        systemd: bool,
+
+        /// 安装为系统级单元（/etc/systemd/system，以 root 运行），默认为当前用户的单元
+        #[arg(long, requires = "systemd")]
+        system: bool,
+    },
+
+    /// 删除配置文件的计划任务
+    Remove {
+        /// 配置文件名称
+        profile: String,
+
+        /// 删除 systemd 单元，而不是 Windows 计划任务
+        #[arg(long)]
+        systemd: bool,
+
+        /// 删除系统级单元
+        #[arg(long, requires = "systemd")]
+        system: bool,
     },
 }
 
+/// 配置检查子命令
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// 检查每个配置文件的源路径、目标是否可写、排除模式和保留数量，输出检查报告
+    Validate,
+}
+
+/// 凭据管理子命令
+#[derive(Subcommand, Debug)]
+enum CredentialCommands {
+    /// 保存密码（交互式输入，不会写入配置文件）
+    Set {
+        #[command(flatten)]
+        target: CredentialTarget,
+
+        /// 从标准输入读取密码（第一行），用于脚本
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// 删除保存的密码
+    Delete {
+        #[command(flatten)]
+        target: CredentialTarget,
+    },
+}
+
+/// 凭据对应的备份目标和用户名
+#[derive(clap::Args, Debug)]
+struct CredentialTarget {
+    /// 备份目标路径（UNC 路径按共享根路径保存，如 \\server\share）
+    #[arg(value_name = "DESTINATION", required_unless_present = "profile")]
+    destination: Option<PathBuf>,
+
+    /// 使用指定配置的目标路径和用户名
+    #[arg(long, conflicts_with = "destination")]
+    profile: Option<String>,
+
+    /// 用户名（使用 --profile 时默认为配置中的 username）
+    #[arg(long)]
+    user: Option<String>,
+}
+
+/// 单次备份的结果，用于 `--json` 输出
+#[derive(Serialize, Debug)]
+struct BackupOutcome {
+    /// 备份状态：`ok`，或有文件失败、异地复制失败时为 `partial`
+    status: &'static str,
+    /// 源目录
+    source: PathBuf,
+    /// 备份根目录
+    destination: PathBuf,
+    /// 快照名称（时间戳）
+    snapshot: String,
+    /// 是否为试运行
+    dry_run: bool,
+    /// 耗时（秒）
+    duration_secs: u64,
+    /// 统计信息
+    stats: BackupStats,
+    /// 异地复制（rclone）的结果，未配置复制时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replication: Option<ReplicationReport>,
+}
+
+/// `run --all` 中单个配置文件的结果，用于 `--json` 输出
+#[derive(Serialize, Debug)]
+struct ProfileOutcome {
+    /// 配置文件名称
+    profile: String,
+    /// 状态：`ok`、`partial` 或 `failed`
+    status: &'static str,
+    /// 失败原因
+    error: Option<String>,
+    /// 备份结果（失败时为空）
+    backup: Option<BackupOutcome>,
+}
+
 /// 命令行参数结构体
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -56,6 +600,34 @@ struct Args {
     #[arg(value_name = "DESTINATION")]
     destination: Option<PathBuf>,
 
+    /// 使用指定的配置文件，而不是系统标准配置目录中的 config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// 便携模式：使用可执行文件所在目录中的 config.toml（如在备份 U 盘上运行）
+    #[arg(long, global = true, conflicts_with = "config")]
+    portable: bool,
+
+    /// 以 JSON 格式输出结果（备份统计、清理结果、差异），提示信息改为写入标准错误
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// 安静模式：只输出汇总信息，不输出过程信息和逐个文件的警告
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// 详细模式：输出对每个文件执行的操作
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// 将提示信息、警告和失败写入日志文件（按所选详细程度）
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// 日志文件大小上限（如 10M），超过后轮转为 `.1` ~ `.5` [默认: 10M]
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size, requires = "log_file")]
+    log_max_size: Option<usize>,
+
     /// 启用内容检查（使用哈希值比较文件，更准确但更慢）
     #[arg(long, global = true)]
     check_content: bool,
@@ -68,237 +640,3288 @@ struct Args {
     #[arg(long, global = true)]
     exclude: Vec<String>,
 
-    /// 工作线程数量
-    #[arg(long, default_value_t = 4)]
-    workers: usize,
+    /// 备份选项
+    #[command(flatten)]
+    backup: BackupOptions,
+}
+
+/// 只用于备份的命令行选项，直接备份（`recall SOURCE DESTINATION`）、`run` 和 `daemon` 共用
+#[derive(clap::Args, Debug)]
+struct BackupOptions {
+    /// 为本次备份创建的快照添加标签或备注（可多次指定，如 `--tag pre-upgrade`）
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// 备份完成后用 rclone 将新快照复制到此远程目标（如 `offsite:backups/Projects`）
+    #[arg(long, value_name = "REMOTE")]
+    replicate: Option<String>,
+
+    /// 以分块去重仓库存储：文件按内容切分为数据块，只写入新的数据块（仅用于新的备份目标）
+    #[arg(long)]
+    chunked: bool,
+
+    /// 加密备份：以分块仓库存储，数据块和索引在写入前加密（仅用于新的备份目标）
+    #[arg(long)]
+    encrypt: bool,
+
+    /// 加密口令的密钥文件（未指定时使用 `RECALL_PASSPHRASE` 或在终端中输入）
+    #[arg(long, value_name = "FILE")]
+    keyfile: Option<PathBuf>,
+
+    /// 备份结束后分别列出写入和硬链接数据最多的 N 个目录（0 表示不显示）[默认: 5]
+    #[arg(long, value_name = "N", default_value_t = 5, hide_default_value = true)]
+    top_dirs: usize,
+
+    /// 备份结束后按扩展名列出写入数据最多的 N 类文件（0 表示不显示）[默认: 5]
+    #[arg(long, value_name = "N", default_value_t = 5, hide_default_value = true)]
+    top_types: usize,
+
+    /// 每次运行结束后写入 HTML 报告（摘要、复制与链接的比例、最大的文件、失败列表）；为目录时按快照名称命名
+    #[arg(long, value_name = "PATH")]
+    report_html: Option<PathBuf>,
+
+    /// 清单签名密钥文件：导入、归档和使用 --manifest 备份时以 HMAC 签名清单，校验时要求签名匹配（未指定时使用 `RECALL_MANIFEST_KEY`）
+    #[arg(long, value_name = "FILE")]
+    manifest_key: Option<PathBuf>,
+
+    /// 备份结束或失败时在桌面上显示系统通知（成功时只在运行超过 30 秒后显示）
+    #[arg(long)]
+    notify: bool,
+
+    /// 以换行分隔的 JSON 输出进度事件，`-` 表示标准输出，否则为文件或命名管道路径
+    #[arg(long, value_name = "TARGET")]
+    progress_json: Option<PathBuf>,
+
+    /// 试运行时把计划导出到文件：每个条目的路径、操作、大小和原因（扩展名为 .json 时导出 JSON，否则导出 CSV）
+    #[arg(long, value_name = "FILE", requires = "dry_run")]
+    plan_out: Option<PathBuf>,
+
+    /// 快照目录名称的时间戳格式（strftime 风格，默认 `%Y-%m-%d_%H-%M-%S`）
+    #[arg(long, value_name = "FORMAT")]
+    snapshot_format: Option<String>,
+
+    /// 快照目录名称使用 UTC 时间，而不是本地时间
+    #[arg(long)]
+    utc: bool,
+
+    /// 额外读取的排除文件（`.recallignore` 格式），可放在源目录以外
+    #[arg(long, value_name = "PATH")]
+    ignore_file: Option<PathBuf>,
+
+    /// 源目录中没有 `.recallignore` 时，创建包含默认排除项的文件
+    #[arg(long)]
+    init_ignore: bool,
+
+    /// 工作线程数量（`auto` 根据 CPU 数量和目标设备类型自动确定）[默认: auto]
+    #[arg(long, value_parser = parse_workers)]
+    workers: Option<usize>,
 
     /// 启用 VSS 快照（仅 Windows）
     #[arg(long)]
     vss: bool,
-}
 
-/// 程序入口
-fn main() -> Result<()> {
-    let args = Args::parse();
+    /// VSS 不可用（非管理员、服务被禁用等）时继续备份，不使用快照
+    #[arg(long)]
+    vss_fallback: bool,
 
-    match &args.command {
-        Some(Commands::Prune { keep, destination }) => {
-            // 处理清理命令
-            let dest = destination
-                .as_ref()
-                .or(args.destination.as_ref())
-                .context("Destination path is required for prune command")?;
+    /// 参与 VSS 快照的写入器（逗号分隔，`all` 表示全部），使快照应用一致
+    #[arg(long, value_delimiter = ',')]
+    vss_writers: Vec<String>,
 
-            // 支持全局 dry_run 参数
-            recall::prune::prune_backups(dest, *keep, args.dry_run)?;
-        }
-        None => {
-            // 执行备份
-            run_backup(args)?;
-        }
-    }
-    Ok(())
-}
+    /// 复用已存在的 VSS 快照（快照 ID），而不是创建新的快照
+    #[arg(long, value_name = "GUID")]
+    snapshot_id: Option<String>,
 
-/// 执行备份操作
-fn run_backup(args: Args) -> Result<()> {
-    // 准备备份配置
-    let (config, _) = if let (Some(src), Some(dest)) = (args.source, args.destination) {
-        // 使用命令行参数指定的路径
-        let source_abs = std::fs::canonicalize(&src).context("Failed to get absolute path of source")?;
+    /// 备份结束后保留本次创建的 VSS 快照（用于调试或后续复用）
+    #[arg(long)]
+    keep_snapshot: bool,
 
-        // 生成项目名称
-        let project_name = if let Some(name) = source_abs.file_name() {
-            name.to_string_lossy().to_string()
-        } else {
-            // 如果是驱动器根目录，生成特殊名称
-            let path_str = source_abs.to_string_lossy();
-             if let Some(colon_idx) = path_str.find(':') {
-                if colon_idx > 0 {
-                    let drive = &path_str[colon_idx - 1..colon_idx];
-                    format!("{}_Drive", drive.to_uppercase())
-                } else {
-                    "Unknown_Drive".to_string()
-                }
-            } else {
-                "Root_Backup".to_string()
-            }
-        };
+    /// 保留所有者（需 root）和扩展属性，并在比较时考虑其差异（仅 Unix）
+    #[arg(long)]
+    preserve_metadata: bool,
 
-        // 构建最终目标路径
-        let final_destination_root = dest.join(&project_name);
-        let config = BackupConfig::new(
-            source_abs,
-            final_destination_root.clone(),
-            args.check_content,
-            args.vss,
-            args.workers,
-            args.exclude,
-            args.dry_run,
-        )?;
-        (config, project_name)
-    } else {
-        // 进入交互模式
-        run_interactive_mode(args.dry_run)?
-    };
+    /// 以后台（低 CPU 和 I/O 优先级）模式运行，减少对前台工作的影响
+    #[arg(long)]
+    background: bool,
 
-    let use_vss = args.vss || config.vss;
+    /// 不续传中断的备份，总是从头开始新的备份
+    #[arg(long)]
+    no_resume: bool,
 
-    #[cfg(windows)]
-    if use_vss {
-        if !recall::vss::ShadowCopy::is_running_as_admin()? {
-            eprintln!("VSS requires Administrator privileges. Please run in an elevated terminal.");
-            return Err(anyhow::anyhow!("Administrator privileges required for VSS"));
-        }
-    }
+    /// 跳过备份前的可用空间检查（不预先扫描源目录）
+    #[arg(long)]
+    skip_space_check: bool,
 
-    // 记录开始时间
-    let start_time = std::time::Instant::now();
-    let now = Local::now();
-    let timestamp_folder_name = now.format("%Y-%m-%d_%H-%M-%S").to_string();
+    /// 被其他进程锁定而无法读取的文件计为失败（默认只单独统计，备份仍视为成功）
+    #[arg(long)]
+    fail_on_locked: bool,
 
-    // 打印备份信息
-    println!("{}", style(format!("Recall Backup Tool v{}", env!("CARGO_PKG_VERSION"))).cyan().bold());
-    println!("Source: {:?}", style(&config.source).blue());
-    println!("Dest:   {:?}", style(&config.destination).blue());
-    println!("Time:   {}", style(&timestamp_folder_name).yellow());
-    println!("{}", style("----------------------------------------").dim());
-
-    // 创建目标根目录（如果不存在）
-    if !config.destination.exists() {
-        if !config.dry_run {
-            std::fs::create_dir_all(&config.destination)
-                .context("Failed to create destination root")?;
-        } else {
-             println!("{} Would create destination root {:?}", style("Dry run:").yellow(), config.destination);
-        }
-    }
+    /// 失败的文件超过 --max-failures 时如何处理快照：commit（照常提交）、incomplete（提交为带 -incomplete 后缀的快照）
+    /// 或 partial（保留为 .partial，下次备份时续传）
+    #[arg(long, value_name = "POLICY", value_parser = parse_failure_policy)]
+    on_failure: Option<FailurePolicy>,
 
-    // 查找最新的备份（用于增量备份）
-    let latest_backup = find_latest_backup(&config.destination)?;
-    if let Some(ref latest) = latest_backup {
-        println!("Found previous backup: {:?}", style(latest.file_name().unwrap()).green());
-    } else {
-        println!("{}", style("Performing initial full backup...").yellow());
-    }
+    /// 允许的失败文件数量，超过时按 --on-failure 处理（默认 0）
+    #[arg(long, value_name = "N")]
+    max_failures: Option<u64>,
 
-    // 准备临时和最终备份目录
-    let temp_folder_name = format!("{}.partial", timestamp_folder_name);
-    let temp_dest_path = config.destination.join(&temp_folder_name);
-    let final_dest_path = config.destination.join(&timestamp_folder_name);
+    /// 提交后把快照设为只读，防止误改与其他快照共享硬链接的文件
+    #[arg(long)]
+    read_only: bool,
 
-    // 创建临时备份目录
-    if !config.dry_run {
-        std::fs::create_dir_all(&temp_dest_path).context("Failed to create temp backup dir")?;
-    } else {
-        println!("{} Would create temp dir {:?}", style("Dry run:").yellow(), temp_dest_path);
-    }
+    /// 提交时写入完整性清单（每个文件的大小和哈希值），供 verify 检查快照是否损坏；
+    /// 同时指定 --manifest-key 时清单带 HMAC 签名
+    #[arg(long)]
+    manifest: bool,
 
-    // === VSS 设置 ===
-    #[cfg(windows)]
-    let _vss_guard = if use_vss && !config.dry_run {
-        println!("{}", style("Initializing VSS Snapshot...").blue());
-        let sc = recall::vss::ShadowCopy::new(&config.source).context("Failed to create VSS snapshot")?;
-        println!("Snapshot created at: {:?}", sc.get_snapshot_path()?);
-        Some(sc)
-    } else {
-        None
-    };
+    /// 复制缓冲区大小（如 `4M`、`512K`），默认使用系统的复制方式
+    #[arg(long, value_parser = parse_size)]
+    buffer_size: Option<usize>,
 
-    #[cfg(not(windows))]
-    if use_vss {
-        println!("{}", style("Warning: VSS is only supported on Windows. Ignoring --vss").yellow());
+    /// 对大文件（≥ 1 GB）使用无缓冲 I/O，避免备份挤占系统页缓存
+    #[arg(long)]
+    unbuffered: bool,
+
+    /// 复制时的带宽上限，每秒字节数（如 `10M`），所有工作线程合计
+    #[arg(long, value_name = "RATE", value_parser = parse_size)]
+    bwlimit: Option<usize>,
+}
+
+/// 解析 `--workers` 参数，`auto` 解析为 0（运行时自动确定）
+fn parse_workers(s: &str) -> Result<usize, String> {
+    if s.eq_ignore_ascii_case("auto") {
+        return Ok(0);
     }
+    s.parse::<usize>()
+        .map_err(|_| format!("invalid worker count '{}', expected a number or 'auto'", s))
+}
 
-    // 准备扫描器配置（可能因 VSS 修改源路径）
-    let mut scan_config = config.clone();
-    #[cfg(windows)]
-    if let Some(ref sc) = _vss_guard {
-         // 将 config.source 映射到快照路径
-         // 1. 获取源的卷根目录（如 C:\）
-         let src_abs = &config.source; // 已经是规范化路径
-         let _volume_root = src_abs.components().take(2).collect::<PathBuf>();
-
-         // 简单方法：找出从卷根目录开始的相对路径
-         // 例如：source = C:\Users\Data，Volume = C:\，Rel = Users\Data
-         // Shadow = \\?\GLOBAL...\
-         // New Source = Shadow\Users\Data
-
-         if let Some(path_str) = src_abs.to_str() {
-             if let Some(colon) = path_str.find(':') {
-                  let _drive_root = &path_str[..colon+1]; // C:
-                  let rel_part = &path_str[colon+1..]; // \Users\Data
-                  let rel_part = rel_part.trim_start_matches('\\').trim_start_matches('/');
-
-                  let shadow_path = sc.get_snapshot_path()?;
-                  let new_source = shadow_path.join(rel_part);
-                  scan_config.source = new_source;
-                  println!("Backup Source mapped to VSS path: {:?}", scan_config.source);
-             }
-         }
-    }
-
-    // === 流水线处理开始 ===
-    let (tx, rx) = crossbeam_channel::bounded(1000);
+/// 解析命令行参数
+///
+/// 备份选项属于直接备份或 `run` / `daemon` 子命令，写在其他子命令之前时报错，而不是静默忽略；
+/// `run` / `daemon` 的备份选项统一放到 `args.backup` 中。
+fn parse_args() -> Args {
+    let matches = Args::command().get_matches();
+    if let Some((name, _)) = matches.subcommand() {
+        let options = <BackupOptions as clap::Args>::augment_args(clap::Command::new("backup"));
+        let misplaced = options
+            .get_arguments()
+            .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine));
+        if let Some(arg) = misplaced {
+            let flag = arg.get_long().unwrap_or_else(|| arg.get_id().as_str());
+            let message = if name == "run" || name == "daemon" {
+                format!("'--{}' must be given after the '{}' subcommand", flag, name)
+            } else {
+                format!("'--{}' only applies to backups and cannot be used with the '{}' subcommand", flag, name)
+            };
+            Args::command().error(ErrorKind::ArgumentConflict, message).exit();
+        }
+    }
 
-    let temp_dest_clone = temp_dest_path.clone();
-    let latest_backup_clone = latest_backup.clone();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Some(Commands::Run { options, .. } | Commands::Daemon { options, .. }) = &mut args.command {
+        std::mem::swap(&mut args.backup, options);
+    }
+    args
+}
 
-    // 使用可能指向 VSS 的 scan_config
-    let config_for_scanner = scan_config.clone();
+/// 程序入口
+///
+/// 错误信息本身保持英文（便于检索），前缀随界面语言显示。
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{} {:#}", style(tr("Error:", "错误：")).red().bold(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    // 启动扫描线程
-    let scanner_handle = thread::spawn(move || {
-        scan_source(
-             &config_for_scanner,
-             &temp_dest_clone,
-             latest_backup_clone.as_deref(),
-             tx
-        )
-    });
+/// 解析参数、初始化输出和日志后执行命令
+fn run() -> Result<()> {
+    let args = parse_args();
 
-    // 在主线程执行备份任务
-    let executor = BackupExecutor::new(config.dry_run);
-    let stats = executor.execute(rx, config.workers)?;
+    // 控制台输出和运行日志都通过 tracing 订阅者处理，需在任何输出之前安装
+    recall::logging::init()?;
 
-    // 等待扫描完成
-    if let Err(e) = scanner_handle.join().unwrap() {
-        eprintln!("{} Scanner failed: {}", style("Error:").red(), e);
-        return Err(e);
+    // 确定配置文件位置，需在任何加载配置的操作之前完成
+    if let Some(ref path) = args.config {
+        AppConfig::set_config_path(path.clone());
+    } else if args.portable {
+        AppConfig::set_config_path(
+            AppConfig::portable_config_path().context("Could not determine executable directory")?,
+        );
     }
-    // === 流水线处理结束 ===
 
-    // 提交备份（重命名临时目录并更新 current 链接）
-    if !config.dry_run {
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_message("Finalizing backup...");
-        spinner.enable_steady_tick(Duration::from_millis(100));
-
-        BackupExecutor::commit_backup(
-            &temp_dest_path,
-            &final_dest_path,
-            &config.destination.join("current"),
-        )?;
-        spinner.finish_and_clear();
-    } else {
-        println!("{} Would rename .partial to {:?}", style("Dry run:").yellow(), final_dest_path);
-        println!("{} Would update 'current' symlink", style("Dry run:").yellow());
-    }
+    // 界面语言：配置文件优先，其次是环境变量
+    let defaults = AppConfig::load().map(|c| c.defaults).unwrap_or_default();
+    set_language(detect_language(defaults.language.as_deref()));
+
+    // NO_COLOR 或非终端环境下关闭颜色和动态进度条
+    init_plain_output(defaults.color.as_deref());
+
+    // 进度事件写入标准输出时，提示信息同样改为写入标准错误
+    let progress_to_stdout = args.backup.progress_json.as_deref() == Some(std::path::Path::new("-"));
+    set_json_output(args.json || progress_to_stdout);
+    set_verbosity(if args.quiet {
+        Verbosity::Quiet
+    } else if args.verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    });
+    if let Some(ref target) = args.backup.progress_json {
+        progress::init(target)?;
+    }
+    if let Some(ref path) = args.log_file {
+        logfile::init(path, args.log_max_size.map_or(DEFAULT_MAX_LOG_SIZE, |n| n as u64))?;
+        logfile::write(&format!(
+            "=== Recall v{} started: {} ===",
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        ));
+    }
+
+    let result = run_command(args);
+    if let Err(ref e) = result {
+        logfile::write(&format!("Error: {:#}", e));
+    }
+    result
+}
+
+/// 按子命令分派执行
+fn run_command(args: Args) -> Result<()> {
+    match &args.command {
+        Some(Commands::Prune { keep, destination, profile, keep_tag }) => {
+            // 处理清理命令
+            let (dest, naming, default_keep) = target_destination(&args, destination, profile, "prune")?;
+            if is_remote_destination(&dest) {
+                anyhow::bail!("Pruning is not supported for remote destinations yet");
+            }
+            let keep = keep.or(default_keep).unwrap_or(5);
+
+            // 支持全局 dry_run 参数
+            let report = prune_backups(&dest, keep, args.dry_run, keep_tag, &naming, &ConsoleSink::new())?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        Some(Commands::Archive { older_than, destination, profile, manifest_key }) => {
+            let (dest, naming, _) = target_destination(&args, destination, profile, "archive")?;
+            if is_remote_destination(&dest) {
+                anyhow::bail!("Archiving is only supported for local destinations");
+            }
+            let key = manifest::read_key(manifest_key.as_deref())?;
+            run_archive(&dest, &naming, *older_than, key.as_deref(), args.dry_run, args.json)?;
+        }
+        Some(Commands::Profile { action }) => match action {
+            ProfileCommands::Rename { old, new } => {
+                AppConfig::update(|app_config| app_config.rename_profile(old, new))?;
+                status!(
+                    "{} {}",
+                    style(tr("Success:", "成功：")).green(),
+                    trf!("'{}' has been renamed to '{}'.", "'{}' 已重命名为 '{}'。", old, new.trim())
+                );
+            }
+            ProfileCommands::Export { names } => {
+                print!("{}", AppConfig::load()?.export(names)?);
+            }
+            ProfileCommands::Import { file, overwrite } => {
+                if !file.exists() {
+                    anyhow::bail!("Import file {:?} does not exist", file);
+                }
+                let imported = AppConfig::load_from(file)?;
+                let (added, overwritten, skipped) = if args.dry_run {
+                    AppConfig::load()?.import(imported, *overwrite)
+                } else {
+                    AppConfig::update(|app_config| Ok(app_config.import(imported, *overwrite)))?
+                };
+                for name in &added {
+                    status!("{} {}", style(tr("Added:", "新增：")).green(), name);
+                }
+                for name in &overwritten {
+                    status!("{} {}", style(tr("Overwritten:", "已覆盖：")).yellow(), name);
+                }
+                for name in &skipped {
+                    status!(
+                        "{} {}",
+                        style(tr("Skipped:", "已跳过：")).dim(),
+                        trf!(
+                            "{} (already exists, use --overwrite to replace)",
+                            "{}（已存在，使用 --overwrite 覆盖）",
+                            name
+                        )
+                    );
+                }
+                if args.dry_run {
+                    status!(
+                        "{} {}",
+                        style(tr("Dry run:", "试运行：")).yellow(),
+                        tr("No changes were saved", "未保存任何更改")
+                    );
+                }
+            }
+        },
+        Some(Commands::Config { action: ConfigCommands::Validate }) => {
+            run_validate(args.json)?;
+        }
+        Some(Commands::Schedule { action }) => {
+            run_schedule(&args, action)?;
+        }
+        Some(Commands::Credential { action }) => {
+            run_credential(action)?;
+        }
+        Some(Commands::Snapshots { volume, delete, clean }) => {
+            run_snapshots(volume.as_deref(), delete, *clean, args.dry_run)?;
+        }
+        Some(Commands::List { destination, profile, keyfile }) => {
+            let (dest, naming, _) = target_destination(&args, destination, profile, "list")?;
+            if is_remote_destination(&dest) {
+                run_remote_list(open_remote_target(&dest, profile, keyfile.as_deref())?.as_ref(), &naming)?;
+            } else {
+                run_list(&dest, &naming)?;
+            }
+        }
+        Some(Commands::Du { destination, profile }) => {
+            let (dest, naming, _) = target_destination(&args, destination, profile, "du")?;
+            run_du(&dest, &naming)?;
+        }
+        Some(Commands::Diff { old, new, against_source, profile }) => {
+            let changes = if *against_source {
+                let (config, _) = match profile {
+                    Some(name) => load_profile(name, true)?,
+                    None => command_line_config(
+                        &args,
+                        old.as_deref().context("Source path is required")?,
+                        new.as_deref().context("Backup root path is required")?,
+                    )?,
+                };
+                diff_against_source(&config)?
+            } else {
+                diff_snapshots(
+                    old.as_deref().context("Two snapshots are required")?,
+                    new.as_deref().context("Two snapshots are required")?,
+                )?
+            };
+            print_changes(&changes, args.json)?;
+        }
+        Some(Commands::Estimate { source, destination, profile }) => {
+            let (config, _) = match profile {
+                Some(name) => load_profile(name, true)?,
+                None => command_line_config(
+                    &args,
+                    source.as_deref().context("Source path is required")?,
+                    destination.as_deref().context("Backup root path is required")?,
+                )?,
+            };
+            run_estimate(&config, args.json)?;
+        }
+        Some(Commands::History { profile, limit }) => {
+            run_history(profile.as_deref(), *limit, args.json)?;
+        }
+        Some(Commands::Stats { destination, profile, csv, sparkline }) => {
+            let (dest, _, _) = target_destination(&args, destination, profile, "stats")?;
+            run_stats(&dest, *csv, *sparkline, args.json)?;
+        }
+        Some(Commands::Tag { backup, text, remove }) => {
+            if !backup.is_dir() {
+                anyhow::bail!("Backup directory {:?} does not exist", backup);
+            }
+            let tags = tag_snapshot(backup, text, *remove)?;
+            status!(
+                "{} {}{}",
+                style(tr("Success:", "成功：")).green(),
+                tr("Tags: ", "标签："),
+                if tags.is_empty() { "-".to_string() } else { tags.join(", ") }
+            );
+        }
+        Some(Commands::Open { destination, profile }) => {
+            let (dest, naming, _) = target_destination(&args, destination, profile, "open")?;
+            run_open(&dest, &naming)?;
+        }
+        Some(Commands::Info { backup }) => {
+            run_info(backup)?;
+        }
+        Some(Commands::Verify { backup, quick, manifest_key }) => {
+            let key = manifest::read_key(manifest_key.as_deref())?;
+            run_verify(backup, &VerifyOptions { quick: *quick, key }, args.json)?;
+        }
+        Some(Commands::Export { backup, output, format }) => {
+            run_export(backup, output, *format, args.json)?;
+        }
+        Some(Commands::Replicate { source, mirror }) => {
+            run_replicate(source, mirror, args.dry_run, args.json)?;
+        }
+        Some(Commands::Migrate { source, target, delete_source }) => {
+            run_migrate(source, target, *delete_source, args.dry_run)?;
+        }
+        Some(Commands::Import { source, destination, profile, layout, subdir, manifest_key }) => {
+            let (dest, naming, _) = target_destination(&args, destination, profile, "import")?;
+            if is_remote_destination(&dest) {
+                anyhow::bail!("Import is only supported for local destinations");
+            }
+            let key = manifest::read_key(manifest_key.as_deref())?;
+            run_import(source, &dest, &naming, *layout, subdir.as_deref(), key.as_deref(), args.dry_run, args.json)?;
+        }
+        Some(Commands::Restore { target, from, profile, snapshot, keyfile }) => {
+            let (dest, naming, _) = target_destination(&args, from, profile, "restore")?;
+            let snapshots: Box<dyn RemoteSnapshots> = if is_remote_destination(&dest) {
+                open_remote_target(&dest, profile, keyfile.as_deref())?
+            } else {
+                Box::new(LocalSnapshots::new(&dest))
+            };
+            let options = RestoreOptions { snapshot: snapshot.clone(), dry_run: args.dry_run };
+            run_restore(snapshots.as_ref(), &naming, target, &options)?;
+        }
+        Some(Commands::Run { all, group, .. }) if *all || group.is_some() => {
+            // 按优先级依次备份所有（或某个分组的）配置文件
+            run_all_profiles(&args, group.as_deref())?;
+        }
+        Some(Commands::Daemon { status, .. }) => {
+            if *status {
+                show_daemon_status(args.json)?;
+            } else {
+                run_daemon(&args)?;
+            }
+        }
+        Some(Commands::Run { .. }) | None => {
+            // 执行备份
+            run_backup(args)?;
+        }
+    }
+    Ok(())
+}
+
+/// 打开远程备份目标（分块仓库或 WebDAV）
+///
+/// 指定配置文件时使用其中的用户名、区域、服务地址和密钥文件，否则使用环境变量中的设置。
+fn open_remote_target(
+    destination: &std::path::Path,
+    profile: &Option<String>,
+    keyfile: Option<&std::path::Path>,
+) -> Result<Box<dyn RemoteSnapshots>> {
+    match profile {
+        Some(name) => {
+            let config = load_profile(name, true)?.0;
+            open_remote(
+                &config.destination,
+                config.username.as_deref(),
+                config.region.as_deref(),
+                config.endpoint.as_deref(),
+                keyfile.or(config.keyfile.as_deref()),
+            )
+        }
+        None => open_remote(destination, None, None, None, keyfile),
+    }
+}
+
+/// 列出远程备份目标中的所有快照及其文件数量和大小
+fn run_remote_list(repo: &dyn RemoteSnapshots, naming: &SnapshotNaming) -> Result<()> {
+    let snapshots = repo.snapshots(naming)?;
+    if snapshots.is_empty() {
+        status!("{}", style(trf!("No backups found in {}", "{} 中没有找到备份", repo.describe())).yellow());
+        return Ok(());
+    }
+
+    status!(
+        "{}  {}  {}",
+        align(tr("Snapshot", "快照"), 19, false),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Size", "大小"), 12, true)
+    );
+    for (_, name) in &snapshots {
+        let (count, size) = repo.totals(name)?;
+        status!("{:<19}  {:>8}  {:>12}", style(name).cyan(), count, format_bytes(size));
+    }
+    Ok(())
+}
+
+/// 从远程备份目标恢复快照
+///
+/// # 参数
+/// * `repo` - 远程备份目标
+/// * `naming` - 快照命名格式
+/// * `target` - 恢复到的目录
+/// * `options` - 恢复选项（快照名称为空时恢复最新快照）
+fn run_restore(
+    repo: &dyn RemoteSnapshots,
+    naming: &SnapshotNaming,
+    target: &std::path::Path,
+    options: &RestoreOptions,
+) -> Result<()> {
+    if options.dry_run {
+        let report = restore(repo, naming, target, options, &NullSink)?;
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!(
+                "Would restore {} file(s) ({}) from {} to {:?}",
+                "将恢复 {} 个文件（{}），来自快照 {}，恢复到 {:?}",
+                report.stats.files,
+                format_bytes(report.stats.bytes),
+                report.snapshot,
+                target
+            )
+        );
+        return Ok(());
+    }
+
+    let spinner = new_spinner(tr("Restoring...", "正在恢复..."));
+    let report = restore(repo, naming, target, options, &NullSink)?;
+    spinner.finish_and_clear();
+
+    let stats = &report.stats;
+    status!(
+        "{} {}",
+        style(tr("Success:", "成功：")).green(),
+        trf!(
+            "Restored {} to {:?}: {} file(s), {} director(ies), {} symlink(s), {}",
+            "已将 {} 恢复到 {:?}：{} 个文件、{} 个目录、{} 个符号链接，{}",
+            report.snapshot,
+            target,
+            stats.files,
+            stats.dirs,
+            stats.symlinks,
+            format_bytes(stats.bytes)
+        )
+    );
+    Ok(())
+}
+
+/// 校验快照目录，逐条列出发现的问题
+///
+/// # 参数
+/// * `backup` - 快照目录
+/// * `options` - 校验选项
+/// * `json` - 以 JSON 格式输出校验结果
+fn run_verify(backup: &std::path::Path, options: &VerifyOptions, json: bool) -> Result<()> {
+    let spinner = new_spinner(tr("Verifying...", "正在校验..."));
+    let report = verify(backup, options, &NullSink)?;
+    spinner.finish_and_clear();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        if !report.manifest && report.is_ok() {
+            note!(
+                "{} {}",
+                style(tr("Note:", "提示：")).yellow(),
+                tr(
+                    "Snapshot has no manifest, only checked that every file can be read",
+                    "快照中没有清单，只检查了每个文件能否读取"
+                )
+            );
+        }
+        match report.integrity {
+            Some(Integrity::Unchecked) => warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow().bold(),
+                style(tr(
+                    "Manifest has no checksum (old format), it could not be checked itself and may have been modified",
+                    "清单没有校验和（旧版格式），无法检查清单本身是否完整，可能已被修改"
+                ))
+                .yellow()
+            ),
+            Some(Integrity::Signed) => {
+                note!("{}", tr("Manifest signature matches the key", "清单签名与密钥匹配"))
+            }
+            _ => {}
+        }
+        for issue in &report.issues {
+            status!("{}  {}", style(&issue.path).red(), issue.problem.message());
+        }
+        if report.is_ok() {
+            status!(
+                "{} {}",
+                style(tr("Success:", "成功：")).green(),
+                trf!(
+                    "Verified {} file(s) ({}), no problems found",
+                    "已校验 {} 个文件（{}），未发现问题",
+                    report.files,
+                    format_bytes(report.bytes)
+                )
+            );
+        }
+    }
+
+    if !report.is_ok() {
+        anyhow::bail!("Verification found {} problem(s) in {:?}", report.issues.len(), backup);
+    }
+    Ok(())
+}
+
+/// 归档后的快照，用于 `--json` 输出
+#[derive(Serialize, Debug)]
+struct ArchivedSnapshot {
+    /// 快照名称
+    name: String,
+    /// 归档文件（试运行时为空）
+    archive: Option<PathBuf>,
+    /// 文件数量
+    files: u64,
+    /// 文件内容的总大小
+    bytes: u64,
+    /// 归档文件的大小
+    archive_size: u64,
+}
+
+/// 将早于指定时间的快照压缩为归档
+///
+/// 最新的快照和 `current` 指向的快照始终保留为目录，作为下一次备份的硬链接基准。
+///
+/// # 参数
+/// * `destination` - 备份目标目录
+/// * `naming` - 快照命名规则
+/// * `older_than` - 时间阈值（秒）
+/// * `key` - 清单签名密钥
+/// * `dry_run` - 只列出将归档的快照
+/// * `json` - 以 JSON 格式输出结果
+fn run_archive(
+    destination: &std::path::Path,
+    naming: &SnapshotNaming,
+    older_than: u64,
+    key: Option<&[u8]>,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let backups = naming.list(destination, false)?;
+    let current = current_snapshot(destination).and_then(|path| path.file_name().map(|n| n.to_os_string()));
+    let cutoff = Local::now().naive_local() - chrono::Duration::seconds(older_than as i64);
+    let candidates: Vec<&PathBuf> = backups
+        .iter()
+        .take(backups.len().saturating_sub(1))
+        .filter(|(time, path)| *time < cutoff && path.file_name() != current.as_deref())
+        .map(|(_, path)| path)
+        .collect();
+
+    if candidates.is_empty() {
+        if json {
+            println!("[]");
+        } else {
+            status!(
+                "{}",
+                style(trf!(
+                    "No snapshots older than {} day(s) to archive in {:?}",
+                    "没有早于 {} 天、需要归档的快照（{:?}）",
+                    older_than / 86400,
+                    destination
+                ))
+                .yellow()
+            );
+        }
+        return Ok(());
+    }
+
+    let mut results = Vec::new();
+    let mut failures = 0;
+    for snapshot in candidates {
+        let name = snapshot.file_name().unwrap().to_string_lossy().to_string();
+        if dry_run {
+            note!("{} {}", style(tr("Dry run:", "试运行：")).yellow(), trf!("Would archive {}", "将归档 {}", name));
+            results.push(ArchivedSnapshot { name, archive: None, files: 0, bytes: 0, archive_size: 0 });
+            continue;
+        }
+
+        let spinner = new_spinner(tr("Archiving...", "正在归档..."));
+        let result = archive_snapshot(snapshot, key);
+        spinner.finish_and_clear();
+        match result {
+            Ok((archive, stats)) => {
+                status!(
+                    "{} {}",
+                    style(tr("Archived:", "已归档：")).green(),
+                    trf!(
+                        "{} ({} file(s), {} -> {})",
+                        "{}（{} 个文件，{} -> {}）",
+                        name,
+                        stats.files,
+                        format_bytes(stats.bytes),
+                        format_bytes(stats.archive_size)
+                    )
+                );
+                results.push(ArchivedSnapshot {
+                    name,
+                    archive: Some(archive),
+                    files: stats.files,
+                    bytes: stats.bytes,
+                    archive_size: stats.archive_size,
+                });
+            }
+            Err(e) => {
+                warning!(
+                    "{} {}",
+                    style(tr("Error:", "错误：")).red(),
+                    trf!("Failed to archive {}: {:#}", "无法归档 {}：{:#}", name, e)
+                );
+                failures += 1;
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    }
+    if failures > 0 {
+        anyhow::bail!("{} snapshot(s) could not be archived", failures);
+    }
+    Ok(())
+}
+
+/// 将快照导出为归档文件并输出统计
+///
+/// # 参数
+/// * `backup` - 快照目录
+/// * `output` - 输出文件
+/// * `format` - 归档格式（为空时根据输出文件扩展名判断）
+/// * `json` - 以 JSON 格式输出统计
+fn run_export(
+    backup: &std::path::Path,
+    output: &std::path::Path,
+    format: Option<ArchiveFormat>,
+    json: bool,
+) -> Result<()> {
+    let format = match format.or_else(|| ArchiveFormat::from_path(output)) {
+        Some(format) => format,
+        None => anyhow::bail!(
+            "Cannot tell the archive format from {:?}, use --format zip|tar|tar.gz|tar.zst",
+            output
+        ),
+    };
+    if output.exists() {
+        anyhow::bail!("Output file {:?} already exists", output);
+    }
+
+    let spinner = new_spinner(tr("Exporting...", "正在导出..."));
+    let stats = export_snapshot(backup, output, format)?;
+    spinner.finish_and_clear();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    status!(
+        "{} {}",
+        style(tr("Success:", "成功：")).green(),
+        trf!(
+            "Exported {} file(s), {} director(ies), {} symlink(s) ({}) to {:?}, archive size {}",
+            "已导出 {} 个文件、{} 个目录、{} 个符号链接（{}）到 {:?}，归档大小 {}",
+            stats.files,
+            stats.dirs,
+            stats.symlinks,
+            format_bytes(stats.bytes),
+            output,
+            format_bytes(stats.archive_size)
+        )
+    );
+    Ok(())
+}
+
+/// 导入已有备份并输出每个快照的结果
+///
+/// # 参数
+/// * `source` - 已有备份的根目录
+/// * `destination` - recall 的备份目标目录
+/// * `naming` - 快照命名
+/// * `layout` - 已有备份的布局（`None` 时自动识别）
+/// * `subdir` - 只导入每个快照中的此子目录
+/// * `key` - 清单签名密钥
+/// * `dry_run` - 只列出将导入的快照
+/// * `json` - 以 JSON 格式输出结果
+#[allow(clippy::too_many_arguments)]
+fn run_import(
+    source: &std::path::Path,
+    destination: &std::path::Path,
+    naming: &SnapshotNaming,
+    layout: Option<Layout>,
+    subdir: Option<&std::path::Path>,
+    key: Option<&[u8]>,
+    dry_run: bool,
+    json: bool,
+) -> Result<()> {
+    let start_time = std::time::Instant::now();
+    let (layout, snapshots) = find_snapshots(source, layout)?;
+    note!("{}{:?} ({})", label("Source: ", "源路径："), style(source).blue(), layout.name());
+    note!("{}{:?}", label("Destination: ", "目标路径："), style(destination).blue());
+
+    let spinner = new_spinner(tr("Importing...", "正在导入..."));
+    let results = import_snapshots(&snapshots, destination, naming, subdir, key, dry_run)?;
+    spinner.finish_and_clear();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    status!(
+        "{}  {}  {}  {}  {}  {}",
+        align(tr("Snapshot", "快照"), 19, false),
+        align(tr("Status", "状态"), 8, false),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Linked", "硬链接"), 8, true),
+        align(tr("Copied", "复制"), 8, true),
+        tr("Source", "原目录")
+    );
+    for result in &results {
+        let status = match result.status {
+            "imported" if result.failed > 0 => style(format!("{:<8}", tr("failed", "有失败"))).red(),
+            "imported" => style(format!("{:<8}", tr("imported", "已导入"))).green(),
+            "exists" => style(format!("{:<8}", tr("exists", "已存在"))).dim(),
+            _ => style(format!("{:<8}", tr("planned", "将导入"))).yellow(),
+        };
+        status!(
+            "{:<19}  {}  {:>8}  {:>8}  {:>8}  {}",
+            style(&result.name).cyan(),
+            status,
+            result.files,
+            result.linked,
+            result.copied,
+            style(result.source.display()).dim()
+        );
+    }
+
+    let imported: Vec<_> = results.iter().filter(|r| r.status == "imported").collect();
+    let failed: u64 = imported.iter().map(|r| r.failed).sum();
+    status!("{}", style("----------------------------------------").dim());
+    if dry_run {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!(
+                "{} snapshot(s) would be imported",
+                "将导入 {} 个快照",
+                results.iter().filter(|r| r.status == "planned").count()
+            )
+        );
+        return Ok(());
+    }
+    status!(
+        "{}{}",
+        label("Imported:        ", "已导入："),
+        style(trf!("{} snapshot(s)", "{} 个快照", imported.len())).green()
+    );
+    status!(
+        "{}{}",
+        label("Data Transferred: ", "传输数据："),
+        style(format_bytes(imported.iter().map(|r| r.bytes_copied).sum())).cyan()
+    );
+    status!(
+        "{}{}",
+        label("Total Duration:   ", "总耗时："),
+        style(format_duration(start_time.elapsed().as_secs())).bold()
+    );
+    if failed > 0 {
+        anyhow::bail!("{} entries could not be imported", failed);
+    }
+    Ok(())
+}
+
+/// 将备份目标目录同步到镜像目录并输出统计
+///
+/// # 参数
+/// * `source` - 备份目标目录
+/// * `mirror` - 镜像目录
+/// * `dry_run` - 只显示将执行的操作
+/// * `json` - 以 JSON 格式输出统计
+fn run_replicate(source: &std::path::Path, mirror: &std::path::Path, dry_run: bool, json: bool) -> Result<()> {
+    let start_time = std::time::Instant::now();
+    note!("{}{:?}", label("Source: ", "源路径："), style(source).blue());
+    note!("{}{:?}", label("Mirror: ", "镜像："), style(mirror).blue());
+
+    let spinner = new_spinner(tr("Replicating...", "正在复制..."));
+    let stats = replicate_tree(source, mirror, true, dry_run)?;
+    spinner.finish_and_clear();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        if dry_run {
+            note!(
+                "{} {}",
+                style(tr("Dry run:", "试运行：")).yellow(),
+                tr("No changes were made to the mirror", "未修改镜像")
+            );
+        }
+        status!("{}", style("----------------------------------------").dim());
+        status!("{}{}", label("Copied:          ", "复制："), style(stats.copied).green());
+        status!("{}{}", label("Hard Linked:     ", "硬链接："), style(stats.linked).dim());
+        status!("{}{}", label("Unchanged:       ", "未变化："), style(stats.unchanged).dim());
+        status!("{}{}", label("Symlinks:        ", "符号链接："), stats.symlinks);
+        status!("{}{}", label("Deleted:         ", "删除："), style(stats.deleted).yellow());
+        status!("{}{}", label("Failed:          ", "失败："), style(stats.failed).red().bold());
+        status!("{}{}", label("Data Transferred: ", "传输数据："), style(format_bytes(stats.bytes_copied)).cyan());
+        status!(
+            "{}{}",
+            label("Total Duration:   ", "总耗时："),
+            style(format_duration(start_time.elapsed().as_secs())).bold()
+        );
+    }
+
+    if stats.failed > 0 {
+        anyhow::bail!("{} item(s) could not be replicated", stats.failed);
+    }
+    Ok(())
+}
+
+/// 将备份目标目录迁移到新位置
+///
+/// 按时间顺序复制所有快照并重建硬链接，随后核对文件数量、大小和硬链接组数量；
+/// 核对通过且指定了 `delete_source` 时删除原目录。目标中已有的条目会保留，
+/// 中断后重新运行只复制尚未完成的部分。
+///
+/// # 参数
+/// * `source` - 当前的备份目标目录
+/// * `target` - 新的备份目标目录
+/// * `delete_source` - 核对通过后是否删除原目录
+/// * `dry_run` - 只显示将执行的操作
+fn run_migrate(source: &std::path::Path, target: &std::path::Path, delete_source: bool, dry_run: bool) -> Result<()> {
+    let start_time = std::time::Instant::now();
+    note!("{}{:?}", label("Source: ", "源路径："), style(source).blue());
+    note!("{}{:?}", label("Target: ", "目标："), style(target).blue());
+
+    let spinner = new_spinner(tr("Copying snapshots...", "正在复制快照..."));
+    let stats = replicate_tree(source, target, false, dry_run)?;
+    spinner.finish_and_clear();
+
+    status!("{}", style("----------------------------------------").dim());
+    status!("{}{}", label("Copied:          ", "复制："), style(stats.copied).green());
+    status!("{}{}", label("Hard Linked:     ", "硬链接："), style(stats.linked).dim());
+    status!("{}{}", label("Already Present: ", "已存在："), style(stats.unchanged).dim());
+    status!("{}{}", label("Failed:          ", "失败："), style(stats.failed).red().bold());
+    status!("{}{}", label("Data Transferred: ", "传输数据："), style(format_bytes(stats.bytes_copied)).cyan());
+
+    if dry_run {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            tr("Nothing was copied, verified or deleted", "未复制、核对或删除任何内容")
+        );
+        return Ok(());
+    }
+    if stats.failed > 0 {
+        anyhow::bail!("{} item(s) could not be copied, the source was left untouched", stats.failed);
+    }
+
+    let spinner = new_spinner(tr("Verifying...", "正在核对..."));
+    let check = verify_tree(source, target)?;
+    spinner.finish_and_clear();
+
+    status!("{}", style("----------------------------------------").dim());
+    status!("{}{}", label("Files:           ", "文件数："), check.files);
+    status!("{}{}", label("Directories:     ", "目录数："), check.dirs);
+    status!("{}{}", label("Symlinks:        ", "符号链接："), check.symlinks);
+    status!("{}{}", label("Data:            ", "数据量："), format_bytes(check.bytes));
+    status!(
+        "{}{}",
+        label("Link Groups:     ", "硬链接组："),
+        trf!("{} (source) / {} (target)", "{}（源）/ {}（目标）", check.source_groups, check.target_groups)
+    );
+    if !check.is_ok() {
+        for mismatch in &check.mismatches {
+            warning!("  {}", mismatch);
+        }
+        anyhow::bail!("Verification failed, the source was left untouched");
+    }
+    status!("{} {}", style(tr("Success:", "成功：")).green(), tr("Verification passed", "核对通过"));
+
+    if delete_source {
+        let spinner = new_spinner(tr("Deleting source...", "正在删除原目录..."));
+        remove_tree(source)?;
+        spinner.finish_and_clear();
+        status!("{}", trf!("Deleted {:?}", "已删除 {:?}", source));
+    }
+    status!(
+        "{}{}",
+        label("Total Duration:   ", "总耗时："),
+        style(format_duration(start_time.elapsed().as_secs())).bold()
+    );
+
+    // 提示仍指向原目录的配置文件
+    let profiles: Vec<String> = AppConfig::load()?
+        .profiles
+        .into_iter()
+        .filter(|(_, profile)| profile.destination.starts_with(source))
+        .map(|(name, _)| name)
+        .collect();
+    if !profiles.is_empty() {
+        note!(
+            "{} {}",
+            style(tr("Note:", "提示：")).yellow(),
+            trf!(
+                "These profiles still use the old destination, update them to {:?}: {}",
+                "以下配置仍使用原目标目录，请改为 {:?}：{}",
+                target,
+                profiles.join(", ")
+            )
+        );
+    }
+    Ok(())
+}
+
+/// 列出目标目录中的所有快照及其摘要
+fn run_list(destination: &std::path::Path, naming: &SnapshotNaming) -> Result<()> {
+    // 快照目录和归档按时间合并排序
+    let mut backups: Vec<(chrono::NaiveDateTime, PathBuf)> = naming.list(destination, false)?;
+    backups.extend(find_archives(destination, naming)?);
+    backups.sort();
+    if backups.is_empty() {
+        status!("{}", style(trf!("No backups found in {:?}", "{:?} 中没有找到备份", destination)).yellow());
+        return Ok(());
+    }
+
+    status!(
+        "{}  {}  {}  {}  {}  {}",
+        align(tr("Snapshot", "快照"), 19, false),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Failed", "失败"), 8, true),
+        align(tr("Transferred", "传输量"), 12, true),
+        align(tr("Duration", "耗时"), 10, true),
+        tr("Tags", "标签")
+    );
+    for (_, backup) in &backups {
+        let archived = backup.is_file();
+        let (name, summary) = if archived {
+            (archive_name(backup), read_metadata(backup).ok().and_then(|(summary, _)| summary))
+        } else {
+            (
+                backup.file_name().unwrap().to_string_lossy().to_string(),
+                BackupSummary::load(backup).ok().flatten(),
+            )
+        };
+        let mut tags = summary
+            .as_ref()
+            .map(|s| s.tags.join(", "))
+            .unwrap_or_default();
+        if archived {
+            tags = format!("[{}] {}", tr("archived", "已归档"), tags).trim_end().to_string();
+        }
+        match summary {
+            Some(summary) if summary.has_stats() => {
+                let failed = if summary.failed > 0 {
+                    style(format!("{:>8}", summary.failed)).red()
+                } else {
+                    style(format!("{:>8}", summary.failed)).dim()
+                };
+                status!(
+                    "{:<19}  {:>8}  {}  {:>12}  {:>10}  {}",
+                    style(&name).cyan(),
+                    summary.total_files,
+                    failed,
+                    format_bytes(summary.bytes_copied),
+                    format_duration(summary.duration_secs),
+                    style(tags).magenta()
+                );
+            }
+            // 旧版本创建的快照没有统计信息
+            _ => status!(
+                "{:<19}  {:>8}  {:>8}  {:>12}  {:>10}  {}",
+                style(&name).cyan(),
+                "-", "-", "-", "-",
+                style(tags).magenta()
+            ),
+        }
+    }
+    status!("{}", style(trf!("{} snapshot(s)", "共 {} 个快照", backups.len())).dim());
+    Ok(())
+}
+
+/// 创建带提示信息的加载动画
+///
+/// 安静模式或纯文本输出（`NO_COLOR`、非终端）下返回隐藏的进度条。
+fn new_spinner(message: &'static str) -> ProgressBar {
+    if verbosity() == Verbosity::Quiet || plain_output() {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// 显示各快照的独占空间和目标目录的实际占用
+fn run_du(destination: &std::path::Path, naming: &SnapshotNaming) -> Result<()> {
+    let spinner = new_spinner(tr("Scanning snapshots...", "正在扫描快照..."));
+    let usage = compute_usage(destination, naming)?;
+    spinner.finish_and_clear();
+
+    if usage.snapshots.is_empty() {
+        status!("{}", style(trf!("No backups found in {:?}", "{:?} 中没有找到备份", destination)).yellow());
+        return Ok(());
+    }
+
+    status!(
+        "{}  {}  {}  {}",
+        align(tr("Snapshot", "快照"), 19, false),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Apparent", "表观大小"), 12, true),
+        align(tr("Exclusive", "独占空间"), 12, true)
+    );
+    for snapshot in &usage.snapshots {
+        status!(
+            "{:<19}  {:>8}  {:>12}  {:>12}",
+            style(snapshot.path.file_name().unwrap().to_string_lossy()).cyan(),
+            snapshot.files,
+            format_bytes(snapshot.apparent),
+            format_bytes(snapshot.exclusive)
+        );
+    }
+
+    let apparent: u64 = usage.snapshots.iter().map(|s| s.apparent).sum();
+    status!("{}", style("----------------------------------------").dim());
+    status!("{}{}", label("Physical Usage:  ", "实际占用："), style(format_bytes(usage.physical)).cyan().bold());
+    status!("{}{}", label("Apparent Size:   ", "表观大小："), format_bytes(apparent));
+    status!(
+        "{}{}",
+        label("Saved by Links:  ", "链接节省："),
+        style(format_bytes(apparent.saturating_sub(usage.physical))).green()
+    );
+    Ok(())
+}
+
+/// 打印快照比较结果
+fn print_changes(changes: &[FileChange], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+
+    let (mut added, mut removed, mut modified) = ((0u64, 0u64), (0u64, 0u64), (0u64, 0u64));
+    let mut unchanged = (0u64, 0u64);
+    for change in changes {
+        let path = change.path.display();
+        match change.kind {
+            ChangeKind::Added => {
+                let size = change.new_size.unwrap_or(0);
+                added = (added.0 + 1, added.1 + size);
+                status!("{} {} ({})", style("+").green(), path, format_bytes(size));
+            }
+            ChangeKind::Removed => {
+                let size = change.old_size.unwrap_or(0);
+                removed = (removed.0 + 1, removed.1 + size);
+                status!("{} {} ({})", style("-").red(), path, format_bytes(size));
+            }
+            ChangeKind::Modified => {
+                let (old_size, new_size) = (change.old_size.unwrap_or(0), change.new_size.unwrap_or(0));
+                modified = (modified.0 + 1, modified.1 + new_size);
+                status!(
+                    "{} {} ({} → {})",
+                    style("M").yellow(),
+                    path,
+                    format_bytes(old_size),
+                    format_bytes(new_size)
+                );
+            }
+            ChangeKind::Unchanged => {
+                unchanged = (unchanged.0 + 1, unchanged.1 + change.new_size.unwrap_or(0));
+            }
+        }
+    }
+
+    status!("{}", style("----------------------------------------").dim());
+    status!("{}{} ({})", label("Added:     ", "新增："), style(added.0).green(), format_bytes(added.1));
+    status!("{}{} ({})", label("Removed:   ", "删除："), style(removed.0).red(), format_bytes(removed.1));
+    status!("{}{} ({})", label("Modified:  ", "修改："), style(modified.0).yellow(), format_bytes(modified.1));
+    if unchanged.0 > 0 {
+        status!("{}{} ({})", label("Unchanged: ", "未变化："), style(unchanged.0).dim(), format_bytes(unchanged.1));
+    }
+    Ok(())
+}
+
+/// 打印试运行生成的备份计划（详细模式下逐个列出条目）
+fn print_plan(plan: &BackupPlan) {
+    for (task, action) in plan.entries.iter().filter(|(task, _)| !task.rel_path.as_os_str().is_empty()) {
+        match action {
+            SyncAction::CreateDir | SyncAction::MakeSymlink(_) | SyncAction::Skip | SyncAction::Resumed => {
+                verbose!("{}: {}", action.name(), task.rel_path.display())
+            }
+            _ => verbose!("{}: {} ({})", action.name(), task.rel_path.display(), format_bytes(task.size)),
+        }
+    }
+
+    let totals = &plan.totals;
+    note!(
+        "{} {}",
+        style(tr("Dry run:", "试运行：")).yellow(),
+        trf!(
+            "Would copy {} file(s) ({}), link {} file(s) ({}), create {} director(ies)",
+            "将复制 {} 个文件（{}），链接 {} 个文件（{}），创建 {} 个目录",
+            totals.copied_new + totals.copied_modified + totals.cloned,
+            format_bytes(totals.bytes_copied),
+            totals.linked,
+            format_bytes(totals.bytes_linked),
+            totals.dirs_created
+        )
+    );
+}
+
+/// 预估下一次备份并打印结果
+fn run_estimate(config: &BackupConfig, json: bool) -> Result<()> {
+    let spinner = new_spinner(tr("Scanning source...", "正在扫描源目录..."));
+    let start = std::time::Instant::now();
+    let estimate = estimate_backup(config)?;
+    spinner.finish_and_clear();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+        return Ok(());
+    }
+
+    status!("{}{:?}", label("Source: ", "源路径："), style(&config.source).blue());
+    status!("{}{:?}", label("Dest:   ", "目标："), style(&config.destination).blue());
+    if estimate.full_backup {
+        status!("{}", style(tr(
+            "No previous backup, next run will be a full backup",
+            "没有上一次备份，下一次运行将是完整备份"
+        ))
+        .yellow());
+    }
+    status!("{}", style("----------------------------------------").dim());
+    status!(
+        "{}{:>8} {}  {:>10}",
+        label("Total:        ", "总计："),
+        estimate.total_files,
+        tr("files", "个文件"),
+        format_bytes(estimate.total_bytes)
+    );
+    status!(
+        "{}{:>8} {}  {:>10}",
+        label("Copy (New):   ", "复制（新增）："),
+        style(estimate.new_files).green(),
+        tr("files", "个文件"),
+        format_bytes(estimate.new_bytes)
+    );
+    status!(
+        "{}{:>8} {}  {:>10}",
+        label("Copy (Mod):   ", "复制（修改）："),
+        style(estimate.modified_files).yellow(),
+        tr("files", "个文件"),
+        format_bytes(estimate.modified_bytes)
+    );
+    status!(
+        "{}{:>8} {}  {:>10}",
+        label("Hard Link:    ", "硬链接："),
+        style(estimate.linked_files).dim(),
+        tr("files", "个文件"),
+        format_bytes(estimate.linked_bytes)
+    );
+    if estimate.symlinks > 0 {
+        status!("{}{:>8}", label("Symlinks:     ", "符号链接："), estimate.symlinks);
+    }
+    if estimate.skipped > 0 {
+        status!("{}{:>8}", label("Skipped:      ", "跳过："), style(estimate.skipped).red());
+    }
+    status!("{}", style("----------------------------------------").dim());
+    status!(
+        "{}{}",
+        label("Projected Growth: ", "预计增长："),
+        style(format_bytes(estimate.projected_growth)).cyan().bold()
+    );
+    status!("{}{}", label("Scan Time:        ", "扫描耗时："), format_duration(start.elapsed().as_secs()));
+    Ok(())
+}
+
+/// 检查配置文件并输出每个配置文件的检查结果
+///
+/// 有任何配置文件未通过检查时返回错误（退出码非零），便于在加入计划任务前确认。
+fn run_validate(json: bool) -> Result<()> {
+    let path = AppConfig::get_config_path()?;
+    let app_config = AppConfig::load()?;
+    let checks = validate_config(&app_config);
+    let failures = checks.iter().filter(|c| !c.passed()).count();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        status!("{}{:?}", label("Config: ", "配置文件："), style(&path).blue());
+        if app_config.profiles.is_empty() {
+            status!("{}", style(tr("No profiles configured.", "没有任何配置。")).yellow());
+        }
+        for check in &checks {
+            let result = if check.passed() {
+                style(format!("{:<4}", "PASS")).green()
+            } else {
+                style(format!("{:<4}", "FAIL")).red()
+            };
+            status!("{}  {}", result, check.profile);
+            for error in &check.errors {
+                status!("      {} {}", style(tr("error:", "错误：")).red(), error);
+            }
+            for warning in &check.warnings {
+                status!("      {} {}", style(tr("warning:", "警告：")).yellow(), warning);
+            }
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} of {} check(s) failed", failures, checks.len());
+    }
+    Ok(())
+}
+
+/// 在系统凭据管理器中保存或删除密码
+fn run_credential(action: &CredentialCommands) -> Result<()> {
+    let target = match action {
+        CredentialCommands::Set { target, .. } | CredentialCommands::Delete { target } => target,
+    };
+    let (destination, user) = match target.profile {
+        Some(ref name) => {
+            let app_config = AppConfig::load()?;
+            let profile = app_config
+                .profiles
+                .get(name)
+                .with_context(|| format!("Profile '{}' not found", name))?;
+            (profile.destination.clone(), target.user.clone().or(profile.username.clone()))
+        }
+        None => (target.destination.clone().unwrap_or_default(), target.user.clone()),
+    };
+    let user = user.context("User name is required (--user, or username in the profile)")?;
+    let key = credential_target(&destination);
+
+    match action {
+        CredentialCommands::Set { password_stdin, .. } => {
+            let password = if *password_stdin {
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line).context("Failed to read password from stdin")?;
+                line.trim_end_matches(['\r', '\n']).to_string()
+            } else {
+                dialoguer::Password::new()
+                    .with_prompt(trf!("Password for {}@{}", "{}@{} 的密码", user, key))
+                    .with_confirmation(tr("Confirm password", "确认密码"), tr("Passwords do not match", "两次输入的密码不一致"))
+                    .interact()?
+            };
+            set_password(&key, &user, &password)?;
+            status!("{}", style(trf!("Password for {}@{} saved.", "已保存 {}@{} 的密码。", user, key)).green());
+        }
+        CredentialCommands::Delete { .. } => {
+            if delete_password(&key, &user)? {
+                status!("{}", trf!("Password for {}@{} deleted.", "已删除 {}@{} 的密码。", user, key));
+            } else {
+                status!("{}", trf!("No password stored for {}@{}.", "没有保存 {}@{} 的密码。", user, key));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 注册或删除配置文件的计划任务（Windows 任务计划程序或 systemd 定时器）
+///
+/// 任务以安静模式运行 `recall run <配置>`，并显式指定当前使用的配置文件，
+/// 全局的 `--log-file` 也会传给任务。试运行时只输出任务定义，不注册。
+fn run_schedule(args: &Args, action: &ScheduleCommands) -> Result<()> {
+    match action {
+        ScheduleCommands::Install { profile, time, days, logon, wake, no_catch_up, elevated, systemd, system } => {
+            let app_config = AppConfig::load()?;
+            let config = app_config
+                .profiles
+                .get(profile)
+                .with_context(|| format!("Profile '{}' not found", profile))?;
+
+            let config_path = std::fs::canonicalize(AppConfig::get_config_path()?)
+                .context("The configuration file does not exist yet")?;
+            let mut arguments = vec![
+                "run".to_string(),
+                profile.clone(),
+                "-q".to_string(),
+                "--config".to_string(),
+                config_path.to_string_lossy().to_string(),
+            ];
+            if let Some(ref log_file) = args.log_file {
+                let log_file = std::path::absolute(log_file).unwrap_or(log_file.clone());
+                arguments.extend(["--log-file".to_string(), log_file.to_string_lossy().to_string()]);
+            }
+
+            let options = TaskOptions {
+                profile: profile.clone(),
+                time: *time,
+                days: days.clone(),
+                logon: *logon,
+                wake: *wake,
+                catch_up: !no_catch_up,
+                elevated: *elevated || config.vss,
+                command: std::env::current_exe().context("Could not determine the recall executable")?,
+                arguments,
+            };
+            if *systemd {
+                let name = unit_name(profile);
+                if args.dry_run {
+                    let (service, timer) = systemd_units(&options);
+                    println!("# {}.service\n{}\n# {}.timer\n{}", name, service, name, timer);
+                    return Ok(());
+                }
+                for path in install_systemd(&options, *system)? {
+                    status!("{}", trf!("Wrote {:?}", "已写入 {:?}", path));
+                }
+                status!(
+                    "{} {}",
+                    style(tr("Success:", "成功：")).green(),
+                    trf!("Timer {}.timer enabled.", "已启用定时器 {}.timer。", name)
+                );
+                return Ok(());
+            }
+            if args.dry_run {
+                print!("{}", task_xml(&options));
+                return Ok(());
+            }
+            install_task(&options)?;
+            status!(
+                "{} {}",
+                style(tr("Success:", "成功：")).green(),
+                trf!("Scheduled task {} registered.", "已注册计划任务 {}。", task_name(profile))
+            );
+        }
+        ScheduleCommands::Remove { profile, systemd, system } => {
+            let name = if *systemd { format!("{}.timer", unit_name(profile)) } else { task_name(profile) };
+            if args.dry_run {
+                status!(
+                    "{} {}",
+                    style(tr("Dry run:", "试运行：")).yellow(),
+                    trf!("Would remove scheduled task {}", "将删除计划任务 {}", name)
+                );
+                return Ok(());
+            }
+            if *systemd {
+                remove_systemd(profile, *system)?;
+            } else {
+                remove_task(profile)?;
+            }
+            status!("{}", trf!("Scheduled task {} removed.", "已删除计划任务 {}。", name));
+        }
+    }
+    Ok(())
+}
+
+/// 在系统文件管理器中打开最新的快照
+///
+/// 优先使用 `current` 链接（或标记文件）指向的快照，缺失或失效时使用最新的快照目录。
+///
+/// # 参数
+/// * `destination` - 备份目标目录（项目目录）
+fn run_open(destination: &std::path::Path, naming: &SnapshotNaming) -> Result<()> {
+    let snapshot = match current_snapshot(destination) {
+        Some(path) => path,
+        None => find_latest_backup(destination, naming)?
+            .with_context(|| format!("No backups found in {:?}", destination))?,
+    };
+
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    status!("{}{:?}", label("Opening: ", "正在打开："), style(&snapshot).blue());
+    std::process::Command::new(opener)
+        .arg(&snapshot)
+        .spawn()
+        .with_context(|| format!("Failed to launch {}", opener))?;
+    Ok(())
+}
+
+/// 显示单个快照的详细信息
+fn run_info(backup: &std::path::Path) -> Result<()> {
+    if !backup.is_dir() {
+        anyhow::bail!("Backup directory {:?} does not exist", backup);
+    }
+    // 支持传入 current 链接，显示其指向的快照
+    let backup = std::fs::canonicalize(backup).context("Failed to resolve backup path")?;
+    let name = backup.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    status!("{}{}", label("Snapshot:  ", "快照："), style(&name).cyan().bold());
+    status!("{}{:?}", label("Path:      ", "路径："), backup);
+
+    let summary = BackupSummary::load(&backup)?;
+    if let Some(ref summary) = summary {
+        if !summary.tags.is_empty() {
+            status!("{}{}", label("Tags:      ", "标签："), style(summary.tags.join(", ")).magenta());
+        }
+    }
+
+    match summary {
+        Some(summary) if summary.has_stats() => {
+            status!("{}{}", label("Started:   ", "开始时间："), summary.started_at);
+            status!("{}{}", label("Duration:  ", "耗时："), format_duration(summary.duration_secs));
+            status!("{}{:?}", label("Source:    ", "源路径："), summary.source);
+            if !summary.host.is_empty() {
+                status!("{}{}", label("Host:      ", "主机："), summary.host);
+            }
+            match summary.previous {
+                Some(ref previous) => status!("{}{}", label("Linked to: ", "链接基准："), previous),
+                None => status!(
+                    "{}{}",
+                    label("Linked to: ", "链接基准："),
+                    style(tr("none (full backup)", "无（完整备份）")).dim()
+                ),
+            }
+            status!("{}{}", label("Version:   ", "版本："), summary.version);
+            if let Some(ref settings) = summary.settings {
+                status!("{}{:?}", label("Target:    ", "备份目标："), settings.destination);
+                let mut options = vec![format!("{} {}", tr("workers", "线程"), settings.workers)];
+                if settings.check_content {
+                    options.push(tr("content check", "内容检查").to_string());
+                }
+                if settings.vss {
+                    options.push("VSS".to_string());
+                }
+                if settings.preserve_metadata {
+                    options.push(tr("metadata", "保留元数据").to_string());
+                }
+                if settings.bandwidth_limit > 0 {
+                    options.push(format!("{}/s", format_bytes(settings.bandwidth_limit)));
+                }
+                if let Some(keep) = settings.keep {
+                    options.push(format!("{} {}", tr("keep", "保留"), keep));
+                }
+                if settings.read_only {
+                    options.push(tr("read-only", "只读").to_string());
+                }
+                status!("{}{}", label("Settings:  ", "设置："), options.join(", "));
+                if !settings.exclude.is_empty() {
+                    status!("{}{}", label("Excludes:  ", "排除："), settings.exclude.join(" "));
+                }
+            }
+            status!("{}", style("----------------------------------------").dim());
+            status!("{}{}", label("Total Files:     ", "文件总数："), summary.total_files);
+            status!("{}{}", label("Copied (New):    ", "复制（新增）："), style(summary.copied_new).green());
+            status!("{}{}", label("Copied (Mod):    ", "复制（修改）："), style(summary.copied_modified).yellow());
+            status!("{}{}", label("Cloned (Mod):    ", "克隆（修改）："), style(summary.cloned).yellow());
+            status!("{}{}", label("Hard Linked:     ", "硬链接："), style(summary.linked).dim());
+            print_deduplication(summary.bytes_linked, summary.bytes_copied);
+            status!("{}{}", label("Link Fallbacks:  ", "链接降级："), style(summary.link_fallbacks).yellow());
+            status!("{}{}", label("Symlinks:        ", "符号链接："), summary.symlinks);
+            status!("{}{}", label("Directories:     ", "目录："), summary.dirs_created);
+            status!("{}{}", label("Skipped:         ", "跳过："), style(summary.skipped).red());
+            status!("{}{}", label("Excluded:        ", "已排除："), style(summary.excluded).dim());
+            status!("{}{}", label("Resumed:         ", "续传保留："), style(summary.resumed).dim());
+            status!("{}{}", label("Failed:          ", "失败："), style(summary.failed).red().bold());
+            status!("{}{}", label("Locked:          ", "锁定："), style(summary.locked).red());
+            if summary.vanished > 0 {
+                status!("{}{}", label("Vanished:        ", "已消失："), style(summary.vanished).dim());
+            }
+            status!("{}{}", label("Data Transferred: ", "传输数据："), style(format_bytes(summary.bytes_copied)).cyan());
+        }
+        _ => status!(
+            "{}",
+            style(tr(
+                "No summary recorded for this snapshot (created by an older version)",
+                "此快照没有摘要（由旧版本创建）"
+            ))
+            .yellow()
+        ),
+    }
+
+    status!("{}", style("----------------------------------------").dim());
+    let manifest = if backup.join(MANIFEST_FILE).exists() {
+        style(tr("present", "有")).green()
+    } else {
+        style(tr("none", "无")).dim()
+    };
+    status!("{}{}", label("Manifest:  ", "清单："), manifest);
+    Ok(())
+}
+
+/// 列出或删除 VSS 卷影副本
+#[cfg(windows)]
+fn run_snapshots(volume: Option<&std::path::Path>, delete: &[String], clean: bool, dry_run: bool) -> Result<()> {
+    use recall::vss::{delete_snapshot, list_snapshots};
+
+    let snapshots = list_snapshots(volume)?;
+
+    // 未指定删除操作时只列出快照
+    if delete.is_empty() && !clean {
+        if snapshots.is_empty() {
+            status!("{}", style(tr("No shadow copies found.", "没有找到卷影副本。")).yellow());
+            return Ok(());
+        }
+        for snap in &snapshots {
+            let created = snap
+                .created
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let owner = if snap.created_by_recall {
+                style("recall").green().to_string()
+            } else {
+                style("other").dim().to_string()
+            };
+            status!("{}  {}  {}  {}", style(&snap.id).cyan(), created, owner, snap.volume);
+            status!("    {}", style(&snap.device_path).dim());
+        }
+        return Ok(());
+    }
+
+    let mut targets: Vec<String> = delete.to_vec();
+    if clean {
+        targets.extend(snapshots.iter().filter(|s| s.created_by_recall).map(|s| s.id.clone()));
+    }
+    if targets.is_empty() {
+        status!("{}", style(tr("No leftover recall snapshots to clean.", "没有需要清理的 recall 遗留快照。")).green());
+        return Ok(());
+    }
+
+    for id in targets {
+        if dry_run {
+            status!(
+                "{} {}",
+                style(tr("Dry run:", "试运行：")).yellow(),
+                trf!("Would delete snapshot {}", "将删除快照 {}", id)
+            );
+            continue;
+        }
+        match delete_snapshot(&id) {
+            Ok(()) => status!("{}", trf!("Deleted snapshot {}", "已删除快照 {}", style(&id).cyan())),
+            Err(e) => warning!(
+                "{} {}",
+                style(tr("Error:", "错误：")).red(),
+                trf!("Failed to delete snapshot {}: {:#}", "无法删除快照 {}：{:#}", id, e)
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// 在非 Windows 平台上，VSS 快照管理不可用
+#[cfg(not(windows))]
+fn run_snapshots(_volume: Option<&std::path::Path>, _delete: &[String], _clean: bool, _dry_run: bool) -> Result<()> {
+    anyhow::bail!("VSS snapshots are only supported on Windows")
+}
+
+/// 执行备份操作
+fn run_backup(args: Args) -> Result<()> {
+    // 准备备份配置
+    // 运行配置文件时以配置文件名称记录历史，否则使用项目名称
+    let (config, label) = if let Some(Commands::Run { profile: Some(ref profile), .. }) = args.command {
+        // 运行指定的配置文件，不进行任何交互
+        let (mut config, _) = load_profile(profile, args.dry_run)?;
+        apply_cli_overrides(&args, &mut config)?;
+        (config, profile.clone())
+    } else if let (Some(src), Some(dest)) = (&args.source, &args.destination) {
+        // 使用命令行参数指定的路径
+        command_line_config(&args, src, dest)?
+    } else {
+        // 进入交互模式，用户选择退出时直接返回
+        let Selection::Run(mut config, profile) = run_interactive_mode(args.dry_run)? else {
+            return Ok(());
+        };
+        apply_cli_overrides(&args, &mut config)?;
+        (*config, profile)
+    };
+
+    // 仅在显式要求时向源目录写入默认排除文件
+    if args.backup.init_ignore && !config.dry_run {
+        if let Some(path) = write_default_ignore_file(&config.source)? {
+            status!("{}", trf!("Created default ignore file at: {:?}", "已创建默认忽略文件：{:?}", path));
+        }
+    }
+
+    let started = Local::now();
+    let dry_run = config.dry_run;
+    let notifications = Notifications::from_config(&config);
+    let result = execute_backup(&args, config);
+    if !dry_run {
+        save_history(&label, started, &result);
+        notifications.send(&label, started, &result);
+        save_report(args.backup.report_html.as_deref(), &label, started, &result);
+    }
+
+    let outcome = result?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&outcome)?);
+    }
+    if let Some(ref report) = outcome.replication {
+        if report.failed() {
+            anyhow::bail!("Backup {} was created but replication to {} failed", outcome.snapshot, report.remote);
+        }
+    }
+    Ok(())
+}
+
+/// 显示硬链接节省的空间：硬链接引用的数据量、实际写入的数据量和节省的比例
+///
+/// # 参数
+/// * `bytes_linked` - 硬链接的文件的总大小
+/// * `bytes_written` - 实际写入的字节数
+fn print_deduplication(bytes_linked: u64, bytes_written: u64) {
+    let Some(percent) = saved_percent(bytes_linked, bytes_written) else { return };
+    status!(
+        "{}{}",
+        label("Deduplicated:    ", "去重："),
+        style(trf!(
+            "{} referenced, {} written ({:.1}% saved)",
+            "引用 {}，写入 {}（节省 {:.1}%）",
+            format_bytes(bytes_linked),
+            format_bytes(bytes_written),
+            percent
+        ))
+        .green()
+    );
+}
+
+/// 显示每秒处理的文件数和各阶段耗时，便于判断瓶颈是磁盘（复制）还是内容检查（哈希）
+fn print_throughput(stats: &BackupStats) {
+    status!(
+        "{}{}",
+        label("Throughput:      ", "处理速度："),
+        trf!(
+            "{:.0} files/s over {:.1}s",
+            "每秒 {:.0} 个文件，共 {:.1} 秒",
+            stats.files_per_sec,
+            stats.execute_secs
+        )
+    );
+    let d = &stats.durations;
+    let mut phases = vec![trf!("scan {:.1}s", "扫描 {:.1} 秒", d.scan)];
+    if d.hash > 0.0 {
+        phases.push(trf!("hash {:.1}s", "哈希 {:.1} 秒", d.hash));
+    }
+    phases.push(trf!("copy {:.1}s", "复制 {:.1} 秒", d.copy + d.clone));
+    phases.push(trf!("link {:.1}s", "链接 {:.1} 秒", d.link));
+    status!(
+        "{}{} {}",
+        label("Phase Times:     ", "各阶段耗时："),
+        phases.join(", "),
+        style(tr("(hash, copy and link summed over threads)", "（哈希、复制和链接为各线程之和）")).dim()
+    );
+}
+
+/// 分别列出写入和硬链接数据最多的目录，找出增量备份突然变大的原因
+///
+/// # 参数
+/// * `stats` - 备份统计
+/// * `count` - 每项最多列出的目录数量（0 表示不显示）
+fn print_top_directories(stats: &BackupStats, count: usize) {
+    let lists = [
+        (false, tr("Top directories by data copied:", "写入数据最多的目录：")),
+        (true, tr("Top directories by data linked:", "硬链接数据最多的目录：")),
+    ];
+    for (linked, title) in lists {
+        let top = stats.top_directories(count, linked);
+        if top.is_empty() {
+            continue;
+        }
+        status!("{}", style(title).bold());
+        for (dir, bytes) in top {
+            status!("  {:>10}  {}", format_bytes(bytes), dir.display());
+        }
+    }
+}
+
+/// 按扩展名列出写入数据最多的文件类型及其占本次写入数据的比例
+fn print_top_extensions(stats: &BackupStats, count: usize) {
+    let top = stats.top_extensions(count);
+    if top.is_empty() || stats.bytes_copied == 0 {
+        return;
+    }
+    status!("{}", style(tr("Top file types by data copied:", "写入数据最多的文件类型：")).bold());
+    for (ext, bytes) in top {
+        status!(
+            "  {:>10}  {:>5.1}%  {:<12}  {}",
+            format_bytes(bytes.bytes),
+            bytes.bytes as f64 * 100.0 / stats.bytes_copied as f64,
+            extension_label(ext),
+            style(trf!("{} file(s)", "{} 个文件", bytes.files)).dim()
+        );
+    }
+}
+
+/// 扩展名的显示名称（`.jpg`，没有扩展名时为 `(none)`）
+fn extension_label(ext: &str) -> String {
+    if ext.is_empty() {
+        tr("(none)", "（无扩展名）").to_string()
+    } else {
+        format!(".{}", ext)
+    }
+}
+
+/// 将一次备份的结果追加到运行历史（本地备份目标还会写入目标根目录的统计记录），写入失败时只打印警告
+fn save_history(profile: &str, started: DateTime<Local>, result: &Result<BackupOutcome>) {
+    let mut record = RunRecord {
+        profile: profile.to_string(),
+        started_at: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+        duration_secs: (Local::now() - started).num_seconds().max(0) as u64,
+        ..Default::default()
+    };
+    match result {
+        Ok(outcome) => {
+            let stats = &outcome.stats;
+            record.status = outcome.status.to_string();
+            record.snapshot = Some(outcome.snapshot.clone());
+            record.duration_secs = outcome.duration_secs;
+            record.total_files = stats.total_files;
+            record.copied = stats.copied_new + stats.copied_modified + stats.cloned + stats.link_fallbacks;
+            record.linked = stats.linked;
+            record.failed = stats.failed;
+            record.bytes_copied = stats.bytes_copied;
+            record.bytes_linked = stats.bytes_linked;
+            record.error = outcome.replication.as_ref().and_then(|r| r.error.clone());
+            if !is_remote_destination(&outcome.destination) {
+                record.free_bytes = free_space(&outcome.destination);
+                if let Err(e) = record_destination_run(&outcome.destination, &record) {
+                    warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+                }
+            }
+        }
+        Err(e) => {
+            record.status = "failed".to_string();
+            record.error = Some(format!("{:#}", e));
+        }
+    }
+    if let Err(e) = record_run(&record) {
+        warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+    }
+}
+
+/// 指定了 `--report-html` 时写入本次运行的 HTML 报告，写入失败时只打印警告
+fn save_report(path: Option<&std::path::Path>, profile: &str, started: DateTime<Local>, result: &Result<BackupOutcome>) {
+    let Some(path) = path else { return };
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    let report = match result {
+        Ok(outcome) => RunReport {
+            profile,
+            status: outcome.status,
+            snapshot: Some(&outcome.snapshot),
+            started_at: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_secs: outcome.duration_secs,
+            stats: Some(&outcome.stats),
+            error: outcome.replication.as_ref().and_then(|r| r.error.as_deref()),
+        },
+        Err(_) => RunReport {
+            profile,
+            status: "failed",
+            snapshot: None,
+            started_at: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_secs: (Local::now() - started).num_seconds().max(0) as u64,
+            stats: None,
+            error: error.as_deref(),
+        },
+    };
+    match report.save(path) {
+        Ok(path) => note!("{}{:?}", label("Report written to: ", "报告已写入："), path),
+        Err(e) => warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e),
+    }
+}
+
+/// 运行结束后的通知设置（备份配置在运行时被消耗，需要提前取出）
+#[derive(Default)]
+struct Notifications {
+    /// Webhook URL
+    webhooks: Vec<String>,
+
+    /// 发送 Webhook 的时机
+    webhook_on: WebhookOn,
+
+    /// 是否显示桌面通知
+    desktop: bool,
+}
+
+impl Notifications {
+    /// 从备份配置中取出通知设置
+    fn from_config(config: &BackupConfig) -> Self {
+        Self {
+            webhooks: config.webhooks.clone(),
+            webhook_on: config.webhook_on,
+            desktop: config.notify,
+        }
+    }
+
+    /// 将一次备份的结果发送到配置的 Webhook 并显示桌面通知，失败时只打印警告
+    ///
+    /// 桌面通知只在交互式桌面上显示；成功的备份运行超过 [`MIN_DURATION_SECS`] 秒才显示。
+    fn send(&self, profile: &str, started: DateTime<Local>, result: &Result<BackupOutcome>) {
+        if self.webhooks.is_empty() && !self.desktop {
+            return;
+        }
+        let error = result.as_ref().err().map(|e| format!("{:#}", e));
+        let payload = match result {
+            Ok(outcome) => WebhookPayload::new(
+                profile,
+                outcome.status,
+                Some(&outcome.snapshot),
+                started.format("%Y-%m-%d %H:%M:%S").to_string(),
+                outcome.duration_secs,
+                Some(&outcome.stats),
+                outcome.replication.as_ref().and_then(|r| r.error.as_deref()),
+            ),
+            Err(_) => WebhookPayload::new(
+                profile,
+                "failed",
+                None,
+                started.format("%Y-%m-%d %H:%M:%S").to_string(),
+                (Local::now() - started).num_seconds().max(0) as u64,
+                None,
+                error.as_deref(),
+            ),
+        };
+
+        if self.webhook_on.should_send(payload.status) {
+            for url in &self.webhooks {
+                if let Err(e) = send_webhook(url, &payload) {
+                    warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+                }
+            }
+        }
+
+        let failed = payload.status != "ok";
+        if self.desktop && (failed || payload.duration_secs >= MIN_DURATION_SECS) && is_interactive_desktop() {
+            if let Err(e) = show_notification(&payload.title, &payload.message, failed) {
+                warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+            }
+        }
+    }
+}
+
+/// 显示历次运行的统计表
+fn run_history(profile: Option<&str>, limit: usize, json: bool) -> Result<()> {
+    let records = load_history(profile)?;
+    let records = &records[records.len().saturating_sub(limit)..];
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(records)?);
+        return Ok(());
+    }
+    if records.is_empty() {
+        status!("{}", style(tr("No backup runs recorded yet.", "还没有备份运行记录。")).yellow());
+        return Ok(());
+    }
+
+    let width = records.iter().map(|r| r.profile.len()).max().unwrap_or(0).max(7);
+    status!(
+        "{}  {}  {}  {}  {}  {}  {}  {}",
+        align(tr("Started", "开始时间"), 19, false),
+        align(tr("Profile", "配置"), width, false),
+        align(tr("Status", "状态"), 7, false),
+        align(tr("Duration", "耗时"), 10, true),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Copied", "传输量"), 10, true),
+        align(tr("Linked", "硬链接"), 6, true),
+        align(tr("Failed", "失败"), 6, true)
+    );
+    for record in records {
+        let status = match record.status.as_str() {
+            "ok" => style(format!("{:<7}", "OK")).green(),
+            "partial" => style(format!("{:<7}", "PARTIAL")).yellow(),
+            _ => style(format!("{:<7}", "FAILED")).red(),
+        };
+        let failed = if record.failed > 0 {
+            style(format!("{:>6}", record.failed)).red()
+        } else {
+            style(format!("{:>6}", record.failed)).dim()
+        };
+        status!(
+            "{:<19}  {:<width$}  {}  {:>10}  {:>8}  {:>10}  {:>5.0}%  {}",
+            record.started_at,
+            record.profile,
+            status,
+            format_duration(record.duration_secs),
+            record.total_files,
+            format_bytes(record.bytes_copied),
+            record.link_ratio() * 100.0,
+            failed,
+            width = width
+        );
+        if let Some(ref error) = record.error {
+            status!("    {}", style(error).red());
+        }
+    }
+    Ok(())
+}
+
+/// 显示备份目标上历次运行的按月趋势，或以 CSV / JSON 导出每次运行的记录
+fn run_stats(dest: &std::path::Path, csv: bool, spark: bool, json: bool) -> Result<()> {
+    let records = load_destination_stats(dest)?;
+    if csv {
+        print!("{}", to_csv(&records));
+        return Ok(());
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+    if records.is_empty() {
+        status!("{}", style(tr("No backup runs recorded for this destination yet.", "此备份目标还没有运行记录。")).yellow());
+        return Ok(());
+    }
+
+    let months = monthly_stats(&records);
+    status!(
+        "{}",
+        trf!(
+            "{} runs between {} and {}",
+            "{} 次运行，从 {} 到 {}",
+            records.len(),
+            records[0].started_at,
+            records[records.len() - 1].started_at
+        )
+    );
+    status!();
+    if spark {
+        let series = |f: fn(&recall::history::MonthStats) -> u64| months.iter().map(f).collect::<Vec<_>>();
+        let first = &months[0];
+        let last = &months[months.len() - 1];
+        status!("{}{} .. {}", label("Months:        ", "月份："), first.month, last.month);
+        for (name, values, format) in [
+            (label("Copied:        ", "传输量："), series(|m| m.bytes_copied), format_bytes as fn(u64) -> String),
+            (label("Snapshot Size: ", "快照大小："), series(|m| m.snapshot_bytes), format_bytes),
+            (label("Duration:      ", "耗时："), series(|m| m.duration_secs), format_duration),
+            (label("Free Space:    ", "可用空间："), series(|m| m.free_bytes.unwrap_or(0)), format_bytes),
+        ] {
+            status!(
+                "{}{}  {} -> {}",
+                name,
+                style(sparkline(&values)).cyan(),
+                format(values[0]),
+                format(values[values.len() - 1])
+            );
+        }
+    } else {
+        status!(
+            "{}  {}  {}  {}  {}  {}",
+            align(tr("Month", "月份"), 7, false),
+            align(tr("Runs", "次数"), 5, true),
+            align(tr("Copied", "传输量"), 10, true),
+            align(tr("Snapshot", "快照大小"), 10, true),
+            align(tr("Avg Time", "平均耗时"), 10, true),
+            align(tr("Free", "可用空间"), 10, true)
+        );
+        for month in &months {
+            status!(
+                "{:<7}  {:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+                month.month,
+                month.runs,
+                format_bytes(month.bytes_copied),
+                format_bytes(month.snapshot_bytes),
+                format_duration(month.duration_secs),
+                month.free_bytes.map(format_bytes).unwrap_or_else(|| "-".to_string())
+            );
+        }
+    }
+
+    if let Some(days) = days_until_full(&records) {
+        status!();
+        status!(
+            "{}{}",
+            label("Forecast:      ", "预测："),
+            style(trf!(
+                "at the current rate the destination fills up in about {:.0} days",
+                "按目前的速度，备份目标约 {:.0} 天后写满",
+                days
+            ))
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// 根据命令行指定的源路径和目标路径创建备份配置
+///
+/// # 返回
+/// * `Ok((BackupConfig, String))` - 备份配置和项目名称
+/// * `Err(anyhow::Error)` - 源路径无效或处理 `.recallignore` 失败
+fn command_line_config(
+    args: &Args,
+    src: &std::path::Path,
+    dest: &std::path::Path,
+) -> Result<(BackupConfig, String)> {
+    let source_abs = std::fs::canonicalize(src).context("Failed to get absolute path of source")?;
+
+    // 生成项目名称（可由源目录中的 .recall.toml 指定）
+    let project_name = project_name(&source_abs)?;
+
+    // 全局默认设置（工作线程数、排除模式、保留数量）
+    let defaults = AppConfig::load()?.defaults;
+
+    // 构建最终目标路径
+    let final_destination_root = dest.join(&project_name);
+    let mut builder = BackupConfig::builder(source_abs, final_destination_root)
+        .check_content(args.check_content)
+        .vss(args.backup.vss, args.backup.vss_fallback)
+        .vss_writers(args.backup.vss_writers.clone())
+        .preserve_metadata(args.backup.preserve_metadata)
+        .workers(args.backup.workers.or(defaults.workers).unwrap_or(0))
+        .excludes(defaults.exclude.iter().chain(&args.exclude))
+        .buffer(args.backup.buffer_size.unwrap_or(0), args.backup.unbuffered)
+        .bandwidth_limit(args.backup.bwlimit.unwrap_or(0) as u64)
+        .skip_space_check(args.backup.skip_space_check)
+        .fail_on_locked(args.backup.fail_on_locked)
+        .on_failure(args.backup.on_failure.unwrap_or_default(), args.backup.max_failures.unwrap_or(0))
+        .read_only(args.backup.read_only)
+        .manifest(args.backup.manifest)
+        .naming(override_naming(args, &SnapshotNaming::new(defaults.snapshot_format.as_deref(), defaults.utc)?)?)
+        .dry_run(args.dry_run);
+    if let Some(ref path) = args.backup.ignore_file {
+        builder = builder.ignore_file(path);
+    }
+    if let Some(keep) = defaults.keep {
+        builder = builder.keep(keep);
+    }
+    let mut config = builder.finish()?;
+
+    // 先按命令行给出的目标根目录检查，同一路径或父子目录给出明确的错误（快照目录另在执行前检查）
+    if !is_remote_destination(dest) {
+        check_path_overlap(&config.source, dest, &config.exclude_patterns)?;
+    }
+    config.webhooks = defaults.webhooks.clone();
+    config.notify = args.backup.notify;
+    config.replicate = args.backup.replicate.clone();
+    config.chunked = args.backup.chunked;
+    config.encrypt = args.backup.encrypt;
+    config.keyfile = args.backup.keyfile.clone();
+    Ok((config, project_name))
+}
+
+/// 将命令行参数覆盖到从配置文件创建的备份配置上
+///
+/// 显式指定的数值参数替换配置文件中的值，开关参数只能开启配置文件中关闭的选项，
+/// 排除模式和排除文件追加到配置文件的排除列表之后。
+fn apply_cli_overrides(args: &Args, config: &mut BackupConfig) -> Result<()> {
+    config.check_content |= args.check_content;
+    config.preserve_metadata |= args.backup.preserve_metadata;
+    config.unbuffered |= args.backup.unbuffered;
+    config.skip_space_check |= args.backup.skip_space_check;
+    config.fail_on_locked |= args.backup.fail_on_locked;
+    config.read_only |= args.backup.read_only;
+    config.manifest |= args.backup.manifest;
+    if let Some(policy) = args.backup.on_failure {
+        config.on_failure = policy;
+    }
+    if let Some(max_failures) = args.backup.max_failures {
+        config.max_failures = max_failures;
+    }
+    config.exclude_patterns.extend(args.exclude.iter().cloned());
+    if let Some(workers) = args.backup.workers {
+        config.workers = workers;
+    }
+    if let Some(buffer_size) = args.backup.buffer_size {
+        config.buffer_size = buffer_size;
+    }
+    if let Some(rate) = args.backup.bwlimit {
+        config.bandwidth_limit = rate as u64;
+    }
+    if let Some(ref path) = args.backup.ignore_file {
+        config.read_ignore_file(path)?;
+    }
+    if let Some(ref remote) = args.backup.replicate {
+        config.replicate = Some(remote.clone());
+    }
+    config.chunked |= args.backup.chunked;
+    config.encrypt |= args.backup.encrypt;
+    config.notify |= args.backup.notify;
+    if let Some(ref keyfile) = args.backup.keyfile {
+        config.keyfile = Some(keyfile.clone());
+    }
+    config.naming = override_naming(args, &config.naming)?;
+    Ok(())
+}
+
+/// 用命令行的 `--snapshot-format` / `--utc` 覆盖快照命名规则
+fn override_naming(args: &Args, naming: &SnapshotNaming) -> Result<SnapshotNaming> {
+    if args.backup.snapshot_format.is_none() && !args.backup.utc {
+        return Ok(naming.clone());
+    }
+    SnapshotNaming::new(
+        Some(args.backup.snapshot_format.as_deref().unwrap_or(naming.format())),
+        args.backup.utc || naming.is_utc(),
+    )
+}
+
+/// 确定只读取备份目标目录的命令（list、du、open、prune）要操作的目录
+///
+/// 指定了配置文件时使用其目标目录、命名规则和保留数量，
+/// 否则使用命令行路径和全局默认设置。
+///
+/// # 返回
+/// * `Ok((PathBuf, SnapshotNaming, Option<usize>))` - 目标目录、快照命名规则和默认保留数量
+/// * `Err(anyhow::Error)` - 未指定目标目录，或配置无效
+fn target_destination(
+    args: &Args,
+    destination: &Option<PathBuf>,
+    profile: &Option<String>,
+    command: &str,
+) -> Result<(PathBuf, SnapshotNaming, Option<usize>)> {
+    if let Some(name) = profile {
+        let config = load_profile(name, true)?.0;
+        return Ok((config.destination, config.naming, config.keep));
+    }
+    let dest = destination
+        .clone()
+        .or(args.destination.clone())
+        .with_context(|| format!("Destination path or --profile is required for {} command", command))?;
+    let defaults = AppConfig::load()?.defaults;
+    let naming = SnapshotNaming::new(defaults.snapshot_format.as_deref(), defaults.utc)?;
+    Ok((dest, naming, defaults.keep))
+}
+
+/// 按优先级依次运行所有（或某个分组的）已保存配置文件，最后打印汇总表
+///
+/// 单个配置文件失败时继续运行其余配置文件；
+/// 只要有配置文件失败、存在未能备份的文件或异地复制失败，最终返回错误。
+fn run_all_profiles(args: &Args, group: Option<&str>) -> Result<()> {
+    let names = profile_names(group)?;
+    if names.is_empty() {
+        match group {
+            Some(group) => anyhow::bail!("No saved profiles in group '{}'", group),
+            None => anyhow::bail!("No saved profiles to run"),
+        }
+    }
+
+    // (名称, 备份结果或错误, 耗时)
+    let mut results: Vec<(String, Result<BackupOutcome>, Duration)> = Vec::new();
+    for name in names {
+        status!();
+        status!("{}", style(trf!("=== Profile: {} ===", "=== 配置：{} ===", name)).magenta().bold());
+        let start = std::time::Instant::now();
+        let started = Local::now();
+        let mut dry_run = args.dry_run;
+        let mut notifications = Notifications { desktop: args.backup.notify, ..Default::default() };
+        let result = load_profile(&name, args.dry_run).and_then(|(mut config, _)| {
+            apply_cli_overrides(args, &mut config)?;
+            dry_run = config.dry_run;
+            notifications = Notifications::from_config(&config);
+            execute_backup(args, config)
+        });
+        if let Err(ref e) = result {
+            warning!(
+                "{} {}",
+                style(tr("Error:", "错误：")).red(),
+                trf!("Profile '{}' failed: {:#}", "配置 '{}' 运行失败：{:#}", name, e)
+            );
+        }
+        if !dry_run {
+            save_history(&name, started, &result);
+            notifications.send(&name, started, &result);
+            save_report(args.backup.report_html.as_deref(), &name, started, &result);
+        }
+        results.push((name, result, start.elapsed()));
+    }
+
+    let failures = results
+        .iter()
+        .filter(|(_, result, _)| !matches!(result, Ok(outcome) if outcome.status == "ok"))
+        .count();
+
+    if args.json {
+        let outcomes: Vec<ProfileOutcome> = results
+            .into_iter()
+            .map(|(profile, result, _)| match result {
+                Ok(outcome) => ProfileOutcome {
+                    profile,
+                    status: outcome.status,
+                    error: None,
+                    backup: Some(outcome),
+                },
+                Err(e) => ProfileOutcome {
+                    profile,
+                    status: "failed",
+                    error: Some(format!("{:#}", e)),
+                    backup: None,
+                },
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&outcomes)?);
+        if failures > 0 {
+            anyhow::bail!("{} of {} profile(s) did not complete cleanly", failures, outcomes.len());
+        }
+        return Ok(());
+    }
+
+    // 打印汇总表
+    let width = results.iter().map(|(name, _, _)| name.len()).max().unwrap_or(0).max(7);
+    status!();
+    status!("{}", style("========================================").dim());
+    status!(
+        "{}  {}  {}  {}  {}  {}  {}  {}",
+        align(tr("Profile", "配置"), width, false),
+        align(tr("Status", "状态"), 7, false),
+        align(tr("Files", "文件数"), 8, true),
+        align(tr("Copied", "复制"), 8, true),
+        align(tr("Linked", "硬链接"), 8, true),
+        align(tr("Failed", "失败"), 6, true),
+        align(tr("Data", "数据量"), 10, true),
+        align(tr("Duration", "耗时"), 10, true)
+    );
+
+    for (name, result, elapsed) in &results {
+        let duration = format_duration(elapsed.as_secs());
+        match result {
+            Ok(outcome) => {
+                let stats = &outcome.stats;
+                let status = if outcome.status == "ok" {
+                    style(format!("{:<7}", "OK")).green()
+                } else {
+                    style(format!("{:<7}", "PARTIAL")).yellow()
+                };
+                status!(
+                    "{:<width$}  {}  {:>8}  {:>8}  {:>8}  {:>6}  {:>10}  {:>10}",
+                    name,
+                    status,
+                    stats.total_files,
+                    stats.copied_new + stats.copied_modified + stats.cloned,
+                    stats.linked,
+                    stats.failed,
+                    format_bytes(stats.bytes_copied),
+                    duration,
+                    width = width
+                );
+            }
+            Err(_) => {
+                status!(
+                    "{:<width$}  {}  {:>8}  {:>8}  {:>8}  {:>6}  {:>10}  {:>10}",
+                    name,
+                    style(format!("{:<7}", "FAILED")).red(),
+                    "-", "-", "-", "-", "-",
+                    duration,
+                    width = width
+                );
+            }
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} of {} profile(s) did not complete cleanly", failures, results.len());
+    }
+    Ok(())
+}
+
+/// 常驻进程检查计划的最长间隔（系统睡眠唤醒后最多延迟这么久发现错过的运行）
+const DAEMON_TICK: Duration = Duration::from_secs(60);
+
+/// 状态文件超过此时间未更新时，认为常驻进程已经退出
+const DAEMON_STALE_SECS: i64 = 180;
+
+/// 常驻运行，按配置文件的计划依次执行备份
+///
+/// 每个配置文件的下一次运行时间从最近一次运行（运行历史中的记录或本进程的运行）开始计算，
+/// 从未运行过的从常驻进程启动时开始计算。因此关机或睡眠期间错过的运行会在启动或唤醒后补一次，
+/// 而不是把错过的每一次都补上。配置文件每分钟重新读取，修改计划无需重启常驻进程。
+fn run_daemon(args: &Args) -> Result<()> {
+    let started = Local::now();
+    let config = AppConfig::load()?;
+    if !config.profiles.values().any(|p| p.schedule.is_some()) {
+        anyhow::bail!("No profiles have a schedule; add e.g. schedule = \"0 2 * * *\" to a profile");
+    }
+
+    let status = std::sync::Arc::new(std::sync::Mutex::new(DaemonStatus {
+        pid: std::process::id(),
+        started_at: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+        updated_at: String::new(),
+        running: None,
+        profiles: Vec::new(),
+    }));
+    // 备份期间主循环被占用，由单独的线程定期刷新状态文件的更新时间
+    {
+        let status = status.clone();
+        thread::spawn(move || loop {
+            save_daemon_status(&status);
+            thread::sleep(DAEMON_TICK / 2);
+        });
+    }
+
+    status!("{}", style(format!("Recall daemon v{}", env!("CARGO_PKG_VERSION"))).cyan().bold());
+    note!("{}", trf!("Started with pid {}", "已启动，进程 ID {}", std::process::id()));
+
+    // 本进程中每个配置文件最近一次运行的开始时间
+    let mut attempts: std::collections::HashMap<String, DateTime<Local>> = std::collections::HashMap::new();
+    let mut invalid: std::collections::HashSet<String> = std::collections::HashSet::new();
+    loop {
+        let config = match AppConfig::load() {
+            Ok(config) => config,
+            Err(e) => {
+                warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+                thread::sleep(DAEMON_TICK);
+                continue;
+            }
+        };
+        // (名称, 表达式, 计划)
+        let mut scheduled = Vec::new();
+        for name in config.run_order(None) {
+            let Some(expr) = config.profiles[&name].schedule.clone() else {
+                continue;
+            };
+            let schedule = match CronSchedule::parse(&expr) {
+                Ok(schedule) => schedule,
+                Err(e) => {
+                    if invalid.insert(format!("{}={}", name, expr)) {
+                        warning!(
+                            "{} {}",
+                            style(tr("Warning:", "警告：")).yellow(),
+                            trf!("Profile '{}' is not scheduled: {:#}", "配置 '{}' 未加入计划：{:#}", name, e)
+                        );
+                    }
+                    continue;
+                }
+            };
+            scheduled.push((name, expr, schedule));
+        }
+
+        // 下一次运行时间从最近一次运行开始计算
+        let next_runs = |attempts: &std::collections::HashMap<String, DateTime<Local>>| {
+            let history = recall::history::last_runs().unwrap_or_default();
+            scheduled
+                .iter()
+                .map(|(name, _, schedule)| {
+                    let last_run = history
+                        .get(name)
+                        .and_then(|record| record.started())
+                        .and_then(|time| time.and_local_timezone(Local).earliest());
+                    let anchor = [last_run, attempts.get(name).copied()].into_iter().flatten().max();
+                    (schedule.next_after(anchor.unwrap_or(started)), history.get(name).cloned())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let now = Local::now();
+        for ((name, _, _), (next, _)) in scheduled.iter().zip(next_runs(&attempts)) {
+            let Some(due) = next else {
+                continue;
+            };
+            if due > now {
+                continue;
+            }
+            if now - due > chrono::Duration::from_std(DAEMON_TICK).unwrap_or_default() {
+                note!(
+                    "{}",
+                    trf!(
+                        "Missed run of '{}' at {}, running now",
+                        "错过了配置 '{}' 在 {} 的运行，现在补上",
+                        name,
+                        due.format("%Y-%m-%d %H:%M")
+                    )
+                );
+            }
+
+            status!();
+            status!(
+                "{}",
+                style(trf!("=== Profile: {} ({}) ===", "=== 配置：{}（{}）===", name, now.format("%Y-%m-%d %H:%M:%S")))
+                    .magenta()
+                    .bold()
+            );
+            status.lock().unwrap().running = Some(name.clone());
+            save_daemon_status(&status);
+
+            let started = Local::now();
+            attempts.insert(name.clone(), started);
+            let mut dry_run = args.dry_run;
+            let mut notifications = Notifications { desktop: args.backup.notify, ..Default::default() };
+            let result = load_profile(name, args.dry_run).and_then(|(mut config, _)| {
+                apply_cli_overrides(args, &mut config)?;
+                dry_run = config.dry_run;
+                notifications = Notifications::from_config(&config);
+                execute_backup(args, config)
+            });
+            if let Err(ref e) = result {
+                warning!(
+                    "{} {}",
+                    style(tr("Error:", "错误：")).red(),
+                    trf!("Profile '{}' failed: {:#}", "配置 '{}' 运行失败：{:#}", name, e)
+                );
+            }
+            if !dry_run {
+                save_history(name, started, &result);
+                notifications.send(name, started, &result);
+                save_report(args.backup.report_html.as_deref(), name, started, &result);
+            }
+            status.lock().unwrap().running = None;
+        }
+
+        // 重新计算运行后的下一次时间，写入状态
+        let next_runs = next_runs(&attempts);
+        let next_due = next_runs.iter().filter_map(|(next, _)| *next).min();
+        status.lock().unwrap().profiles = scheduled
+            .iter()
+            .zip(next_runs)
+            .map(|((name, expr, _), (next, record))| ScheduledProfile {
+                profile: name.clone(),
+                schedule: expr.clone(),
+                next_run: next.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
+                last_run: record.as_ref().map(|r| r.started_at.clone()),
+                last_status: record.map(|r| r.status),
+            })
+            .collect();
+        save_daemon_status(&status);
+
+        // 睡眠到下一次运行，但每分钟醒来一次，以便发现系统睡眠期间错过的运行和配置的修改
+        let wait = next_due
+            .and_then(|due| (due - Local::now()).to_std().ok())
+            .unwrap_or(DAEMON_TICK)
+            .min(DAEMON_TICK);
+        thread::sleep(wait.max(Duration::from_secs(1)));
+    }
+}
+
+/// 刷新更新时间并写入常驻进程状态文件，写入失败时只记录到日志
+fn save_daemon_status(status: &std::sync::Mutex<DaemonStatus>) {
+    let mut status = status.lock().unwrap();
+    status.updated_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    if let Err(e) = status.save() {
+        logfile::write(&format!("Warning: {:#}", e));
+    }
+}
+
+/// 显示常驻进程的状态
+fn show_daemon_status(json: bool) -> Result<()> {
+    let Some(status) = DaemonStatus::load()? else {
+        status!("{}", style(tr("The daemon has never been started.", "常驻进程从未启动过。")).yellow());
+        return Ok(());
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    let alive = status
+        .updated()
+        .is_some_and(|time| (Local::now().naive_local() - time).num_seconds() < DAEMON_STALE_SECS);
+    if alive {
+        status!(
+            "{} {}",
+            style(tr("Daemon:", "常驻进程：")).bold(),
+            style(trf!("running (pid {}, since {})", "运行中（进程 ID {}，启动于 {}）", status.pid, status.started_at)).green()
+        );
+        if let Some(ref name) = status.running {
+            status!("{} {}", style(tr("Backing up:", "正在备份：")).bold(), style(name).cyan());
+        }
+    } else {
+        status!(
+            "{} {}",
+            style(tr("Daemon:", "常驻进程：")).bold(),
+            style(trf!("not running (last seen {})", "未运行（最后更新于 {}）", status.updated_at)).red()
+        );
+    }
+    if status.profiles.is_empty() {
+        return Ok(());
+    }
+
+    let width = status.profiles.iter().map(|p| p.profile.len()).max().unwrap_or(0).max(7);
+    let schedule_width = status.profiles.iter().map(|p| p.schedule.len()).max().unwrap_or(0).max(8);
+    status!();
+    status!(
+        "{}  {}  {}  {}  {}",
+        align(tr("Profile", "配置"), width, false),
+        align(tr("Schedule", "计划"), schedule_width, false),
+        align(tr("Next run", "下一次运行"), 19, false),
+        align(tr("Last run", "最近一次运行"), 19, false),
+        tr("Status", "状态")
+    );
+    for profile in &status.profiles {
+        let last_status = match profile.last_status.as_deref() {
+            Some("ok") => style("OK").green(),
+            Some("partial") => style("PARTIAL").yellow(),
+            Some(_) => style("FAILED").red(),
+            None => style("-").dim(),
+        };
+        status!(
+            "{:<width$}  {:<schedule_width$}  {:<19}  {:<19}  {}",
+            profile.profile,
+            profile.schedule,
+            profile.next_run.as_deref().unwrap_or("-"),
+            profile.last_run.as_deref().unwrap_or("-"),
+            last_status,
+            width = width,
+            schedule_width = schedule_width
+        );
+    }
+    Ok(())
+}
+
+/// 按给定配置执行一次备份（包括监控心跳和备份前后的钩子），返回备份结果
+///
+/// 设置了 `ping_url` 时，运行开始前和结束后（含钩子）各请求一次监控 URL；心跳失败只输出警告。
+/// 试运行时不发送心跳。
+fn execute_backup(args: &Args, config: BackupConfig) -> Result<BackupOutcome> {
+    // 目标位于源目录中时拒绝运行，避免每次备份都包含之前的快照
+    config.check_paths()?;
+
+    let ping_url = match config.ping_url {
+        Some(ref url) if !config.dry_run => url.clone(),
+        _ => return run_with_hooks(args, config),
+    };
+
+    let ping = |event: PingEvent, body: &str| {
+        if let Err(e) = send_ping(&ping_url, event, body) {
+            warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+        }
+    };
+    ping(PingEvent::Start, "");
+    let result = run_with_hooks(args, config);
+    match result {
+        Ok(ref outcome) => {
+            let stats = &outcome.stats;
+            let event = if outcome.status == "ok" { PingEvent::Success } else { PingEvent::Failure };
+            ping(
+                event,
+                &format!(
+                    "{} {}: {} files, {} transferred, {} failed, took {}",
+                    outcome.status,
+                    outcome.snapshot,
+                    stats.total_files,
+                    format_bytes(stats.bytes_copied),
+                    stats.failed,
+                    format_duration(outcome.duration_secs)
+                ),
+            );
+        }
+        Err(ref e) => ping(PingEvent::Failure, &format!("failed: {:#}", e)),
+    }
+    result
+}
+
+/// 执行一次备份（包括备份前后的钩子），返回备份结果
+///
+/// 备份前钩子失败且 `hook_failure = "abort"` 时不执行备份；备份后钩子在备份失败时同样运行，
+/// 失败且为 `abort` 时本次运行记为失败（已创建的快照保留）。试运行时不运行钩子。
+fn run_with_hooks(args: &Args, config: BackupConfig) -> Result<BackupOutcome> {
+    if config.pre_hook.is_none() && config.post_hook.is_none() {
+        return perform_backup(args, config);
+    }
+    if config.dry_run {
+        note!("{}", style(tr("Dry run: hooks are not run", "试运行：不运行钩子")).yellow());
+        return perform_backup(args, config);
+    }
+
+    let abort = config.hook_failure.is_abort();
+    let post_hook = config.post_hook.clone();
+    let mut env = vec![
+        ("RECALL_SOURCE", config.source.to_string_lossy().to_string()),
+        ("RECALL_DESTINATION", config.destination.to_string_lossy().to_string()),
+    ];
+    if let Some(ref command) = config.pre_hook {
+        note!("{}{}", label("Pre-backup hook: ", "备份前钩子："), style(command).cyan());
+        if let Err(e) = run_hook(command, HookPhase::Pre, &env) {
+            if abort {
+                return Err(e.context("Backup was not started"));
+            }
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow(),
+                trf!("{:#}, continuing", "{:#}，继续备份", e)
+            );
+        }
+    }
+
+    let result = perform_backup(args, config);
+    let Some(command) = post_hook else {
+        return result;
+    };
+
+    match result {
+        Ok(ref outcome) => {
+            let stats = &outcome.stats;
+            env.extend([
+                ("RECALL_STATUS", outcome.status.to_string()),
+                ("RECALL_SNAPSHOT", outcome.snapshot.clone()),
+                (
+                    "RECALL_SNAPSHOT_PATH",
+                    outcome.destination.join(&outcome.snapshot).to_string_lossy().to_string(),
+                ),
+                ("RECALL_TOTAL_FILES", stats.total_files.to_string()),
+                (
+                    "RECALL_COPIED",
+                    (stats.copied_new + stats.copied_modified + stats.cloned + stats.link_fallbacks).to_string(),
+                ),
+                ("RECALL_LINKED", stats.linked.to_string()),
+                ("RECALL_FAILED", stats.failed.to_string()),
+                ("RECALL_BYTES_COPIED", stats.bytes_copied.to_string()),
+                ("RECALL_DURATION_SECS", outcome.duration_secs.to_string()),
+            ]);
+        }
+        Err(ref e) => {
+            env.extend([("RECALL_STATUS", "failed".to_string()), ("RECALL_ERROR", format!("{:#}", e))]);
+        }
+    }
+
+    note!("{}{}", label("Post-backup hook: ", "备份后钩子："), style(&command).cyan());
+    if let Err(e) = run_hook(&command, HookPhase::Post, &env) {
+        if abort {
+            if let Ok(ref outcome) = result {
+                return Err(e.context(format!("Backup {} was created", outcome.snapshot)));
+            }
+        }
+        warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+    }
+    result
+}
+
+/// 执行一次备份（不含钩子），返回备份结果
+fn perform_backup(args: &Args, config: BackupConfig) -> Result<BackupOutcome> {
+    // 使用系统凭据管理器中的密码连接目标所在的网络共享
+    if let Some(ref user) = config.username {
+        connect(&config.destination, user)?;
+    }
+
+    // 命令行的 VSS 设置与配置合并，写入器以命令行指定的优先
+    let mut config = config;
+    config.vss |= args.backup.vss || args.backup.snapshot_id.is_some();
+    config.vss_fallback |= args.backup.vss_fallback;
+    if !args.backup.vss_writers.is_empty() {
+        config.vss_writers = args.backup.vss_writers.clone();
+    }
+
+    // 降低进程优先级，避免备份拖慢前台工作
+    if args.backup.background {
+        if let Err(e) = enter_background_mode() {
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow(),
+                trf!("Failed to enter background mode: {}", "无法进入后台模式：{}", e)
+            );
+        }
+    }
+
+    // 管理员运行时启用备份特权，以便读取受 ACL 保护的文件
+    #[cfg(windows)]
+    if recall::vss::ShadowCopy::is_running_as_admin().unwrap_or(false) {
+        if let Err(e) = recall::utils::enable_backup_privilege() {
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow(),
+                trf!("Failed to enable backup privilege: {}", "无法启用备份特权：{}", e)
+            );
+        }
+    }
+
+    // 远程目标（S3 分块仓库、WebDAV）和本地分块仓库不使用硬链接布局，使用独立的存储流程
+    if is_remote_destination(&config.destination) || config.chunked || config.encrypt {
+        let use_vss = config.vss;
+        return execute_remote_backup(config, use_vss);
+    }
+
+    // 记录开始时间
+    let start_time = std::time::Instant::now();
+
+    // 确定快照名称，查找上一个快照和可续传的中断备份，创建临时目录
+    let destination_existed = config.destination.exists();
+    let mut session = BackupSession::begin(config.clone(), !args.backup.no_resume)?
+        .with_tags(args.backup.tag.clone())
+        .with_manifest_key(manifest::read_key(args.backup.manifest_key.as_deref())?);
+
+    // 打印备份信息
+    note!("{}", style(format!("Recall Backup Tool v{}", env!("CARGO_PKG_VERSION"))).cyan().bold());
+    note!("{}{:?}", label("Source: ", "源路径："), style(&config.source).blue());
+    note!("{}{:?}", label("Dest:   ", "目标："), style(&config.destination).blue());
+    note!("{}{}", label("Time:   ", "时间："), style(session.snapshot()).yellow());
+    note!("{}", style("----------------------------------------").dim());
+
+    if !destination_existed && config.dry_run {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!("Would create destination root {:?}", "将创建目标根目录 {:?}", config.destination)
+        );
+    }
+    if config.workers == 0 {
+        note!("{}", trf!("Workers: {} (auto)", "工作线程：{}（自动）", session.workers()));
+    }
+    if let Some(latest) = session.latest_backup() {
+        note!("{}{:?}", label("Found previous backup: ", "找到上一次备份："), style(latest.file_name().unwrap()).green());
+    } else {
+        note!("{}", style(tr("Performing initial full backup...", "正在执行首次完整备份...")).yellow());
+    }
+    if session.resumed() {
+        note!(
+            "{} {:?}",
+            style(tr("Resuming interrupted backup:", "继续中断的备份：")).yellow(),
+            session.temp_path().file_name().unwrap()
+        );
+    }
+    if config.dry_run {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!("Would create temp dir {:?}", "将创建临时目录 {:?}", session.temp_path())
+        );
+    }
+
+    // 创建或打开卷影副本，之后从快照中读取源文件（会话结束时释放）
+    if config.vss && !config.dry_run && cfg!(windows) {
+        note!("{}", style(tr("Initializing VSS Snapshot...", "正在初始化 VSS 快照...")).blue());
+    }
+    match session.start_vss(args.backup.snapshot_id.as_deref(), args.backup.keep_snapshot)? {
+        VssStatus::Active { source, snapshot_id, reused, components } => {
+            if reused {
+                note!("{}{}", label("Using existing snapshot: ", "使用已有快照："), snapshot_id);
+            } else if args.backup.keep_snapshot {
+                note!("{}{}", label("Snapshot will be kept after backup: ", "备份后保留快照："), snapshot_id);
+            }
+            if !config.vss_writers.is_empty() {
+                note!("{}{}", label("VSS writer components: ", "VSS 写入器组件："), components);
+            }
+            note!("{}{:?}", label("Backup Source mapped to VSS path: ", "备份源已映射到 VSS 路径："), source);
+        }
+        VssStatus::Unavailable(reason) => {
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow(),
+                trf!(
+                    "VSS is not available ({}), continuing without snapshot",
+                    "VSS 不可用（{}），将不使用快照继续备份",
+                    reason
+                )
+            );
+        }
+        VssStatus::Unsupported => {
+            note!("{}", style(tr(
+                "Warning: VSS is only supported on Windows. Ignoring --vss",
+                "警告：VSS 仅支持 Windows，忽略 --vss"
+            ))
+            .yellow());
+        }
+        VssStatus::Disabled => {}
+    }
+
+    if let Some(latest) = session.clock_behind() {
+        warning!(
+            "{} {}",
+            style(tr("Warning:", "警告：")).yellow().bold(),
+            style(trf!(
+                "The system clock is behind the latest snapshot {}, the new snapshot will be named {} instead. Check the system clock and time zone.",
+                "系统时钟落后于最新快照 {}，新快照改名为 {}。请检查系统时钟和时区设置。",
+                latest,
+                session.snapshot()
+            ))
+            .yellow()
+        );
+    }
+    if !session.supports_hard_links() {
+        warning!(
+            "{} {}",
+            style(tr("Warning:", "警告：")).yellow().bold(),
+            style(tr(
+                "The destination does not support hard links (e.g. exFAT, FAT32 or this network share). \
+                 Every file will be copied in full, snapshots will not share space.",
+                "目标不支持硬链接（如 exFAT、FAT32 或此网络共享），所有文件都将完整复制，快照之间不共享空间。"
+            ))
+            .yellow()
+        );
+    }
+    if !session.supports_symlinks() {
+        warning!(
+            "{} {}",
+            style(tr("Warning:", "警告：")).yellow(),
+            tr(
+                "The destination does not allow symbolic links, symlinks in the source will be copied as files",
+                "目标不允许创建符号链接，源中的符号链接将复制为其指向的文件"
+            )
+        );
+    }
+    if session.uses_reflink() {
+        note!("{}", style(tr(
+            "Block cloning supported, modified files will be cloned",
+            "支持块克隆，已修改的文件将被克隆"
+        ))
+        .dim());
+    }
+
+    // 预先扫描源目录，可用空间不足时在复制前中止，而不是写满磁盘后失败
+    if !config.dry_run && !config.skip_space_check {
+        let spinner = new_spinner(tr("Checking free space...", "正在检查可用空间..."));
+        let check = session.check_free_space()?;
+        spinner.finish_and_clear();
+        if let Some(check) = check {
+            if !check.sufficient() {
+                // 只删除刚创建的空临时目录，续传的中断备份保留
+                let _ = std::fs::remove_dir(session.temp_path());
+                anyhow::bail!(
+                    "Not enough free space on {:?}: the backup needs about {} but only {} is available \
+                     (free up space, or use --skip-space-check to try anyway)",
+                    config.destination,
+                    format_bytes(check.required),
+                    format_bytes(check.available)
+                );
+            }
+            note!(
+                "{}{}",
+                label("Free space: ", "可用空间："),
+                trf!(
+                    "{} available, about {} needed",
+                    "{} 可用，预计需要 {}",
+                    format_bytes(check.available),
+                    format_bytes(check.required)
+                )
+            );
+        }
+    }
+
+    // 试运行时只生成备份计划并显示；否则扫描源目录并写入临时目录（进度条显示在控制台）
+    let sink = if config.dry_run { ConsoleSink::new() } else { ConsoleSink::with_progress() };
+    let session = session.with_events(Arc::new(sink));
+    let stats = if config.dry_run {
+        let plan = session.plan()?;
+        print_plan(&plan);
+        if let Some(ref path) = args.backup.plan_out {
+            let rows = plan.export(path)?;
+            note!("{}", trf!("Exported {} plan entries to {:?}", "已将 {} 个计划条目导出到 {:?}", rows, path));
+        }
+        if let Some(available) = free_space(&config.destination).filter(|&a| plan.totals.bytes_copied > a) {
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow(),
+                trf!(
+                    "The backup would copy {} but only {} is free on the destination",
+                    "备份将复制 {}，但目标只有 {} 可用空间",
+                    format_bytes(plan.totals.bytes_copied),
+                    format_bytes(available)
+                )
+            );
+        }
+        plan.totals
+    } else {
+        session.transfer()?
+    };
+
+    // 提交备份（写入摘要，重命名临时目录并更新 current 链接）
+    let mut final_dest_path = session.snapshot_path();
+    if !config.dry_run {
+        let spinner = new_spinner(tr("Finalizing backup...", "正在完成备份..."));
+        let committed = session.commit(&stats);
+        spinner.finish_and_clear();
+        final_dest_path = committed?;
+        if final_dest_path != session.snapshot_path() {
+            warning!(
+                "{} {}",
+                style(tr("Warning:", "警告：")).yellow().bold(),
+                style(trf!(
+                    "{} file(s) failed, the snapshot was committed as incomplete: {:?} ('current' was not updated)",
+                    "{} 个文件失败，快照已标记为不完整：{:?}（未更新 'current'）",
+                    stats.failed,
+                    final_dest_path
+                ))
+                .yellow()
+            );
+        }
+    } else {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!("Would rename .partial to {:?}", "将把 .partial 重命名为 {:?}", final_dest_path)
+        );
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            tr("Would update 'current' symlink", "将更新 'current' 符号链接")
+        );
+    }
+    let timestamp_folder_name =
+        final_dest_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let resume = session.resumed();
+    let used_vss = session.uses_vss();
+
+    // 将新快照复制到异地（rclone）
+    let replication = match config.replicate {
+        Some(ref remote) if !config.dry_run => {
+            note!("{}{}", label("Replicating to: ", "复制到："), style(remote).blue());
+            let spinner = new_spinner(tr("Replicating snapshot...", "正在复制快照..."));
+            let report = replicate_snapshot(&final_dest_path, remote, &timestamp_folder_name);
+            spinner.finish_and_clear();
+            Some(report)
+        }
+        Some(ref remote) => {
+            note!(
+                "{} {}",
+                style(tr("Dry run:", "试运行：")).yellow(),
+                trf!("Would replicate the snapshot to {}", "将把快照复制到 {}", remote)
+            );
+            None
+        }
+        None => None,
+    };
 
     // 打印备份统计信息
-    println!("{}", style("----------------------------------------").dim());
-    println!("{}", style("Backup Completed Successfully!").green().bold());
-    println!("Total Files:     {}", stats.total_files);
-    println!("Copied (New):    {}", style(stats.copied_new).green());
-    println!("Copied (Mod):    {}", style(stats.copied_modified).yellow());
-    println!("Hard Linked:     {}", style(stats.linked).dim());
-    println!("Skipped:         {}", style(stats.skipped).red());
-    println!("Failed:          {}", style(stats.failed).red().bold());
-    println!("Data Transferred: {}", style(format_bytes(stats.bytes_copied)).cyan());
-    println!(
-        "Total Duration:   {}",
+    print_backup_summary(args, config.dry_run, &stats, resume, used_vss, replication.as_ref(), start_time.elapsed().as_secs());
+
+    // 配置文件设置了保留数量时，清理超出的旧快照
+    if let (Some(keep), false) = (config.keep, config.dry_run) {
+        status!("{}", style("----------------------------------------").dim());
+        if let Err(e) = prune_backups(&config.destination, keep, false, &[], &config.naming, &ConsoleSink::new()) {
+            warning!("{} {:#}", style(tr("Warning:", "警告：")).yellow(), e);
+        }
+    }
+
+    let replication_failed = replication.as_ref().is_some_and(ReplicationReport::failed);
+    let status = if stats.failed == 0 && !replication_failed { "ok" } else { "partial" };
+    progress::emit(&ProgressEvent::Finished { status, stats: &stats });
+
+    Ok(BackupOutcome {
+        status,
+        source: config.source,
+        destination: config.destination,
+        snapshot: timestamp_folder_name,
+        dry_run: config.dry_run,
+        duration_secs: start_time.elapsed().as_secs(),
+        stats,
+        replication,
+    })
+}
+
+/// 打印备份汇总的标题行：试运行、有文件失败时不显示为成功
+fn print_completion_banner(dry_run: bool, failed: u64) {
+    if dry_run {
+        status!("{}", style(tr("Dry Run Completed (no changes were made)", "试运行完成（未做任何更改）")).yellow().bold());
+    } else if failed > 0 {
+        status!(
+            "{}",
+            style(trf!("Backup Completed with {} Failure(s)", "备份完成，但有 {} 个文件失败", failed)).yellow().bold()
+        );
+    } else {
+        status!("{}", style(tr("Backup Completed Successfully!", "备份成功完成！")).green().bold());
+    }
+}
+
+/// 打印本地备份的统计信息、锁定和链接降级的提示，以及异地复制的结果
+#[allow(clippy::too_many_arguments)]
+fn print_backup_summary(
+    args: &Args,
+    dry_run: bool,
+    stats: &BackupStats,
+    resume: bool,
+    used_vss: bool,
+    replication: Option<&ReplicationReport>,
+    elapsed_secs: u64,
+) {
+    status!("{}", style("----------------------------------------").dim());
+    print_completion_banner(dry_run, stats.failed);
+    status!("{}{}", label("Total Files:     ", "文件总数："), stats.total_files);
+    status!("{}{}", label("Copied (New):    ", "复制（新增）："), style(stats.copied_new).green());
+    status!("{}{}", label("Copied (Mod):    ", "复制（修改）："), style(stats.copied_modified).yellow());
+    status!("{}{}", label("Cloned (Mod):    ", "克隆（修改）："), style(stats.cloned).yellow());
+    status!(
+        "{}{} ({})",
+        label("Hard Linked:     ", "硬链接："),
+        style(stats.linked).dim(),
+        format_bytes(stats.bytes_linked)
+    );
+    print_deduplication(stats.bytes_linked, stats.bytes_copied);
+    status!("{}{}", label("Link Fallbacks:  ", "链接降级："), style(stats.link_fallbacks).yellow());
+    if stats.symlinks > 0 {
+        status!("{}{}", label("Symlinks:        ", "符号链接："), stats.symlinks);
+    }
+    status!("{}{}", label("Directories:     ", "目录："), stats.dirs_created);
+    status!("{}{}", label("Skipped:         ", "跳过："), style(stats.skipped).red());
+    if stats.excluded > 0 {
+        status!("{}{}", label("Excluded:        ", "已排除："), style(stats.excluded).dim());
+    }
+    if resume {
+        status!("{}{}", label("Resumed:         ", "续传保留："), style(stats.resumed).dim());
+    }
+    status!("{}{}", label("Failed:          ", "失败："), style(stats.failed).red().bold());
+    if stats.locked > 0 {
+        status!("{}{}", label("Locked:          ", "锁定："), style(stats.locked).red());
+    }
+    if stats.vanished > 0 {
+        status!("{}{}", label("Vanished:        ", "已消失："), style(stats.vanished).dim());
+    }
+    status!(
+        "{}{} ({}/s)",
+        label("Data Transferred: ", "传输数据："),
+        style(format_bytes(stats.bytes_copied)).cyan(),
+        format_bytes(stats.bytes_per_sec)
+    );
+    if !dry_run {
+        print_throughput(stats);
+    }
+    print_top_directories(stats, args.backup.top_dirs);
+    print_top_extensions(stats, args.backup.top_types);
+    if let Some(report) = replication {
+        if report.failed() {
+            status!("{}{}", label("Replicated:       ", "异地复制："), style(tr("FAILED", "失败")).red().bold());
+        } else {
+            status!(
+                "{}{}",
+                label("Replicated:       ", "异地复制："),
+                style(trf!(
+                    "{} file(s), {} in {}",
+                    "{} 个文件，{}，耗时 {}",
+                    report.transfers,
+                    format_bytes(report.bytes),
+                    format_duration(report.elapsed_secs as u64)
+                ))
+                .cyan()
+            );
+        }
+    }
+    status!(
+        "{}{}",
+        label("Total Duration:   ", "总耗时："),
+        style(format_duration(elapsed_secs)).bold()
+    );
+
+    if !stats.locked_files.is_empty() {
+        // 已经使用了卷影副本时 --vss 无济于事，只能关闭占用文件的程序
+        let hint = if used_vss {
+            trf!(
+                "{} file(s) were locked even in the shadow copy, close the programs using them and retry:",
+                "{} 个文件在卷影副本中仍被锁定，请关闭占用这些文件的程序后重试：",
+                stats.locked_files.len()
+            )
+        } else {
+            trf!(
+                "{} file(s) were locked by other processes, retry with --vss to back them up:",
+                "{} 个文件被其他进程锁定，可使用 --vss 重试以备份这些文件：",
+                stats.locked_files.len()
+            )
+        };
+        status!("{} {}", style(tr("Note:", "提示：")).yellow(), hint);
+        for path in &stats.locked_files {
+            note!("  {}", path.display());
+        }
+    }
+
+    if stats.link_fallbacks > 0 {
+        status!(
+            "{} {}",
+            style(tr("Note:", "提示：")).yellow(),
+            trf!(
+                "{} file(s) were copied because hard links could not be created at the destination.",
+                "{} 个文件因目标位置无法创建硬链接而改为复制。",
+                stats.link_fallbacks
+            )
+        );
+    }
+
+    if let Some(ReplicationReport { error: Some(e), remote, .. }) = replication {
+        warning!(
+            "{} {}",
+            style(tr("Error:", "错误：")).red(),
+            trf!("Replication to {} failed: {}", "复制到 {} 失败：{}", remote, e)
+        );
+    }
+}
+
+/// 备份到远程目标（S3 分块仓库、WebDAV）
+///
+/// 扫描和同步决策与普通备份相同；远程目标没有可供本地比较的快照目录，
+/// 由写入后端根据上一个快照的索引判断文件是否需要读取和上传。
+///
+/// # 参数
+/// * `config` - 备份配置（目标为远程地址）
+/// * `use_vss` - 是否请求了 VSS（远程备份不支持，仅给出警告）
+fn execute_remote_backup(config: BackupConfig, use_vss: bool) -> Result<BackupOutcome> {
+    let start_time = std::time::Instant::now();
+    let snapshot = config.naming.format_name(Local::now());
+
+    note!("{}", style(format!("Recall Backup Tool v{}", env!("CARGO_PKG_VERSION"))).cyan().bold());
+    note!("{}{:?}", label("Source: ", "源路径："), style(&config.source).blue());
+    note!("{}{}", label("Dest:   ", "目标："), style(config.destination.display()).blue());
+    note!("{}{}", label("Time:   ", "时间："), style(&snapshot).yellow());
+    note!("{}", style("----------------------------------------").dim());
+
+    if use_vss {
+        warning!(
+            "{} {}",
+            style(tr("Warning:", "警告：")).yellow(),
+            tr(
+                "VSS is not supported for remote destinations, backing up live files",
+                "远程目标不支持 VSS，将直接备份当前文件"
+            )
+        );
+    }
+
+    // 上一个快照的索引用于跳过未变化的文件
+    let writer = open_writer(&config, &snapshot)?;
+    match writer.previous() {
+        Some(name) => {
+            note!("{}{:?}", label("Found previous backup: ", "找到上一次备份："), style(name).green());
+        }
+        None => {
+            note!("{}", style(tr("Performing initial full backup...", "正在执行首次完整备份...")).yellow());
+        }
+    }
+
+    // 上传以网络为瓶颈，自动模式下使用固定的并发数
+    let workers = if config.workers == 0 {
+        note!("{}", trf!("Workers: {} (auto)", "工作线程：{}（自动）", REPOSITORY_WORKERS));
+        REPOSITORY_WORKERS
+    } else {
+        config.workers
+    };
+
+    progress::start(&config.source, &config.destination, &snapshot);
+    let (tx, rx) = crossbeam_channel::bounded(1000);
+    let config_for_scanner = config.clone();
+    let events = Arc::new(ConsoleSink::with_progress());
+    let scanner_events = events.clone();
+    let scanner_handle = thread::spawn(move || {
+        scan_source(&config_for_scanner, std::path::Path::new(""), None, tx, &*scanner_events)
+    });
+
+    let executor = BackupExecutor::with_backend(Box::new(writer.clone())).with_events(events);
+    let mut stats = executor.execute(rx, workers)?;
+
+    match scanner_handle.join().unwrap() {
+        Ok(scan) => {
+            stats.excluded = scan.excluded;
+            stats.durations.scan = scan.elapsed.as_secs_f64();
+        }
+        Err(e) => {
+            warning!(
+                "{} {}",
+                style(tr("Error:", "错误：")).red(),
+                trf!("Scanner failed: {}", "扫描失败：{}", e)
+            );
+            return Err(e.into());
+        }
+    }
+
+    if !config.dry_run {
+        let spinner = new_spinner(tr("Finalizing backup...", "正在完成备份..."));
+        writer.commit(&snapshot)?;
+        spinner.finish_and_clear();
+    } else {
+        note!(
+            "{} {}",
+            style(tr("Dry run:", "试运行：")).yellow(),
+            trf!("Would write snapshot index {}", "将写入快照索引 {}", snapshot)
+        );
+    }
+
+    status!("{}", style("----------------------------------------").dim());
+    print_completion_banner(config.dry_run, stats.failed);
+    status!("{}{}", label("Total Files:     ", "文件总数："), stats.total_files);
+    status!(
+        "{}{}",
+        label("Uploaded:        ", "已上传："),
+        style(stats.copied_new + stats.copied_modified).green()
+    );
+    status!("{}{}", label("Unchanged:       ", "未变化："), style(stats.linked).dim());
+    status!("{}{}", label("Failed:          ", "失败："), style(stats.failed).red().bold());
+    status!("{}{}", label("Data Uploaded:    ", "上传数据："), style(format_bytes(stats.bytes_copied)).cyan());
+    status!(
+        "{}{}",
+        label("Total Duration:   ", "总耗时："),
         style(format_duration(start_time.elapsed().as_secs())).bold()
     );
 
-    Ok(())
+    if config.keep.is_some() {
+        note!(
+            "{} {}",
+            style(tr("Note:", "提示：")).yellow(),
+            tr(
+                "Pruning is not supported for remote destinations yet, `keep` is ignored",
+                "远程目标暂不支持清理旧快照，已忽略 `keep`"
+            )
+        );
+    }
+    if config.replicate.is_some() {
+        note!(
+            "{} {}",
+            style(tr("Note:", "提示：")).yellow(),
+            tr(
+                "Replication is only supported for local destinations, `replicate` is ignored",
+                "只有本地目标支持异地复制，已忽略 `replicate`"
+            )
+        );
+    }
+
+    let status = if stats.failed == 0 { "ok" } else { "partial" };
+    progress::emit(&ProgressEvent::Finished { status, stats: &stats });
+
+    Ok(BackupOutcome {
+        status,
+        source: config.source,
+        destination: config.destination,
+        snapshot,
+        dry_run: config.dry_run,
+        duration_secs: start_time.elapsed().as_secs(),
+        stats,
+        replication: None,
+    })
 }
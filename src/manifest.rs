@@ -0,0 +1,319 @@
+// Recall - 快照清单模块
+// 记录快照中每个文件的大小、修改时间和 XXH3 哈希值，用于比较快照和检查数据是否损坏；
+// 清单本身带有校验和（可选 HMAC 签名），被截断或篡改时读取会失败
+
+use crate::hasher::calculate_snapshot_hash;
+use crate::summary::{BackupSummary, MANIFEST_FILE, SUMMARY_FILE};
+use crate::utils::{file_identity, to_verbatim_path};
+use anyhow::{Context, Result};
+use filetime::FileTime;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// 当前清单格式的版本（带校验和），写入快照摘要的 `manifest` 字段
+pub const MANIFEST_VERSION: u32 = 2;
+
+/// 清单文件的首行（格式标识和版本）
+const HEADER: &str = "# recall manifest v2";
+
+/// 没有校验和的旧版清单的首行
+const LEGACY_HEADER: &str = "# recall manifest v1";
+
+/// 校验和行的前缀（清单的最后一行，覆盖之前的全部内容）
+const CHECKSUM_PREFIX: &str = "# xxh3 ";
+
+/// 签名行的前缀（位于校验和行之前，覆盖首行和全部文件行）
+const HMAC_PREFIX: &str = "# hmac-sha256 ";
+
+/// 未指定密钥文件时读取签名密钥的环境变量
+pub const MANIFEST_KEY_ENV: &str = "RECALL_MANIFEST_KEY";
+
+/// 清单的完整性检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Integrity {
+    /// 旧版清单，没有校验和，无法检查
+    Unchecked,
+
+    /// 校验和一致（清单没有签名，或没有提供密钥而未检查签名）
+    Checksum,
+
+    /// 校验和与 HMAC 签名都一致
+    Signed,
+}
+
+/// 清单中的单个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// 相对于快照目录的路径（`/` 分隔）
+    pub path: String,
+
+    /// 文件大小（字节）
+    pub size: u64,
+
+    /// 修改时间（Unix 秒）
+    pub mtime: i64,
+
+    /// 内容的 XXH3 哈希值
+    pub hash: u64,
+}
+
+/// 快照清单
+///
+/// 以文本形式保存在快照根部的 [`MANIFEST_FILE`] 中，每行一个文件：
+/// `哈希值<TAB>大小<TAB>修改时间<TAB>路径`，按路径排序。
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    /// 所有文件（按路径排序）
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// 计算快照目录的清单
+    ///
+    /// 同一文件的多个硬链接只读取一次：`cache` 以文件标识为键保存已计算的哈希值，
+    /// 为多个共享数据的快照生成清单时传入同一个缓存。
+    ///
+    /// # 参数
+    /// * `snapshot` - 快照目录
+    /// * `cache` - 文件标识到哈希值的缓存
+    ///
+    /// # 返回
+    /// * `Ok(Manifest)` - 清单
+    /// * `Err(anyhow::Error)` - 读取文件失败
+    pub fn build(snapshot: &Path, cache: &mut HashMap<(u64, u64), u64>) -> Result<Self> {
+        let snapshot = &to_verbatim_path(snapshot);
+        let mut entries = Vec::new();
+        for entry in WalkDir::new(snapshot).follow_links(false).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let rel = entry.path().strip_prefix(snapshot)?;
+            if rel == Path::new(SUMMARY_FILE) || rel == Path::new(MANIFEST_FILE) {
+                continue;
+            }
+
+            let meta = entry.metadata()?;
+            let hash = match file_identity(entry.path()) {
+                Some(id) => match cache.get(&id) {
+                    Some(hash) => *hash,
+                    None => *cache.entry(id).or_insert(calculate_snapshot_hash(entry.path())?),
+                },
+                None => calculate_snapshot_hash(entry.path())?,
+            };
+            entries.push(ManifestEntry {
+                path: rel.to_string_lossy().replace('\\', "/"),
+                size: meta.len(),
+                mtime: FileTime::from_last_modification_time(&meta).unix_seconds(),
+                hash,
+            });
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Self { entries })
+    }
+
+    /// 将清单写入快照目录
+    ///
+    /// 末尾写入覆盖全部内容的 XXH3 校验和；提供密钥时在校验和之前写入 HMAC-SHA256 签名，
+    /// 没有密钥的人修改清单后无法重新生成签名。
+    ///
+    /// # 参数
+    /// * `snapshot` - 快照目录
+    /// * `key` - 签名密钥（见 [`read_key`]）
+    pub fn save(&self, snapshot: &Path, key: Option<&[u8]>) -> Result<()> {
+        let path = snapshot.join(MANIFEST_FILE);
+        fs::write(&path, self.to_text(key)).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// 生成清单文件的内容
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::manifest::{Integrity, Manifest};
+    ///
+    /// let text = Manifest::default().to_text(Some(b"secret"));
+    /// assert_eq!(Manifest::parse_with_key(&text, Some(b"secret")).unwrap().1, Integrity::Signed);
+    /// assert!(Manifest::parse_with_key(&text, Some(b"other")).is_err());
+    ///
+    /// // 截断的清单没有校验和行
+    /// assert!(Manifest::parse(&text[..text.len() - 10]).is_err());
+    /// ```
+    pub fn to_text(&self, key: Option<&[u8]>) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for e in &self.entries {
+            let _ = writeln!(text, "{:016x}\t{}\t{}\t{}", e.hash, e.size, e.mtime, e.path);
+        }
+        if let Some(key) = key {
+            let _ = writeln!(text, "{}{}", HMAC_PREFIX, hex::encode(sign(key, &text).finalize().into_bytes()));
+        }
+        let _ = writeln!(text, "{}{:016x}", CHECKSUM_PREFIX, xxh3_64(text.as_bytes()));
+        text
+    }
+
+    /// 读取快照目录中的清单
+    ///
+    /// # 返回
+    /// * `Ok(Some(Manifest))` - 清单
+    /// * `Ok(None)` - 快照中没有清单
+    /// * `Err(anyhow::Error)` - 清单存在但无法读取、格式错误或校验和不符
+    pub fn load(snapshot: &Path) -> Result<Option<Self>> {
+        Ok(Self::load_with_key(snapshot, None)?.map(|(manifest, _)| manifest))
+    }
+
+    /// 读取快照目录中的清单并检查签名
+    ///
+    /// # 参数
+    /// * `snapshot` - 快照目录
+    /// * `key` - 签名密钥，提供时清单必须带有与之匹配的签名
+    ///
+    /// # 返回
+    /// * `Ok(Some((Manifest, Integrity)))` - 清单和完整性检查结果
+    /// * `Ok(None)` - 快照中没有清单
+    /// * `Err(anyhow::Error)` - 清单存在但无法读取、格式错误、校验和或签名不符，
+    ///   或快照摘要记录写入了带校验和的清单而现在是旧版清单
+    pub fn load_with_key(snapshot: &Path, key: Option<&[u8]>) -> Result<Option<(Self, Integrity)>> {
+        let path = snapshot.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        let parsed = Self::parse_with_key(&content, key).with_context(|| format!("Invalid manifest {:?}", path))?;
+
+        // 快照创建时写入的是带校验和的清单，现在却是旧版格式：校验和行被删掉或清单被替换
+        if parsed.1 == Integrity::Unchecked {
+            let written = BackupSummary::load(snapshot).ok().flatten().and_then(|summary| summary.manifest);
+            if written.is_some_and(|version| version >= MANIFEST_VERSION) {
+                anyhow::bail!(
+                    "Invalid manifest {:?}: the snapshot was created with a checksummed manifest but it now has none, it was downgraded or replaced",
+                    path
+                );
+            }
+        }
+        Ok(Some(parsed))
+    }
+
+    /// 解析清单文本（如从归档中读取的清单）
+    ///
+    /// # 参数
+    /// * `content` - 清单文件的内容
+    ///
+    /// # 返回
+    /// * `Ok(Manifest)` - 清单
+    /// * `Err(anyhow::Error)` - 格式错误或校验和不符
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(Self::parse_with_key(content, None)?.0)
+    }
+
+    /// 解析清单文本，检查校验和，提供密钥时检查签名
+    ///
+    /// 旧版（v1）清单没有校验和，不提供密钥时照常读取，结果为 [`Integrity::Unchecked`]。
+    ///
+    /// # 参数
+    /// * `content` - 清单文件的内容
+    /// * `key` - 签名密钥
+    ///
+    /// # 返回
+    /// * `Ok((Manifest, Integrity))` - 清单和完整性检查结果
+    /// * `Err(anyhow::Error)` - 格式错误、清单被截断或修改，或签名缺失、不符
+    pub fn parse_with_key(content: &str, key: Option<&[u8]>) -> Result<(Self, Integrity)> {
+        let (body, integrity) = match content.lines().next() {
+            Some(HEADER) => check_integrity(content, key)?,
+            Some(LEGACY_HEADER) if key.is_some() => anyhow::bail!("Manifest is not signed (created by an older version)"),
+            Some(LEGACY_HEADER) => (content, Integrity::Unchecked),
+            _ => anyhow::bail!("Not a recall manifest"),
+        };
+
+        let mut entries = Vec::new();
+        for (number, line) in body.lines().skip(1).enumerate() {
+            let parse = || -> Option<ManifestEntry> {
+                let mut fields = line.splitn(4, '\t');
+                Some(ManifestEntry {
+                    hash: u64::from_str_radix(fields.next()?, 16).ok()?,
+                    size: fields.next()?.parse().ok()?,
+                    mtime: fields.next()?.parse().ok()?,
+                    path: fields.next()?.to_string(),
+                })
+            };
+            entries.push(parse().with_context(|| format!("Malformed line {}", number + 2))?);
+        }
+        Ok((Self { entries }, integrity))
+    }
+}
+
+/// 检查清单的校验和与签名
+///
+/// # 返回
+/// * `Ok((&str, Integrity))` - 去掉校验和行与签名行后的内容，以及检查结果
+/// * `Err(anyhow::Error)` - 校验和或签名缺失、不符
+fn check_integrity<'a>(content: &'a str, key: Option<&[u8]>) -> Result<(&'a str, Integrity)> {
+    let (rest, checksum) = split_last_line(content);
+    let checksum = checksum
+        .strip_prefix(CHECKSUM_PREFIX)
+        .context("Manifest has no checksum, it may be truncated")?;
+    if u64::from_str_radix(checksum, 16).ok() != Some(xxh3_64(rest.as_bytes())) {
+        anyhow::bail!("Manifest checksum does not match, it was modified or damaged");
+    }
+
+    let (body, signature) = match split_last_line(rest) {
+        (body, line) if line.starts_with(HMAC_PREFIX) => (body, Some(&line[HMAC_PREFIX.len()..])),
+        _ => (rest, None),
+    };
+    let Some(key) = key else {
+        return Ok((body, Integrity::Checksum));
+    };
+    let signature = signature.context("Manifest is not signed")?;
+    let signature = hex::decode(signature).context("Malformed manifest signature")?;
+    sign(key, body)
+        .verify_slice(&signature)
+        .map_err(|_| anyhow::anyhow!("Manifest signature does not match the key, it was modified"))?;
+    Ok((body, Integrity::Signed))
+}
+
+/// 拆分出最后一行
+///
+/// # 返回
+/// * `(&str, &str)` - 最后一行之前的内容（含换行符）和最后一行（不含换行符）
+fn split_last_line(content: &str) -> (&str, &str) {
+    let trimmed = content.strip_suffix('\n').unwrap_or(content);
+    match trimmed.rfind('\n') {
+        Some(pos) => (&content[..=pos], &trimmed[pos + 1..]),
+        None => ("", trimmed),
+    }
+}
+
+/// 以密钥计算清单内容的 HMAC-SHA256
+fn sign(key: &[u8], content: &str) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(content.as_bytes());
+    mac
+}
+
+/// 读取清单签名密钥
+///
+/// 依次使用密钥文件的内容和环境变量 [`MANIFEST_KEY_ENV`]。
+///
+/// # 参数
+/// * `keyfile` - 密钥文件
+///
+/// # 返回
+/// * `Ok(Some(Vec<u8>))` - 密钥
+/// * `Ok(None)` - 没有指定密钥
+/// * `Err(anyhow::Error)` - 密钥文件无法读取或为空
+pub fn read_key(keyfile: Option<&Path>) -> Result<Option<Vec<u8>>> {
+    if let Some(path) = keyfile {
+        let key = fs::read(path).with_context(|| format!("Failed to read key file {:?}", path))?;
+        if key.is_empty() {
+            anyhow::bail!("Key file {:?} is empty", path);
+        }
+        return Ok(Some(key));
+    }
+    Ok(std::env::var(MANIFEST_KEY_ENV).ok().filter(|key| !key.is_empty()).map(String::into_bytes))
+}
@@ -0,0 +1,131 @@
+// Recall - 文件元数据保留模块
+// 负责在 Unix 平台上保留文件所有者（uid/gid）和扩展属性（xattr）
+
+use anyhow::Result;
+use std::path::Path;
+
+#[cfg(unix)]
+use anyhow::Context;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+/// 将源文件的所有者和扩展属性复制到目标文件
+///
+/// 所有者（uid/gid）只有在以 root 身份运行时才能修改，
+/// 权限不足时会静默跳过，不视为错误。`security.*`、`system.*` 和 `trusted.*` 扩展属性
+/// 同样需要特权，没有权限或目标不支持时跳过（记录调试日志）。
+///
+/// # 参数
+/// * `src` - 源文件路径
+/// * `dest` - 目标文件路径
+///
+/// # 返回
+/// * `Ok(())` - 元数据复制成功
+/// * `Err(anyhow::Error)` - 读取或写入扩展属性失败
+#[cfg(unix)]
+pub fn preserve_metadata(src: &Path, dest: &Path) -> Result<()> {
+    let src_meta = std::fs::symlink_metadata(src)
+        .with_context(|| format!("Failed to read metadata of {:?}", src))?;
+
+    // 复制所有者；非 root 用户通常没有 chown 权限
+    if let Err(e) = std::os::unix::fs::lchown(dest, Some(src_meta.uid()), Some(src_meta.gid())) {
+        if e.kind() != std::io::ErrorKind::PermissionDenied {
+            return Err(e).with_context(|| format!("Failed to set owner for {:?}", dest));
+        }
+    }
+
+    // 复制扩展属性
+    let names = match xattr::list(src) {
+        Ok(names) => names,
+        // 文件系统不支持扩展属性时直接跳过
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to list xattrs of {:?}", src)),
+    };
+
+    for name in names {
+        let value = match xattr::get(src, &name) {
+            Ok(Some(value)) => value,
+            Ok(None) => continue,
+            Err(e) if is_privileged_xattr(&name) && is_xattr_denied(&e) => {
+                tracing::debug!(name = ?name, path = %src.display(), error = %e, "skipped xattr");
+                continue;
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to read xattr {:?} of {:?}", name, src)),
+        };
+        match xattr::set(dest, &name, &value) {
+            Ok(()) => {}
+            // security.* / system.* 通常只有 root 才能写入，或目标文件系统不支持；跳过这些属性，不让文件失败
+            Err(e) if is_privileged_xattr(&name) && is_xattr_denied(&e) => {
+                tracing::debug!(name = ?name, path = %dest.display(), error = %e, "skipped xattr");
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to set xattr {:?} on {:?}", name, dest)),
+        }
+    }
+
+    Ok(())
+}
+
+/// 扩展属性是否属于需要特权的命名空间（`security.*`、`system.*`、`trusted.*`）
+#[cfg(unix)]
+fn is_privileged_xattr(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    ["security.", "system.", "trusted."].iter().any(|ns| name.starts_with(ns))
+}
+
+/// 错误是否表示没有权限或文件系统不支持该扩展属性
+#[cfg(unix)]
+fn is_xattr_denied(err: &std::io::Error) -> bool {
+    matches!(err.kind(), std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::Unsupported)
+        || err.raw_os_error().is_some_and(|code| {
+            [libc::EPERM, libc::EACCES, libc::ENOTSUP, libc::EOPNOTSUPP].contains(&code)
+        })
+}
+
+/// 在非 Unix 平台上，所有者和扩展属性的保留不适用
+#[cfg(not(unix))]
+pub fn preserve_metadata(_src: &Path, _dest: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// 检查两个文件的所有者或扩展属性是否不同
+///
+/// # 参数
+/// * `src` - 源文件路径
+/// * `old` - 上一次备份中的文件路径
+///
+/// # 返回
+/// * `true` - 所有者或扩展属性不同（或无法读取）
+/// * `false` - 元数据一致
+#[cfg(unix)]
+pub fn metadata_differs(src: &Path, old: &Path) -> bool {
+    let (src_meta, old_meta) = match (std::fs::symlink_metadata(src), std::fs::symlink_metadata(old)) {
+        (Ok(s), Ok(o)) => (s, o),
+        _ => return true,
+    };
+
+    if src_meta.uid() != old_meta.uid() || src_meta.gid() != old_meta.gid() {
+        return true;
+    }
+
+    read_xattrs(src) != read_xattrs(old)
+}
+
+/// 在非 Unix 平台上，始终认为元数据一致
+#[cfg(not(unix))]
+pub fn metadata_differs(_src: &Path, _old: &Path) -> bool {
+    false
+}
+
+/// 读取文件的所有扩展属性，按名称排序以便比较
+#[cfg(unix)]
+fn read_xattrs(path: &Path) -> Option<Vec<(std::ffi::OsString, Vec<u8>)>> {
+    let mut attrs: Vec<_> = xattr::list(path)
+        .ok()?
+        .filter_map(|name| {
+            let value = xattr::get(path, &name).ok()??;
+            Some((name, value))
+        })
+        .collect();
+    attrs.sort();
+    Some(attrs)
+}
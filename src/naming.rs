@@ -0,0 +1,182 @@
+// Recall - 快照命名模块
+// 负责生成和解析快照目录名称（可配置的时间戳格式，本地时间或 UTC）
+
+use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 默认的快照目录名称格式（如 `2024-01-15_10-30-00`）
+pub const DEFAULT_SNAPSHOT_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// 未完成备份目录的后缀
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+/// 有文件失败、按 [`FailurePolicy::Incomplete`](crate::session::FailurePolicy::Incomplete) 提交的快照目录的后缀
+pub const INCOMPLETE_SUFFIX: &str = "-incomplete";
+
+/// 快照目录名称是否带有 [`INCOMPLETE_SUFFIX`]（有文件在备份时失败，内容不完整）
+///
+/// # 示例
+/// ```
+/// use recall::naming::is_incomplete;
+/// assert!(is_incomplete("2024-01-15_10-30-00-incomplete"));
+/// assert!(!is_incomplete("2024-01-15_10-30-00"));
+/// ```
+pub fn is_incomplete(name: &str) -> bool {
+    name.ends_with(INCOMPLETE_SUFFIX)
+}
+
+/// 快照命名规则
+///
+/// 新快照按配置的格式命名；识别已有快照时同时接受默认格式，
+/// 修改格式后旧快照仍可被查找、清理和用作硬链接基准。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotNaming {
+    /// 时间戳格式（strftime 风格）
+    format: String,
+
+    /// 是否使用 UTC 时间（否则使用本地时间）
+    utc: bool,
+}
+
+impl Default for SnapshotNaming {
+    fn default() -> Self {
+        Self {
+            format: DEFAULT_SNAPSHOT_FORMAT.to_string(),
+            utc: false,
+        }
+    }
+}
+
+impl SnapshotNaming {
+    /// 创建快照命名规则
+    ///
+    /// 格式必须包含完整的年月日时分秒（可以从名称还原出时间），
+    /// 且生成的名称不能包含路径分隔符或 `:`。
+    ///
+    /// # 参数
+    /// * `format` - 时间戳格式，`None` 表示使用默认格式
+    /// * `utc` - 是否使用 UTC 时间
+    ///
+    /// # 返回
+    /// * `Ok(SnapshotNaming)` - 命名规则
+    /// * `Err(anyhow::Error)` - 格式无效
+    pub fn new(format: Option<&str>, utc: bool) -> Result<Self> {
+        let format = format.unwrap_or(DEFAULT_SNAPSHOT_FORMAT);
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            anyhow::bail!("Invalid snapshot name format {:?}", format);
+        }
+
+        // 用一个各字段都不同的时间验证格式能够完整往返
+        let sample = NaiveDate::from_ymd_opt(2001, 12, 23)
+            .and_then(|d| d.and_hms_opt(14, 35, 46))
+            .context("Invalid sample time")?;
+        let mut name = String::new();
+        write!(name, "{}", sample.format(format))
+            .map_err(|_| anyhow::anyhow!("Invalid snapshot name format {:?}", format))?;
+        if name.is_empty()
+            || name.contains(['/', '\\', ':'])
+            || name.ends_with(PARTIAL_SUFFIX)
+            || is_incomplete(&name)
+        {
+            anyhow::bail!("Snapshot name format {:?} produces an invalid folder name {:?}", format, name);
+        }
+        if NaiveDateTime::parse_from_str(&name, format).ok() != Some(sample) {
+            anyhow::bail!(
+                "Snapshot name format {:?} must contain the full date and time (year, month, day, hour, minute, second)",
+                format
+            );
+        }
+
+        Ok(Self {
+            format: format.to_string(),
+            utc,
+        })
+    }
+
+    /// 时间戳格式
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// 是否使用 UTC 时间
+    pub fn is_utc(&self) -> bool {
+        self.utc
+    }
+
+    /// 生成指定时间的快照目录名称
+    pub fn format_name(&self, time: DateTime<Local>) -> String {
+        if self.utc {
+            time.with_timezone(&Utc).format(&self.format).to_string()
+        } else {
+            time.format(&self.format).to_string()
+        }
+    }
+
+    /// 生成比指定时间晚一秒的快照目录名称
+    ///
+    /// 系统时钟落后于最新快照时使用，保证新快照排在最新快照之后。
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::naming::SnapshotNaming;
+    ///
+    /// let naming = SnapshotNaming::default();
+    /// let latest = naming.parse("2024-01-15_10-30-59").unwrap();
+    /// assert_eq!(naming.name_after(latest), "2024-01-15_10-31-00");
+    /// ```
+    pub fn name_after(&self, time: NaiveDateTime) -> String {
+        (time + chrono::Duration::seconds(1)).format(&self.format).to_string()
+    }
+
+    /// 解析快照目录名称中的时间
+    ///
+    /// 先按配置的格式解析，失败时按默认格式解析。名称末尾的 [`INCOMPLETE_SUFFIX`] 会被忽略。
+    ///
+    /// # 返回
+    /// * `Some(NaiveDateTime)` - 名称中的时间
+    /// * `None` - 不是快照目录名称
+    pub fn parse(&self, name: &str) -> Option<NaiveDateTime> {
+        let name = name.strip_suffix(INCOMPLETE_SUFFIX).unwrap_or(name);
+        NaiveDateTime::parse_from_str(name, &self.format)
+            .or_else(|_| NaiveDateTime::parse_from_str(name, DEFAULT_SNAPSHOT_FORMAT))
+            .ok()
+    }
+
+    /// 列出目标目录中的快照目录（或 `.partial` 目录），按名称中的时间排序（最旧的在前）
+    ///
+    /// # 参数
+    /// * `destination` - 备份目标根目录
+    /// * `partial` - 为 `true` 时列出未完成的 `.partial` 目录，否则列出完整快照
+    ///
+    /// # 返回
+    /// * `Ok(Vec<(NaiveDateTime, PathBuf)>)` - 快照时间和路径
+    /// * `Err(anyhow::Error)` - 读取目录失败
+    pub fn list(&self, destination: &Path, partial: bool) -> Result<Vec<(NaiveDateTime, PathBuf)>> {
+        if !destination.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshots: Vec<(NaiveDateTime, PathBuf)> = fs::read_dir(destination)
+            .context("Cannot read destination directory")?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let stem = match name.strip_suffix(PARTIAL_SUFFIX) {
+                    Some(stem) if partial => stem,
+                    None if !partial => name,
+                    _ => return None,
+                };
+                Some((self.parse(stem)?, path))
+            })
+            .collect();
+
+        snapshots.sort();
+        Ok(snapshots)
+    }
+}
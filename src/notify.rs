@@ -0,0 +1,100 @@
+// Recall - 桌面通知模块
+// 在交互式桌面上用系统通知显示备份结果（Windows 通知中心、notify-send、macOS 通知中心），无需一直盯着控制台窗口
+
+use anyhow::{Context, Result};
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+
+/// 成功的备份至少运行这么久才显示通知（秒），失败时总是显示
+pub const MIN_DURATION_SECS: u64 = 30;
+
+/// 当前是否在交互式桌面会话中运行
+///
+/// 标准输出不是终端（计划任务、常驻进程、重定向）时不显示通知；
+/// Linux 上还要求存在图形会话（`DISPLAY` 或 `WAYLAND_DISPLAY`）。
+pub fn is_interactive_desktop() -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return false;
+    }
+    true
+}
+
+/// 显示一条桌面通知
+///
+/// 通过系统自带的命令显示：Windows 上由 PowerShell 调用通知中心，macOS 上为 `osascript`，
+/// 其他平台为 `notify-send`（libnotify）。
+///
+/// # 参数
+/// * `title` - 标题
+/// * `message` - 正文
+/// * `failed` - 是否为失败通知（Linux 上以紧急级别显示）
+///
+/// # 返回
+/// * `Ok(())` - 通知已发出
+/// * `Err(anyhow::Error)` - 通知命令不存在或执行失败
+pub fn show_notification(title: &str, message: &str, failed: bool) -> Result<()> {
+    let mut command = notification_command(title, message, failed);
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to show desktop notification")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to show desktop notification: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// 构造显示通知的命令（标题和正文通过环境变量传入，无需转义）
+#[cfg(windows)]
+fn notification_command(title: &str, message: &str, _failed: bool) -> Command {
+    // 使用 PowerShell 的应用标识，未安装的程序无法以自己的名义发送通知
+    const SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $template.GetElementsByTagName('text')
+$text.Item(0).AppendChild($template.CreateTextNode($env:RECALL_NOTIFY_TITLE)) | Out-Null
+$text.Item(1).AppendChild($template.CreateTextNode($env:RECALL_NOTIFY_MESSAGE)) | Out-Null
+$app = '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe'
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($app).Show([Windows.UI.Notifications.ToastNotification]::new($template))
+"#;
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("RECALL_NOTIFY_TITLE", title)
+        .env("RECALL_NOTIFY_MESSAGE", message);
+    command
+}
+
+/// 构造显示通知的命令（标题和正文通过环境变量传入，无需转义）
+#[cfg(target_os = "macos")]
+fn notification_command(title: &str, message: &str, _failed: bool) -> Command {
+    const SCRIPT: &str = r#"display notification (system attribute "RECALL_NOTIFY_MESSAGE") with title (system attribute "RECALL_NOTIFY_TITLE")"#;
+    let mut command = Command::new("osascript");
+    command
+        .args(["-e", SCRIPT])
+        .env("RECALL_NOTIFY_TITLE", title)
+        .env("RECALL_NOTIFY_MESSAGE", message);
+    command
+}
+
+/// 构造显示通知的命令
+#[cfg(not(any(windows, target_os = "macos")))]
+fn notification_command(title: &str, message: &str, failed: bool) -> Command {
+    let mut command = Command::new("notify-send");
+    command
+        .arg("--app-name=recall")
+        .arg(if failed { "--urgency=critical" } else { "--urgency=normal" })
+        .arg("--")
+        .arg(title)
+        .arg(message);
+    command
+}
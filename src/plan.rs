@@ -0,0 +1,291 @@
+// Recall - 备份计划模块
+// 试运行时扫描源目录，列出每个条目将执行的操作和预计的统计信息，供审阅、导出或确认后执行
+
+use crate::actions::{BackupStats, FileTask, SyncAction};
+use crate::config::BackupConfig;
+use crate::error::{Classify, RecallError, Result};
+use crate::events::EventSink;
+use crate::scanner::scan_source;
+use crate::utils::csv_field;
+use anyhow::Context;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 一次备份将执行的操作
+///
+/// 由 [`BackupSession::plan`](crate::session::BackupSession::plan) 生成，
+/// 审阅后可以交给 [`BackupSession::execute_plan`](crate::session::BackupSession::execute_plan) 执行，不需要重新扫描。
+#[derive(Debug, Clone, Default)]
+pub struct BackupPlan {
+    /// 每个条目及其操作（按相对路径排序）
+    pub entries: Vec<(FileTask, SyncAction)>,
+
+    /// 预计的统计信息（假设每个操作都成功；复制和克隆计入传输字节数）
+    pub totals: BackupStats,
+
+    /// 被排除模式跳过的条目（排除的目录只记录一次，按路径排序）
+    pub excluded: Vec<Exclusion>,
+}
+
+/// 被排除模式跳过的条目
+#[derive(Debug, Clone)]
+pub struct Exclusion {
+    /// 相对于源目录的路径
+    pub path: PathBuf,
+
+    /// 匹配的排除模式
+    pub pattern: String,
+}
+
+/// 导出的计划中的一行
+#[derive(Debug, Serialize)]
+struct PlanRow<'a> {
+    /// 相对于源目录的路径（`/` 分隔）
+    path: String,
+
+    /// 操作名称（见 [`SyncAction::name`]，被排除的条目为 `exclude`）
+    action: &'a str,
+
+    /// 文件大小（字节，目录和被排除的条目为 0）
+    size: u64,
+
+    /// 决定这个操作的原因
+    reason: String,
+}
+
+impl BackupPlan {
+    /// 扫描源目录并生成备份计划（不写入任何内容）
+    ///
+    /// # 参数
+    /// * `config` - 备份配置
+    /// * `current_backup_dir` - 当前备份的目标目录
+    /// * `latest_backup` - 最新备份路径（用于增量备份）
+    /// * `events` - 事件接收者（决定的操作、无法访问的路径、无效的排除模式）
+    ///
+    /// # 返回
+    /// * `Ok(BackupPlan)` - 备份计划
+    /// * `Err(RecallError)` - 扫描失败
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::actions::SyncAction;
+    /// use recall::config::BackupConfig;
+    /// use recall::events::NullSink;
+    /// use recall::plan::BackupPlan;
+    ///
+    /// let source = tempfile::tempdir().unwrap();
+    /// let backups = tempfile::tempdir().unwrap();
+    /// std::fs::write(source.path().join("a.txt"), "hello").unwrap();
+    ///
+    /// let config = BackupConfig::builder(source.path(), backups.path()).finish().unwrap();
+    /// let plan = BackupPlan::build(&config, &backups.path().join("snapshot"), None, &NullSink).unwrap();
+    /// let (task, action) = plan.entries.iter().find(|(t, _)| t.rel_path.ends_with("a.txt")).unwrap();
+    /// assert!(matches!(action, SyncAction::CopyNew));
+    /// assert_eq!(task.size, 5);
+    /// assert_eq!(plan.totals.bytes_copied, 5);
+    /// ```
+    pub fn build(
+        config: &BackupConfig,
+        current_backup_dir: &Path,
+        latest_backup: Option<&Path>,
+        events: &dyn EventSink,
+    ) -> Result<Self> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let recorder = ExclusionRecorder { inner: events, excluded: Mutex::default() };
+        let scan = scan_source(config, current_backup_dir, latest_backup, tx, &recorder)?;
+
+        let mut plan = Self::default();
+        plan.totals.excluded = scan.excluded;
+        plan.totals.durations.scan = scan.elapsed.as_secs_f64();
+        plan.totals.durations.hash = scan.hash_time.as_secs_f64();
+        for (task, action) in rx {
+            plan.push(task, action);
+        }
+        plan.entries.sort_by(|a, b| a.0.rel_path.cmp(&b.0.rel_path));
+        plan.excluded = recorder.excluded.into_inner().unwrap_or_default();
+        plan.excluded.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(plan)
+    }
+
+    /// 添加一个条目并更新预计的统计信息
+    ///
+    /// # 参数
+    /// * `task` - 文件任务
+    /// * `action` - 将执行的操作
+    pub fn push(&mut self, task: FileTask, action: SyncAction) {
+        let t = &mut self.totals;
+        match action {
+            SyncAction::CopyNew => {
+                t.copied_new += 1;
+                t.bytes_copied += task.size;
+                t.record_written(&task.rel_path, task.size);
+            }
+            SyncAction::CopyModified => {
+                t.copied_modified += 1;
+                t.bytes_copied += task.size;
+                t.record_written(&task.rel_path, task.size);
+            }
+            SyncAction::Clone(_) => {
+                t.cloned += 1;
+                t.bytes_copied += task.size;
+                t.record_written(&task.rel_path, task.size);
+            }
+            SyncAction::Link(_) => {
+                t.linked += 1;
+                t.bytes_linked += task.size;
+                t.record_linked(&task.rel_path, task.size);
+            }
+            SyncAction::MakeSymlink(_) => t.symlinks += 1,
+            SyncAction::CreateDir => t.dirs_created += 1,
+            SyncAction::Skip => t.skipped += 1,
+            SyncAction::Resumed => t.resumed += 1,
+        }
+        if !matches!(action, SyncAction::CreateDir) {
+            t.total_files += 1;
+        }
+        self.entries.push((task, action));
+    }
+
+    /// 计划中的条目数量（包括目录）
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 计划是否为空（源目录中没有需要处理的条目）
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 将计划导出为 CSV 或 JSON 文件
+    ///
+    /// 每个条目一行：路径、操作、大小和原因，被排除的条目以 `exclude` 操作列出并注明匹配的模式，
+    /// 便于在正式运行前核对新的排除模式的效果。文件扩展名为 `.json` 时导出 JSON 数组，否则导出 CSV。
+    ///
+    /// # 参数
+    /// * `path` - 导出文件
+    ///
+    /// # 返回
+    /// * `Ok(usize)` - 导出的行数
+    /// * `Err(RecallError)` - 写入失败
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::config::BackupConfig;
+    /// use recall::events::NullSink;
+    /// use recall::plan::BackupPlan;
+    ///
+    /// let source = tempfile::tempdir().unwrap();
+    /// let backups = tempfile::tempdir().unwrap();
+    /// std::fs::write(source.path().join("a.txt"), "hello").unwrap();
+    /// std::fs::write(source.path().join("b.tmp"), "").unwrap();
+    ///
+    /// let config = BackupConfig::builder(source.path(), backups.path()).exclude("*.tmp").finish().unwrap();
+    /// let plan = BackupPlan::build(&config, &backups.path().join("snapshot"), None, &NullSink).unwrap();
+    /// let out = backups.path().join("plan.csv");
+    /// plan.export(&out).unwrap();
+    ///
+    /// let csv = std::fs::read_to_string(&out).unwrap();
+    /// assert!(csv.starts_with("path,action,size,reason\n"));
+    /// assert!(csv.contains("a.txt,copy_new,5,"));
+    /// assert!(csv.contains("b.tmp,exclude,0,matches exclude pattern '*.tmp'"));
+    /// ```
+    pub fn export(&self, path: &Path) -> Result<usize> {
+        let rows = self.rows();
+        let json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let content = if json {
+            serde_json::to_string_pretty(&rows).classify(RecallError::Other)?
+        } else {
+            let mut csv = String::from("path,action,size,reason\n");
+            for row in &rows {
+                let _ = writeln!(csv, "{},{},{},{}", csv_field(&row.path), row.action, row.size, csv_field(&row.reason));
+            }
+            csv
+        };
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write {:?}", path))
+            .classify(RecallError::Other)?;
+        Ok(rows.len())
+    }
+
+    /// 计划中的每个条目（不包括源目录本身）和被排除的条目，按路径排序
+    fn rows(&self) -> Vec<PlanRow<'_>> {
+        let mut rows: Vec<PlanRow> = self
+            .entries
+            .iter()
+            .filter(|(task, _)| !task.rel_path.as_os_str().is_empty())
+            .map(|(task, action)| PlanRow {
+                path: task.rel_path.to_string_lossy().replace('\\', "/"),
+                action: action.name(),
+                size: if matches!(action, SyncAction::CreateDir) { 0 } else { task.size },
+                reason: reason(task, action),
+            })
+            .chain(self.excluded.iter().map(|e| PlanRow {
+                path: e.path.to_string_lossy().replace('\\', "/"),
+                action: "exclude",
+                size: 0,
+                reason: format!("matches exclude pattern '{}'", e.pattern),
+            }))
+            .collect();
+        rows.sort_by(|a, b| a.path.cmp(&b.path));
+        rows
+    }
+}
+
+/// 说明扫描器为什么为条目决定了这个操作
+///
+/// 已修改的文件与上一个快照中的文件比较大小和修改时间，指出是哪一项不同。
+fn reason(task: &FileTask, action: &SyncAction) -> String {
+    match action {
+        SyncAction::CreateDir => "directory".to_string(),
+        SyncAction::CopyNew if task.old_path.is_none() => "no previous snapshot".to_string(),
+        SyncAction::CopyNew => "not in the previous snapshot".to_string(),
+        SyncAction::CopyModified | SyncAction::Clone(_) => {
+            let old = task.old_path.as_ref().and_then(|p| fs::metadata(p).ok());
+            let src = fs::symlink_metadata(&task.src_path).ok();
+            match (old, src) {
+                (Some(old), _) if old.len() != task.size => format!("size changed ({} -> {})", old.len(), task.size),
+                (Some(old), Some(src)) if old.modified().ok() != src.modified().ok() => {
+                    "modification time changed".to_string()
+                }
+                _ => "content, permissions or metadata changed".to_string(),
+            }
+        }
+        SyncAction::Link(_) => "unchanged since the previous snapshot".to_string(),
+        SyncAction::MakeSymlink(target) => format!("symbolic link to {}", target.display()),
+        SyncAction::Skip => "cannot be read".to_string(),
+        SyncAction::Resumed => "already complete in the interrupted backup".to_string(),
+    }
+}
+
+/// 转发扫描事件，同时记录被排除的条目
+struct ExclusionRecorder<'a> {
+    /// 原来的事件接收者
+    inner: &'a dyn EventSink,
+
+    /// 被排除的条目
+    excluded: Mutex<Vec<Exclusion>>,
+}
+
+impl EventSink for ExclusionRecorder<'_> {
+    fn action_decided(&self, task: &FileTask, action: &SyncAction) {
+        self.inner.action_decided(task, action);
+    }
+
+    fn path_inaccessible(&self, path: Option<&Path>, error: &str) {
+        self.inner.path_inaccessible(path, error);
+    }
+
+    fn path_excluded(&self, path: &Path, pattern: &str) {
+        self.inner.path_excluded(path, pattern);
+        if let Ok(mut excluded) = self.excluded.lock() {
+            excluded.push(Exclusion { path: path.to_path_buf(), pattern: pattern.to_string() });
+        }
+    }
+
+    fn warning(&self, message: &str) {
+        self.inner.warning(message);
+    }
+}
@@ -0,0 +1,204 @@
+// Recall - 进度事件模块
+// 以换行分隔的 JSON（NDJSON）输出备份进度事件，供图形界面前端渲染自己的进度显示
+
+use crate::actions::BackupStats;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 两次 `progress` 事件之间的最短间隔，避免大量小文件时事件刷屏
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 进度事件的输出目标（`--progress-json`）
+static SINK: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// 已扫描的文件数量
+static SCANNED_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// 已扫描的文件总大小
+static SCANNED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// 扫描是否已完成（此后 `files_total` / `bytes_total` 不再变化）
+static SCAN_COMPLETE: AtomicBool = AtomicBool::new(false);
+
+/// 进度事件
+///
+/// 每个事件序列化为一行 JSON，`event` 字段标识事件类型。
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent<'a> {
+    /// 备份开始
+    Started {
+        source: &'a Path,
+        destination: &'a Path,
+        snapshot: &'a str,
+    },
+
+    /// 源目录扫描完成，总量已确定
+    ScanComplete { files: u64, bytes: u64 },
+
+    /// 单个文件处理完成
+    File {
+        path: &'a Path,
+        action: &'static str,
+        bytes: u64,
+    },
+
+    /// 单个文件处理失败，`locked` 表示文件被其他进程锁定
+    Failed {
+        path: &'a Path,
+        error: String,
+        locked: bool,
+    },
+
+    /// 整体进度；扫描未完成时总量仍会增长
+    Progress {
+        files_done: u64,
+        bytes_done: u64,
+        files_total: u64,
+        bytes_total: u64,
+        scan_complete: bool,
+    },
+
+    /// 备份结束，`status` 为 `ok` 或 `partial`
+    Finished {
+        status: &'a str,
+        stats: &'a BackupStats,
+    },
+}
+
+/// 启用进度事件输出
+///
+/// # 参数
+/// * `target` - `-` 表示标准输出，否则为文件或命名管道路径
+///
+/// # 返回
+/// * `Ok(())` - 已启用
+/// * `Err(anyhow::Error)` - 无法打开输出目标
+pub fn init(target: &Path) -> Result<()> {
+    let writer: Box<dyn Write + Send> = if target == Path::new("-") {
+        Box::new(std::io::stdout())
+    } else {
+        // 不使用 truncate：命名管道不支持截断，普通文件在打开后手动清空
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(target)
+            .with_context(|| format!("Failed to open progress output {:?}", target))?;
+        if file.metadata().is_ok_and(|m| m.is_file()) {
+            file.set_len(0)?;
+        }
+        Box::new(file)
+    };
+    let _ = SINK.set(Mutex::new(writer));
+    Ok(())
+}
+
+/// 是否启用了进度事件输出
+pub fn enabled() -> bool {
+    SINK.get().is_some()
+}
+
+/// 输出一个进度事件
+///
+/// 写入失败（如前端已关闭管道）时静默忽略，不影响备份本身。
+pub fn emit(event: &ProgressEvent) {
+    if let Some(sink) = SINK.get() {
+        if let Ok(line) = serde_json::to_string(event) {
+            let mut writer = sink.lock().unwrap();
+            let _ = writeln!(writer, "{}", line);
+            let _ = writer.flush();
+        }
+    }
+}
+
+/// 开始一次新的备份：重置扫描计数并输出 `started` 事件
+///
+/// `run --all` 会依次运行多个备份，每次开始前都需要调用。
+pub fn start(source: &Path, destination: &Path, snapshot: &str) {
+    if !enabled() {
+        return;
+    }
+    SCANNED_FILES.store(0, Ordering::Relaxed);
+    SCANNED_BYTES.store(0, Ordering::Relaxed);
+    SCAN_COMPLETE.store(false, Ordering::Relaxed);
+    emit(&ProgressEvent::Started {
+        source,
+        destination,
+        snapshot,
+    });
+}
+
+/// 记录扫描到的一个文件
+pub fn record_scanned(size: u64) {
+    SCANNED_FILES.fetch_add(1, Ordering::Relaxed);
+    SCANNED_BYTES.fetch_add(size, Ordering::Relaxed);
+}
+
+/// 标记扫描完成并输出 `scan_complete` 事件
+pub fn finish_scan() {
+    if !enabled() {
+        return;
+    }
+    SCAN_COMPLETE.store(true, Ordering::Relaxed);
+    emit(&ProgressEvent::ScanComplete {
+        files: SCANNED_FILES.load(Ordering::Relaxed),
+        bytes: SCANNED_BYTES.load(Ordering::Relaxed),
+    });
+}
+
+/// 执行阶段的进度计数器
+///
+/// 由执行器在每个文件处理完成后更新，按固定间隔输出 `progress` 事件。
+pub struct ProgressTracker {
+    /// 已处理的文件数量
+    files_done: u64,
+    /// 已处理的文件总大小（包括硬链接的文件）
+    bytes_done: u64,
+    /// 上一次输出 `progress` 事件的时间
+    last_emit: Option<Instant>,
+}
+
+impl ProgressTracker {
+    /// 创建新的进度计数器
+    pub fn new() -> Self {
+        Self {
+            files_done: 0,
+            bytes_done: 0,
+            last_emit: None,
+        }
+    }
+
+    /// 记录一个已处理的文件，距上次输出超过间隔时输出 `progress` 事件
+    pub fn file_done(&mut self, size: u64) {
+        self.files_done += 1;
+        self.bytes_done += size;
+        if self.last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL) {
+            self.emit();
+        }
+    }
+
+    /// 立即输出当前进度
+    pub fn emit(&mut self) {
+        self.last_emit = Some(Instant::now());
+        emit(&ProgressEvent::Progress {
+            files_done: self.files_done,
+            bytes_done: self.bytes_done,
+            files_total: SCANNED_FILES.load(Ordering::Relaxed),
+            bytes_total: SCANNED_BYTES.load(Ordering::Relaxed),
+            scan_complete: SCAN_COMPLETE.load(Ordering::Relaxed),
+        });
+    }
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
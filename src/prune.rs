@@ -1,9 +1,14 @@
 // Recall - 备份清理模块
 // 提供查找和删除旧备份的功能，帮助管理磁盘空间
 
-use anyhow::{Context, Result};
-use chrono::NaiveDateTime;
-use console::style;
+use crate::events::EventSink;
+use crate::summary::BackupSummary;
+use crate::error::{Classify, RecallError, Result};
+use anyhow::Context;
+use crate::naming::{is_incomplete, SnapshotNaming};
+use crate::executor::make_dirs_writable;
+use crate::utils::to_verbatim_path;
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -12,117 +17,141 @@ use std::path::{Path, PathBuf};
 /// 有效备份文件夹的定义：
 /// - 是目录
 /// - 名称不以 `.partial` 结尾（未完成的备份）
-/// - 名称不是 `current`（符号链接）
-/// - 名称格式为 `%Y-%m-%d_%H-%M-%S`（时间戳格式）
+/// - 名称可按快照命名规则解析为时间（`current` 链接等其他目录被忽略）
 ///
 /// # 参数
 /// * `destination` - 备份目标根目录
+/// * `naming` - 快照命名规则
 ///
 /// # 返回
 /// * `Ok(Vec<PathBuf>)` - 按时间顺序排列的备份路径（最旧的在前）
-/// * `Err(anyhow::Error)` - 读取目录失败
-pub fn find_all_backups(destination: &Path) -> Result<Vec<PathBuf>> {
-    if !destination.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut backups: Vec<PathBuf> = fs::read_dir(destination)
-        .context("Cannot read destination directory")?
-        .filter_map(|entry| entry.ok())
-        .map(|entry| entry.path())
-        .filter(|path| {
-            path.is_dir()
-                && !path.to_string_lossy().ends_with(".partial")
-                && path.file_name().is_some_and(|n| n != "current")
-                && is_valid_backup_folder_name(path)
-        })
-        .collect();
-
-    // 按名称排序，最旧的在前（名称本身就是时间戳）
-    backups.sort();
-
-    Ok(backups)
+/// * `Err(RecallError)` - 读取目录失败
+pub fn find_all_backups(destination: &Path, naming: &SnapshotNaming) -> Result<Vec<PathBuf>> {
+    Ok(naming
+        .list(destination, false)
+        .classify(RecallError::Destination)?
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect())
 }
 
-/// 检查目录名称是否是有效的备份文件夹名称
-///
-/// 有效格式：`YYYY-MM-DD_HH-MM-SS`（如 `2024-01-15_14-30-00`）
-///
-/// # 参数
-/// * `path` - 要检查的路径
-///
-/// # 返回
-/// * `true` - 是有效的备份文件夹名称
-/// * `false` - 不是有效的备份文件夹名称
-fn is_valid_backup_folder_name(path: &Path) -> bool {
-    path.file_name()
-        .and_then(|n| n.to_str())
-        .map(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d_%H-%M-%S").is_ok())
-        .unwrap_or(false)
+/// 清理结果，可序列化为 JSON 供脚本使用
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneReport {
+    /// 备份目标根目录
+    pub destination: PathBuf,
+    /// 找到的备份数量
+    pub found: usize,
+    /// 要保留的备份数量
+    pub keep: usize,
+    /// 已删除（或试运行时将删除）的备份名称，按时间顺序排列
+    pub deleted: Vec<String>,
+    /// 被保留的备份名称（包括受标签保护的备份），按时间顺序排列
+    pub kept: Vec<String>,
+    /// 因带有受保护标签而保留的备份名称
+    pub protected: Vec<String>,
+    /// 是否为试运行
+    pub dry_run: bool,
 }
 
 /// 清理旧备份，保留最新的 `keep` 个备份
 ///
+/// 带有 `keep_tags` 中任一标签的备份始终保留，且不计入 `keep`。
+/// 不完整的快照（名称带 `-incomplete` 后缀）也不计入 `keep`，比保留的最旧完整快照更旧时被删除。
+///
 /// # 参数
 /// * `destination` - 备份目标根目录
 /// * `keep` - 要保留的最新备份数量
 /// * `dry_run` - 是否为试运行模式（不实际删除）
+/// * `keep_tags` - 受保护的标签
+/// * `naming` - 快照命名规则
+/// * `events` - 事件接收者（受保护、删除和保留的快照）
 ///
 /// # 返回
-/// * `Ok(())` - 清理完成
-/// * `Err(anyhow::Error)` - 清理过程中出现错误
+/// * `Ok(PruneReport)` - 清理完成，包含删除和保留的备份
+/// * `Err(RecallError)` - 清理过程中出现错误
 ///
 /// # 示例
 /// ```ignore
 /// // 保留最新的 5 个备份
-/// prune_backups(Path::new("D:/Backups/MyProject"), 5, false)?;
+/// prune_backups(Path::new("D:/Backups/MyProject"), 5, false, &[], &SnapshotNaming::default(), &NullSink)?;
 /// ```
-pub fn prune_backups(destination: &Path, keep: usize, dry_run: bool) -> Result<()> {
-    let backups = find_all_backups(destination)?;
+pub fn prune_backups(
+    destination: &Path,
+    keep: usize,
+    dry_run: bool,
+    keep_tags: &[String],
+    naming: &SnapshotNaming,
+    events: &dyn EventSink,
+) -> Result<PruneReport> {
+    let all_backups = find_all_backups(destination, naming)?;
+
+    // 带有受保护标签的备份不参与清理
+    let (protected, backups): (Vec<PathBuf>, Vec<PathBuf>) =
+        all_backups.iter().cloned().partition(|path| {
+            !keep_tags.is_empty()
+                && BackupSummary::load(path)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|s| s.tags.iter().any(|t| keep_tags.contains(t)))
+        });
+    let name_of = |path: &PathBuf| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut report = PruneReport {
+        destination: destination.to_path_buf(),
+        found: all_backups.len(),
+        keep,
+        deleted: Vec::new(),
+        kept: all_backups.iter().map(name_of).collect(),
+        protected: protected.iter().map(name_of).collect(),
+        dry_run,
+    };
 
-    // 如果备份数量不超过保留数量，无需清理
-    if backups.len() <= keep {
-        println!(
-            "Found {} backup(s), keeping {}. Nothing to prune.",
-            backups.len(),
-            keep
-        );
-        return Ok(());
+    for name in &report.protected {
+        events.snapshot_protected(name);
     }
 
-    let to_delete_count = backups.len() - keep;
-    let to_delete = &backups[..to_delete_count];
+    // 不完整的快照不计入保留数量，只删除比保留的最旧完整快照更旧的
+    let complete: Vec<&PathBuf> = backups.iter().filter(|path| !is_incomplete(&name_of(path))).collect();
+
+    // 如果完整备份数量不超过保留数量，无需清理
+    if complete.len() <= keep {
+        events.prune_planned(backups.len(), 0, keep);
+        return Ok(report);
+    }
+
+    // 保留的最旧完整快照之前的快照都被删除（`keep` 为 0 时全部删除）
+    let oldest_kept = complete.get(complete.len() - keep).copied();
+    let to_delete: Vec<PathBuf> = backups.iter().take_while(|path| Some(*path) != oldest_kept).cloned().collect();
+    let to_delete_count = to_delete.len();
+    report.kept = all_backups
+        .iter()
+        .filter(|path| !to_delete.contains(path))
+        .map(name_of)
+        .collect();
 
-    println!(
-        "Found {} backup(s). Will delete {} oldest, keeping {} newest.",
-        backups.len(),
-        to_delete_count,
-        keep
-    );
+    events.prune_planned(backups.len(), to_delete_count, keep);
 
     // 删除旧的备份
-    for path in to_delete {
-        if dry_run {
-            println!(
-                "{} Would delete: {:?}",
-                style("Dry run:").yellow(),
-                path.file_name().unwrap()
-            );
-        } else {
-            println!("Deleting: {:?}", style(path.file_name().unwrap()).red());
-            fs::remove_dir_all(path)
-                .with_context(|| format!("Failed to delete backup {:?}", path))?;
+    for path in &to_delete {
+        events.snapshot_deleting(&name_of(path), dry_run);
+        if !dry_run {
+            // 只读快照的目录没有写权限，先恢复；使用逐字路径，删除很深的目录树时不受 Windows 路径长度限制
+            make_dirs_writable(path);
+            fs::remove_dir_all(to_verbatim_path(path))
+                .with_context(|| format!("Failed to delete backup {:?}", path))
+                .classify(RecallError::Destination)?;
         }
+        report.deleted.push(name_of(path));
     }
 
-    if !dry_run {
-        println!(
-            "{}",
-            style(format!("Pruned {} old backup(s).", to_delete_count))
-                .green()
-                .bold()
-        );
+    for name in &report.kept {
+        events.snapshot_kept(name);
     }
+    events.prune_finished(to_delete_count, dry_run);
 
-    Ok(())
+    Ok(report)
 }
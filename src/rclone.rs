@@ -0,0 +1,125 @@
+// Recall - rclone 复制模块
+// 备份完成后调用 rclone 将新快照复制到异地远程存储（任何 rclone 支持的后端）
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// 一次复制的结果，用于备份汇总和 `--json` 输出
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ReplicationReport {
+    /// rclone 远程目标（快照复制到其下同名目录）
+    pub remote: String,
+
+    /// 复制状态：`ok` 或 `failed`
+    pub status: &'static str,
+
+    /// 传输的文件数量
+    pub transfers: u64,
+
+    /// 传输的字节数
+    pub bytes: u64,
+
+    /// rclone 报告的错误数量
+    pub errors: u64,
+
+    /// 耗时（秒）
+    pub elapsed_secs: f64,
+
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReplicationReport {
+    /// 复制是否失败
+    pub fn failed(&self) -> bool {
+        self.status != "ok"
+    }
+}
+
+/// 使用 rclone 将快照目录复制到远程目标下的同名目录
+///
+/// 调用 `rclone copy`，通过 JSON 日志读取最终的传输统计。
+/// 符号链接以 rclone 的 `.rclonelink` 文件形式保存（`--links`）。
+///
+/// # 参数
+/// * `snapshot_dir` - 已提交的快照目录
+/// * `remote` - rclone 远程目标（如 `offsite:backups/Projects`）
+/// * `name` - 快照名称
+///
+/// # 返回
+/// 复制结果；无法启动 rclone 或 rclone 以非零状态退出时状态为 `failed`
+pub fn replicate_snapshot(snapshot_dir: &Path, remote: &str, name: &str) -> ReplicationReport {
+    let mut report = ReplicationReport { remote: remote.to_string(), status: "ok", ..Default::default() };
+    if let Err(e) = run_rclone(snapshot_dir, &remote_path(remote, name), &mut report) {
+        report.status = "failed";
+        report.error = Some(format!("{:#}", e));
+    }
+    report
+}
+
+/// 运行 `rclone copy` 并把统计信息写入 `report`
+fn run_rclone(snapshot_dir: &Path, dest: &str, report: &mut ReplicationReport) -> Result<()> {
+    // 统计间隔设得足够长，只输出结束时的最终统计
+    let output = Command::new("rclone")
+        .arg("copy")
+        .arg(snapshot_dir)
+        .arg(dest)
+        .args(["--links", "--use-json-log", "--stats", "24h", "--stats-log-level", "NOTICE"])
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => anyhow::anyhow!("rclone was not found on PATH"),
+            _ => anyhow::Error::new(e).context("Failed to start rclone"),
+        })?;
+
+    let log = String::from_utf8_lossy(&output.stderr);
+    let mut errors = Vec::new();
+    for line in log.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if let Some(stats) = entry.get("stats") {
+            let field = |key: &str| stats.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            report.transfers = field("transfers");
+            report.bytes = field("bytes");
+            report.errors = field("errors");
+            report.elapsed_secs = stats.get("elapsedTime").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        }
+        if entry.get("level").and_then(|v| v.as_str()) == Some("error") {
+            if let Some(msg) = entry.get("msg").and_then(|v| v.as_str()) {
+                errors.push(msg.trim().to_string());
+            }
+        }
+    }
+
+    if !output.status.success() {
+        // 只保留最后几条错误，完整日志可用 rclone 单独排查
+        let detail = match errors.len() {
+            0 => log.lines().last().unwrap_or("").trim().to_string(),
+            n => errors[n.saturating_sub(3)..].join("; "),
+        };
+        return Err(anyhow::anyhow!("{}", detail))
+            .with_context(|| format!("rclone copy to {} failed ({})", dest, output.status));
+    }
+    Ok(())
+}
+
+/// 拼接远程目标和快照名称（`remote:` 形式的根目录不再添加 `/`）
+///
+/// # 示例
+/// ```
+/// use recall::rclone::remote_path;
+/// assert_eq!(remote_path("offsite:backups/", "2024-01-15"), "offsite:backups/2024-01-15");
+/// assert_eq!(remote_path("offsite:", "2024-01-15"), "offsite:2024-01-15");
+/// ```
+pub fn remote_path(remote: &str, name: &str) -> String {
+    let remote = remote.trim_end_matches('/');
+    if remote.ends_with(':') {
+        format!("{}{}", remote, name)
+    } else {
+        format!("{}/{}", remote, name)
+    }
+}
@@ -0,0 +1,434 @@
+// Recall - 备份集复制模块
+// 将整个备份目标目录（所有快照）同步或迁移到另一个本地位置，按文件标识重建硬链接，副本不会膨胀为快照数倍的大小
+
+use crate::copy::{copy_file, CopyOptions};
+use crate::utils::{file_identity, is_too_many_links, to_verbatim_path};
+use crate::{trf, verbose, warning};
+use anyhow::{Context, Result};
+use filetime::FileTime;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 复制结果统计
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ReplicateStats {
+    /// 复制的文件数量
+    pub copied: u64,
+
+    /// 新建硬链接的文件数量（源中与已复制的文件是同一文件）
+    pub linked: u64,
+
+    /// 镜像中已是最新、无需处理的文件数量
+    pub unchanged: u64,
+
+    /// 新建或更新的符号链接数量
+    pub symlinks: u64,
+
+    /// 从镜像中删除的条目数量（源中已不存在，如被清理的快照）
+    pub deleted: u64,
+
+    /// 处理失败的条目数量
+    pub failed: u64,
+
+    /// 复制的字节数
+    pub bytes_copied: u64,
+}
+
+/// 将备份目标目录同步到镜像目录
+///
+/// 源中互为硬链接的文件（同一文件标识）在镜像中只复制一次，其余位置创建指向该副本的硬链接；
+/// 大小和修改时间与源相同的镜像文件保持不变，因此重复运行只复制新快照中变化的数据。
+/// 文件总是先写入临时文件再替换，不会修改镜像中可能与其他快照共享的文件内容。
+///
+/// # 参数
+/// * `source` - 备份目标目录（如 `E:\Backups`）
+/// * `mirror` - 镜像目录（如 `F:\Mirror`）
+/// * `delete_extra` - 是否删除源中已不存在的镜像条目（如已清理的快照）
+/// * `dry_run` - 只统计将执行的操作，不修改镜像
+///
+/// # 返回
+/// * `Ok(ReplicateStats)` - 复制结果统计（单个条目失败时记录在 `failed` 中）
+/// * `Err(anyhow::Error)` - 源目录无法读取，或镜像与源目录相互嵌套
+pub fn replicate_tree(source: &Path, mirror: &Path, delete_extra: bool, dry_run: bool) -> Result<ReplicateStats> {
+    let source = fs::canonicalize(source).with_context(|| format!("Failed to access {:?}", source))?;
+    let mirror_abs = absolute_path(mirror)?;
+    if mirror_abs.starts_with(&source) || source.starts_with(&mirror_abs) {
+        anyhow::bail!("Mirror {:?} and source {:?} must not contain each other", mirror, source);
+    }
+    if !dry_run {
+        fs::create_dir_all(&mirror_abs).with_context(|| format!("Failed to create {:?}", mirror_abs))?;
+    }
+
+    let mut stats = ReplicateStats::default();
+    // 源文件标识 -> 镜像中已写入的副本
+    let mut copies: HashMap<(u64, u64), PathBuf> = HashMap::new();
+    let mut expected: HashSet<PathBuf> = HashSet::new();
+
+    // 按名称排序遍历，快照按时间顺序处理，文件的第一次出现通常位于镜像中已有的旧快照
+    for entry in WalkDir::new(&source).follow_links(false).min_depth(1).sort_by_file_name() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                warning!(
+                    "{}",
+                    trf!(
+                        "Warning: Failed to read {:?}: {}",
+                        "警告：无法读取 {:?}：{}",
+                        e.path().unwrap_or(&source),
+                        e
+                    )
+                );
+                stats.failed += 1;
+                continue;
+            }
+        };
+        let rel = entry.path().strip_prefix(&source)?.to_path_buf();
+        let dest = mirror_abs.join(&rel);
+        expected.insert(rel.clone());
+
+        let result = if entry.file_type().is_dir() {
+            sync_dir(&dest, dry_run)
+        } else if entry.file_type().is_symlink() {
+            sync_symlink(entry.path(), &dest, &source, &mirror_abs, dry_run).map(|updated| {
+                if updated {
+                    verbose!("symlink: {}", rel.display());
+                    stats.symlinks += 1;
+                }
+            })
+        } else {
+            sync_file(entry.path(), &dest, &rel, dry_run, &mut copies, &mut stats)
+        };
+
+        if let Err(e) = result {
+            warning!(
+                "{}",
+                trf!(
+                    "Warning: Failed to replicate {}: {:#}",
+                    "警告：无法复制 {}：{:#}",
+                    rel.display(),
+                    e
+                )
+            );
+            stats.failed += 1;
+        }
+    }
+
+    // 删除源中已不存在的条目（先处理目录内容，再处理目录本身）
+    if delete_extra && mirror_abs.is_dir() {
+        for entry in WalkDir::new(&mirror_abs).follow_links(false).min_depth(1).contents_first(true) {
+            let Ok(entry) = entry else { continue };
+            let rel = entry.path().strip_prefix(&mirror_abs)?;
+            if expected.contains(rel) {
+                continue;
+            }
+            verbose!("delete: {}", rel.display());
+            stats.deleted += 1;
+            if dry_run {
+                continue;
+            }
+            let removed = if entry.file_type().is_dir() {
+                fs::remove_dir(entry.path())
+            } else {
+                remove_entry(entry.path())
+            };
+            if let Err(e) = removed {
+                warning!(
+                    "{}",
+                    trf!(
+                        "Warning: Failed to delete {:?}: {}",
+                        "警告：无法删除 {:?}：{}",
+                        entry.path(),
+                        e
+                    )
+                );
+                stats.failed += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// 确保镜像中的目录存在（同名的文件或链接会被替换）
+fn sync_dir(dest: &Path, dry_run: bool) -> Result<()> {
+    match fs::symlink_metadata(dest) {
+        Ok(meta) if meta.is_dir() => Ok(()),
+        _ if dry_run => Ok(()),
+        Ok(_) => {
+            remove_entry(dest)?;
+            fs::create_dir(dest).with_context(|| format!("Failed to create {:?}", dest))
+        }
+        Err(_) => fs::create_dir_all(dest).with_context(|| format!("Failed to create {:?}", dest)),
+    }
+}
+
+/// 同步符号链接（如 `current`），链接目标相同时不做处理
+///
+/// 指向源目录内部的绝对路径链接改为指向镜像中的对应位置，避免镜像链接回原备份盘。
+///
+/// # 返回
+/// * `Ok(true)` - 已创建或更新
+/// * `Ok(false)` - 镜像中已是相同的链接
+fn sync_symlink(src: &Path, dest: &Path, source: &Path, mirror: &Path, dry_run: bool) -> Result<bool> {
+    let mut target = fs::read_link(src).with_context(|| format!("Failed to read link {:?}", src))?;
+    if let Ok(inner) = target.strip_prefix(source) {
+        target = mirror.join(inner);
+    }
+    if fs::read_link(dest).is_ok_and(|existing| existing == target) {
+        return Ok(false);
+    }
+    if dry_run {
+        return Ok(true);
+    }
+    if fs::symlink_metadata(dest).is_ok() {
+        remove_entry(dest)?;
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, dest)
+        .with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, target))?;
+
+    // Windows 区分文件链接和目录链接，按源链接指向的类型创建
+    #[cfg(windows)]
+    {
+        let result = if fs::metadata(src).is_ok_and(|m| m.is_dir()) {
+            std::os::windows::fs::symlink_dir(&target, dest)
+        } else {
+            std::os::windows::fs::symlink_file(&target, dest)
+        };
+        result.with_context(|| format!("Failed to symlink {:?} -> {:?}", dest, target))?;
+    }
+
+    Ok(true)
+}
+
+/// 同步单个文件：已复制过的同一文件创建硬链接，否则在镜像文件过期时复制
+fn sync_file(
+    src: &Path,
+    dest: &Path,
+    rel: &Path,
+    dry_run: bool,
+    copies: &mut HashMap<(u64, u64), PathBuf>,
+    stats: &mut ReplicateStats,
+) -> Result<()> {
+    let id = file_identity(src);
+
+    if let Some(first) = id.and_then(|id| copies.get(&id)) {
+        if file_identity(dest).is_some_and(|existing| Some(existing) == file_identity(first)) {
+            stats.unchanged += 1;
+            return Ok(());
+        }
+        verbose!("link: {}", rel.display());
+        if dry_run {
+            stats.linked += 1;
+            return Ok(());
+        }
+        if fs::symlink_metadata(dest).is_ok() {
+            remove_entry(dest)?;
+        }
+        match fs::hard_link(first, dest) {
+            Ok(()) => {
+                stats.linked += 1;
+                return Ok(());
+            }
+            // 链接数达到上限时复制一份，之后的链接指向新副本
+            Err(e) if is_too_many_links(&e) => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to link {:?} to {:?}", dest, first)),
+        }
+    } else if is_up_to_date(src, dest) {
+        stats.unchanged += 1;
+        if let Some(id) = id {
+            copies.insert(id, dest.to_path_buf());
+        }
+        return Ok(());
+    }
+
+    verbose!("copy: {}", rel.display());
+    stats.copied += 1;
+    if dry_run {
+        stats.bytes_copied += fs::metadata(src)?.len();
+    } else {
+        stats.bytes_copied += copy_replacing(src, dest)?;
+    }
+    if let Some(id) = id {
+        copies.insert(id, dest.to_path_buf());
+    }
+    Ok(())
+}
+
+/// 检查镜像文件的大小和修改时间是否与源文件相同
+fn is_up_to_date(src: &Path, dest: &Path) -> bool {
+    let (Ok(src_meta), Ok(dest_meta)) = (fs::metadata(src), fs::symlink_metadata(dest)) else {
+        return false;
+    };
+    dest_meta.is_file()
+        && src_meta.len() == dest_meta.len()
+        && FileTime::from_last_modification_time(&src_meta) == FileTime::from_last_modification_time(&dest_meta)
+}
+
+/// 通过临时文件复制并替换镜像文件，保留修改时间和只读属性
+///
+/// # 返回
+/// * `Ok(u64)` - 写入的字节数
+/// * `Err(anyhow::Error)` - 复制失败
+fn copy_replacing(src: &Path, dest: &Path) -> Result<u64> {
+    let name = dest.file_name().context("Invalid destination path")?.to_string_lossy();
+    let temp = dest.with_file_name(format!(".{}.recall-tmp", name));
+    fs::remove_file(&temp).ok();
+
+    let bytes = copy_file(src, &temp, &CopyOptions::default())?;
+    let src_meta = fs::metadata(src)?;
+    filetime::set_file_mtime(&temp, FileTime::from_last_modification_time(&src_meta))
+        .with_context(|| format!("Failed to set modification time of {:?}", temp))?;
+    fs::set_permissions(&temp, src_meta.permissions()).ok();
+
+    if fs::symlink_metadata(dest).is_ok() {
+        remove_entry(dest)?;
+    }
+    fs::rename(&temp, dest).with_context(|| format!("Failed to rename {:?} to {:?}", temp, dest))?;
+    Ok(bytes)
+}
+
+/// 删除文件或链接（先清除只读属性；Windows 的目录链接需按目录删除）
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if let Ok(meta) = fs::symlink_metadata(path) {
+        if meta.is_file() && meta.permissions().readonly() {
+            let mut perms = meta.permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            perms.set_readonly(false);
+            fs::set_permissions(path, perms).ok();
+        }
+    }
+    fs::remove_file(path).or_else(|e| fs::remove_dir(path).map_err(|_| e))
+}
+
+/// 获取绝对路径（路径不存在时规范化其最近的已存在上级目录）
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    if let Ok(abs) = fs::canonicalize(path) {
+        return Ok(abs);
+    }
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().with_context(|| format!("Invalid path {:?}", path))?;
+    Ok(absolute_path(parent)?.join(name))
+}
+
+/// 复制结果核对
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct TreeCheck {
+    /// 源中的文件数量
+    pub files: u64,
+
+    /// 源中的目录数量
+    pub dirs: u64,
+
+    /// 源中的符号链接数量
+    pub symlinks: u64,
+
+    /// 源中所有文件的大小之和（硬链接重复计算）
+    pub bytes: u64,
+
+    /// 源中不同文件（硬链接组）的数量
+    pub source_groups: u64,
+
+    /// 副本中对应文件的硬链接组数量
+    pub target_groups: u64,
+
+    /// 不一致的条目（最多记录 [`MAX_MISMATCHES`] 条）
+    pub mismatches: Vec<String>,
+}
+
+impl TreeCheck {
+    /// 副本是否与源一致（条目齐全、大小相同、硬链接组数相同）
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty() && self.source_groups == self.target_groups
+    }
+
+    /// 记录一条不一致（超过上限后只计入结果，不再记录内容）
+    fn mismatch(&mut self, message: String) {
+        if self.mismatches.len() < MAX_MISMATCHES {
+            self.mismatches.push(message);
+        }
+    }
+}
+
+/// 核对时最多记录的不一致条目数量
+pub const MAX_MISMATCHES: usize = 20;
+
+/// 核对副本是否完整：源中的每个条目在副本中都存在且类型、大小相同，硬链接组数量相同
+///
+/// # 参数
+/// * `source` - 源目录
+/// * `target` - 副本目录
+///
+/// # 返回
+/// * `Ok(TreeCheck)` - 核对结果
+/// * `Err(anyhow::Error)` - 源目录无法读取
+pub fn verify_tree(source: &Path, target: &Path) -> Result<TreeCheck> {
+    let mut check = TreeCheck::default();
+    let mut source_ids = HashSet::new();
+    let mut target_ids = HashSet::new();
+
+    for entry in WalkDir::new(source).follow_links(false).min_depth(1) {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(source)?;
+        let dest = target.join(rel);
+        let dest_meta = fs::symlink_metadata(&dest).ok();
+
+        if entry.file_type().is_dir() {
+            check.dirs += 1;
+            if !dest_meta.is_some_and(|m| m.is_dir()) {
+                check.mismatch(format!("missing directory: {}", rel.display()));
+            }
+        } else if entry.file_type().is_symlink() {
+            check.symlinks += 1;
+            if !dest_meta.is_some_and(|m| m.file_type().is_symlink()) {
+                check.mismatch(format!("missing symlink: {}", rel.display()));
+            }
+        } else {
+            let size = entry.metadata()?.len();
+            check.files += 1;
+            check.bytes += size;
+            if let Some(id) = file_identity(entry.path()) {
+                source_ids.insert(id);
+            }
+            match dest_meta {
+                Some(meta) if meta.is_file() && meta.len() == size => {
+                    if let Some(id) = file_identity(&dest) {
+                        target_ids.insert(id);
+                    }
+                }
+                Some(meta) if meta.is_file() => check.mismatch(
+                    format!("size differs: {} ({} vs {} bytes)", rel.display(), size, meta.len()),
+                ),
+                _ => check.mismatch(format!("missing file: {}", rel.display())),
+            }
+        }
+    }
+
+    check.source_groups = source_ids.len() as u64;
+    check.target_groups = target_ids.len() as u64;
+    Ok(check)
+}
+
+/// 删除目录中的所有内容（清除只读属性），最后尝试删除目录本身（如磁盘根目录则保留）
+///
+/// # 参数
+/// * `path` - 要删除的目录
+pub fn remove_tree(path: &Path) -> Result<()> {
+    let path = &to_verbatim_path(path);
+    crate::executor::make_dirs_writable(path);
+    for entry in WalkDir::new(path).follow_links(false).min_depth(1).contents_first(true) {
+        let entry = entry?;
+        let removed = if entry.file_type().is_dir() {
+            fs::remove_dir(entry.path())
+        } else {
+            remove_entry(entry.path())
+        };
+        removed.with_context(|| format!("Failed to delete {:?}", entry.path()))?;
+    }
+    fs::remove_dir(path).ok();
+    Ok(())
+}
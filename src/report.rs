@@ -0,0 +1,359 @@
+// Recall - HTML 运行报告模块
+// 把一次备份的结果渲染为独立的 HTML 文件（摘要、复制与链接的比例、最大的文件和目录、按扩展名的汇总、失败列表），便于发送邮件或与快照一起存档
+
+use crate::actions::{BackupStats, LARGEST_FILES};
+use crate::i18n::{language, tr, Language};
+use crate::utils::{format_bytes, format_duration};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 报告的样式（内嵌在 HTML 中，不引用外部资源）
+const STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI',Roboto,'Microsoft YaHei',sans-serif;color:#222;max-width:960px;margin:2em auto;padding:0 1em}
+h1{font-size:1.5em;margin-bottom:.2em}h2{font-size:1.15em;margin-top:2em;border-bottom:1px solid #ddd;padding-bottom:.3em}
+.status{display:inline-block;padding:.2em .7em;border-radius:4px;color:#fff;font-weight:bold}
+.ok{background:#2e7d32}.partial{background:#ef6c00}.failed{background:#c62828}
+table{border-collapse:collapse;width:100%}td,th{text-align:left;padding:.35em .6em;border-bottom:1px solid #eee;vertical-align:top}
+th{background:#f5f5f5}td.num{text-align:right;white-space:nowrap}.path{font-family:Consolas,monospace;word-break:break-all}
+.bar{display:flex;height:22px;border-radius:4px;overflow:hidden;background:#eee;margin:.3em 0 1em}
+.bar div{height:100%}.copied{background:#1976d2}.cloned{background:#7b1fa2}.linked{background:#9e9e9e}
+.legend span{display:inline-block;width:.8em;height:.8em;margin:0 .3em 0 1em;vertical-align:middle}
+.error{color:#c62828}footer{margin-top:3em;color:#888;font-size:.85em}
+";
+
+/// 一次运行的报告内容
+#[derive(Debug)]
+pub struct RunReport<'a> {
+    /// 配置文件名称（命令行运行时为项目名称）
+    pub profile: &'a str,
+
+    /// 运行结果：`ok`、`partial` 或 `failed`
+    pub status: &'a str,
+
+    /// 创建的快照名称（失败时为空）
+    pub snapshot: Option<&'a str>,
+
+    /// 开始时间（`%Y-%m-%d %H:%M:%S`）
+    pub started_at: String,
+
+    /// 耗时（秒）
+    pub duration_secs: u64,
+
+    /// 备份统计（失败时为空）
+    pub stats: Option<&'a BackupStats>,
+
+    /// 失败原因
+    pub error: Option<&'a str>,
+}
+
+impl RunReport<'_> {
+    /// 渲染为独立的 HTML 文档
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::actions::BackupStats;
+    /// use recall::report::RunReport;
+    ///
+    /// let mut stats = BackupStats::new();
+    /// stats.copied_new = 3;
+    /// stats.linked = 1;
+    /// let report = RunReport {
+    ///     profile: "Docs & <Notes>",
+    ///     status: "ok",
+    ///     snapshot: Some("2024-01-15_10-30-00"),
+    ///     started_at: "2024-01-15 10:30:00".to_string(),
+    ///     duration_secs: 5,
+    ///     stats: Some(&stats),
+    ///     error: None,
+    /// };
+    /// let html = report.to_html();
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// assert!(html.contains("Docs &amp; &lt;Notes&gt;"));
+    /// ```
+    pub fn to_html(&self) -> String {
+        let zh = language() == Language::Chinese;
+        let mut html = String::new();
+        let title = format!("recall: {} {}", self.profile, self.snapshot.unwrap_or(&self.started_at));
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+            if zh { "zh" } else { "en" },
+            escape(&title),
+            STYLE
+        );
+
+        let status = match self.status {
+            "ok" => tr("Succeeded", "成功"),
+            "partial" => tr("Completed with failures", "部分失败"),
+            _ => tr("Failed", "失败"),
+        };
+        let _ = writeln!(
+            html,
+            "<h1>{}</h1>\n<p><span class=\"status {}\">{}</span></p>",
+            escape(self.profile),
+            escape(self.status),
+            status
+        );
+        if let Some(error) = self.error {
+            let _ = writeln!(html, "<p class=\"error\">{}</p>", escape(error));
+        }
+
+        // 摘要
+        let mut rows = vec![
+            (tr("Snapshot", "快照"), self.snapshot.unwrap_or("-").to_string()),
+            (tr("Started", "开始时间"), self.started_at.clone()),
+            (tr("Duration", "耗时"), format_duration(self.duration_secs)),
+        ];
+        if let Some(stats) = self.stats {
+            rows.extend([
+                (tr("Files", "文件总数"), stats.total_files.to_string()),
+                (tr("Copied (new)", "复制（新增）"), stats.copied_new.to_string()),
+                (tr("Copied (modified)", "复制（修改）"), stats.copied_modified.to_string()),
+                (tr("Cloned", "克隆"), stats.cloned.to_string()),
+                (tr("Hard linked", "硬链接"), format!("{} ({})", stats.linked, format_bytes(stats.bytes_linked))),
+                (tr("Link fallbacks", "链接降级"), stats.link_fallbacks.to_string()),
+                (
+                    tr("Deduplicated", "去重"),
+                    stats.saved_percent().map_or_else(
+                        || "-".to_string(),
+                        |percent| {
+                            crate::trf!(
+                                "{} referenced, {} written ({:.1}% saved)",
+                                "引用 {}，写入 {}（节省 {:.1}%）",
+                                format_bytes(stats.bytes_linked),
+                                format_bytes(stats.bytes_copied),
+                                percent
+                            )
+                        },
+                    ),
+                ),
+                (tr("Failed", "失败"), stats.failed.to_string()),
+                (tr("Locked", "锁定"), stats.locked.to_string()),
+                (tr("Vanished", "已消失"), stats.vanished.to_string()),
+                (
+                    tr("Data transferred", "传输数据"),
+                    format!("{} ({}/s)", format_bytes(stats.bytes_copied), format_bytes(stats.bytes_per_sec)),
+                ),
+                (
+                    tr("Throughput", "处理速度"),
+                    crate::trf!("{:.0} files/s", "每秒 {:.0} 个文件", stats.files_per_sec),
+                ),
+                (
+                    tr("Phase times", "各阶段耗时"),
+                    crate::trf!(
+                        "scan {:.1}s, hash {:.1}s, copy {:.1}s, link {:.1}s",
+                        "扫描 {:.1} 秒，哈希 {:.1} 秒，复制 {:.1} 秒，链接 {:.1} 秒",
+                        stats.durations.scan,
+                        stats.durations.hash,
+                        stats.durations.copy + stats.durations.clone,
+                        stats.durations.link
+                    ),
+                ),
+            ]);
+        }
+        let _ = writeln!(html, "<h2>{}</h2>\n<table>", tr("Summary", "摘要"));
+        for (name, value) in rows {
+            let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, escape(&value));
+        }
+        html.push_str("</table>\n");
+
+        if let Some(stats) = self.stats {
+            write_charts(&mut html, stats);
+            write_largest(&mut html, stats);
+            write_directories(&mut html, stats);
+            write_extensions(&mut html, stats);
+            write_failures(&mut html, stats);
+        }
+
+        let _ = write!(
+            html,
+            "<footer>{} recall v{}</footer>\n</body>\n</html>\n",
+            tr("Generated by", "生成工具："),
+            env!("CARGO_PKG_VERSION")
+        );
+        html
+    }
+
+    /// 写入报告文件
+    ///
+    /// # 参数
+    /// * `path` - 报告文件路径；为已存在的目录时写入其中的 `<快照名称>.html`（失败时以开始时间命名）
+    ///
+    /// # 返回
+    /// * `Ok(PathBuf)` - 写入的文件
+    /// * `Err(anyhow::Error)` - 写入失败
+    pub fn save(&self, path: &Path) -> Result<PathBuf> {
+        let path = if path.is_dir() {
+            let name = self.snapshot.map(str::to_string).unwrap_or_else(|| self.started_at.replace([' ', ':'], "-"));
+            path.join(format!("{}.html", name))
+        } else {
+            path.to_path_buf()
+        };
+        fs::write(&path, self.to_html()).with_context(|| format!("Failed to write report {:?}", path))?;
+        Ok(path)
+    }
+}
+
+/// 复制、克隆与硬链接的比例（按文件数量和字节数）
+fn write_charts(html: &mut String, stats: &BackupStats) {
+    let copied = stats.copied_new + stats.copied_modified + stats.link_fallbacks;
+    let bars = [
+        (tr("Files", "文件数"), [copied, stats.cloned, stats.linked], false),
+        (tr("Bytes", "数据量"), [stats.bytes_copied, 0, stats.bytes_linked], true),
+    ];
+    let _ = writeln!(html, "<h2>{}</h2>", tr("Copied vs linked", "复制与硬链接"));
+    for (name, values, bytes) in bars {
+        let total: u64 = values.iter().sum();
+        let format = |v: u64| if bytes { format_bytes(v) } else { v.to_string() };
+        let _ = writeln!(html, "<div>{}: {}</div>\n<div class=\"bar\">", name, format(total));
+        for (class, value) in ["copied", "cloned", "linked"].iter().zip(values) {
+            if value > 0 {
+                let percent = value as f64 * 100.0 / total as f64;
+                let _ = writeln!(
+                    html,
+                    "<div class=\"{}\" style=\"width:{:.2}%\" title=\"{} ({:.1}%)\"></div>",
+                    class,
+                    percent,
+                    format(value),
+                    percent
+                );
+            }
+        }
+        html.push_str("</div>\n");
+    }
+    let _ = writeln!(
+        html,
+        "<div class=\"legend\"><span class=\"copied\"></span>{}<span class=\"cloned\"></span>{}<span class=\"linked\"></span>{}</div>",
+        tr("Copied", "复制"),
+        tr("Cloned", "克隆"),
+        tr("Hard linked", "硬链接")
+    );
+}
+
+/// 写入数据最多的文件
+fn write_largest(html: &mut String, stats: &BackupStats) {
+    if stats.largest.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th></tr>",
+        tr("Largest files written", "写入最多的文件"),
+        tr("Path", "路径"),
+        tr("Size", "大小")
+    );
+    for file in &stats.largest {
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"num\">{}</td></tr>",
+            escape(&file.path.to_string_lossy()),
+            format_bytes(file.bytes)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// 写入数据最多的目录
+fn write_directories(html: &mut String, stats: &BackupStats) {
+    let top = stats.top_directories(LARGEST_FILES, false);
+    if top.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th></tr>",
+        tr("Directories with the most data written", "写入数据最多的目录"),
+        tr("Directory", "目录"),
+        tr("Written", "写入"),
+        tr("Linked", "硬链接")
+    );
+    for (dir, bytes) in top {
+        let linked = stats.directories.get(dir).map_or(0, |d| d.linked);
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape(&dir.to_string_lossy()),
+            format_bytes(bytes),
+            format_bytes(linked)
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// 按扩展名汇总写入的文件数和字节数
+fn write_extensions(html: &mut String, stats: &BackupStats) {
+    let top = stats.top_extensions(LARGEST_FILES);
+    if top.is_empty() || stats.bytes_copied == 0 {
+        return;
+    }
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+        tr("Data written by file type", "按文件类型的写入数据"),
+        tr("Type", "类型"),
+        tr("Files", "文件数"),
+        tr("Written", "写入"),
+        tr("Share", "占比")
+    );
+    for (ext, bytes) in top {
+        let name = if ext.is_empty() { tr("(none)", "（无扩展名）").to_string() } else { format!(".{}", ext) };
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td></tr>",
+            escape(&name),
+            bytes.files,
+            format_bytes(bytes.bytes),
+            bytes.bytes as f64 * 100.0 / stats.bytes_copied as f64
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// 失败和被锁定的文件
+fn write_failures(html: &mut String, stats: &BackupStats) {
+    if stats.errors.is_empty() && stats.locked_files.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        html,
+        "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th></tr>",
+        tr("Failures", "失败的文件"),
+        tr("Path", "路径"),
+        tr("Error", "原因")
+    );
+    for error in &stats.errors {
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"error\">{}</td></tr>",
+            escape(&error.path.to_string_lossy()),
+            escape(&error.error)
+        );
+    }
+    for path in &stats.locked_files {
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"path\">{}</td><td class=\"error\">{}</td></tr>",
+            escape(&path.to_string_lossy()),
+            tr("locked by another process", "被其他进程锁定")
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// 转义 HTML 特殊字符
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,98 @@
+// Recall - 快照恢复模块
+// 从本地或远程备份目标中选择快照并恢复到指定目录，供命令行和其他程序调用
+
+use crate::backend::{RemoteSnapshots, RestoreStats};
+use crate::error::{Classify, RecallError, Result};
+use crate::events::EventSink;
+use crate::naming::{is_incomplete, SnapshotNaming};
+use chrono::NaiveDateTime;
+use std::path::Path;
+
+/// 恢复选项
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// 要恢复的快照名称（为空时恢复 `current` 指向的快照，没有时恢复名称最新的完整快照）
+    pub snapshot: Option<String>,
+
+    /// 试运行：只统计将恢复的文件，不写入任何内容
+    pub dry_run: bool,
+}
+
+/// 一次恢复的结果
+#[derive(Debug)]
+pub struct RestoreReport {
+    /// 恢复的快照名称
+    pub snapshot: String,
+
+    /// 恢复统计（试运行时只有文件数量和字节数，为快照中的总量）
+    pub stats: RestoreStats,
+}
+
+/// 将快照恢复到本地目录
+///
+/// 目标中已存在的同名文件会被覆盖，目标中多出的文件保持不变。
+///
+/// # 参数
+/// * `repo` - 备份目标（本地快照目录和归档、分块仓库或 WebDAV）
+/// * `naming` - 快照命名格式（选择最新快照时使用）
+/// * `target` - 恢复到的目录
+/// * `options` - 恢复选项
+/// * `events` - 每恢复一个文件调用一次 [`EventSink::file_restored`]
+///
+/// # 返回
+/// * `Ok(RestoreReport)` - 恢复的快照和统计
+/// * `Err(RecallError)` - 备份目标中没有快照、快照不存在或写入失败
+///
+/// # 示例
+/// ```
+/// use recall::archive::LocalSnapshots;
+/// use recall::events::NullSink;
+/// use recall::naming::SnapshotNaming;
+/// use recall::restore::{restore, RestoreOptions};
+///
+/// let backups = tempfile::tempdir().unwrap();
+/// let snapshot = backups.path().join("2024-01-15_10-30-00");
+/// std::fs::create_dir(&snapshot).unwrap();
+/// std::fs::write(snapshot.join("a.txt"), "hello").unwrap();
+///
+/// let target = tempfile::tempdir().unwrap();
+/// let repo = LocalSnapshots::new(backups.path());
+/// let report = restore(&repo, &SnapshotNaming::default(), target.path(), &RestoreOptions::default(), &NullSink).unwrap();
+/// assert_eq!(report.snapshot, "2024-01-15_10-30-00");
+/// assert_eq!(report.stats.files, 1);
+/// assert!(target.path().join("a.txt").is_file());
+/// ```
+pub fn restore(
+    repo: &dyn RemoteSnapshots,
+    naming: &SnapshotNaming,
+    target: &Path,
+    options: &RestoreOptions,
+    events: &dyn EventSink,
+) -> Result<RestoreReport> {
+    let snapshot = match options.snapshot.clone().or_else(|| repo.current()) {
+        Some(name) => name,
+        // 没有 current 时选择最新的完整快照，只有不完整的快照时才选择不完整的
+        None => match pick_latest(repo.snapshots(naming).classify(RecallError::Destination)?) {
+            Some(name) => name,
+            None => {
+                return Err(RecallError::Destination(anyhow::anyhow!("No backups found in {}", repo.describe())))
+            }
+        },
+    };
+
+    let stats = if options.dry_run {
+        let (files, bytes) = repo.totals(&snapshot).classify(RecallError::Destination)?;
+        RestoreStats { files, bytes, ..Default::default() }
+    } else {
+        repo.restore(&snapshot, target, events).classify(RecallError::Copy)?
+    };
+    Ok(RestoreReport { snapshot, stats })
+}
+
+/// 选择最新的完整快照（没有完整快照时选择最新的不完整快照）
+fn pick_latest(mut snapshots: Vec<(NaiveDateTime, String)>) -> Option<String> {
+    match snapshots.iter().rposition(|(_, name)| !is_incomplete(name)) {
+        Some(index) => Some(snapshots.swap_remove(index).1),
+        None => snapshots.pop().map(|(_, name)| name),
+    }
+}
@@ -0,0 +1,297 @@
+// Recall - S3 对象存储模块
+// 通过 S3 兼容接口（AWS S3、MinIO、Cloudflare R2、Backblaze B2 等）读写分块仓库中的对象，请求使用 SigV4 签名
+
+use crate::chunks::ObjectStore;
+use crate::credentials::{credential_target, get_password};
+use crate::http::{self, percent_encode, xml_values};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// S3 目标路径的前缀
+pub const SCHEME: &str = "s3://";
+
+/// S3 兼容对象存储
+pub struct S3Store {
+    /// 存储桶名称
+    bucket: String,
+
+    /// 对象键前缀（不含首尾的 `/`，可为空）
+    prefix: String,
+
+    /// 区域（如 `us-east-1`）
+    region: String,
+
+    /// 自定义服务地址（如 `http://localhost:9000`），设置时使用路径风格的 URL
+    endpoint: Option<String>,
+
+    /// 访问密钥 ID
+    access_key: String,
+
+    /// 访问密钥
+    secret_key: String,
+
+    /// HTTP 客户端
+    agent: ureq::Agent,
+}
+
+/// 检查备份目标是否为 S3 路径（`s3://bucket/prefix`）
+pub fn is_s3_destination(destination: &Path) -> bool {
+    destination.to_string_lossy().starts_with(SCHEME)
+}
+
+impl S3Store {
+    /// 根据备份目标打开 S3 存储
+    ///
+    /// 访问密钥 ID 取自配置的用户名，密钥保存在系统凭据管理器中（`recall credential set`）；
+    /// 未设置用户名时使用环境变量 `AWS_ACCESS_KEY_ID` 和 `AWS_SECRET_ACCESS_KEY`。
+    /// 区域和服务地址未配置时分别使用 `AWS_REGION` 和 `AWS_ENDPOINT_URL`。
+    ///
+    /// # 参数
+    /// * `destination` - 备份目标（`s3://bucket/prefix`）
+    /// * `username` - 访问密钥 ID
+    /// * `region` - 区域
+    /// * `endpoint` - 自定义服务地址
+    ///
+    /// # 返回
+    /// * `Ok(S3Store)` - 打开的存储
+    /// * `Err(anyhow::Error)` - 目标格式无效或缺少凭据
+    pub fn open(
+        destination: &Path,
+        username: Option<&str>,
+        region: Option<&str>,
+        endpoint: Option<&str>,
+    ) -> Result<Self> {
+        let (bucket, prefix) = parse_destination(destination)?;
+
+        let (access_key, secret_key) = match username {
+            Some(user) => (user.to_string(), get_password(&credential_target(destination), user)?),
+            None => (
+                std::env::var("AWS_ACCESS_KEY_ID")
+                    .context("No S3 credentials: set `username` in the profile or AWS_ACCESS_KEY_ID")?,
+                std::env::var("AWS_SECRET_ACCESS_KEY").context("AWS_SECRET_ACCESS_KEY is not set")?,
+            ),
+        };
+
+        let region = region
+            .map(str::to_string)
+            .or_else(|| std::env::var("AWS_REGION").ok())
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint = endpoint
+            .map(str::to_string)
+            .or_else(|| std::env::var("AWS_ENDPOINT_URL").ok())
+            .map(|e| e.trim_end_matches('/').to_string());
+
+        Ok(Self { bucket, prefix, region, endpoint, access_key, secret_key, agent: http::agent() })
+    }
+
+    /// 获取对象的完整键（附加前缀）
+    fn full_key(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{}", self.prefix, key)
+        }
+    }
+
+    /// 获取请求的主机名和路径（路径已编码）
+    fn host_and_path(&self, key: &str) -> (String, String, String) {
+        let encoded = percent_encode(key, false);
+        match self.endpoint {
+            Some(ref endpoint) => {
+                let (scheme, host) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+                (scheme.to_string(), host.to_string(), format!("/{}/{}", self.bucket, encoded))
+            }
+            None => (
+                "https".to_string(),
+                format!("{}.s3.{}.amazonaws.com", self.bucket, self.region),
+                format!("/{}", encoded),
+            ),
+        }
+    }
+
+    /// 发送签名后的请求，网络错误和服务端错误时重试
+    ///
+    /// # 参数
+    /// * `method` - HTTP 方法
+    /// * `key` - 对象键（已附加前缀；列举请求为空）
+    /// * `query` - 查询参数
+    /// * `body` - 请求体
+    ///
+    /// # 返回
+    /// * `Ok(Some(Response))` - 请求成功
+    /// * `Ok(None)` - 对象不存在（404）
+    /// * `Err(anyhow::Error)` - 请求失败
+    fn send(&self, method: &str, key: &str, query: &[(&str, &str)], body: &[u8]) -> Result<Option<ureq::Response>> {
+        match http::with_retry(|| self.send_once(method, key, query, body)).map_err(|e| *e) {
+            Ok(resp) => Ok(Some(resp)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(code, resp)) => {
+                let detail = resp.into_string().unwrap_or_default();
+                anyhow::bail!("S3 {} {} failed with HTTP {}: {}", method, key, code, error_message(&detail));
+            }
+            Err(ureq::Error::Transport(t)) => {
+                Err(anyhow::anyhow!("{}", t)).with_context(|| format!("S3 {} {} failed", method, key))
+            }
+        }
+    }
+
+    /// 发送一次签名请求
+    fn send_once(
+        &self,
+        method: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        body: &[u8],
+    ) -> std::result::Result<ureq::Response, Box<ureq::Error>> {
+        let (scheme, host, path) = self.host_and_path(key);
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body));
+
+        let mut params: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (percent_encode(k, true), percent_encode(v, true)))
+            .collect();
+        params.sort();
+        let canonical_query = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, canonical_query, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut signing_key = hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        );
+
+        let mut url = format!("{}://{}{}", scheme, host, path);
+        if !canonical_query.is_empty() {
+            url.push('?');
+            url.push_str(&canonical_query);
+        }
+
+        let request = self
+            .agent
+            .request(method, &url)
+            .set("x-amz-date", &amz_date)
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("Authorization", &authorization);
+        let result = if method == "PUT" {
+            request.send_bytes(body)
+        } else {
+            request.call()
+        };
+        result.map_err(Box::new)
+    }
+}
+
+impl ObjectStore for S3Store {
+    fn describe(&self) -> String {
+        format!("{}{}/{}", SCHEME, self.bucket, self.prefix)
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.send("HEAD", &self.full_key(key), &[], &[])?.is_some())
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.send("PUT", &self.full_key(key), &[], data)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let full_key = self.full_key(key);
+        let resp = self
+            .send("GET", &full_key, &[], &[])?
+            .with_context(|| format!("Object {} not found in bucket {}", full_key, self.bucket))?;
+        let mut data = Vec::new();
+        resp.into_reader()
+            .read_to_end(&mut data)
+            .with_context(|| format!("Failed to download {}", full_key))?;
+        Ok(data)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let full_prefix = self.full_key(prefix);
+        let strip = if self.prefix.is_empty() { 0 } else { self.prefix.len() + 1 };
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", full_prefix.as_str())];
+            if let Some(ref t) = token {
+                query.push(("continuation-token", t.as_str()));
+            }
+            let body = match self.send("GET", "", &query, &[])? {
+                Some(resp) => resp.into_string().context("Failed to read S3 listing")?,
+                None => anyhow::bail!("Bucket {} does not exist", self.bucket),
+            };
+
+            keys.extend(xml_values(&body, "Key").into_iter().map(|k| k[strip..].to_string()));
+            token = xml_values(&body, "NextContinuationToken").into_iter().next();
+            if token.is_none() {
+                break;
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+/// 解析 `s3://bucket/prefix` 形式的目标
+///
+/// Windows 下拼接的项目名使用 `\` 分隔，统一转换为 `/`。
+fn parse_destination(destination: &Path) -> Result<(String, String)> {
+    let dest = destination.to_string_lossy().replace('\\', "/");
+    let rest = dest
+        .strip_prefix(SCHEME)
+        .with_context(|| format!("Not an S3 destination: {}", dest))?;
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    if bucket.is_empty() {
+        anyhow::bail!("Missing bucket name in {}", dest);
+    }
+    let prefix = prefix
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok((bucket.to_string(), prefix))
+}
+
+/// 计算 HMAC-SHA256
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 从 S3 错误响应中提取错误信息
+fn error_message(body: &str) -> String {
+    xml_values(body, "Message")
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| body.trim().to_string())
+}
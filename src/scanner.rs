@@ -3,15 +3,26 @@
 
 use crate::actions::{FileTask, SyncAction};
 use crate::config::BackupConfig;
-use crate::hasher::calculate_hash;
-use crate::utils::{matches_exclude_pattern, to_verbatim_path};
-use anyhow::{Context, Result};
-use chrono::NaiveDateTime;
+use crate::events::EventSink;
+use crate::executor::CURRENT_MARKER_FILE;
+use crate::hasher::{calculate_hash, calculate_snapshot_hash};
+use crate::metadata::metadata_differs;
+use crate::progress;
+use crate::utils::{matching_exclude_pattern, name_key, to_verbatim_path};
+use crate::error::{Classify, RecallError, Result};
+use anyhow::Context;
+use crate::naming::{is_incomplete, SnapshotNaming};
 use crossbeam_channel::Sender;
+use crate::storage::cpu_count;
 use glob::Pattern;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use walkdir::WalkDir;
 
 #[cfg(unix)]
@@ -19,45 +30,95 @@ use std::os::unix::fs::PermissionsExt;
 
 /// 查找最新的备份目录
 ///
-/// 在目标目录中查找最新的备份文件夹（按时间戳排序）。
+/// 在目标目录中查找最新的备份文件夹（按名称中的时间戳排序），优先选择完整的快照：
+/// 带 `-incomplete` 后缀的快照只在没有完整快照时使用。没有名称可按命名规则解析的快照时
+/// （如修改了名称格式），使用 `current` 链接或标记文件指向的快照（见 [`current_snapshot`]）。
 ///
 /// # 参数
 /// * `destination` - 备份目标根目录
+/// * `naming` - 快照命名规则
 ///
 /// # 返回
 /// * `Ok(Some(PathBuf))` - 找到的最新备份路径
 /// * `Ok(None)` - 没有找到有效备份
-/// * `Err(anyhow::Error)` - 读取目录失败
-pub fn find_latest_backup(destination: &Path) -> Result<Option<PathBuf>> {
-    if !destination.exists() {
-        return Ok(None);
-    }
-
-    let mut backups: Vec<PathBuf> = fs::read_dir(destination)
-        .context("can not read the backup dir")?
-        .filter_map(|entry| entry.ok())
-        .map(|entry| entry.path())
-        .filter(|path| {
-            path.is_dir()
-                && !path.to_string_lossy().ends_with(".partial")
-                && path.file_name().is_some_and(|n| n != "current")
-                && is_valid_backup_folder_name(path)
-        })
-        .collect();
-
-    backups.sort();
+/// * `Err(RecallError)` - 读取目录失败
+pub fn find_latest_backup(destination: &Path, naming: &SnapshotNaming) -> Result<Option<PathBuf>> {
+    let mut snapshots = naming.list(destination, false).classify(RecallError::Destination)?;
+    let complete = snapshots
+        .iter()
+        .rposition(|(_, path)| !path.file_name().and_then(|n| n.to_str()).is_some_and(is_incomplete));
+    let latest = match complete {
+        Some(index) => Some(snapshots.swap_remove(index).1),
+        None => snapshots.pop().map(|(_, path)| path),
+    };
+    Ok(latest.or_else(|| current_snapshot(destination)))
+}
 
-    Ok(backups.last().cloned())
+/// 读取 `current` 指向的快照目录
+///
+/// 依次尝试 `current` 符号链接（Windows 上也可能是目录联接）和记录快照名称的
+/// [`CURRENT_MARKER_FILE`]（目标不支持符号链接时由提交备份写入）。
+///
+/// # 参数
+/// * `destination` - 备份目标根目录
+///
+/// # 返回
+/// * `Some(PathBuf)` - 指向的快照目录（存在）
+/// * `None` - 没有 `current`，或指向的快照已被删除
+///
+/// # 示例
+/// ```
+/// use recall::scanner::current_snapshot;
+///
+/// let dest = tempfile::tempdir().unwrap();
+/// std::fs::create_dir(dest.path().join("2024-01-15_10-30-00")).unwrap();
+/// std::fs::write(dest.path().join("current.txt"), "2024-01-15_10-30-00\n").unwrap();
+/// assert_eq!(current_snapshot(dest.path()), Some(dest.path().join("2024-01-15_10-30-00")));
+/// ```
+pub fn current_snapshot(destination: &Path) -> Option<PathBuf> {
+    let name = fs::read_link(destination.join("current"))
+        .ok()
+        .and_then(|target| target.file_name().map(|n| n.to_os_string()))
+        .or_else(|| {
+            fs::read_to_string(destination.join(CURRENT_MARKER_FILE))
+                .ok()
+                .map(|content| content.trim().into())
+                .filter(|name: &std::ffi::OsString| !name.is_empty())
+        })?;
+    Some(destination.join(name)).filter(|path| path.is_dir())
 }
 
-/// 检查目录名称是否是有效的备份文件夹名称
+/// 查找可续传的未完成备份目录
+///
+/// 在目标目录中查找最新的 `.partial` 目录。只有当它比最新的完整备份更新时
+/// 才可续传，否则说明它是更早之前遗留的残留目录。
+///
+/// # 参数
+/// * `destination` - 备份目标根目录
+/// * `latest_backup` - 最新的完整备份（如有）
+/// * `naming` - 快照命名规则
 ///
-/// 有效格式：`YYYY-MM-DD_HH-MM-SS`
-fn is_valid_backup_folder_name(path: &Path) -> bool {
-    path.file_name()
+/// # 返回
+/// * `Ok(Some(PathBuf))` - 可续传的 `.partial` 目录
+/// * `Ok(None)` - 没有可续传的目录
+/// * `Err(RecallError)` - 读取目录失败
+pub fn find_resumable_partial(
+    destination: &Path,
+    latest_backup: Option<&Path>,
+    naming: &SnapshotNaming,
+) -> Result<Option<PathBuf>> {
+    let (newest_time, newest) = match naming.list(destination, true).classify(RecallError::Destination)?.pop() {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+
+    let latest_time = latest_backup
+        .and_then(|lb| lb.file_name())
         .and_then(|n| n.to_str())
-        .map(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d_%H-%M-%S").is_ok())
-        .unwrap_or(false)
+        .and_then(|n| naming.parse(n));
+    let newer_than_latest = latest_time.is_none_or(|t| newest_time > t);
+
+    Ok(newer_than_latest.then_some(newest))
 }
 
 /// 扫描源目录并生成文件任务
@@ -70,22 +131,184 @@ fn is_valid_backup_folder_name(path: &Path) -> bool {
 /// * `current_backup_dir` - 当前备份的目标目录
 /// * `latest_backup` - 最新备份路径（用于增量备份）
 /// * `tx` - 任务发送通道
+/// * `events` - 事件接收者（决定的操作、无法访问的路径、无效的排除模式）
 ///
 /// # 返回
-/// * `Ok(())` - 扫描完成
-/// * `Err(anyhow::Error)` - 扫描失败
+/// * `Ok(ScanTotals)` - 扫描完成，返回被排除的条目数量和各阶段耗时
+/// * `Err(RecallError)` - 扫描失败
 ///
 /// # 流程
 /// 1. 编译排除模式
 /// 2. 遍历源目录（跳过排除的文件）
-/// 3. 为每个文件/目录创建任务并决定操作
+/// 3. 为每个文件/目录创建任务并决定操作（启用内容检查时在独立的哈希线程池中并行进行）
 /// 4. 通过通道发送任务
 pub fn scan_source(
     config: &BackupConfig,
     current_backup_dir: &Path,
     latest_backup: Option<&Path>,
     tx: Sender<(FileTask, SyncAction)>,
-) -> Result<()> {
+    events: &dyn EventSink,
+) -> Result<ScanTotals> {
+    let _span = tracing::info_span!("scan", source = %config.source.display()).entered();
+    let started = Instant::now();
+    let counters = Arc::new(ScanCounters::default());
+    let tasks = walk_source(config, current_backup_dir, latest_backup, events, counters.clone());
+
+    // 决定操作并通过通道发送任务，返回 false 表示接收端已关闭
+    let dispatch = |task: FileTask| -> bool {
+        let decide_started = Instant::now();
+        let action = decide_action(&task, config);
+        if config.check_content {
+            // 启用内容检查时决策的耗时主要是计算哈希
+            counters.hash_nanos.fetch_add(decide_started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        }
+        tracing::trace!(path = %task.rel_path.display(), action = action.name(), "decided");
+        events.action_decided(&task, &action);
+        tx.send((task, action)).is_ok()
+    };
+
+    if config.check_content {
+        // 内容检查需要计算哈希，属于 CPU 密集型操作：
+        // 使用独立的线程池并行决策，既不让单线程扫描成为瓶颈，也不占用执行器的 I/O 线程
+        let hash_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(cpu_count())
+            .build()
+            .context("Failed to build hash thread pool")
+            .classify(RecallError::Scan)?;
+
+        let result = hash_pool.install(|| {
+            tasks.par_bridge().try_for_each(|task| match task {
+                Ok(task) => {
+                    if dispatch(task) {
+                        Ok(())
+                    } else {
+                        Err(None) // 接收端已关闭，停止扫描
+                    }
+                }
+                Err(e) => Err(Some(e)),
+            })
+        });
+
+        if let Err(Some(e)) = result {
+            return Err(RecallError::Scan(e));
+        }
+    } else {
+        for task in tasks {
+            if !dispatch(task.classify(RecallError::Scan)?) {
+                break; // 接收端已关闭，停止扫描
+            }
+        }
+    }
+
+    progress::finish_scan();
+    let totals = ScanTotals {
+        excluded: counters.excluded.load(Ordering::Relaxed),
+        elapsed: started.elapsed(),
+        hash_time: Duration::from_nanos(counters.hash_nanos.load(Ordering::Relaxed)),
+    };
+    tracing::debug!(excluded = totals.excluded, elapsed = ?totals.elapsed, hash_time = ?totals.hash_time, "scan finished");
+
+    // 修改时间在未来的文件（相机时钟错误、时区问题）：汇总为一条警告，而不是逐个文件报告
+    let future = counters.future.load(Ordering::Relaxed);
+    if let Some(example) = counters.first_future.lock().ok().and_then(|p| p.clone()) {
+        events.warning(&crate::trf!(
+            "Warning: {} file(s) have modification times in the future (e.g. {}); check the system clock or the files' timestamps",
+            "警告：{} 个文件的修改时间在未来（如 {}），请检查系统时钟或文件的时间戳",
+            future,
+            example.display()
+        ));
+    }
+    Ok(totals)
+}
+
+/// 一次扫描的结果
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanTotals {
+    /// 被排除模式跳过的条目数量（排除的目录只计一次）
+    pub excluded: u64,
+
+    /// 扫描耗时（从开始遍历到发出最后一个任务，包括等待执行器接收任务的时间）
+    pub elapsed: Duration,
+
+    /// 启用内容检查时比较哈希的累计耗时（所有哈希线程之和）
+    pub hash_time: Duration,
+}
+
+/// 逐个扫描源目录并决定每个条目的同步操作，不创建线程和通道
+///
+/// 与 [`scan_source`] 的结果相同，但只在迭代时才遍历目录和比较文件，适合只需要备份计划
+/// （或其中一部分）的程序和测试。启用内容检查时哈希在当前线程中逐个计算。
+///
+/// # 参数
+/// * `config` - 备份配置
+/// * `current_backup_dir` - 任务的目标目录（只用于构造目标路径，不会写入）
+/// * `latest_backup` - 最新备份路径（用于增量备份）
+/// * `events` - 事件接收者（决定的操作、无法访问的路径、无效的排除模式）
+///
+/// # 返回
+/// 每个条目的文件任务和同步操作；无法计算相对路径时产生 `Err(RecallError::Scan)`
+///
+/// # 示例
+/// ```
+/// use recall::actions::SyncAction;
+/// use recall::config::BackupConfig;
+/// use recall::events::NullSink;
+/// use recall::scanner::scan_tasks;
+///
+/// let source = tempfile::tempdir().unwrap();
+/// std::fs::write(source.path().join("a.txt"), "hello").unwrap();
+/// let config = BackupConfig::builder(source.path(), "/backups").read_ignore_files(false).finish().unwrap();
+///
+/// let new_files = scan_tasks(&config, "/backups/new".as_ref(), None, &NullSink)
+///     .filter_map(Result::ok)
+///     .filter(|(_, action)| matches!(action, SyncAction::CopyNew))
+///     .count();
+/// assert_eq!(new_files, 1);
+/// ```
+pub fn scan_tasks<'a>(
+    config: &'a BackupConfig,
+    current_backup_dir: &'a Path,
+    latest_backup: Option<&'a Path>,
+    events: &'a dyn EventSink,
+) -> impl Iterator<Item = Result<(FileTask, SyncAction)>> + 'a {
+    walk_source(config, current_backup_dir, latest_backup, events, Arc::default()).map(move |task| {
+        let task = task.classify(RecallError::Scan)?;
+        let action = decide_action(&task, config);
+        tracing::trace!(path = %task.rel_path.display(), action = action.name(), "decided");
+        events.action_decided(&task, &action);
+        Ok((task, action))
+    })
+}
+
+/// 修改时间超过当前时间多久才视为在未来（容忍源和本机之间的少量时钟偏差）
+const FUTURE_MTIME_TOLERANCE: Duration = Duration::from_secs(10 * 60);
+
+/// 扫描过程中的计数
+#[derive(Default)]
+struct ScanCounters {
+    /// 被排除模式跳过的条目数量
+    excluded: AtomicU64,
+
+    /// 启用内容检查时决定操作（计算哈希）的累计耗时（纳秒）
+    hash_nanos: AtomicU64,
+
+    /// 修改时间在未来的文件数量
+    future: AtomicU64,
+
+    /// 第一个修改时间在未来的文件（相对路径）
+    first_future: Mutex<Option<PathBuf>>,
+}
+
+/// 遍历源目录（跳过排除的条目和无法访问的路径），为每个条目创建文件任务
+///
+/// 被排除的条目和修改时间在未来的文件记入 `counters`。
+fn walk_source<'a>(
+    config: &'a BackupConfig,
+    current_backup_dir: &'a Path,
+    latest_backup: Option<&'a Path>,
+    events: &'a dyn EventSink,
+    counters: Arc<ScanCounters>,
+) -> impl Iterator<Item = anyhow::Result<FileTask>> + Send + 'a {
     // 编译 Glob 模式以提高性能
     let compiled_patterns: Vec<Pattern> = config
         .exclude_patterns
@@ -93,59 +316,197 @@ pub fn scan_source(
         .filter_map(|s| match Pattern::new(s) {
             Ok(p) => Some(p),
             Err(e) => {
-                eprintln!("Warning: Invalid glob pattern '{}': {}", s, e);
+                events.warning(&crate::trf!(
+                    "Warning: Invalid glob pattern '{}': {}",
+                    "警告：无效的匹配模式 '{}'：{}",
+                    s, e
+                ));
                 None
             }
         })
         .collect();
 
     // 创建目录遍历器，不跟随符号链接
+    let filter_counters = counters.clone();
     let walker = WalkDir::new(&config.source)
         .follow_links(false)
         .into_iter()
-        .filter_entry(|e| {
+        .filter_entry(move |e| {
             let path = e.path();
             if let Ok(rel) = path.strip_prefix(&config.source) {
-                !matches_exclude_pattern(rel, &compiled_patterns)
+                let pattern = matching_exclude_pattern(rel, &compiled_patterns);
+                if let Some(pattern) = pattern {
+                    tracing::debug!(path = %rel.display(), pattern = pattern.as_str(), "excluded");
+                    filter_counters.excluded.fetch_add(1, Ordering::Relaxed);
+                    events.path_excluded(rel, pattern.as_str());
+                }
+                pattern.is_none()
             } else {
                 true
             }
         });
 
-    // 遍历所有条目
-    for entry in walker {
+    let mut previous = latest_backup.map(PreviousSnapshot::new);
+
+    // 遍历所有条目，生成文件任务
+    walker.filter_map(move |entry| {
         let entry = match entry {
             Ok(e) => e,
             Err(err) => {
-                eprintln!("警告: 无法访问 {:?}: {}", err.path(), err);
-                continue;
+                tracing::warn!(path = ?err.path(), error = %err, "path inaccessible");
+                events.path_inaccessible(err.path(), &err.to_string());
+                return None;
             }
         };
 
         let path = entry.path();
 
         // 计算相对路径
-        let rel_path = path
-            .strip_prefix(&config.source)
-            .context("无法计算相对路径")?
-            .to_path_buf();
+        let rel_path = match path.strip_prefix(&config.source).context("无法计算相对路径") {
+            Ok(rel) => rel.to_path_buf(),
+            Err(e) => return Some(Err(e)),
+        };
 
         // 转换为逐字路径（Windows 支持长路径）
         let src_path = to_verbatim_path(path);
         let dest_path = to_verbatim_path(&current_backup_dir.join(&rel_path));
-        let old_path = latest_backup.map(|lb| to_verbatim_path(&lb.join(&rel_path)));
+        let old_path = previous.as_mut().map(|p| to_verbatim_path(&p.resolve(&rel_path, entry.file_type().is_dir())));
 
-        // 创建文件任务并决定操作
-        let task = FileTask::new(rel_path, src_path, dest_path, old_path);
-        let action = decide_action(&task, config);
+        // 文件大小用于统计硬链接的数据量和进度事件（Windows 上遍历目录时已读取，无需额外查询）
+        let size = if entry.file_type().is_dir() {
+            0
+        } else {
+            let meta = entry.metadata().ok();
+            let future = meta
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .is_some_and(|t| t > SystemTime::now() + FUTURE_MTIME_TOLERANCE);
+            if future {
+                tracing::debug!(path = %rel_path.display(), "modification time in the future");
+                if counters.future.fetch_add(1, Ordering::Relaxed) == 0 {
+                    if let Ok(mut first) = counters.first_future.lock() {
+                        *first = Some(rel_path.clone());
+                    }
+                }
+            }
+            meta.map(|m| m.len()).unwrap_or(0)
+        };
+        if progress::enabled() {
+            progress::record_scanned(size);
+        }
+
+        Some(Ok(FileTask::new(rel_path, src_path, dest_path, old_path, size)))
+    })
+}
+
+/// 在上一个快照中查找源条目对应的路径
+///
+/// 名称完全相同的条目优先；不存在时按 [`name_key`] 匹配只修改了大小写或 Unicode 规范化形式
+/// （NFC / NFD）不同的条目，这样改名后的文件仍能与旧文件比较并创建硬链接。
+/// 多个旧条目的键相同时（区分大小写的文件系统上同时存在 `a` 和 `A`）不做匹配。
+///
+/// 只缓存当前条目所在目录及其上级目录的名称，内存占用与目录深度有关，而不是快照大小。
+struct PreviousSnapshot<'a> {
+    /// 上一个快照的根目录
+    root: &'a Path,
+
+    /// 当前路径上的目录（从根目录开始）
+    stack: Vec<PreviousDir>,
+}
+
+/// 上一个快照中与源目录对应的目录
+struct PreviousDir {
+    /// 源目录的相对路径
+    rel_path: PathBuf,
+
+    /// 上一个快照中对应的目录
+    path: PathBuf,
+
+    /// 目录中的名称（首次查找时读取）
+    names: Option<DirNames>,
+}
+
+/// 目录中的名称
+#[derive(Default)]
+struct DirNames {
+    /// 实际名称
+    exact: HashSet<OsString>,
 
-        // 通过通道发送任务
-        if tx.send((task, action)).is_err() {
-            break; // 接收端已关闭，停止扫描
+    /// 比较键 → 实际名称（键冲突时为 `None`）
+    folded: HashMap<String, Option<OsString>>,
+}
+
+impl DirNames {
+    /// 读取目录中的名称（目录不存在或无法读取时为空）
+    fn read(dir: &Path) -> Self {
+        let mut names = Self::default();
+        for entry in fs::read_dir(to_verbatim_path(dir)).into_iter().flatten().flatten() {
+            let name = entry.file_name();
+            if let Some(key) = name_key(&name) {
+                names
+                    .folded
+                    .entry(key)
+                    .and_modify(|actual| *actual = None)
+                    .or_insert_with(|| Some(name.clone()));
+            }
+            names.exact.insert(name);
         }
+        names
+    }
+
+    /// 查找与 `name` 对应的实际名称
+    fn find<'s>(&'s self, name: &'s OsStr) -> Option<&'s OsStr> {
+        if self.exact.contains(name) {
+            return Some(name);
+        }
+        self.folded.get(&name_key(name)?)?.as_deref()
+    }
+}
+
+impl<'a> PreviousSnapshot<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self { root, stack: Vec::new() }
     }
 
-    Ok(())
+    /// 返回源条目在上一个快照中对应的路径（找不到时为直接拼接的路径，该路径不存在）
+    ///
+    /// 条目必须按遍历顺序（父目录先于其内容）传入。
+    ///
+    /// # 参数
+    /// * `rel_path` - 源条目的相对路径
+    /// * `is_dir` - 源条目是否为目录
+    fn resolve(&mut self, rel_path: &Path, is_dir: bool) -> PathBuf {
+        let old = match (rel_path.parent(), rel_path.file_name()) {
+            (Some(parent), Some(name)) => {
+                // 离开了之前的目录：只保留当前条目的上级目录
+                while self.stack.last().is_some_and(|d| d.rel_path != parent) {
+                    self.stack.pop();
+                }
+                match self.stack.last_mut() {
+                    Some(dir) => {
+                        let names = dir.names.get_or_insert_with(|| DirNames::read(&dir.path));
+                        match names.find(name) {
+                            Some(actual) if actual != name => {
+                                tracing::debug!(path = %rel_path.display(), previous = ?actual, "matched renamed entry");
+                                dir.path.join(actual)
+                            }
+                            _ => dir.path.join(name),
+                        }
+                    }
+                    None => self.root.join(rel_path),
+                }
+            }
+            // 源目录本身
+            _ => {
+                self.stack.clear();
+                self.root.to_path_buf()
+            }
+        };
+        if is_dir {
+            self.stack.push(PreviousDir { rel_path: rel_path.to_path_buf(), path: old.clone(), names: None });
+        }
+        old
+    }
 }
 
 /// 决定对文件执行何种同步操作
@@ -153,12 +514,14 @@ pub fn scan_source(
 /// 根据源文件和旧备份的状态比较，决定需要执行的操作。
 ///
 /// # 决策逻辑
+/// 0. **续传**：目标中已存在完整文件时，直接保留
 /// 1. **首次备份**：如果 `old_path` 为 `None`，直接复制新文件
 /// 2. **文件不存在**：如果旧备份中不存在该文件，复制新文件
 /// 3. **目录**：总是创建目录
 /// 4. **符号链接**：重新创建符号链接
 /// 5. **大小不同**：文件已修改，需要复制
 /// 6. **权限不同**（Unix）：文件已修改，需要复制
+///    - 启用 `preserve_metadata` 时，所有者或扩展属性不同也视为修改
 /// 7. **修改时间**：
 ///    - 如果差异小于 1 秒，认为未修改
 ///    - 如果未修改且未启用内容检查，使用硬链接
@@ -166,6 +529,7 @@ pub fn scan_source(
 ///    - 计算源文件和旧文件的哈希值
 ///    - 哈希相同：使用硬链接
 ///    - 哈希不同：复制修改的文件
+/// 9. **块克隆**：目标支持 reflink 时，已修改文件改为从旧备份克隆（`Clone`）
 ///
 /// # 参数
 /// * `task` - 文件任务
@@ -174,6 +538,11 @@ pub fn scan_source(
 /// # 返回
 /// 需要执行的同步操作
 pub fn decide_action(task: &FileTask, config: &BackupConfig) -> SyncAction {
+    // 续传时，目标中已完整存在的文件无需再次处理
+    if config.resume && !task.src_path.is_dir() && is_already_complete(task, config) {
+        return SyncAction::Resumed;
+    }
+
     let old_path = match &task.old_path {
         Some(p) => p,
         None => {
@@ -244,19 +613,26 @@ pub fn decide_action(task: &FileTask, config: &BackupConfig) -> SyncAction {
 
     // 文件大小不同，已修改
     if src_meta.len() != old_meta.len() {
-        return SyncAction::CopyModified;
+        return modified_action(old_path, config);
     }
 
     // Unix: 检查权限
     #[cfg(unix)]
     {
-        let src_mode = src_meta.permissions().mode();
-        let old_mode = old_meta.permissions().mode();
+        // 只读快照中的文件没有写权限位，比较时忽略
+        let mask = if config.read_only { !0o222 } else { !0 };
+        let src_mode = src_meta.permissions().mode() & mask;
+        let old_mode = old_meta.permissions().mode() & mask;
         if src_mode != old_mode {
-             return SyncAction::CopyModified;
+             return modified_action(old_path, config);
         }
     }
 
+    // 启用元数据保留时，所有者或扩展属性不同也视为已修改
+    if config.preserve_metadata && metadata_differs(&task.src_path, old_path) {
+        return modified_action(old_path, config);
+    }
+
     // 检查修改时间
     let src_mtime = src_meta.modified().ok();
     let old_mtime = old_meta.modified().ok();
@@ -281,20 +657,108 @@ pub fn decide_action(task: &FileTask, config: &BackupConfig) -> SyncAction {
     // 如果启用内容检查，比较哈希值
     if config.check_content {
         let src_hash = calculate_hash(&task.src_path);
-        let old_hash = calculate_hash(old_path);
+        let old_hash = calculate_snapshot_hash(old_path);
 
         match (src_hash, old_hash) {
             (Ok(s), Ok(o)) if s == o => {
                 return SyncAction::Link(old_path.clone());
             }
             (Ok(_), Ok(_)) => {
-                return SyncAction::CopyModified;
+                return modified_action(old_path, config);
             }
             _ => {
-                return SyncAction::CopyModified;
+                return modified_action(old_path, config);
             }
         }
     }
 
-    SyncAction::CopyModified
+    modified_action(old_path, config)
+}
+
+/// 检查续传目录中的目标文件是否已完整
+///
+/// 目标文件必须与源文件大小一致、修改时间相差小于 1 秒；
+/// 启用内容检查时还要求哈希值相同。符号链接则要求指向相同目标。
+fn is_already_complete(task: &FileTask, config: &BackupConfig) -> bool {
+    let (src_meta, dest_meta) = match (
+        fs::symlink_metadata(&task.src_path),
+        fs::symlink_metadata(&task.dest_path),
+    ) {
+        (Ok(s), Ok(d)) => (s, d),
+        _ => return false,
+    };
+
+    if src_meta.is_symlink() || dest_meta.is_symlink() {
+        return src_meta.is_symlink()
+            && dest_meta.is_symlink()
+            && fs::read_link(&task.src_path).ok() == fs::read_link(&task.dest_path).ok();
+    }
+
+    if src_meta.len() != dest_meta.len() {
+        return false;
+    }
+
+    let mtime_match = match (src_meta.modified(), dest_meta.modified()) {
+        (Ok(src), Ok(dest)) => {
+            let diff = if src > dest {
+                src.duration_since(dest).unwrap_or(Duration::ZERO)
+            } else {
+                dest.duration_since(src).unwrap_or(Duration::ZERO)
+            };
+            diff.as_millis() < 1000
+        }
+        _ => false,
+    };
+
+    if !mtime_match {
+        return false;
+    }
+
+    if config.check_content {
+        return match (calculate_hash(&task.src_path), calculate_snapshot_hash(&task.dest_path)) {
+            (Ok(s), Ok(d)) => s == d,
+            _ => false,
+        };
+    }
+
+    true
+}
+
+/// 已修改文件的同步操作
+///
+/// 目标文件系统支持块克隆时，从上一次备份克隆后只写入变化的数据块；
+/// 否则完整复制源文件。
+fn modified_action(old_path: &Path, config: &BackupConfig) -> SyncAction {
+    if config.reflink {
+        SyncAction::Clone(old_path.to_path_buf())
+    } else {
+        SyncAction::CopyModified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在目标目录中创建快照目录
+    fn snapshots(names: &[&str]) -> tempfile::TempDir {
+        let dest = tempfile::tempdir().unwrap();
+        for name in names {
+            fs::create_dir(dest.path().join(name)).unwrap();
+        }
+        dest
+    }
+
+    #[test]
+    fn latest_backup_prefers_complete_snapshots() {
+        let naming = SnapshotNaming::default();
+        let dest = snapshots(&["2024-01-15_10-30-00", "2024-01-16_10-30-00", "2024-01-17_10-30-00-incomplete"]);
+        let latest = find_latest_backup(dest.path(), &naming).unwrap();
+        assert_eq!(latest, Some(dest.path().join("2024-01-16_10-30-00")));
+
+        // 没有完整快照时才使用不完整的快照
+        let dest = snapshots(&["2024-01-15_10-30-00-incomplete", "2024-01-16_10-30-00-incomplete"]);
+        let latest = find_latest_backup(dest.path(), &naming).unwrap();
+        assert_eq!(latest, Some(dest.path().join("2024-01-16_10-30-00-incomplete")));
+    }
 }
@@ -0,0 +1,387 @@
+// Recall - 计划任务模块
+// 解析配置文件中的 cron 表达式并计算下一次运行时间，记录常驻进程（recall daemon）的运行状态
+
+use crate::store::AppConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 常驻进程状态文件名（位于配置文件所在目录）
+pub const DAEMON_STATUS_FILE: &str = "daemon.json";
+
+/// 查找下一次运行时间时最多向后搜索的天数（如 `0 0 29 2 *` 最长约 8 年一次）
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// cron 表达式（分 时 日 月 星期）
+///
+/// 支持 `*`、数值、范围（`1-5`）、列表（`1,15`）、步长（`*/15`、`0-30/10`），
+/// 月份和星期可以使用英文缩写（`jan`、`mon`），星期中 0 和 7 都表示星期日；
+/// 以及 `@hourly`、`@daily`、`@weekly`、`@monthly`、`@yearly` 简写。
+/// 与标准 cron 相同，日和星期都不以 `*` 开头时，满足其中之一即运行；否则两者都要满足。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    /// 允许的分钟（第 n 位表示 n）
+    minutes: u64,
+
+    /// 允许的小时
+    hours: u64,
+
+    /// 允许的日期（1 ~ 31）
+    days: u64,
+
+    /// 允许的月份（1 ~ 12）
+    months: u64,
+
+    /// 允许的星期（0 为星期日）
+    weekdays: u64,
+
+    /// 日字段是否以 `*` 开头（`*`、`*/2`）
+    any_day: bool,
+
+    /// 星期字段是否以 `*` 开头
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// 解析 cron 表达式
+    ///
+    /// # 参数
+    /// * `expr` - 五个字段的 cron 表达式或 `@daily` 等简写
+    ///
+    /// # 返回
+    /// * `Ok(CronSchedule)` - 解析结果
+    /// * `Err(anyhow::Error)` - 格式错误或数值超出范围
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::schedule::CronSchedule;
+    /// assert!(CronSchedule::parse("0 2 * * *").is_ok());
+    /// assert!(CronSchedule::parse("*/15 9-17 * * mon-fri").is_ok());
+    /// assert!(CronSchedule::parse("@weekly").is_ok());
+    /// assert!(CronSchedule::parse("60 * * * *").is_err());
+    /// assert!(CronSchedule::parse("0 2 * *").is_err());
+    /// ```
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expr = expr.trim();
+        let expanded = match expr.to_lowercase().as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            _ => expr,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            anyhow::bail!(
+                "Invalid schedule '{}': expected 5 fields (minute hour day month weekday)",
+                expr
+            );
+        }
+        let parse = |index: usize, min: u32, max: u32, names: &[&str]| {
+            parse_field(fields[index], min, max, names)
+                .with_context(|| format!("Invalid schedule '{}'", expr))
+        };
+
+        let mut weekdays = parse(4, 0, 7, &WEEKDAY_NAMES)?;
+        // 7 与 0 都表示星期日
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: parse(0, 0, 59, &[])?,
+            hours: parse(1, 0, 23, &[])?,
+            days: parse(2, 1, 31, &[])?,
+            months: parse(3, 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        })
+    }
+
+    /// 检查日期是否满足日、月和星期字段
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        // 与 Vixie cron 相同：任一字段以 `*` 开头时两者都要满足（`*` 本身匹配所有取值）
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// 计算晚于指定时间的下一次运行时间
+    ///
+    /// 夏令时切换时不存在的本地时间改在切换后的第一个有效时刻运行，重复的本地时间取较早的一次。
+    ///
+    /// # 参数
+    /// * `after` - 起始时间（不包含）
+    ///
+    /// # 返回
+    /// * `Some(DateTime<Local>)` - 下一次运行时间
+    /// * `None` - 表达式永远不会匹配（如 `0 0 31 2 *`）
+    ///
+    /// # 示例
+    /// ```
+    /// use chrono::{Local, TimeZone};
+    /// use recall::schedule::CronSchedule;
+    /// let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+    /// let now = Local.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+    /// let next = schedule.next_after(now).unwrap();
+    /// assert_eq!(next, Local.with_ymd_and_hms(2024, 1, 16, 2, 30, 0).unwrap());
+    /// ```
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        // 从下一个整分钟开始
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        let last = date + Duration::days(SEARCH_DAYS);
+
+        while date <= last {
+            if self.matches_date(date) {
+                for hour in 0..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    for minute in 0..60 {
+                        if self.minutes & (1 << minute) == 0 {
+                            continue;
+                        }
+                        let time = NaiveDateTime::new(date, chrono::NaiveTime::from_hms_opt(hour, minute, 0)?);
+                        if time < start {
+                            continue;
+                        }
+                        return match Local.from_local_datetime(&time) {
+                            LocalResult::Single(local) => Some(local),
+                            // 部分平台上两个候选时间的顺序不固定，按实际时刻取较早的一次
+                            LocalResult::Ambiguous(a, b) => Some(a.min(b)),
+                            // 时钟拨快跳过的时间：在跳过的区间结束后立即运行，而不是等到下一个匹配的日期
+                            LocalResult::None => (1..=24 * 60)
+                                .find_map(|minutes| Local.from_local_datetime(&(time + Duration::minutes(minutes))).earliest()),
+                        };
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+/// 月份缩写（下标 1 ~ 12）
+const MONTH_NAMES: [&str; 13] = ["", "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// 星期缩写（下标 0 ~ 6，0 为星期日）
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// 解析单个字段，返回允许的取值集合（位图）
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let lower = s.to_lowercase();
+        let n = match names.iter().position(|name| !name.is_empty() && *name == lower) {
+            Some(index) => index as u32,
+            None => s.parse().with_context(|| format!("'{}' is not a number", s))?,
+        };
+        if n < min || n > max {
+            anyhow::bail!("{} is out of range {}-{}", n, min, max);
+        }
+        Ok(n)
+    };
+
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().with_context(|| format!("Invalid step '{}'", step))?;
+                if step == 0 {
+                    anyhow::bail!("Step must be greater than 0");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let n = value(range)?;
+            // `5/10` 表示从 5 开始每隔 10
+            (n, if step > 1 { max } else { n })
+        };
+        if start > end {
+            anyhow::bail!("Invalid range '{}'", range);
+        }
+        for n in (start..=end).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+/// 常驻进程中单个配置文件的计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledProfile {
+    /// 配置文件名称
+    pub profile: String,
+
+    /// cron 表达式
+    pub schedule: String,
+
+    /// 下一次运行时间（`%Y-%m-%d %H:%M:%S`，表达式永远不匹配时为空）
+    pub next_run: Option<String>,
+
+    /// 最近一次运行的开始时间
+    pub last_run: Option<String>,
+
+    /// 最近一次运行的结果：`ok`、`partial` 或 `failed`
+    pub last_status: Option<String>,
+}
+
+/// 常驻进程状态
+///
+/// 常驻进程每分钟写入一次，`recall daemon --status` 读取它来显示计划和运行情况。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// 进程 ID
+    pub pid: u32,
+
+    /// 启动时间
+    pub started_at: String,
+
+    /// 状态更新时间
+    pub updated_at: String,
+
+    /// 正在运行的配置文件
+    pub running: Option<String>,
+
+    /// 所有带计划的配置文件
+    pub profiles: Vec<ScheduledProfile>,
+}
+
+impl DaemonStatus {
+    /// 状态文件路径（与配置文件位于同一目录）
+    pub fn path() -> Result<PathBuf> {
+        Ok(AppConfig::get_config_path()?.with_file_name(DAEMON_STATUS_FILE))
+    }
+
+    /// 写入状态文件（先写入临时文件再重命名，读取方不会看到写了一半的内容）
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {:?}", temp))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// 读取状态文件
+    ///
+    /// # 返回
+    /// * `Ok(Some(DaemonStatus))` - 状态
+    /// * `Ok(None)` - 常驻进程从未运行过
+    /// * `Err(anyhow::Error)` - 状态文件存在但无法读取
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        Ok(Some(serde_json::from_str(&content).with_context(|| format!("Invalid daemon status {:?}", path))?))
+    }
+
+    /// 状态更新时间（解析失败时为 `None`）
+    pub fn updated(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.updated_at, "%Y-%m-%d %H:%M:%S").ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    /// 构造本地时间；所有用例都使用有夏令时的同一时区，并行运行时互不影响
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        std::env::set_var("TZ", "America/New_York");
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).earliest().unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Local>) -> Option<NaiveDateTime> {
+        CronSchedule::parse(expr).unwrap().next_after(after).map(|t| t.naive_local())
+    }
+
+    fn naive(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn bits(set: u64) -> Vec<u32> {
+        (0..64).filter(|n| set & (1 << n) != 0).collect()
+    }
+
+    #[test]
+    fn step_fields() {
+        assert_eq!(bits(CronSchedule::parse("*/15 * * * *").unwrap().minutes), [0, 15, 30, 45]);
+        assert_eq!(bits(CronSchedule::parse("0-30/10 * * * *").unwrap().minutes), [0, 10, 20, 30]);
+        assert_eq!(bits(CronSchedule::parse("5/20 * * * *").unwrap().minutes), [5, 25, 45]);
+        assert_eq!(bits(CronSchedule::parse("0 */6 * * *").unwrap().hours), [0, 6, 12, 18]);
+        assert_eq!(bits(CronSchedule::parse("0 0 * * 5-7").unwrap().weekdays), [0, 5, 6, 7]);
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("30-10 * * * *").is_err());
+    }
+
+    #[test]
+    fn day_or_weekday() {
+        // 13 日或星期五
+        let expr = "0 0 13 * fri";
+        assert_eq!(next(expr, local(2024, 10, 1, 0, 0)), Some(naive(2024, 10, 4, 0, 0)));
+        assert_eq!(next(expr, local(2024, 10, 11, 0, 0)), Some(naive(2024, 10, 13, 0, 0)));
+        assert_eq!(next(expr, local(2024, 10, 13, 0, 0)), Some(naive(2024, 10, 18, 0, 0)));
+    }
+
+    #[test]
+    fn star_step_requires_both_fields() {
+        // `*/2` 以 `*` 开头：单数日且为星期一
+        let expr = "0 0 */2 * mon";
+        assert_eq!(next(expr, local(2024, 9, 1, 0, 0)), Some(naive(2024, 9, 9, 0, 0)));
+        assert_eq!(next(expr, local(2024, 9, 9, 0, 0)), Some(naive(2024, 9, 23, 0, 0)));
+
+        // 日为 `*` 时只看星期
+        assert_eq!(next("0 0 * * mon", local(2024, 9, 1, 0, 0)), Some(naive(2024, 9, 2, 0, 0)));
+    }
+
+    #[test]
+    fn dst_gap_runs_after_the_gap() {
+        // 2024-03-10 02:00 拨快到 03:00，02:30 不存在
+        let expr = "30 2 * * *";
+        let first = CronSchedule::parse(expr).unwrap().next_after(local(2024, 3, 10, 0, 0)).unwrap();
+        assert_eq!(first.naive_local(), naive(2024, 3, 10, 3, 0));
+        assert_eq!(first.with_timezone(&Utc).naive_utc(), naive(2024, 3, 10, 7, 0));
+
+        // 补上的运行之后回到正常时间
+        assert_eq!(next(expr, first), Some(naive(2024, 3, 11, 2, 30)));
+    }
+
+    #[test]
+    fn dst_overlap_runs_once() {
+        // 2024-11-03 02:00 拨回到 01:00，01:30 出现两次，取较早的一次
+        let expr = "30 1 * * *";
+        let first = CronSchedule::parse(expr).unwrap().next_after(local(2024, 11, 3, 0, 0)).unwrap();
+        assert_eq!(first.with_timezone(&Utc).naive_utc(), naive(2024, 11, 3, 5, 30));
+        assert_eq!(next(expr, first), Some(naive(2024, 11, 4, 1, 30)));
+    }
+
+    #[test]
+    fn never_matching_schedule() {
+        assert_eq!(next("0 0 31 2 *", local(2024, 1, 1, 0, 0)), None);
+        assert_eq!(next("0 0 29 2 *", local(2024, 3, 1, 0, 0)), Some(naive(2028, 2, 29, 0, 0)));
+    }
+}
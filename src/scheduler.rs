@@ -0,0 +1,429 @@
+// Recall - 系统计划任务模块
+// 为配置文件注册 / 删除 Windows 任务计划程序中的任务或 systemd 定时器，按选定的触发器运行 `recall run <配置>`
+
+use anyhow::{Context, Result};
+use chrono::{NaiveTime, Weekday};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 任务计划程序中存放 recall 任务的文件夹
+pub const TASK_FOLDER: &str = "\\Recall\\";
+
+/// 计划任务的设置
+#[derive(Debug, Clone)]
+pub struct TaskOptions {
+    /// 配置文件名称
+    pub profile: String,
+
+    /// 每天（或每周指定日期）的运行时间
+    pub time: NaiveTime,
+
+    /// 每周运行的日期（为空时每天运行）
+    pub days: Vec<Weekday>,
+
+    /// 用户登录时也运行一次
+    pub logon: bool,
+
+    /// 到时间时唤醒计算机
+    pub wake: bool,
+
+    /// 错过计划时间（关机、睡眠）后尽快补上
+    pub catch_up: bool,
+
+    /// 以最高权限运行（VSS 需要管理员权限）
+    pub elevated: bool,
+
+    /// 要运行的程序（recall 可执行文件）
+    pub command: PathBuf,
+
+    /// 程序参数
+    pub arguments: Vec<String>,
+}
+
+/// 配置文件对应的任务名称（含文件夹）
+///
+/// # 示例
+/// ```
+/// assert_eq!(recall::scheduler::task_name("Projects"), "\\Recall\\Projects");
+/// ```
+pub fn task_name(profile: &str) -> String {
+    format!("{}{}", TASK_FOLDER, profile)
+}
+
+/// 解析 `--time` 参数（`HH:MM`）
+///
+/// # 示例
+/// ```
+/// use chrono::NaiveTime;
+/// use recall::scheduler::parse_time;
+/// assert_eq!(parse_time("02:30"), Ok(NaiveTime::from_hms_opt(2, 30, 0).unwrap()));
+/// assert!(parse_time("25:00").is_err());
+/// ```
+pub fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").map_err(|_| format!("invalid time '{}', expected HH:MM", s))
+}
+
+/// 解析 `--days` 中的单个星期（英文名称或缩写）
+///
+/// # 示例
+/// ```
+/// use chrono::Weekday;
+/// use recall::scheduler::parse_weekday;
+/// assert_eq!(parse_weekday("mon"), Ok(Weekday::Mon));
+/// assert_eq!(parse_weekday("Sunday"), Ok(Weekday::Sun));
+/// assert!(parse_weekday("someday").is_err());
+/// ```
+pub fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    s.trim().parse().map_err(|_| format!("invalid day '{}', expected mon, tue, ... sun", s))
+}
+
+/// 生成任务计划程序的任务定义（XML）
+///
+/// 任务以当前用户身份运行（需已登录），同一任务不会同时运行多个实例，且没有运行时长限制。
+pub fn task_xml(options: &TaskOptions) -> String {
+    // 起始日期只用于确定每天的运行时间
+    let start = format!("2024-01-01T{}", options.time.format("%H:%M:%S"));
+    let schedule = if options.days.is_empty() {
+        "<ScheduleByDay><DaysInterval>1</DaysInterval></ScheduleByDay>".to_string()
+    } else {
+        let days: String = options.days.iter().map(|day| format!("<{}/>", day_element(*day))).collect();
+        format!("<ScheduleByWeek><DaysOfWeek>{}</DaysOfWeek><WeeksInterval>1</WeeksInterval></ScheduleByWeek>", days)
+    };
+    let logon = if options.logon {
+        "\n    <LogonTrigger>\n      <Enabled>true</Enabled>\n    </LogonTrigger>"
+    } else {
+        ""
+    };
+    let arguments: Vec<String> = options.arguments.iter().map(|arg| quote_argument(arg)).collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Description>{description}</Description>
+    <URI>{uri}</URI>
+  </RegistrationInfo>
+  <Triggers>
+    <CalendarTrigger>
+      <StartBoundary>{start}</StartBoundary>
+      <Enabled>true</Enabled>
+      {schedule}
+    </CalendarTrigger>{logon}
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <LogonType>InteractiveToken</LogonType>
+      <RunLevel>{run_level}</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <StartWhenAvailable>{catch_up}</StartWhenAvailable>
+    <WakeToRun>{wake}</WakeToRun>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <Enabled>true</Enabled>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>{command}</Command>
+      <Arguments>{arguments}</Arguments>
+    </Exec>
+  </Actions>
+</Task>
+"#,
+        description = escape_xml(&format!("Recall backup of profile '{}'", options.profile)),
+        uri = escape_xml(&task_name(&options.profile)),
+        start = start,
+        schedule = schedule,
+        logon = logon,
+        run_level = if options.elevated { "HighestAvailable" } else { "LeastPrivilege" },
+        catch_up = options.catch_up,
+        wake = options.wake,
+        command = escape_xml(&options.command.to_string_lossy()),
+        arguments = escape_xml(&arguments.join(" ")),
+    )
+}
+
+/// 任务定义中的星期元素名称
+fn day_element(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// 转义 XML 文本中的特殊字符
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// 按 Windows 命令行规则为包含空格或引号的参数加引号
+fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    // 引号前（及结尾处）的反斜杠需要加倍，引号本身需要转义
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        if c == '"' {
+            quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+        } else {
+            quoted.push_str(&"\\".repeat(backslashes));
+        }
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// 在任务计划程序中注册（或替换）计划任务
+///
+/// # 参数
+/// * `options` - 任务设置
+///
+/// # 返回
+/// * `Ok(())` - 注册成功
+/// * `Err(anyhow::Error)` - 非 Windows 平台，或 schtasks 执行失败（如需要管理员权限）
+#[cfg(windows)]
+pub fn install_task(options: &TaskOptions) -> Result<()> {
+    // schtasks 读取的任务定义需为 UTF-16（带 BOM）
+    let xml = task_xml(options);
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(xml.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    let path = std::env::temp_dir().join(format!("recall-task-{}.xml", std::process::id()));
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {:?}", path))?;
+
+    let name = task_name(&options.profile);
+    let result = schtasks(&["/Create", "/TN", &name, "/XML", &path.to_string_lossy(), "/F"]);
+    std::fs::remove_file(&path).ok();
+    result
+}
+
+/// 在非 Windows 平台上，任务计划程序不可用
+#[cfg(not(windows))]
+pub fn install_task(_options: &TaskOptions) -> Result<()> {
+    anyhow::bail!("Task Scheduler is only available on Windows (use --systemd on Linux)")
+}
+
+/// 从任务计划程序中删除配置文件的计划任务
+///
+/// # 返回
+/// * `Ok(())` - 删除成功
+/// * `Err(anyhow::Error)` - 非 Windows 平台，任务不存在，或 schtasks 执行失败
+#[cfg(windows)]
+pub fn remove_task(profile: &str) -> Result<()> {
+    schtasks(&["/Delete", "/TN", &task_name(profile), "/F"])
+}
+
+/// 在非 Windows 平台上，任务计划程序不可用
+#[cfg(not(windows))]
+pub fn remove_task(_profile: &str) -> Result<()> {
+    anyhow::bail!("Task Scheduler is only available on Windows (use --systemd on Linux)")
+}
+
+/// 运行 schtasks，失败时返回其错误输出
+#[cfg(windows)]
+fn schtasks(args: &[&str]) -> Result<()> {
+    let output = Command::new("schtasks")
+        .args(args)
+        .output()
+        .context("Failed to run schtasks")?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("schtasks failed: {}", message.trim());
+    }
+    Ok(())
+}
+
+/// 配置文件对应的 systemd 单元名称（不含扩展名），单元名称中不允许的字符转义为 `\xNN`
+///
+/// # 示例
+/// ```
+/// use recall::scheduler::unit_name;
+/// assert_eq!(unit_name("Projects"), "recall-Projects");
+/// assert_eq!(unit_name("My Docs"), "recall-My\\x20Docs");
+/// ```
+pub fn unit_name(profile: &str) -> String {
+    let mut name = String::from("recall-");
+    for (i, byte) in profile.bytes().enumerate() {
+        let keep = byte.is_ascii_alphanumeric() || matches!(byte, b':' | b'_' | b'-') || (byte == b'.' && i > 0);
+        if keep {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    name
+}
+
+/// 生成 systemd 定时器的 `OnCalendar` 表达式
+///
+/// # 示例
+/// ```
+/// use chrono::{NaiveTime, Weekday};
+/// use recall::scheduler::on_calendar;
+/// let time = NaiveTime::from_hms_opt(2, 30, 0).unwrap();
+/// assert_eq!(on_calendar(time, &[]), "*-*-* 02:30:00");
+/// assert_eq!(on_calendar(time, &[Weekday::Mon, Weekday::Fri]), "Mon,Fri *-*-* 02:30:00");
+/// ```
+pub fn on_calendar(time: NaiveTime, days: &[Weekday]) -> String {
+    let time = format!("*-*-* {}", time.format("%H:%M:%S"));
+    if days.is_empty() {
+        return time;
+    }
+    let days: Vec<String> = days.iter().map(|day| day.to_string()).collect();
+    format!("{} {}", days.join(","), time)
+}
+
+/// 生成 systemd 服务单元和定时器单元的内容
+///
+/// # 返回
+/// * `(String, String)` - (`.service` 内容, `.timer` 内容)
+pub fn systemd_units(options: &TaskOptions) -> (String, String) {
+    let command: Vec<String> = std::iter::once(options.command.to_string_lossy().to_string())
+        .chain(options.arguments.iter().cloned())
+        .map(|arg| quote_systemd(&arg))
+        .collect();
+    let service = format!(
+        "[Unit]
+Description=Recall backup of profile '{profile}'
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=oneshot
+ExecStart={command}
+Nice=10
+IOSchedulingClass=idle
+",
+        profile = options.profile,
+        command = command.join(" "),
+    );
+    let timer = format!(
+        "[Unit]
+Description=Run recall backup of profile '{profile}' on schedule
+
+[Timer]
+OnCalendar={calendar}
+Persistent={persistent}
+WakeSystem={wake}
+
+[Install]
+WantedBy=timers.target
+",
+        profile = options.profile,
+        calendar = on_calendar(options.time, &options.days),
+        persistent = options.catch_up,
+        wake = options.wake,
+    );
+    (service, timer)
+}
+
+/// 为 systemd 单元文件中的 `ExecStart` 参数加引号（`%` 需要写成 `%%`）
+fn quote_systemd(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if !escaped.is_empty() && !escaped.contains([' ', '\t', '"', '\'', '\\', ';']) {
+        return escaped;
+    }
+    format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// systemd 单元文件所在目录
+///
+/// # 参数
+/// * `system` - 系统级单元（`/etc/systemd/system`，以 root 运行），否则为当前用户的单元（`~/.config/systemd/user`）
+pub fn systemd_unit_dir(system: bool) -> Result<PathBuf> {
+    if system {
+        return Ok(PathBuf::from("/etc/systemd/system"));
+    }
+    let config = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME").context("Could not determine home directory")?).join(".config"),
+    };
+    Ok(config.join("systemd").join("user"))
+}
+
+/// 写入配置文件的 systemd 服务和定时器单元，并启用定时器
+///
+/// # 参数
+/// * `options` - 任务设置
+/// * `system` - 是否为系统级单元（需要 root）
+///
+/// # 返回
+/// * `Ok(Vec<PathBuf>)` - 写入的单元文件
+/// * `Err(anyhow::Error)` - 写入失败（如系统级单元需要 root），或 systemctl 执行失败
+pub fn install_systemd(options: &TaskOptions, system: bool) -> Result<Vec<PathBuf>> {
+    let dir = systemd_unit_dir(system)?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+
+    let name = unit_name(&options.profile);
+    let (service, timer) = systemd_units(options);
+    let paths = vec![dir.join(format!("{}.service", name)), dir.join(format!("{}.timer", name))];
+    for (path, content) in paths.iter().zip([service, timer]) {
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+
+    systemctl(system, &["daemon-reload"])?;
+    systemctl(system, &["enable", "--now", &format!("{}.timer", name)])?;
+    Ok(paths)
+}
+
+/// 停用并删除配置文件的 systemd 单元
+///
+/// # 返回
+/// * `Ok(())` - 删除成功
+/// * `Err(anyhow::Error)` - 单元不存在，或删除失败
+pub fn remove_systemd(profile: &str, system: bool) -> Result<()> {
+    let dir = systemd_unit_dir(system)?;
+    let name = unit_name(profile);
+    let paths = [dir.join(format!("{}.service", name)), dir.join(format!("{}.timer", name))];
+    if !paths.iter().any(|path| path.exists()) {
+        anyhow::bail!("No systemd units for profile '{}' in {:?}", profile, dir);
+    }
+
+    // 定时器可能已被手动停用，停用失败不影响删除
+    systemctl(system, &["disable", "--now", &format!("{}.timer", name)]).ok();
+    for path in &paths {
+        remove_if_exists(path)?;
+    }
+    systemctl(system, &["daemon-reload"])
+}
+
+/// 删除文件，文件不存在时忽略
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 运行 systemctl（用户级单元加 `--user`），失败时返回其错误输出
+fn systemctl(system: bool, args: &[&str]) -> Result<()> {
+    let mut command = Command::new("systemctl");
+    if !system {
+        command.arg("--user");
+    }
+    let output = command.args(args).output().context("Failed to run systemctl")?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("systemctl {} failed: {}", args.join(" "), message.trim());
+    }
+    Ok(())
+}
@@ -0,0 +1,767 @@
+// Recall - 备份会话模块
+// 把一次完整的硬链接快照备份（命名、续传、卷影副本、扫描与执行流水线、摘要和提交）封装为可复用的类型，供命令行和其他程序调用
+
+use crate::actions::BackupStats;
+use crate::config::BackupConfig;
+use crate::copy::{supports_hard_links, supports_reflink, supports_symlinks};
+use crate::error::{Classify, RecallError, Result};
+use crate::estimate::{estimate_with, SpaceCheck};
+use crate::events::{EventSink, NullSink};
+use crate::executor::{remove_temp_files, set_snapshot_read_only, BackupExecutor, LinkTreeBackend};
+use crate::manifest::{Manifest, MANIFEST_VERSION};
+use crate::plan::BackupPlan;
+use crate::progress;
+use crate::scanner::{find_latest_backup, find_resumable_partial, scan_source};
+use crate::storage::{auto_workers, free_space};
+use crate::naming::INCOMPLETE_SUFFIX;
+use crate::summary::{BackupInfo, BackupSettings, BackupSummary};
+use crate::utils::file_identity;
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 扫描器和执行器之间的任务通道容量
+const CHANNEL_CAPACITY: usize = 1000;
+
+/// 失败的文件超过 `max_failures` 时如何处理快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailurePolicy {
+    /// 照常提交，快照看起来与完整的快照相同（默认）
+    #[default]
+    Commit,
+
+    /// 提交，但快照目录名加上 `-incomplete` 后缀，且不更新 `current`：
+    /// 恢复时默认不选择它，清理时它不计入保留数量
+    Incomplete,
+
+    /// 不提交，保留为 `.partial` 目录，下次备份时从中断处续传
+    Partial,
+}
+
+impl FailurePolicy {
+    /// 是否为默认的 `commit`（保存配置时省略）
+    pub fn is_commit(&self) -> bool {
+        *self == FailurePolicy::Commit
+    }
+}
+
+/// 解析 `--on-failure` 参数
+///
+/// # 示例
+/// ```
+/// use recall::session::{parse_failure_policy, FailurePolicy};
+/// assert_eq!(parse_failure_policy("Incomplete"), Ok(FailurePolicy::Incomplete));
+/// assert!(parse_failure_policy("ignore").is_err());
+/// ```
+pub fn parse_failure_policy(s: &str) -> std::result::Result<FailurePolicy, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "commit" => Ok(FailurePolicy::Commit),
+        "incomplete" => Ok(FailurePolicy::Incomplete),
+        "partial" => Ok(FailurePolicy::Partial),
+        _ => Err(format!("unknown failure policy '{}' (expected commit, incomplete or partial)", s)),
+    }
+}
+
+/// 卷影副本（VSS）的使用情况，见 [`BackupSession::start_vss`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VssStatus {
+    /// 未启用 VSS，或为试运行
+    Disabled,
+
+    /// 当前平台不支持 VSS（仅 Windows），直接读取源目录
+    Unsupported,
+
+    /// VSS 不可用（缺少管理员权限或无法创建快照），已启用 `vss_fallback`，直接读取源目录
+    Unavailable(String),
+
+    /// 从卷影副本读取源文件
+    Active {
+        /// 源目录在卷影副本中的路径
+        source: PathBuf,
+
+        /// 快照 ID（GUID），可通过 `--snapshot-id` 复用
+        snapshot_id: String,
+
+        /// 是否复用了已有的快照（而不是新建）
+        reused: bool,
+
+        /// 加入备份的写入器组件数量
+        components: usize,
+    },
+}
+
+/// 把源目录映射到卷影副本中的对应路径
+///
+/// 卷影副本的根目录对应源目录所在卷的根目录，例如源目录 `C:\Users\Data` 映射为
+/// `\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopyN\Users\Data`。
+///
+/// # 参数
+/// * `shadow_root` - 卷影副本的根路径
+/// * `source` - 源目录（带盘符的绝对路径）
+///
+/// # 返回
+/// * `Some(PathBuf)` - 卷影副本中的路径
+/// * `None` - 源目录不是带盘符的路径（如 UNC 路径），无法映射
+///
+/// # 示例
+/// ```
+/// use recall::session::shadow_copy_path;
+/// use std::path::Path;
+///
+/// let root = Path::new(r"\\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy1");
+/// let mapped = shadow_copy_path(root, Path::new(r"C:\Users\Data")).unwrap();
+/// assert!(mapped.starts_with(root));
+/// assert!(mapped.to_string_lossy().ends_with(r"Users\Data"));
+/// assert!(shadow_copy_path(root, Path::new(r"\\server\share")).is_none());
+/// ```
+pub fn shadow_copy_path(shadow_root: &Path, source: &Path) -> Option<PathBuf> {
+    let source = source.to_str()?;
+    let source = source.strip_prefix(r"\\?\").unwrap_or(source);
+    let (drive, rest) = source.split_once(':')?;
+    if drive.len() != 1 || !drive.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(shadow_root.join(rest.trim_start_matches(['\\', '/'])))
+}
+
+/// 一次备份到本地（或网络共享）硬链接快照目录的会话
+///
+/// 分为三个阶段：[`begin`](BackupSession::begin) 确定快照名称、查找上一个快照和可续传的中断备份并创建
+/// `.partial` 临时目录（需要时再用 [`start_vss`](BackupSession::start_vss) 从卷影副本读取源文件）；
+/// [`transfer`](BackupSession::transfer) 扫描源目录并复制或链接文件；
+/// [`commit`](BackupSession::commit) 写入快照摘要（启用时还有清单），把临时目录重命名为快照目录并更新 `current` 链接。
+/// 不需要在阶段之间做额外处理时直接使用 [`run`](BackupSession::run)。
+///
+/// 试运行时不创建目录也不复制文件，[`plan`](BackupSession::plan) 返回将要执行的操作。
+///
+/// # 示例
+/// ```
+/// use recall::config::BackupConfig;
+/// use recall::session::BackupSession;
+///
+/// let source = tempfile::tempdir().unwrap();
+/// let backups = tempfile::tempdir().unwrap();
+/// std::fs::write(source.path().join("a.txt"), "hello").unwrap();
+///
+/// let config = BackupConfig::builder(source.path(), backups.path()).finish().unwrap();
+/// let (snapshot, stats) = BackupSession::begin(config, true).unwrap().run().unwrap();
+/// assert_eq!(stats.copied_new, 1);
+/// assert!(backups.path().join(&snapshot).join("a.txt").is_file());
+/// ```
+pub struct BackupSession {
+    /// 备份配置
+    config: BackupConfig,
+
+    /// 事件接收者
+    events: Arc<dyn EventSink>,
+
+    /// 开始时间
+    started_at: DateTime<Local>,
+
+    /// 开始时刻（计算耗时）
+    start: Instant,
+
+    /// 快照名称（续传时沿用中断备份的名称）
+    snapshot: String,
+
+    /// 上一个快照（增量备份的比较对象）
+    latest_backup: Option<PathBuf>,
+
+    /// 是否继续中断的备份
+    resumed: bool,
+
+    /// 实际使用的工作线程数量
+    workers: usize,
+
+    /// 扫描的源目录（使用卷影副本时为快照中的路径）
+    read_path: PathBuf,
+
+    /// 是否从卷影副本读取
+    vss: bool,
+
+    /// 修改过的文件是否通过块克隆写入
+    reflink: bool,
+
+    /// 目标是否支持硬链接（不支持时所有文件完整复制）
+    hard_links: bool,
+
+    /// 目标是否支持符号链接（不支持时符号链接复制为其指向的文件）
+    symlinks: bool,
+
+    /// 系统时钟落后时比新快照时间更晚的最新快照名称（新快照已改名排在它之后）
+    clock_behind: Option<String>,
+
+    /// 快照的标签和备注
+    tags: Vec<String>,
+
+    /// 清单的签名密钥（为空时只写入校验和）
+    manifest_key: Option<Vec<u8>>,
+
+    /// 正在使用的卷影副本，会话结束时释放（非持久快照随之删除）
+    #[cfg(windows)]
+    shadow_copy: std::cell::RefCell<Option<crate::vss::ShadowCopy>>,
+}
+
+impl BackupSession {
+    /// 开始一次备份：确定快照名称和工作线程数量，查找上一个快照，创建临时目录
+    ///
+    /// # 参数
+    /// * `config` - 备份配置
+    /// * `resume` - 是否继续比上一个快照更新的中断备份（`.partial` 目录）
+    ///
+    /// # 返回
+    /// * `Ok(BackupSession)` - 已准备好的会话
+    /// * `Err(RecallError)` - 目标位于源目录中（见 [`BackupConfig::check_paths`]），无法创建目标目录或读取已有快照
+    pub fn begin(config: BackupConfig, resume: bool) -> Result<Self> {
+        config.check_paths()?;
+        let started_at = Local::now();
+        let mut snapshot = config.naming.format_name(started_at);
+
+        if !config.destination.exists() && !config.dry_run {
+            std::fs::create_dir_all(&config.destination)
+                .context("Failed to create destination root")
+                .classify(RecallError::Destination)?;
+        }
+
+        let workers = match config.workers {
+            0 => auto_workers(&config.destination),
+            n => n,
+        };
+
+        let latest_backup = find_latest_backup(&config.destination, &config.naming)?;
+
+        // 查找可续传的中断备份，沿用其时间戳继续写入
+        let mut resumed = false;
+        if resume {
+            let partial = find_resumable_partial(&config.destination, latest_backup.as_deref(), &config.naming)?;
+            if let Some(stem) = partial.as_deref().and_then(Path::file_stem) {
+                snapshot = stem.to_string_lossy().to_string();
+                resumed = true;
+            }
+        }
+
+        // 系统时钟落后于最新快照（时钟回拨、时区设置错误）时，新快照会排在最新快照之前，
+        // 之后的备份也不会以它为基准：改用比最新快照晚一秒的名称。
+        // 同一秒内的连续备份同样改名以免重名，但这不是时钟问题，不给出警告
+        let mut clock_behind = None;
+        if !resumed {
+            let latest = latest_backup.as_deref().and_then(|path| {
+                let name = path.file_name()?.to_str()?;
+                Some((config.naming.parse(name)?, name.to_string()))
+            });
+            if let Some((latest_time, latest_name)) = latest {
+                match config.naming.parse(&snapshot) {
+                    Some(time) if time > latest_time => {}
+                    time => {
+                        snapshot = config.naming.name_after(latest_time);
+                        if time.is_none_or(|time| time < latest_time) {
+                            clock_behind = Some(latest_name);
+                        }
+                    }
+                }
+            }
+        }
+
+        // 块克隆只在增量备份时有意义
+        let reflink = latest_backup.is_some() && !config.dry_run && supports_reflink(&config.destination);
+
+        // 预先探测目标的链接能力，不支持时直接降级，而不是每个文件都先失败一次（试运行时不写入探测文件）
+        let (hard_links, symlinks) = if config.dry_run {
+            (true, true)
+        } else {
+            (supports_hard_links(&config.destination), supports_symlinks(&config.destination))
+        };
+
+        let session = Self {
+            read_path: config.source.clone(),
+            config,
+            events: Arc::new(NullSink),
+            started_at,
+            start: Instant::now(),
+            snapshot,
+            latest_backup,
+            resumed,
+            workers,
+            vss: false,
+            reflink,
+            hard_links,
+            symlinks,
+            clock_behind,
+            tags: Vec::new(),
+            manifest_key: None,
+            #[cfg(windows)]
+            shadow_copy: std::cell::RefCell::new(None),
+        };
+
+        if !session.config.dry_run {
+            let temp_path = session.temp_path();
+            std::fs::create_dir_all(&temp_path)
+                .context("Failed to create temp backup dir")
+                .classify(RecallError::Destination)?;
+
+            // 续传时清理上次中断时未完成的临时文件
+            if resumed {
+                remove_temp_files(&temp_path)?;
+            }
+        }
+        Ok(session)
+    }
+
+    /// 设置接收扫描和执行事件的对象（默认忽略）
+    pub fn with_events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 设置写入快照摘要的标签和备注
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// 设置提交时为快照清单签名的密钥（见 [`crate::manifest::read_key`]，只在启用 `manifest` 时使用）
+    pub fn with_manifest_key(mut self, key: Option<Vec<u8>>) -> Self {
+        self.manifest_key = key;
+        self
+    }
+
+    /// 从卷影副本中的路径读取源文件（快照摘要中记录为使用了 VSS）
+    ///
+    /// # 参数
+    /// * `path` - 源目录在卷影副本中对应的路径
+    pub fn read_from_snapshot(&mut self, path: PathBuf) {
+        self.read_path = path;
+        self.vss = true;
+    }
+
+    /// 按配置（`vss`、`vss_fallback`、`vss_writers`）创建或打开卷影副本，之后从快照中读取源文件
+    ///
+    /// 卷影副本在会话结束时释放，非持久快照随之删除；[`commit`](BackupSession::commit) 成功时
+    /// 向参与备份的写入器报告备份结果。试运行或未启用 VSS 时不做任何操作。
+    ///
+    /// # 参数
+    /// * `snapshot_id` - 复用已有快照的 ID（指定时即使配置未启用 VSS 也使用快照）
+    /// * `keep` - 新建的快照在备份结束后是否保留（持久快照）
+    ///
+    /// # 返回
+    /// * `Ok(VssStatus)` - 卷影副本的使用情况
+    /// * `Err(RecallError)` - 缺少管理员权限、创建或打开快照失败，且未启用 `vss_fallback`
+    pub fn start_vss(&mut self, snapshot_id: Option<&str>, keep: bool) -> Result<VssStatus> {
+        if self.config.dry_run || !(self.config.vss || snapshot_id.is_some()) {
+            return Ok(VssStatus::Disabled);
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = keep;
+            Ok(VssStatus::Unsupported)
+        }
+        #[cfg(windows)]
+        {
+            self.start_shadow_copy(snapshot_id, keep)
+        }
+    }
+
+    /// 创建或打开卷影副本并映射源目录（Windows）
+    #[cfg(windows)]
+    fn start_shadow_copy(&mut self, snapshot_id: Option<&str>, keep: bool) -> Result<VssStatus> {
+        use crate::vss::ShadowCopy;
+
+        let opened = (|| -> Result<(ShadowCopy, PathBuf)> {
+            if !ShadowCopy::is_running_as_admin()? {
+                return Err(RecallError::Vss(anyhow::anyhow!(
+                    "VSS requires Administrator privileges, run recall in an elevated terminal"
+                )));
+            }
+            let shadow_copy = match snapshot_id {
+                Some(id) => ShadowCopy::open(id, &self.config.source)?,
+                None => ShadowCopy::new(&self.config.source, &self.config.vss_writers, keep)?,
+            };
+            let source = shadow_copy_path(&shadow_copy.get_snapshot_path()?, &self.config.source).ok_or_else(|| {
+                RecallError::Vss(anyhow::anyhow!("Cannot map {:?} into the shadow copy", self.config.source))
+            })?;
+            Ok((shadow_copy, source))
+        })();
+
+        let (shadow_copy, source) = match opened {
+            Ok(opened) => opened,
+            Err(e) if self.config.vss_fallback => return Ok(VssStatus::Unavailable(format!("{:#}", e.inner()))),
+            Err(e) => return Err(e),
+        };
+        let status = VssStatus::Active {
+            source: source.clone(),
+            snapshot_id: shadow_copy.snapshot_id(),
+            reused: snapshot_id.is_some(),
+            components: shadow_copy.selected_component_count(),
+        };
+        self.read_from_snapshot(source);
+        *self.shadow_copy.borrow_mut() = Some(shadow_copy);
+        Ok(status)
+    }
+
+    /// 向参与备份的 VSS 写入器报告备份结果（没有使用卷影副本时不做任何操作）
+    fn report_to_writers(&self, stats: &BackupStats) {
+        #[cfg(windows)]
+        if let Some(shadow_copy) = self.shadow_copy.borrow_mut().as_mut() {
+            shadow_copy.set_backup_succeeded(stats.failed == 0);
+        }
+        #[cfg(not(windows))]
+        let _ = stats;
+    }
+
+    /// 备份配置
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    /// 开始时间
+    pub fn started_at(&self) -> DateTime<Local> {
+        self.started_at
+    }
+
+    /// 快照名称
+    pub fn snapshot(&self) -> &str {
+        &self.snapshot
+    }
+
+    /// 上一个快照的路径（首次备份时为空）
+    pub fn latest_backup(&self) -> Option<&Path> {
+        self.latest_backup.as_deref()
+    }
+
+    /// 是否继续了中断的备份
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// 实际使用的工作线程数量
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// 备份过程中写入的临时目录（`<快照名称>.partial`）
+    pub fn temp_path(&self) -> PathBuf {
+        self.config.destination.join(format!("{}.partial", self.snapshot))
+    }
+
+    /// 提交后的快照目录（按 [`FailurePolicy::Incomplete`] 提交时为 [`commit`](BackupSession::commit) 返回的路径）
+    pub fn snapshot_path(&self) -> PathBuf {
+        self.config.destination.join(&self.snapshot)
+    }
+
+    /// 是否从卷影副本读取源文件（见 [`read_from_snapshot`](BackupSession::read_from_snapshot)）
+    pub fn uses_vss(&self) -> bool {
+        self.vss
+    }
+
+    /// 修改过的文件是否通过块克隆写入
+    ///
+    /// 仅在增量备份且目标文件系统支持块克隆（ReFS、Btrfs、XFS、APFS）时为 `true`。
+    pub fn uses_reflink(&self) -> bool {
+        self.reflink
+    }
+
+    /// 目标是否支持硬链接
+    ///
+    /// 不支持时（exFAT、FAT32、部分 SMB 共享）未修改的文件也完整复制，快照之间不共享空间。
+    pub fn supports_hard_links(&self) -> bool {
+        self.hard_links
+    }
+
+    /// 目标是否支持符号链接（Windows 上还需要开发者模式或管理员权限）
+    ///
+    /// 不支持时源中的符号链接复制为其指向的文件，`current` 改为标记文件。
+    pub fn supports_symlinks(&self) -> bool {
+        self.symlinks
+    }
+
+    /// 系统时钟落后于最新快照时，该快照的名称
+    ///
+    /// 此时新快照没有按当前时间命名，而是改用比该快照晚一秒的名称，保证查找最新快照时仍能找到它。
+    pub fn clock_behind(&self) -> Option<&str> {
+        self.clock_behind.as_deref()
+    }
+
+    /// 扫描源目录并把文件写入临时目录
+    ///
+    /// 扫描线程和执行器通过有界通道组成流水线，边扫描边复制。
+    ///
+    /// # 返回
+    /// * `Ok(BackupStats)` - 备份统计信息（单个文件的失败计入统计）
+    /// * `Err(RecallError)` - 扫描或执行失败
+    pub fn transfer(&self) -> Result<BackupStats> {
+        let scan_config = self.scan_config();
+
+        progress::start(&self.config.source, &self.config.destination, &self.snapshot);
+        let (tx, rx) = crossbeam_channel::bounded(CHANNEL_CAPACITY);
+
+        let temp_path = self.temp_path();
+        let latest_backup = self.latest_backup.clone();
+        let scanner_events = self.events.clone();
+        let scanner = std::thread::spawn(move || {
+            scan_source(&scan_config, &temp_path, latest_backup.as_deref(), tx, &*scanner_events)
+        });
+
+        let mut stats = self.executor().execute(rx, self.workers)?;
+
+        match scanner.join() {
+            Ok(Ok(scan)) => {
+                stats.excluded = scan.excluded;
+                stats.durations.scan = scan.elapsed.as_secs_f64();
+                stats.durations.hash = scan.hash_time.as_secs_f64();
+                Ok(stats)
+            }
+            Ok(Err(e)) => Err(RecallError::Scan(anyhow::Error::from(e).context("Scanner failed"))),
+            Err(_) => Err(RecallError::Scan(anyhow::anyhow!("Scanner thread panicked"))),
+        }
+    }
+
+    /// 扫描源目录，生成将要执行的操作列表和预计的统计信息（不写入任何内容）
+    ///
+    /// 试运行和“审阅后确认”的流程使用：确认后把计划交给 [`execute_plan`](BackupSession::execute_plan)。
+    ///
+    /// # 返回
+    /// * `Ok(BackupPlan)` - 备份计划
+    /// * `Err(RecallError)` - 扫描失败
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::config::BackupConfig;
+    /// use recall::session::BackupSession;
+    ///
+    /// let source = tempfile::tempdir().unwrap();
+    /// let backups = tempfile::tempdir().unwrap();
+    /// std::fs::write(source.path().join("a.txt"), "hello").unwrap();
+    ///
+    /// let config = BackupConfig::builder(source.path(), backups.path()).finish().unwrap();
+    /// let session = BackupSession::begin(config, true).unwrap();
+    /// let plan = session.plan().unwrap();
+    /// assert_eq!(plan.totals.copied_new, 1);
+    ///
+    /// let stats = session.execute_plan(plan).unwrap();
+    /// session.commit(&stats).unwrap();
+    /// assert!(session.snapshot_path().join("a.txt").is_file());
+    /// ```
+    pub fn plan(&self) -> Result<BackupPlan> {
+        BackupPlan::build(&self.scan_config(), &self.temp_path(), self.latest_backup.as_deref(), &*self.events)
+    }
+
+    /// 检查目标所在卷的可用空间能否容纳这次备份
+    ///
+    /// 预先扫描一遍源目录，按与备份相同的决策统计将复制的新文件和已修改文件的大小。
+    /// 扫描时不比较文件内容，启用内容检查时只因内容变化而需要复制的文件不计入；
+    /// 块克隆、目录和硬链接占用的空间也不计入，结果只是估计值。
+    ///
+    /// # 返回
+    /// * `Ok(Some(SpaceCheck))` - 预计写入量和可用空间
+    /// * `Ok(None)` - 无法查询目标的可用空间
+    /// * `Err(RecallError)` - 扫描失败
+    pub fn check_free_space(&self) -> Result<Option<SpaceCheck>> {
+        let Some(available) = free_space(&self.config.destination) else {
+            return Ok(None);
+        };
+        let mut scan_config = self.scan_config();
+        scan_config.check_content = false;
+        let estimate = estimate_with(&scan_config, &self.temp_path(), self.latest_backup.as_deref(), &NullSink)
+            .classify(RecallError::Scan)?;
+        Ok(Some(SpaceCheck { required: estimate.projected_growth, available }))
+    }
+
+    /// 执行 [`plan`](BackupSession::plan) 生成的计划，把文件写入临时目录
+    ///
+    /// 生成计划之后源目录的变化不会被发现：新增的文件不会备份，已删除的文件计为失败。
+    ///
+    /// # 参数
+    /// * `plan` - 备份计划
+    ///
+    /// # 返回
+    /// * `Ok(BackupStats)` - 备份统计信息（单个文件的失败计入统计）
+    /// * `Err(RecallError)` - 执行失败
+    pub fn execute_plan(&self, plan: BackupPlan) -> Result<BackupStats> {
+        progress::start(&self.config.source, &self.config.destination, &self.snapshot);
+        let (tx, rx) = crossbeam_channel::bounded(CHANNEL_CAPACITY);
+        let (excluded, scan, hash) = (plan.totals.excluded, plan.totals.durations.scan, plan.totals.durations.hash);
+        let feeder = std::thread::spawn(move || {
+            for entry in plan.entries {
+                if tx.send(entry).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut stats = self.executor().execute(rx, self.workers)?;
+        feeder
+            .join()
+            .map_err(|_| RecallError::Scan(anyhow::anyhow!("Plan feeder thread panicked")))?;
+        stats.excluded = excluded;
+        stats.durations.scan = scan;
+        stats.durations.hash = hash;
+        Ok(stats)
+    }
+
+    /// 扫描使用的配置（从卷影副本读取，续传和块克隆设置）
+    fn scan_config(&self) -> BackupConfig {
+        let mut scan_config = self.config.clone();
+        scan_config.source = self.read_path.clone();
+        scan_config.resume = self.resumed;
+        scan_config.reflink = self.reflink;
+        scan_config
+    }
+
+    /// 写入临时目录的执行器
+    fn executor(&self) -> BackupExecutor {
+        let backend = LinkTreeBackend::new(self.config.dry_run, self.config.preserve_metadata, self.config.copy_options())
+            .with_link_support(self.hard_links, self.symlinks);
+        BackupExecutor::with_backend(Box::new(backend))
+            .with_events(self.events.clone())
+            .with_fail_on_locked(self.config.fail_on_locked)
+    }
+
+    /// 写入快照摘要（启用时还有清单），把临时目录重命名为快照目录并更新 `current` 链接（试运行时不做任何操作）
+    ///
+    /// 启用 `manifest` 时写入清单，记录每个文件的大小和哈希值（见 [`Manifest`]），供 `recall verify` 检查快照是否损坏。
+    /// 摘要或清单写入失败只报告警告，不影响提交。失败的文件超过 `max_failures` 时按 `on_failure` 处理：
+    /// 照常提交、提交为带 `-incomplete` 后缀的快照（不更新 `current`），或保留 `.partial` 目录并返回错误。
+    ///
+    /// # 参数
+    /// * `stats` - [`transfer`](BackupSession::transfer) 返回的统计信息
+    ///
+    /// # 返回
+    /// * `Ok(PathBuf)` - 提交后的快照目录
+    /// * `Err(RecallError)` - 失败的文件过多而保留为 `.partial`，已有同名或更新的快照（备份保留在临时目录中），或重命名失败
+    pub fn commit(&self, stats: &BackupStats) -> Result<PathBuf> {
+        if self.config.dry_run {
+            return Ok(self.snapshot_path());
+        }
+        let temp_path = self.temp_path();
+
+        let too_many_failures = stats.failed > self.config.max_failures;
+        if too_many_failures && self.config.on_failure == FailurePolicy::Partial {
+            return Err(RecallError::Copy(anyhow::anyhow!(
+                "{} file(s) failed (more than {}), the backup was left unfinished in {:?}; run the backup again to resume it",
+                stats.failed,
+                self.config.max_failures,
+                temp_path
+            )));
+        }
+
+        // 确认新快照排在所有已有快照之后，否则查找最新快照时会选中别的快照，下次备份也不会以它为基准
+        if let Some(time) = self.config.naming.parse(&self.snapshot) {
+            let newest = self.config.naming.list(&self.config.destination, false).classify(RecallError::Destination)?.pop();
+            if let Some((_, newest)) = newest.filter(|(newest_time, _)| *newest_time >= time) {
+                return Err(RecallError::Destination(anyhow::anyhow!(
+                    "Snapshot {} would not be the latest ({:?} is not older), the system clock may have moved backwards; the backup was kept in {:?}",
+                    self.snapshot,
+                    newest,
+                    temp_path
+                )));
+            }
+        }
+
+        // 自描述信息写在清单之前，启用清单时同样受其保护
+        let info = BackupInfo::new(&self.config, self.latest_backup.as_deref(), self.vss);
+        let info_written = info.save(&temp_path).unwrap_or_else(|e| {
+            self.events.warning(&crate::trf!("Warning: {:#}", "警告：{:#}", e));
+            false
+        });
+
+        // 清单写入失败只报告警告：快照本身已完整，只是之后无法按清单校验
+        let manifest_written = self.config.manifest
+            && match self.write_manifest(&temp_path) {
+                Ok(()) => true,
+                Err(e) => {
+                    self.events.warning(&crate::trf!("Warning: {:#}", "警告：{:#}", e));
+                    false
+                }
+            };
+
+        let mut summary = BackupSummary::new(
+            stats,
+            &self.config.source,
+            self.latest_backup.as_deref(),
+            self.started_at,
+            self.start.elapsed().as_secs(),
+            self.tags.clone(),
+            BackupSettings::from_config(&self.config, self.workers, self.vss),
+        );
+        summary.manifest = manifest_written.then_some(MANIFEST_VERSION);
+        summary.info_file = info_written;
+        if let Err(e) = summary.save(&temp_path) {
+            self.events.warning(&crate::trf!("Warning: {:#}", "警告：{:#}", e));
+        }
+
+        // 不完整的快照不更新 current，恢复和下次查找 current 时仍指向最后一个完整的快照
+        if too_many_failures && self.config.on_failure == FailurePolicy::Incomplete {
+            let final_path = self.config.destination.join(format!("{}{}", self.snapshot, INCOMPLETE_SUFFIX));
+            if final_path.exists() {
+                return Err(RecallError::Destination(anyhow::anyhow!(
+                    "Destination backup folder already exists: {:?}",
+                    final_path
+                )));
+            }
+            std::fs::rename(&temp_path, &final_path)
+                .with_context(|| format!("Failed to rename {:?} to {:?}", temp_path, final_path))
+                .classify(RecallError::Destination)?;
+            self.protect(&final_path);
+            self.report_to_writers(stats);
+            return Ok(final_path);
+        }
+
+        let final_path = self.snapshot_path();
+        BackupExecutor::commit_backup(&temp_path, &final_path, &self.config.destination.join("current"), &*self.events)?;
+        self.protect(&final_path);
+        self.report_to_writers(stats);
+        Ok(final_path)
+    }
+
+    /// 为临时目录中的快照写入清单（带校验和，设置了密钥时带签名）
+    ///
+    /// 从上一个快照硬链接来的文件与上一个快照中的是同一个文件，直接沿用其清单中的哈希值，
+    /// 只有新写入的文件需要读取。设置了密钥时只沿用签名与密钥匹配的清单，
+    /// 避免把被改写过的哈希值签进新清单。
+    fn write_manifest(&self, temp_path: &Path) -> anyhow::Result<()> {
+        let mut hashes = HashMap::new();
+        if let Some(latest) = self.latest_backup.as_deref() {
+            if let Ok(Some((previous, _))) = Manifest::load_with_key(latest, self.manifest_key.as_deref()) {
+                for entry in previous.entries {
+                    if let Some(id) = file_identity(&latest.join(&entry.path)) {
+                        hashes.insert(id, entry.hash);
+                    }
+                }
+            }
+        }
+        Manifest::build(temp_path, &mut hashes)
+            .and_then(|manifest| manifest.save(temp_path, self.manifest_key.as_deref()))
+            .context("Failed to write the snapshot manifest")
+    }
+
+    /// 启用 `read_only` 时把已提交的快照设为只读（失败只报告警告，快照已经提交）
+    fn protect(&self, snapshot: &Path) {
+        if !self.config.read_only {
+            return;
+        }
+        if let Err(e) = set_snapshot_read_only(snapshot) {
+            self.events.warning(&crate::trf!(
+                "Warning: Cannot make the snapshot read-only: {:#}",
+                "警告：无法把快照设为只读：{:#}",
+                e.inner()
+            ));
+        }
+    }
+
+    /// 依次执行传输和提交
+    ///
+    /// # 返回
+    /// * `Ok((String, BackupStats))` - 快照目录名称和备份统计信息
+    /// * `Err(RecallError)` - 任一阶段失败
+    pub fn run(self) -> Result<(String, BackupStats)> {
+        let stats = self.transfer()?;
+        let path = self.commit(&stats)?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or(self.snapshot);
+        Ok((name, stats))
+    }
+}
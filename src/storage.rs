@@ -0,0 +1,282 @@
+// Recall - 存储设备检测模块
+// 检测备份目标所在设备的类型和可用空间，用于自动确定合适的工作线程数和备份前的空间检查
+
+use std::path::Path;
+
+/// 存储设备类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    /// 固态硬盘（无寻道开销）
+    Solid,
+
+    /// 机械硬盘（并发随机 I/O 会导致频繁寻道）
+    Rotational,
+
+    /// 可移动设备（U 盘、移动硬盘等）
+    Removable,
+
+    /// 网络共享（SMB、NFS 等）
+    Network,
+
+    /// 无法确定
+    Unknown,
+}
+
+/// 根据 CPU 数量和目标设备类型自动确定工作线程数
+///
+/// - 机械硬盘和可移动设备：2 个线程，避免并发写入造成的寻道抖动
+/// - 网络共享：4 个线程，用并发掩盖网络延迟
+/// - 固态硬盘或未知设备：CPU 核心数，限制在 2 ~ 16 之间
+///
+/// # 参数
+/// * `destination` - 备份目标路径
+///
+/// # 返回
+/// 推荐的工作线程数
+pub fn auto_workers(destination: &Path) -> usize {
+    match detect_storage_kind(destination) {
+        StorageKind::Rotational | StorageKind::Removable => 2,
+        StorageKind::Network => 4,
+        StorageKind::Solid | StorageKind::Unknown => cpu_count().clamp(2, 16),
+    }
+}
+
+/// 获取可用的 CPU 核心数
+pub fn cpu_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// 检测路径所在存储设备的类型（Windows）
+///
+/// 通过 `GetDriveTypeW` 区分可移动设备、网络驱动器和固定磁盘，
+/// 固定磁盘再通过 `IOCTL_STORAGE_QUERY_PROPERTY` 查询寻道开销区分机械硬盘和固态硬盘。
+#[cfg(windows)]
+pub fn detect_storage_kind(path: &Path) -> StorageKind {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::{GetDriveTypeW, GetVolumePathNameW};
+    use winapi::um::winbase::{DRIVE_REMOTE, DRIVE_REMOVABLE};
+
+    // UNC 路径一定是网络共享
+    if path.to_string_lossy().starts_with(r"\\") && !path.to_string_lossy().starts_with(r"\\?\") {
+        return StorageKind::Network;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut volume = [0u16; 261];
+    if unsafe { GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return StorageKind::Unknown;
+    }
+
+    match unsafe { GetDriveTypeW(volume.as_ptr()) } {
+        DRIVE_REMOVABLE => StorageKind::Removable,
+        DRIVE_REMOTE => StorageKind::Network,
+        _ => match query_seek_penalty(&volume) {
+            Some(true) => StorageKind::Rotational,
+            Some(false) => StorageKind::Solid,
+            None => StorageKind::Unknown,
+        },
+    }
+}
+
+/// 查询卷所在设备是否有寻道开销（Windows）
+///
+/// 与 Linux 下的 `queue/rotational` 属性对应：有寻道开销即为机械硬盘。
+///
+/// # 参数
+/// * `volume` - `GetVolumePathNameW` 返回的以 0 结尾的卷挂载点（如 `C:\`）
+///
+/// # 返回
+/// * `Some(true)` - 设备有寻道开销（机械硬盘）
+/// * `Some(false)` - 设备无寻道开销（固态硬盘）
+/// * `None` - 无法打开卷或设备不支持该查询（部分 RAID 控制器、虚拟磁盘）
+#[cfg(windows)]
+fn query_seek_penalty(volume: &[u16]) -> Option<bool> {
+    use std::mem::size_of;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr::null_mut;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::fileapi::GetVolumeNameForVolumeMountPointW;
+    use winapi::um::ioapiset::DeviceIoControl;
+
+    /// `CTL_CODE(IOCTL_STORAGE_BASE, 0x0500, METHOD_BUFFERED, FILE_ANY_ACCESS)`
+    const IOCTL_STORAGE_QUERY_PROPERTY: DWORD = 0x002D_1400;
+    /// `STORAGE_PROPERTY_ID::StorageDeviceSeekPenaltyProperty`
+    const STORAGE_DEVICE_SEEK_PENALTY_PROPERTY: u32 = 7;
+    /// `STORAGE_QUERY_TYPE::PropertyStandardQuery`
+    const PROPERTY_STANDARD_QUERY: u32 = 0;
+
+    /// `STORAGE_PROPERTY_QUERY`
+    #[repr(C)]
+    struct PropertyQuery {
+        property_id: u32,
+        query_type: u32,
+        additional_parameters: [u8; 1],
+    }
+
+    /// `DEVICE_SEEK_PENALTY_DESCRIPTOR`
+    #[repr(C)]
+    #[derive(Default)]
+    struct SeekPenaltyDescriptor {
+        version: DWORD,
+        size: DWORD,
+        incurs_seek_penalty: u8,
+    }
+
+    // 挂载点 → `\\?\Volume{GUID}\`，去掉末尾的反斜杠后即可作为设备打开
+    let mut name = [0u16; 64];
+    if unsafe { GetVolumeNameForVolumeMountPointW(volume.as_ptr(), name.as_mut_ptr(), name.len() as u32) } == 0 {
+        return None;
+    }
+    let len = name.iter().position(|&c| c == 0)?;
+    let device = String::from_utf16_lossy(&name[..len]);
+    let device = device.trim_end_matches('\\');
+
+    // 访问权限为 0 时无需管理员权限即可发送查询类 IOCTL
+    let handle = std::fs::OpenOptions::new().access_mode(0).open(device).ok()?;
+
+    let mut query = PropertyQuery {
+        property_id: STORAGE_DEVICE_SEEK_PENALTY_PROPERTY,
+        query_type: PROPERTY_STANDARD_QUERY,
+        additional_parameters: [0],
+    };
+    let mut descriptor = SeekPenaltyDescriptor::default();
+    let mut returned: DWORD = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            handle.as_raw_handle() as _,
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            &mut query as *mut _ as *mut _,
+            size_of::<PropertyQuery>() as DWORD,
+            &mut descriptor as *mut _ as *mut _,
+            size_of::<SeekPenaltyDescriptor>() as DWORD,
+            &mut returned,
+            null_mut(),
+        )
+    };
+    if ok == 0 || returned == 0 {
+        return None;
+    }
+
+    Some(descriptor.incurs_seek_penalty != 0)
+}
+
+/// 检测路径所在存储设备的类型（Linux）
+///
+/// 先通过 `statfs` 识别网络文件系统，再通过 sysfs 中块设备的
+/// `removable` 和 `queue/rotational` 属性判断设备类型。
+#[cfg(target_os = "linux")]
+pub fn detect_storage_kind(path: &Path) -> StorageKind {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+
+    const NFS_SUPER_MAGIC: i64 = 0x6969;
+    const SMB_SUPER_MAGIC: i64 = 0x517B;
+    const CIFS_MAGIC_NUMBER: i64 = 0xFF53_4D42;
+    const SMB2_MAGIC_NUMBER: i64 = 0xFE53_4D42;
+
+    // 目标目录可能尚未创建，向上查找第一个存在的路径
+    let existing = match path.ancestors().find(|p| p.exists()) {
+        Some(p) => p,
+        None => return StorageKind::Unknown,
+    };
+
+    if let Ok(c_path) = std::ffi::CString::new(existing.as_os_str().as_bytes()) {
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } == 0 {
+            let fs_type = stat.f_type as i64;
+            if [NFS_SUPER_MAGIC, SMB_SUPER_MAGIC, CIFS_MAGIC_NUMBER, SMB2_MAGIC_NUMBER].contains(&fs_type) {
+                return StorageKind::Network;
+            }
+        }
+    }
+
+    let dev = match std::fs::metadata(existing) {
+        Ok(meta) => meta.dev(),
+        Err(_) => return StorageKind::Unknown,
+    };
+    let sys_dir = std::path::PathBuf::from(format!(
+        "/sys/dev/block/{}:{}",
+        libc::major(dev),
+        libc::minor(dev)
+    ));
+
+    // 分区的属性位于其父设备目录中
+    let read_attr = |name: &str| -> Option<String> {
+        std::fs::read_to_string(sys_dir.join(name))
+            .or_else(|_| std::fs::read_to_string(sys_dir.join("..").join(name)))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    if read_attr("removable").as_deref() == Some("1") {
+        return StorageKind::Removable;
+    }
+
+    match read_attr("queue/rotational").as_deref() {
+        Some("1") => StorageKind::Rotational,
+        Some("0") => StorageKind::Solid,
+        _ => StorageKind::Unknown,
+    }
+}
+
+/// 其他平台无法检测设备类型
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn detect_storage_kind(_path: &Path) -> StorageKind {
+    StorageKind::Unknown
+}
+
+/// 获取路径所在卷上当前用户可用的空间（字节，Windows）
+///
+/// 路径尚未创建时查询最近的已存在上级目录。
+///
+/// # 返回
+/// * `Some(u64)` - 可用空间
+/// * `None` - 无法查询
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let wide: Vec<u16> = existing.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available: u64 = 0;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            (&mut available as *mut u64).cast(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(available)
+}
+
+/// 获取路径所在卷上当前用户可用的空间（字节，Unix）
+///
+/// 通过 `statvfs` 查询（不包括为 root 保留的块）。路径尚未创建时查询最近的已存在上级目录。
+///
+/// # 返回
+/// * `Some(u64)` - 可用空间
+/// * `None` - 无法查询
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// 其他平台无法查询可用空间
+#[cfg(not(any(windows, unix)))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
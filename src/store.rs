@@ -1,24 +1,68 @@
 // Recall - 配置文件存储模块
 // 负责管理用户配置文件的加载和保存
 
+use crate::hooks::HookFailure;
+use crate::session::FailurePolicy;
+use crate::webhook::WebhookOn;
+use crate::error::{Classify, RecallError};
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// 通过 `--config` / `--portable` 指定的配置文件路径（覆盖系统标准配置目录）
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// 配置文件名
+const CONFIG_FILE_NAME: &str = "config.toml";
 
 /// 备份配置文件（Profile）
 ///
 /// 定义单个备份任务的所有配置参数，保存在全局配置文件中。
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Profile {
+    /// 继承的配置模板名称（`[templates.<name>]`），未在本配置中设置的选项使用模板中的值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
     /// 源路径（要备份的目录）
     pub source: PathBuf,
 
     /// 备份目标根路径
     pub destination: PathBuf,
 
+    /// 连接备份目标（如需要认证的 SMB 共享）所用的用户名，密码保存在系统凭据管理器中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// 对象存储目标（`s3://`）的区域，未设置时使用 `AWS_REGION`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// S3 兼容服务（如 MinIO）的地址，未设置时使用 `AWS_ENDPOINT_URL`，仍未设置则为 AWS S3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// 备份完成后用 rclone 复制新快照的远程目标（如 `offsite:backups/Projects`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicate: Option<String>,
+
+    /// 是否以分块去重仓库存储（适合内容局部修改的大文件），而不是硬链接快照目录
+    #[serde(default)]
+    pub chunked: bool,
+
+    /// 是否加密备份（以分块仓库存储，数据块和索引在写入前加密）
+    #[serde(default)]
+    pub encrypt: bool,
+
+    /// 加密口令的密钥文件，未设置时使用 `RECALL_PASSPHRASE` 或在终端中输入
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyfile: Option<PathBuf>,
+
     /// 是否启用内容检查（使用哈希值比较文件，更准确但更慢）
     pub check_content: bool,
 
@@ -26,22 +70,191 @@ pub struct Profile {
     #[serde(default)]
     pub vss: bool,
 
-    /// 工作线程数量
+    /// VSS 不可用时是否继续备份（不使用快照）
+    #[serde(default)]
+    pub vss_fallback: bool,
+
+    /// 参与 VSS 快照的写入器名称（为空时只创建崩溃一致的快照）
+    #[serde(default)]
+    pub vss_writers: Vec<String>,
+
+    /// 是否保留所有者和扩展属性（仅 Unix）
+    #[serde(default)]
+    pub preserve_metadata: bool,
+
+    /// 工作线程数量（0 表示自动确定）
     #[serde(default = "default_workers")]
     pub workers: usize,
 
     /// 排除模式列表（Glob 风格）
     pub exclude: Vec<String>,
+
+    /// 额外读取的排除文件（`.recallignore` 格式），相对路径相对于配置文件所在目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_file: Option<PathBuf>,
+
+    /// 复制缓冲区大小（字节），0 表示使用系统默认的复制方式
+    #[serde(default)]
+    pub buffer_size: usize,
+
+    /// 是否对大文件使用无缓冲 I/O（绕过系统页缓存）
+    #[serde(default)]
+    pub unbuffered: bool,
+
+    /// 复制时的带宽上限（字节/秒），0 表示不限制
+    #[serde(default)]
+    pub bandwidth_limit: u64,
+
+    /// 是否跳过备份前的可用空间检查（省去预先扫描源目录）
+    #[serde(default)]
+    pub skip_space_check: bool,
+
+    /// 被其他进程锁定而无法读取的文件是否计为失败（否则只单独统计，不影响备份结果）
+    #[serde(default)]
+    pub fail_on_locked: bool,
+
+    /// 失败的文件超过 `max_failures` 时如何处理快照：`commit`（默认）、`incomplete` 或 `partial`
+    #[serde(default, skip_serializing_if = "FailurePolicy::is_commit")]
+    pub on_failure: FailurePolicy,
+
+    /// 允许的失败文件数量，超过时按 `on_failure` 处理
+    #[serde(default)]
+    pub max_failures: u64,
+
+    /// 提交后是否把快照设为只读，防止误改与其他快照共享硬链接的文件
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// 提交时是否写入完整性清单，供 `recall verify` 按哈希值校验快照
+    #[serde(default)]
+    pub manifest: bool,
+
+    /// 备份成功后保留的快照数量，超出的旧快照会被自动清理（未设置时不自动清理）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<usize>,
+
+    /// 是否默认以试运行模式运行（用于先验证新配置）
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// 快照目录名称的时间戳格式（strftime 风格，未设置时使用全局默认或 `%Y-%m-%d_%H-%M-%S`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_format: Option<String>,
+
+    /// 快照目录名称是否使用 UTC 时间（适用于会切换时区或夏令时的服务器）
+    #[serde(default)]
+    pub utc: bool,
+
+    /// 所属的分组（`recall run --group <GROUP>` 只运行该分组的配置文件）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+
+    /// 批量运行时的优先级，数值大的先运行（相同时按名称排序）
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+
+    /// 备份开始前运行的命令（如导出数据库），通过系统 shell 执行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_hook: Option<String>,
+
+    /// 备份结束后运行的命令（如卸载备份盘），备份失败时同样运行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_hook: Option<String>,
+
+    /// 钩子失败时的处理方式：`abort`（默认）或 `continue`
+    #[serde(default, skip_serializing_if = "HookFailure::is_abort")]
+    pub hook_failure: HookFailure,
+
+    /// 每次运行后接收结果（JSON）的 Webhook URL（Slack、Discord、Gotify、ntfy 等）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<String>,
+
+    /// 发送 Webhook 的时机：`always`（默认）或 `failure`
+    #[serde(default, skip_serializing_if = "WebhookOn::is_always")]
+    pub webhook_on: WebhookOn,
+
+    /// 运行开始和结束时请求的监控 URL（Healthchecks.io 等），长时间没有请求时由监控服务告警
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_url: Option<String>,
+
+    /// 备份结束或失败时在桌面上显示系统通知
+    #[serde(default)]
+    pub notify: bool,
+
+    /// `recall daemon` 运行此配置文件的计划（cron 表达式，如 `0 2 * * *`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+}
+
+/// 全局默认设置（`[defaults]`）
+///
+/// 适用于命令行临时运行（`recall <SOURCE> <DESTINATION>`），
+/// 以及没有单独设置对应选项的配置文件。
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Defaults {
+    /// 默认工作线程数量（配置文件中 `workers = 0` 时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workers: Option<usize>,
+
+    /// 默认排除模式，追加在每个配置文件和命令行的排除模式之前
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// 所有运行都会通知的 Webhook URL，追加在每个配置文件的 Webhook 之前
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<String>,
+
+    /// 默认保留的快照数量（配置文件未设置 `keep` 时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<usize>,
+
+    /// 默认的快照目录名称格式（配置文件未设置 `snapshot_format` 时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_format: Option<String>,
+
+    /// 快照目录名称是否默认使用 UTC 时间
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub utc: bool,
+
+    /// 颜色输出：`auto`（默认，终端中启用）、`always` 或 `never`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+
+    /// 界面语言：`en`、`zh` 或 `auto`（根据 LANG 等环境变量选择）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl Defaults {
+    /// 是否没有设置任何默认值（保存时省略空的 `[defaults]` 表）
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_none()
+            && self.exclude.is_empty()
+            && self.webhooks.is_empty()
+            && self.keep.is_none()
+            && self.snapshot_format.is_none()
+            && !self.utc
+            && self.color.is_none()
+            && self.language.is_none()
+    }
 }
 
 /// 应用程序全局配置
 ///
-/// 包含所有用户定义的备份配置文件（Profile）。
+/// 包含全局默认设置和所有用户定义的备份配置文件（Profile）。
 /// 配置文件存储在系统标准配置目录中。
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct AppConfig {
-    /// 配置文件集合，键为配置文件名称
-    pub profiles: HashMap<String, Profile>,
+    /// 全局默认设置
+    #[serde(default, skip_serializing_if = "Defaults::is_empty")]
+    pub defaults: Defaults,
+
+    /// 配置模板（`[templates.<name>]`），可包含配置文件的任意选项，供配置文件通过 `extends` 继承
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, toml::Table>,
+
+    /// 配置文件集合，键为配置文件名称（按名称排序，保存的文件内容稳定，便于版本管理）
+    pub profiles: BTreeMap<String, Profile>,
 }
 
 impl AppConfig {
@@ -49,49 +262,421 @@ impl AppConfig {
     ///
     /// # 返回
     /// * `Ok(AppConfig)` - 加载的配置，如果文件不存在则返回空配置
-    /// * `Err(anyhow::Error)` - 如果配置文件存在但解析失败
-    pub fn load() -> Result<Self> {
-        let path = Self::get_config_path()?;
+    /// * `Err(RecallError)` - 如果配置文件存在但解析失败
+    pub fn load() -> crate::error::Result<Self> {
+        Self::load_from(&Self::get_config_path().classify(RecallError::Config)?)
+    }
+
+    /// 从指定文件加载应用配置
+    ///
+    /// # 参数
+    /// * `path` - 配置文件路径
+    ///
+    /// # 返回
+    /// * `Ok(AppConfig)` - 加载的配置，如果文件不存在则返回空配置
+    /// * `Err(RecallError)` - 如果配置文件存在但解析失败
+    pub fn load_from(path: &std::path::Path) -> crate::error::Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
-        let content = fs::read_to_string(path)?;
-        toml::from_str(&content).context("Failed to parse config file")
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))
+            .classify(RecallError::Config)?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse config file {:?}", path))
+            .classify(RecallError::Config)
+    }
+
+    /// 解析配置文件内容，并展开配置文件继承的模板
+    ///
+    /// 模板中的选项作为配置文件的默认值；`exclude` 列表合并（模板的排除模式在前）。
+    /// 模板本身也可以通过 `extends` 继承其他模板。
+    ///
+    /// # 参数
+    /// * `content` - TOML 格式的配置文件内容
+    ///
+    /// # 返回
+    /// * `Ok(AppConfig)` - 解析后的配置（配置文件中的值已与模板合并）
+    /// * `Err(RecallError)` - 解析失败、模板不存在或模板循环继承
+    pub fn parse(content: &str) -> crate::error::Result<Self> {
+        let mut raw: toml::Table = toml::from_str(content).classify(RecallError::Config)?;
+        let templates: BTreeMap<String, toml::Table> = match raw.get("templates") {
+            Some(value) => value.clone().try_into().classify(RecallError::Config)?,
+            None => BTreeMap::new(),
+        };
+
+        if let Some(toml::Value::Table(profiles)) = raw.get_mut("profiles") {
+            for (name, value) in profiles.iter_mut() {
+                let toml::Value::Table(profile) = value else {
+                    continue;
+                };
+                if let Some(base) = profile_template(&templates, profile)
+                    .with_context(|| format!("Invalid template for profile '{}'", name))
+                    .classify(RecallError::Config)?
+                {
+                    *profile = merge_template(base, profile);
+                }
+            }
+        }
+
+        toml::Value::Table(raw).try_into().classify(RecallError::Config)
+    }
+
+    /// 将配置序列化为 TOML 文本
+    ///
+    /// 继承模板的配置文件只写出与模板不同的选项（`exclude` 只写出模板之外的模式），
+    /// 修改模板后所有继承它的配置文件都会生效。
+    pub fn to_toml(&self) -> Result<String> {
+        let mut raw = toml::Table::try_from(self)?;
+        if let Some(toml::Value::Table(profiles)) = raw.get_mut("profiles") {
+            for (name, value) in profiles.iter_mut() {
+                let toml::Value::Table(profile) = value else {
+                    continue;
+                };
+                if let Some(base) = profile_template(&self.templates, profile)
+                    .with_context(|| format!("Invalid template for profile '{}'", name))?
+                {
+                    strip_template(profile, &base);
+                }
+            }
+        }
+        Ok(toml::to_string_pretty(&raw)?)
     }
 
     /// 保存配置到文件
     ///
-    /// 如果配置目录不存在，会自动创建。
+    /// 如果配置目录不存在，会自动创建。持有配置文件锁并原子替换文件，
+    /// 多个 recall 实例同时保存时不会写出损坏的文件；
+    /// 修改已有配置时应使用 [`AppConfig::update`]，避免覆盖其他实例保存的修改。
     ///
     /// # 返回
     /// * `Ok(())` - 配置保存成功
     /// * `Err(anyhow::Error)` - 保存失败
     pub fn save(&self) -> Result<()> {
         let path = Self::get_config_path()?;
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+        let _lock = lock_config(&path)?;
+        self.write_to(&path)
+    }
+
+    /// 重新加载配置、应用修改并保存
+    ///
+    /// 整个过程持有配置文件锁：先从磁盘重新加载最新的配置，再应用修改，
+    /// 其他 recall 实例在此期间保存的配置文件不会被覆盖。
+    ///
+    /// # 参数
+    /// * `modify` - 对最新配置的修改；返回错误时不保存
+    ///
+    /// # 返回
+    /// * `Ok(T)` - `modify` 的返回值（配置已保存）
+    /// * `Err(anyhow::Error)` - 加载、修改或保存失败
+    pub fn update<T>(modify: impl FnOnce(&mut AppConfig) -> Result<T>) -> Result<T> {
+        let path = Self::get_config_path()?;
+        let _lock = lock_config(&path)?;
+        let mut config = Self::load_from(&path)?;
+        let result = modify(&mut config)?;
+        config.write_to(&path)?;
+        Ok(result)
+    }
+
+    /// 将配置写入指定文件（先写入同目录的临时文件，再原子替换）
+    ///
+    /// 配置文件是符号链接时写入链接指向的文件，保留链接本身。
+    fn write_to(&self, path: &std::path::Path) -> Result<()> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::create_dir_all(&dir)?;
+
+        let content = self.to_toml()?;
+        let mut temp = tempfile::NamedTempFile::new_in(&dir)
+            .with_context(|| format!("Failed to create temporary file in {:?}", dir))?;
+        temp.write_all(content.as_bytes())
+            .and_then(|_| temp.as_file().sync_all())
+            .context("Failed to write config file")?;
+        temp.persist(&path)
+            .with_context(|| format!("Failed to replace config file {:?}", path))?;
+        Ok(())
+    }
+
+    /// 导出配置文件（Profile）为 TOML 文本
+    ///
+    /// 导出的配置文件继承的模板会一并导出。
+    ///
+    /// # 参数
+    /// * `names` - 要导出的配置文件名称；为空时导出全部
+    ///
+    /// # 返回
+    /// * `Ok(String)` - 与配置文件格式相同的 TOML 文本，可直接用于导入
+    /// * `Err(anyhow::Error)` - 指定的配置文件不存在或序列化失败
+    pub fn export(&self, names: &[String]) -> Result<String> {
+        let exported = if names.is_empty() {
+            self.profiles.clone()
+        } else {
+            names
+                .iter()
+                .map(|name| {
+                    self.profiles
+                        .get(name)
+                        .map(|p| (name.clone(), p.clone()))
+                        .with_context(|| format!("Profile '{}' not found", name))
+                })
+                .collect::<Result<_>>()?
+        };
+
+        // 导出的配置文件继承的模板（包括模板继承的模板）
+        let mut templates = BTreeMap::new();
+        let mut pending: Vec<String> = exported.values().filter_map(|p| p.extends.clone()).collect();
+        while let Some(name) = pending.pop() {
+            if templates.contains_key(&name) {
+                continue;
+            }
+            if let Some(template) = self.templates.get(&name) {
+                if let Some(toml::Value::String(parent)) = template.get("extends") {
+                    pending.push(parent.clone());
+                }
+                templates.insert(name, template.clone());
+            }
+        }
+
+        AppConfig { defaults: Defaults::default(), templates, profiles: exported }
+            .to_toml()
+            .context("Failed to serialize profiles")
+    }
+
+    /// 导入配置文件（Profile）
+    ///
+    /// 导入文件中的模板在本地不存在时一并导入（`overwrite` 为 `true` 时覆盖同名模板）。
+    ///
+    /// # 参数
+    /// * `other` - 要导入的配置
+    /// * `overwrite` - 同名配置文件是否覆盖；为 `false` 时保留现有配置并跳过
+    ///
+    /// # 返回
+    /// (新增的名称, 覆盖的名称, 跳过的名称)
+    pub fn import(&mut self, other: AppConfig, overwrite: bool) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let (mut added, mut overwritten, mut skipped) = (Vec::new(), Vec::new(), Vec::new());
+        for (name, template) in other.templates {
+            if overwrite || !self.templates.contains_key(&name) {
+                self.templates.insert(name, template);
+            }
+        }
+        for (name, profile) in other.profiles {
+            if self.profiles.contains_key(&name) {
+                if !overwrite {
+                    skipped.push(name);
+                    continue;
+                }
+                overwritten.push(name.clone());
+            } else {
+                added.push(name.clone());
+            }
+            self.profiles.insert(name, profile);
+        }
+        (added, overwritten, skipped)
+    }
+
+    /// 重命名配置文件（Profile），保留其所有设置
+    ///
+    /// # 参数
+    /// * `old` - 原名称
+    /// * `new` - 新名称
+    ///
+    /// # 返回
+    /// * `Ok(())` - 重命名成功（尚未保存到磁盘）
+    /// * `Err(anyhow::Error)` - 原配置不存在、新名称为空或已被占用
+    pub fn rename_profile(&mut self, old: &str, new: &str) -> Result<()> {
+        let new = new.trim();
+        if new.is_empty() {
+            anyhow::bail!("Profile name cannot be empty");
+        }
+        if old == new {
+            return Ok(());
         }
-        let content = toml::to_string_pretty(self)?;
-        fs::write(path, content).context("Failed to write config file")
+        if self.profiles.contains_key(new) {
+            anyhow::bail!("Profile '{}' already exists", new);
+        }
+        let profile = self
+            .profiles
+            .remove(old)
+            .with_context(|| format!("Profile '{}' not found", old))?;
+        self.profiles.insert(new.to_string(), profile);
+        Ok(())
+    }
+
+    /// 按批量运行的顺序列出配置文件名称
+    ///
+    /// 优先级高的在前，相同优先级按名称排序。
+    ///
+    /// # 参数
+    /// * `group` - 只列出属于此分组的配置文件（`None` 表示全部）
+    ///
+    /// # 返回
+    /// 配置文件名称列表
+    pub fn run_order(&self, group: Option<&str>) -> Vec<String> {
+        let mut profiles: Vec<(&String, &Profile)> = self
+            .profiles
+            .iter()
+            .filter(|(_, profile)| group.is_none_or(|g| profile.groups.iter().any(|pg| pg == g)))
+            .collect();
+        profiles.sort_by(|a, b| b.1.priority.cmp(&a.1.priority).then_with(|| a.0.cmp(b.0)));
+        profiles.into_iter().map(|(name, _)| name.clone()).collect()
     }
 
     /// 获取配置文件的路径
     ///
-    /// 使用 `directories` crate 获取平台标准的配置目录：
-    /// - Windows: `C:\Users\<用户>\AppData\Roaming\recall\config.toml`
-    /// - macOS: `~/Library/Application Support/recall/config.toml`
-    /// - Linux: `~/.config/recall/config.toml`
+    /// 按以下顺序确定：
+    /// 1. 通过 [`AppConfig::set_config_path`] 指定的路径（`--config` / `--portable`）
+    /// 2. 可执行文件所在目录中已存在的 `config.toml`（便携模式，如在备份 U 盘上运行）
+    /// 3. 使用 `directories` crate 获取的平台标准配置目录：
+    ///    - Windows: `C:\Users\<用户>\AppData\Roaming\recall\config.toml`
+    ///    - macOS: `~/Library/Application Support/recall/config.toml`
+    ///    - Linux: `~/.config/recall/config.toml`
     ///
     /// # 返回
     /// * `Ok(PathBuf)` - 配置文件的完整路径
     /// * `Err(anyhow::Error)` - 无法确定配置目录
-    fn get_config_path() -> Result<PathBuf> {
+    pub fn get_config_path() -> Result<PathBuf> {
+        if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+            return Ok(path.clone());
+        }
+        if let Some(path) = Self::portable_config_path() {
+            if path.exists() {
+                return Ok(path);
+            }
+        }
         let proj_dirs =
             ProjectDirs::from("", "", "recall").context("Could not determine config directory")?;
-        Ok(proj_dirs.config_dir().join("config.toml"))
+        Ok(proj_dirs.config_dir().join(CONFIG_FILE_NAME))
+    }
+
+    /// 指定本次运行使用的配置文件路径
+    ///
+    /// 只能在加载配置之前调用一次，之后的调用会被忽略。
+    ///
+    /// # 参数
+    /// * `path` - 配置文件路径
+    pub fn set_config_path(path: PathBuf) {
+        let _ = CONFIG_PATH_OVERRIDE.set(path);
+    }
+
+    /// 便携模式下的配置文件路径（可执行文件所在目录中的 `config.toml`）
+    pub fn portable_config_path() -> Option<PathBuf> {
+        let exe = std::env::current_exe().ok()?;
+        Some(exe.parent()?.join(CONFIG_FILE_NAME))
     }
 }
 
 fn default_workers() -> usize {
-    4
+    0
+}
+
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+/// 获取配置文件的独占锁（与配置文件同目录的 `<配置文件名>.lock`）
+///
+/// 锁在返回的文件关闭时释放；其他 recall 实例正在保存时等待其完成。
+fn lock_config(path: &std::path::Path) -> Result<fs::File> {
+    // 通过符号链接访问同一配置文件时使用同一个锁
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    if let Some(parent) = lock_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open config lock file {:?}", lock_path))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock config file {:?}", lock_path))?;
+    Ok(file)
+}
+
+/// 获取配置文件继承的模板（已展开模板自身的继承）
+///
+/// # 返回
+/// * `Ok(Some(Table))` - 合并后的模板选项
+/// * `Ok(None)` - 配置文件没有继承模板
+/// * `Err(anyhow::Error)` - 模板不存在或循环继承
+fn profile_template(
+    templates: &BTreeMap<String, toml::Table>,
+    profile: &toml::Table,
+) -> Result<Option<toml::Table>> {
+    match profile.get("extends") {
+        None => Ok(None),
+        Some(toml::Value::String(name)) => resolve_template(templates, name, &mut Vec::new()).map(Some),
+        Some(_) => anyhow::bail!("'extends' must be a template name"),
+    }
+}
+
+/// 展开模板及其继承的模板
+fn resolve_template(
+    templates: &BTreeMap<String, toml::Table>,
+    name: &str,
+    chain: &mut Vec<String>,
+) -> Result<toml::Table> {
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_string());
+        anyhow::bail!("Template inheritance cycle: {}", chain.join(" -> "));
+    }
+    let template = templates
+        .get(name)
+        .with_context(|| format!("Template '{}' not found", name))?;
+    chain.push(name.to_string());
+
+    match template.get("extends") {
+        None => Ok(template.clone()),
+        Some(toml::Value::String(parent)) => {
+            let base = resolve_template(templates, parent, chain)?;
+            Ok(merge_template(base, template))
+        }
+        Some(_) => anyhow::bail!("'extends' in template '{}' must be a template name", name),
+    }
+}
+
+/// 用配置文件（或子模板）的选项覆盖模板中的选项，`exclude` 列表合并
+fn merge_template(mut base: toml::Table, child: &toml::Table) -> toml::Table {
+    for (key, value) in child {
+        match (key.as_str(), base.get_mut(key), value) {
+            ("exclude", Some(toml::Value::Array(patterns)), toml::Value::Array(extra)) => {
+                for pattern in extra {
+                    if !patterns.contains(pattern) {
+                        patterns.push(pattern.clone());
+                    }
+                }
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    base
+}
+
+/// 移除配置文件中与模板相同的选项（`exclude` 中移除模板已包含的模式）
+fn strip_template(profile: &mut toml::Table, base: &toml::Table) {
+    for (key, value) in base {
+        if key == "extends" {
+            continue;
+        }
+        if let ("exclude", Some(toml::Value::Array(patterns)), toml::Value::Array(inherited)) =
+            (key.as_str(), profile.get_mut(key), value)
+        {
+            patterns.retain(|pattern| !inherited.contains(pattern));
+            if patterns.is_empty() {
+                profile.remove(key);
+            }
+            continue;
+        }
+        if profile.get(key) == Some(value) {
+            profile.remove(key);
+        }
+    }
 }
@@ -0,0 +1,379 @@
+// Recall - 备份摘要模块
+// 在每个快照目录中记录本次备份的统计信息，供 list / info 等命令读取
+
+use crate::actions::BackupStats;
+use crate::config::BackupConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 摘要文件名（位于快照目录根部）
+pub const SUMMARY_FILE: &str = ".recall-summary.toml";
+
+/// 完整性清单文件名（位于快照目录根部）
+pub const MANIFEST_FILE: &str = ".recall-manifest";
+
+/// 自描述信息文件名（位于快照目录根部）
+pub const INFO_FILE: &str = "backup.toml";
+
+/// 单次备份的摘要
+///
+/// 备份完成后写入快照目录，记录运行时间、统计数据、备份设置和所用的 recall 版本，
+/// 配置文件丢失后快照仍可自我描述。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSummary {
+    /// 执行备份的 recall 版本
+    pub version: String,
+
+    /// 开始时间（本地时间，`%Y-%m-%d %H:%M:%S`）
+    pub started_at: String,
+
+    /// 耗时（秒）
+    pub duration_secs: u64,
+
+    /// 源路径
+    pub source: PathBuf,
+
+    /// 执行备份的主机名（由旧版本创建的快照为空）
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub host: String,
+
+    /// 作为硬链接基准的上一次备份（快照目录名）
+    pub previous: Option<String>,
+
+    /// 处理的文件总数
+    pub total_files: u64,
+
+    /// 复制的新文件数量
+    pub copied_new: u64,
+
+    /// 复制的已修改文件数量
+    pub copied_modified: u64,
+
+    /// 通过块克隆更新的已修改文件数量
+    pub cloned: u64,
+
+    /// 硬链接的文件数量
+    pub linked: u64,
+
+    /// 硬链接的文件的总大小
+    pub bytes_linked: u64,
+
+    /// 无法创建硬链接而改为复制的文件数量
+    pub link_fallbacks: u64,
+
+    /// 跳过的文件数量
+    pub skipped: u64,
+
+    /// 创建的目录数量
+    pub dirs_created: u64,
+
+    /// 重新创建的符号链接数量
+    pub symlinks: u64,
+
+    /// 被排除模式跳过的条目数量
+    pub excluded: u64,
+
+    /// 续传时已完整存在而保留的文件数量
+    pub resumed: u64,
+
+    /// 失败的文件数量
+    pub failed: u64,
+
+    /// 被锁定而无法读取的文件数量
+    pub locked: u64,
+
+    /// 扫描之后被删除的源文件数量
+    pub vanished: u64,
+
+    /// 传输的总字节数
+    pub bytes_copied: u64,
+
+    /// 平均传输速度（字节/秒）
+    pub bytes_per_sec: u64,
+
+    /// 快照的标签和备注（创建时用 `--tag` 指定，或之后用 `recall tag` 添加）
+    pub tags: Vec<String>,
+
+    /// 创建快照时使用的备份设置（由旧版本创建或导入的快照为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<BackupSettings>,
+
+    /// 同时写入的完整性清单的格式版本（没有写入清单时为空）
+    ///
+    /// 记录为带校验和的版本时，读取清单会拒绝没有校验和的旧版清单，防止删掉校验和行后降级为不检查。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<u32>,
+
+    /// 快照根部的 `backup.toml` 是否由 recall 写入（为 `false` 时同名文件来自备份源，恢复时照常还原）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub info_file: bool,
+}
+
+/// 快照的自描述信息
+///
+/// 以 `backup.toml` 的文件名写入快照目录，不需要 recall 也能直接查看：多年后翻看备份盘时，
+/// 仍能知道快照来自哪台主机的哪个目录、由哪个版本创建、排除了哪些文件。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupInfo {
+    /// 源路径
+    pub source: PathBuf,
+
+    /// 执行备份的主机名
+    pub host: String,
+
+    /// 执行备份的 recall 版本
+    pub version: String,
+
+    /// 作为硬链接基准的上一个快照（快照目录名，首次备份时为空）
+    pub linked_against: Option<String>,
+
+    /// 是否使用了 VSS 快照
+    pub vss: bool,
+
+    /// 生效的排除模式（包括 `.recallignore` 和 `.recall.toml` 中的模式）
+    pub exclude: Vec<String>,
+}
+
+impl BackupInfo {
+    /// 根据本次备份的配置创建自描述信息
+    ///
+    /// # 参数
+    /// * `config` - 备份配置
+    /// * `previous` - 作为硬链接基准的上一个快照的路径（如有）
+    /// * `vss` - 是否实际使用了 VSS 快照
+    pub fn new(config: &BackupConfig, previous: Option<&Path>, vss: bool) -> Self {
+        Self {
+            source: config.source.clone(),
+            host: crate::utils::host_name().unwrap_or_default(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            linked_against: previous
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+            vss,
+            exclude: config.exclude_patterns.clone(),
+        }
+    }
+
+    /// 将自描述信息写入快照目录
+    ///
+    /// 备份源根部本身有 `backup.toml` 时，快照中的同名文件属于备份内容，不会被覆盖。
+    ///
+    /// # 参数
+    /// * `backup_dir` - 快照目录
+    ///
+    /// # 返回
+    /// * `Ok(true)` - 已写入
+    /// * `Ok(false)` - 快照中已有来自备份源的同名文件，没有写入
+    /// * `Err(anyhow::Error)` - 序列化或写入失败
+    pub fn save(&self, backup_dir: &Path) -> Result<bool> {
+        let path = backup_dir.join(INFO_FILE);
+        if fs::symlink_metadata(&path).is_ok() {
+            tracing::debug!("{:?} comes from the source, not writing backup information", path);
+            return Ok(false);
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize backup information")?;
+        fs::write(&path, content).with_context(|| format!("Failed to write backup information to {:?}", path))?;
+        Ok(true)
+    }
+}
+
+/// 创建快照时使用的备份设置
+///
+/// 与统计信息一起写入摘要，配置文件丢失或修改后仍能知道快照是如何创建的。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// 备份目标
+    pub destination: PathBuf,
+
+    /// 是否使用哈希值比较文件内容
+    pub check_content: bool,
+
+    /// 是否使用 VSS 快照
+    pub vss: bool,
+
+    /// 是否保留所有者和扩展属性
+    pub preserve_metadata: bool,
+
+    /// 工作线程数量
+    pub workers: usize,
+
+    /// 带宽上限（字节/秒，0 表示不限制）
+    pub bandwidth_limit: u64,
+
+    /// 自动清理时保留的快照数量
+    pub keep: Option<usize>,
+
+    /// 排除模式（包括 `.recallignore` 和 `.recall.toml` 中的模式）
+    pub exclude: Vec<String>,
+
+    /// 快照是否设为只读（其中文件的写权限在恢复时补回）
+    pub read_only: bool,
+}
+
+impl BackupSettings {
+    /// 从实际使用的备份配置中提取设置
+    ///
+    /// # 参数
+    /// * `config` - 备份配置
+    /// * `workers` - 实际使用的工作线程数量（配置为 0 时自动确定）
+    /// * `vss` - 是否实际使用了 VSS 快照
+    pub fn from_config(config: &BackupConfig, workers: usize, vss: bool) -> Self {
+        Self {
+            destination: config.destination.clone(),
+            check_content: config.check_content,
+            vss,
+            preserve_metadata: config.preserve_metadata,
+            workers,
+            bandwidth_limit: config.bandwidth_limit,
+            keep: config.keep,
+            exclude: config.exclude_patterns.clone(),
+            read_only: config.read_only,
+        }
+    }
+}
+
+impl BackupSummary {
+    /// 根据备份统计信息创建摘要
+    ///
+    /// # 参数
+    /// * `stats` - 备份统计信息
+    /// * `source` - 源路径
+    /// * `previous` - 上一次备份的路径（如有）
+    /// * `started_at` - 开始时间
+    /// * `duration_secs` - 耗时（秒）
+    /// * `tags` - 快照的标签和备注
+    /// * `settings` - 备份设置
+    pub fn new(
+        stats: &BackupStats,
+        source: &Path,
+        previous: Option<&Path>,
+        started_at: DateTime<Local>,
+        duration_secs: u64,
+        tags: Vec<String>,
+        settings: BackupSettings,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_secs,
+            source: source.to_path_buf(),
+            host: crate::utils::host_name().unwrap_or_default(),
+            previous: previous
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string()),
+            total_files: stats.total_files,
+            copied_new: stats.copied_new,
+            copied_modified: stats.copied_modified,
+            cloned: stats.cloned,
+            linked: stats.linked,
+            bytes_linked: stats.bytes_linked,
+            link_fallbacks: stats.link_fallbacks,
+            skipped: stats.skipped,
+            dirs_created: stats.dirs_created,
+            symlinks: stats.symlinks,
+            excluded: stats.excluded,
+            resumed: stats.resumed,
+            failed: stats.failed,
+            locked: stats.locked,
+            vanished: stats.vanished,
+            bytes_copied: stats.bytes_copied,
+            bytes_per_sec: stats.bytes_per_sec,
+            tags,
+            settings: Some(settings),
+            manifest: None,
+            info_file: false,
+        }
+    }
+
+    /// 摘要中是否包含备份统计信息
+    ///
+    /// 为旧版本创建的快照添加标签时会写入只有标签的摘要，此时返回 `false`。
+    pub fn has_stats(&self) -> bool {
+        !self.started_at.is_empty()
+    }
+
+    /// 快照中的相对路径是否为 recall 写入的摘要、清单或自描述信息
+    ///
+    /// # 参数
+    /// * `rel` - 相对于快照目录的路径
+    /// * `summary` - 快照的摘要（没有摘要时为 `None`，此时 `backup.toml` 视为备份内容）
+    pub fn is_metadata(rel: &Path, summary: Option<&Self>) -> bool {
+        rel == Path::new(SUMMARY_FILE)
+            || rel == Path::new(MANIFEST_FILE)
+            || (rel == Path::new(INFO_FILE) && summary.is_some_and(|s| s.info_file))
+    }
+
+    /// 将摘要写入快照目录
+    ///
+    /// # 参数
+    /// * `backup_dir` - 快照目录
+    ///
+    /// # 返回
+    /// * `Ok(())` - 写入成功
+    /// * `Err(anyhow::Error)` - 序列化或写入失败
+    pub fn save(&self, backup_dir: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize backup summary")?;
+        fs::write(backup_dir.join(SUMMARY_FILE), content)
+            .with_context(|| format!("Failed to write backup summary to {:?}", backup_dir))
+    }
+
+    /// 从快照目录读取摘要
+    ///
+    /// # 参数
+    /// * `backup_dir` - 快照目录
+    ///
+    /// # 返回
+    /// * `Ok(Some(BackupSummary))` - 读取成功
+    /// * `Ok(None)` - 快照中没有摘要（由旧版本创建）
+    /// * `Err(anyhow::Error)` - 摘要文件存在但读取或解析失败
+    pub fn load(backup_dir: &Path) -> Result<Option<Self>> {
+        let path = backup_dir.join(SUMMARY_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read backup summary {:?}", path))?;
+        let summary = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse backup summary {:?}", path))?;
+        Ok(Some(summary))
+    }
+}
+
+/// 添加或移除快照的标签
+///
+/// 快照没有摘要（由旧版本创建）时，写入只包含标签的摘要。
+///
+/// # 参数
+/// * `backup_dir` - 快照目录
+/// * `tag` - 标签或备注文本
+/// * `remove` - 是否移除该标签（否则添加）
+///
+/// # 返回
+/// * `Ok(Vec<String>)` - 修改后的全部标签
+/// * `Err(anyhow::Error)` - 标签为空、要移除的标签不存在，或读写摘要失败
+pub fn tag_snapshot(backup_dir: &Path, tag: &str, remove: bool) -> Result<Vec<String>> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        anyhow::bail!("Tag cannot be empty");
+    }
+
+    let mut summary = BackupSummary::load(backup_dir)?.unwrap_or_default();
+    if remove {
+        let before = summary.tags.len();
+        summary.tags.retain(|t| t != tag);
+        if summary.tags.len() == before {
+            anyhow::bail!("Snapshot {:?} has no tag '{}'", backup_dir, tag);
+        }
+    } else if !summary.tags.iter().any(|t| t == tag) {
+        summary.tags.push(tag.to_string());
+    }
+
+    summary.save(backup_dir)?;
+    Ok(summary.tags)
+}
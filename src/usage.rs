@@ -0,0 +1,103 @@
+// Recall - 磁盘占用统计模块
+// 按文件标识识别硬链接，计算每个快照独占的空间和目标目录的实际物理占用
+
+use crate::naming::SnapshotNaming;
+use crate::prune::find_all_backups;
+use crate::utils::{file_identity, to_verbatim_path};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 单个快照的空间占用
+#[derive(Debug, Clone)]
+pub struct SnapshotUsage {
+    /// 快照目录
+    pub path: PathBuf,
+
+    /// 文件数量
+    pub files: u64,
+
+    /// 表观大小（所有文件大小之和，硬链接重复计算）
+    pub apparent: u64,
+
+    /// 独占大小（只被此快照引用的数据，删除此快照可释放的空间）
+    pub exclusive: u64,
+}
+
+/// 备份目标目录的空间占用
+#[derive(Debug, Clone)]
+pub struct DestinationUsage {
+    /// 各快照的空间占用（最旧的在前）
+    pub snapshots: Vec<SnapshotUsage>,
+
+    /// 实际物理占用（每份数据只计算一次）
+    pub physical: u64,
+}
+
+/// 计算备份目标目录中各快照的空间占用
+///
+/// 同一文件的多个硬链接具有相同的文件标识，只在第一次出现时计入物理占用；
+/// 只出现在一个快照中的数据计入该快照的独占大小。
+///
+/// # 参数
+/// * `destination` - 备份目标根目录（项目目录）
+/// * `naming` - 快照命名规则
+///
+/// # 返回
+/// * `Ok(DestinationUsage)` - 空间占用统计
+/// * `Err(anyhow::Error)` - 读取目录失败
+pub fn compute_usage(destination: &Path, naming: &SnapshotNaming) -> Result<DestinationUsage> {
+    let backups = find_all_backups(destination, naming)?;
+
+    // 文件标识 -> (大小, 首次出现的快照序号, 是否被多个快照共享)
+    let mut seen: HashMap<(u64, u64), (u64, usize, bool)> = HashMap::new();
+    let mut snapshots = Vec::with_capacity(backups.len());
+    let mut physical = 0u64;
+
+    for (index, backup) in backups.iter().enumerate() {
+        let mut usage = SnapshotUsage {
+            path: backup.clone(),
+            files: 0,
+            apparent: 0,
+            exclusive: 0,
+        };
+
+        for entry in WalkDir::new(to_verbatim_path(backup)).follow_links(false).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let size = match entry.metadata() {
+                Ok(meta) => meta.len(),
+                Err(_) => continue,
+            };
+            usage.files += 1;
+            usage.apparent += size;
+
+            match file_identity(entry.path()) {
+                Some(id) => {
+                    let record = seen.entry(id).or_insert((size, index, false));
+                    if record.1 != index {
+                        record.2 = true;
+                    }
+                }
+                // 无法识别的文件按独立数据计算
+                None => {
+                    usage.exclusive += size;
+                    physical += size;
+                }
+            }
+        }
+
+        snapshots.push(usage);
+    }
+
+    for (size, first, shared) in seen.into_values() {
+        physical += size;
+        if !shared {
+            snapshots[first].exclusive += size;
+        }
+    }
+
+    Ok(DestinationUsage { snapshots, physical })
+}
@@ -2,7 +2,184 @@
 // 提供路径处理、模式匹配、格式化等辅助功能
 
 use glob::Pattern;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// 是否以 JSON 输出结果（`--json`）
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否以 JSON 输出结果
+///
+/// 启用后，[`status!`](crate::status) 输出的提示信息改为写入标准错误，
+/// 标准输出只保留 JSON 结果，便于脚本解析。
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// 是否以 JSON 输出结果
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// 输出面向用户的提示信息
+///
+/// 与 `println!` 用法相同，作为目标为 `recall::console` 的 `INFO` 事件发出。
+/// 命令行程序的订阅者把它写入标准输出（启用 JSON 输出时改为标准错误）和日志文件（`--log-file`）；
+/// 用于结果和汇总信息，任何详细程度下都会输出。
+#[macro_export]
+macro_rules! status {
+    () => {
+        $crate::status!("")
+    };
+    ($($arg:tt)*) => {
+        $crate::tracing::event!(target: "recall::console", $crate::tracing::Level::INFO, "{}", format_args!($($arg)*))
+    };
+}
+
+/// 输出警告或错误信息（`WARN` 事件，写入标准错误和日志文件）
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::tracing::event!(target: "recall::console", $crate::tracing::Level::WARN, "{}", format_args!($($arg)*))
+    };
+}
+
+/// 输出过程信息（`DEBUG` 事件），安静模式（`-q`）下不输出
+#[macro_export]
+macro_rules! note {
+    ($($arg:tt)*) => {
+        $crate::tracing::event!(target: "recall::console", $crate::tracing::Level::DEBUG, "{}", format_args!($($arg)*))
+    };
+}
+
+/// 输出详细信息（`TRACE` 事件），仅在详细模式（`-v`）下输出
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        $crate::tracing::event!(target: "recall::console", $crate::tracing::Level::TRACE, "{}", format_args!($($arg)*))
+    };
+}
+
+/// 输出详细程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// 安静模式：只输出汇总信息，不输出逐个文件的警告
+    Quiet,
+    /// 默认：输出过程信息和逐个文件的警告、失败
+    Normal,
+    /// 详细模式：额外输出对每个文件执行的操作
+    Verbose,
+}
+
+/// 当前输出详细程度（`-q` / `-v`）
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// 设置输出详细程度
+pub fn set_verbosity(level: Verbosity) {
+    VERBOSITY.store(level as u8, Ordering::Relaxed);
+}
+
+/// 当前输出详细程度
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// 为终端输出的文本设置颜色和样式（由 console 提供）
+#[cfg(feature = "cli")]
+pub use console::style;
+
+/// 为终端输出的文本设置颜色和样式（没有 `cli` 功能时不输出颜色）
+#[cfg(not(feature = "cli"))]
+pub fn style<D>(value: D) -> Unstyled<D> {
+    Unstyled(value)
+}
+
+/// 不带颜色的文本，样式方法不做任何修改（没有 `cli` 功能时代替 console 的样式）
+#[cfg(not(feature = "cli"))]
+pub struct Unstyled<D>(D);
+
+#[cfg(not(feature = "cli"))]
+impl<D> Unstyled<D> {
+    pub fn red(self) -> Self {
+        self
+    }
+
+    pub fn green(self) -> Self {
+        self
+    }
+
+    pub fn yellow(self) -> Self {
+        self
+    }
+
+    pub fn blue(self) -> Self {
+        self
+    }
+
+    pub fn cyan(self) -> Self {
+        self
+    }
+
+    pub fn dim(self) -> Self {
+        self
+    }
+
+    pub fn bold(self) -> Self {
+        self
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+impl<D: std::fmt::Display> std::fmt::Display for Unstyled<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+impl<D: std::fmt::Debug> std::fmt::Debug for Unstyled<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// 是否使用纯文本输出（`NO_COLOR` 或非终端环境）
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// 检测输出环境并设置纯文本输出
+///
+/// 设置了 `NO_COLOR` 环境变量，或标准输出不是终端（cron、CI、重定向到日志文件）时，
+/// 关闭 ANSI 颜色，动态进度条改为定期输出的纯文本进度行，避免日志中充满控制字符。
+///
+/// # 参数
+/// * `color` - 配置文件中的颜色设置：`always` 总是输出颜色，`never` 从不输出，其他值自动检测
+pub fn init_plain_output(color: Option<&str>) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let plain = no_color || color == Some("never") || !std::io::stdout().is_terminal();
+    #[cfg(feature = "cli")]
+    {
+        let colors = match color {
+            Some("always") => true,
+            Some("never") => false,
+            _ => !plain,
+        };
+        if !colors || color == Some("always") {
+            console::set_colors_enabled(colors);
+            console::set_colors_enabled_stderr(colors);
+        }
+    }
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+}
+
+/// 是否使用纯文本输出（不显示动态进度条和颜色）
+pub fn plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
 
 /// 将路径转换为 Windows 逐字路径格式
 ///
@@ -72,6 +249,300 @@ pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
     }
 }
 
+/// 将当前进程切换为后台（低优先级）模式
+///
+/// - Windows：`PROCESS_MODE_BACKGROUND_BEGIN`，同时降低 CPU 和 I/O 优先级
+/// - Unix：将 nice 值调到最低优先级，I/O 调度器会随之降低优先级
+///
+/// # 返回
+/// * `Ok(())` - 切换成功
+/// * `Err(anyhow::Error)` - 系统调用失败
+#[cfg(windows)]
+pub fn enter_background_mode() -> anyhow::Result<()> {
+    use winapi::um::processthreadsapi::{GetCurrentProcess, SetPriorityClass};
+    use winapi::um::winbase::PROCESS_MODE_BACKGROUND_BEGIN;
+
+    if unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN) } == 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// 将当前进程切换为后台（低优先级）模式（Unix 使用 nice）
+#[cfg(unix)]
+pub fn enter_background_mode() -> anyhow::Result<()> {
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 19) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// 为当前进程启用备份特权（`SeBackupPrivilege`）
+///
+/// 启用后，以 `FILE_FLAG_BACKUP_SEMANTICS` 打开的文件会绕过 ACL 检查，
+/// 管理员可以读取其他用户的配置文件、系统目录等受保护的文件。
+///
+/// # 返回
+/// * `Ok(())` - 特权已启用
+/// * `Err(anyhow::Error)` - 进程令牌中没有此特权（通常是未以管理员身份运行）
+#[cfg(windows)]
+pub fn enable_backup_privilege() -> anyhow::Result<()> {
+    use winapi::shared::winerror::ERROR_NOT_ALL_ASSIGNED;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
+    use winapi::um::securitybaseapi::AdjustTokenPrivileges;
+    use winapi::um::winbase::LookupPrivilegeValueW;
+    use winapi::um::winnt::{
+        SE_BACKUP_NAME, SE_PRIVILEGE_ENABLED, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+
+    let name: Vec<u16> = SE_BACKUP_NAME.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut token = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY, &mut token) == 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        let mut privileges: TOKEN_PRIVILEGES = std::mem::zeroed();
+        privileges.PrivilegeCount = 1;
+        privileges.Privileges[0].Attributes = SE_PRIVILEGE_ENABLED;
+
+        let result = if LookupPrivilegeValueW(std::ptr::null(), name.as_ptr(), &mut privileges.Privileges[0].Luid) == 0
+            || AdjustTokenPrivileges(token, 0, &mut privileges, 0, std::ptr::null_mut(), std::ptr::null_mut()) == 0
+        {
+            Err(std::io::Error::last_os_error().into())
+        } else if GetLastError() == ERROR_NOT_ALL_ASSIGNED {
+            // 调用成功但令牌中没有此特权
+            Err(anyhow::anyhow!("SeBackupPrivilege is not held by the current user"))
+        } else {
+            Ok(())
+        };
+
+        CloseHandle(token);
+        result
+    }
+}
+
+/// 单个文件允许的最大硬链接数（NTFS 限制为 1023 个额外链接）
+///
+/// 仅用于 Windows；Unix 各文件系统上限差异很大（ext4 为 65000，btrfs、XFS 远高于此），
+/// 直接尝试链接并在 `EMLINK` 时复制，见 [`is_too_many_links`]。
+#[cfg(windows)]
+pub const MAX_HARD_LINKS: u64 = 1023;
+
+/// 获取文件当前的硬链接数
+///
+/// # 参数
+/// * `path` - 文件路径
+///
+/// # 返回
+/// * `Some(u64)` - 硬链接数
+/// * `None` - 无法读取
+#[cfg(unix)]
+pub fn hard_link_count(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().map(|m| m.nlink())
+}
+
+/// 获取文件当前的硬链接数（Windows 通过 `GetFileInformationByHandle` 读取）
+#[cfg(windows)]
+pub fn hard_link_count(path: &Path) -> Option<u64> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+    Some(info.nNumberOfLinks as u64)
+}
+
+/// 获取本机的主机名
+///
+/// # 返回
+/// * `Some(String)` - 主机名
+/// * `None` - 无法获取
+#[cfg(unix)]
+pub fn host_name() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: 缓冲区有效且长度正确，gethostname 最多写入 buf.len() 字节
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned()).filter(|name| !name.is_empty())
+}
+
+/// 获取本机的主机名（Windows 使用 `COMPUTERNAME` 环境变量）
+#[cfg(not(unix))]
+pub fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok().filter(|name| !name.is_empty())
+}
+
+/// 获取文件的唯一标识，同一文件的所有硬链接标识相同
+///
+/// # 参数
+/// * `path` - 文件路径
+///
+/// # 返回
+/// * `Some((u64, u64))` - (设备号, inode 号)
+/// * `None` - 无法读取
+#[cfg(unix)]
+pub fn file_identity(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+/// 获取文件的唯一标识（Windows 使用卷序列号和文件索引）
+#[cfg(windows)]
+pub fn file_identity(path: &Path) -> Option<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Some((info.dwVolumeSerialNumber as u64, index))
+}
+
+/// 检查错误是否由于硬链接数超过文件系统上限
+///
+/// - Unix: `EMLINK`
+/// - Windows: `ERROR_TOO_MANY_LINKS`
+pub fn is_too_many_links(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const TOO_MANY_LINKS: i32 = libc::EMLINK;
+    #[cfg(windows)]
+    const TOO_MANY_LINKS: i32 = winapi::shared::winerror::ERROR_TOO_MANY_LINKS as i32;
+
+    err.raw_os_error() == Some(TOO_MANY_LINKS)
+}
+
+/// 检查错误是否表示无法在此位置创建硬链接
+///
+/// 包括跨卷链接以及目标文件系统不支持硬链接（exFAT、部分 SMB 共享等），属于整个目标的限制。
+/// 单个文件拒绝链接（`EPERM`）见 [`is_link_denied`]。
+pub fn is_link_unsupported(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const UNSUPPORTED: &[i32] = &[libc::EXDEV, libc::ENOTSUP, libc::EOPNOTSUPP, libc::ENOSYS];
+    #[cfg(windows)]
+    const UNSUPPORTED: &[i32] = &[
+        winapi::shared::winerror::ERROR_NOT_SAME_DEVICE as i32,
+        winapi::shared::winerror::ERROR_NOT_SUPPORTED as i32,
+        winapi::shared::winerror::ERROR_INVALID_FUNCTION as i32,
+    ];
+
+    err.raw_os_error().is_some_and(|code| UNSUPPORTED.contains(&code))
+}
+
+/// 检查错误是否表示不允许链接这一个文件
+///
+/// Unix 上的 `EPERM`：启用 `fs.protected_hardlinks` 时链接他人拥有的文件、不可变或只追加的文件等。
+/// 只影响这一个文件，应改为复制它，而不是认为整个目标不支持硬链接
+/// （FAT32 在 Linux 上也返回 `EPERM`，由启动时的探测发现）。
+pub fn is_link_denied(err: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const DENIED: Option<i32> = Some(libc::EPERM);
+    #[cfg(not(unix))]
+    const DENIED: Option<i32> = None;
+
+    DENIED.is_some() && err.raw_os_error() == DENIED
+}
+
+/// 检查错误是否表示无法在此位置创建符号链接
+///
+/// 除 [`is_link_unsupported`] 的情况外，还包括 Windows 上缺少创建符号链接的权限
+/// （未启用开发者模式且非管理员，或 SMB 共享不允许创建符号链接），以及 Unix 上的 `EPERM`
+/// （创建符号链接不涉及已有文件，`EPERM` 只会来自文件系统本身，如 FAT32）。
+pub fn is_symlink_unsupported(err: &anyhow::Error) -> bool {
+    #[cfg(windows)]
+    const NO_PRIVILEGE: Option<i32> = Some(winapi::shared::winerror::ERROR_PRIVILEGE_NOT_HELD as i32);
+    #[cfg(not(windows))]
+    const NO_PRIVILEGE: Option<i32> = None;
+
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|io_err| {
+            is_link_unsupported(io_err)
+                || is_link_denied(io_err)
+                || (NO_PRIVILEGE.is_some() && io_err.raw_os_error() == NO_PRIVILEGE)
+        })
+}
+
+/// 检查错误是否由于网络连接中断（可能是暂时的，值得重试）
+///
+/// 会沿错误链查找底层的 I/O 错误，例如 SMB 共享连接断开、网络名不再可用、超时等。
+pub fn is_network_error(err: &anyhow::Error) -> bool {
+    #[cfg(unix)]
+    const NETWORK: &[i32] = &[
+        libc::ETIMEDOUT,
+        libc::ECONNRESET,
+        libc::ECONNABORTED,
+        libc::EHOSTDOWN,
+        libc::EHOSTUNREACH,
+        libc::ENETDOWN,
+        libc::ENETRESET,
+        libc::ENETUNREACH,
+        libc::ESTALE,
+    ];
+    #[cfg(windows)]
+    const NETWORK: &[i32] = &[
+        winapi::shared::winerror::ERROR_BAD_NETPATH as i32,
+        winapi::shared::winerror::ERROR_NETWORK_BUSY as i32,
+        winapi::shared::winerror::ERROR_DEV_NOT_EXIST as i32,
+        winapi::shared::winerror::ERROR_UNEXP_NET_ERR as i32,
+        winapi::shared::winerror::ERROR_NETNAME_DELETED as i32,
+        winapi::shared::winerror::ERROR_SEM_TIMEOUT as i32,
+        winapi::shared::winerror::ERROR_NETWORK_UNREACHABLE as i32,
+        winapi::shared::winerror::ERROR_CONNECTION_ABORTED as i32,
+    ];
+
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|io_err| {
+            io_err.kind() == std::io::ErrorKind::TimedOut
+                || io_err.raw_os_error().is_some_and(|code| NETWORK.contains(&code))
+        })
+}
+
+/// 检查错误是否由于文件被其他进程锁定（共享冲突）
+///
+/// 会沿错误链查找底层的 I/O 错误。仅 Windows 上存在强制文件锁：
+/// `ERROR_SHARING_VIOLATION` 或 `ERROR_LOCK_VIOLATION`。
+pub fn is_sharing_violation(err: &anyhow::Error) -> bool {
+    #[cfg(windows)]
+    const LOCKED: &[i32] = &[
+        winapi::shared::winerror::ERROR_SHARING_VIOLATION as i32,
+        winapi::shared::winerror::ERROR_LOCK_VIOLATION as i32,
+    ];
+    #[cfg(not(windows))]
+    const LOCKED: &[i32] = &[];
+
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|io_err| io_err.raw_os_error().is_some_and(|code| LOCKED.contains(&code)))
+}
+
+/// 检查错误是否为文件或目录不存在（`NotFound`）
+///
+/// # 参数
+/// * `err` - 要检查的错误（检查整个错误链）
+///
+/// # 返回
+/// * `true` - 错误链中包含 `NotFound` 错误
+/// * `false` - 其他错误
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|io_err| io_err.kind() == std::io::ErrorKind::NotFound)
+}
+
 /// 检查路径是否匹配任一排除模式
 ///
 /// 使用 Glob 风格的模式匹配来判断文件路径是否应该被排除。
@@ -90,15 +561,48 @@ pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
 /// // 模式 "node_modules" 可以匹配任何目录名为 node_modules 的路径
 /// ```
 pub fn matches_exclude_pattern(rel_path: &Path, patterns: &[Pattern]) -> bool {
-    let path_str = rel_path.to_string_lossy();
+    matching_exclude_pattern(rel_path, patterns).is_some()
+}
 
-    for pattern in patterns {
-        if pattern.matches(&path_str) {
-            return true;
-        }
-    }
+/// 查找与路径匹配的第一个排除模式
+///
+/// # 参数
+/// * `rel_path` - 相对路径
+/// * `patterns` - 排除模式列表
+///
+/// # 返回
+/// * `Some(&Pattern)` - 匹配的模式
+/// * `None` - 没有模式匹配
+pub fn matching_exclude_pattern<'p>(rel_path: &Path, patterns: &'p [Pattern]) -> Option<&'p Pattern> {
+    let path_str = rel_path.to_string_lossy();
+    patterns.iter().find(|pattern| pattern.matches(&path_str))
+}
 
-    false
+/// 计算文件名用于比较的键（Unicode NFC 规范化并转为小写）
+///
+/// macOS 上的文件名通常为 NFD 形式，复制到 Windows 或 Linux 后可能变为 NFC 形式；
+/// 只修改了大小写的重命名（`Readme.md` → `README.md`）在区分大小写的文件系统上也是不同的名称。
+/// 两个名称的键相同时，可以认为它们指向同一个文件。
+///
+/// # 参数
+/// * `name` - 文件名（单个路径组成部分）
+///
+/// # 返回
+/// * `Some(String)` - 比较键
+/// * `None` - 名称不是有效的 UTF-8
+///
+/// # 示例
+/// ```
+/// use recall::utils::name_key;
+/// use std::ffi::OsStr;
+///
+/// assert_eq!(name_key(OsStr::new("Readme.md")), name_key(OsStr::new("README.md")));
+/// // "é" 的 NFC（U+00E9）和 NFD（e + U+0301）形式
+/// assert_eq!(name_key(OsStr::new("caf\u{e9}")), name_key(OsStr::new("cafe\u{301}")));
+/// ```
+pub fn name_key(name: &std::ffi::OsStr) -> Option<String> {
+    use unicode_normalization::UnicodeNormalization;
+    Some(name.to_str()?.nfc().collect::<String>().to_lowercase())
 }
 
 /// 格式化字节数为人类可读的单位
@@ -113,9 +617,10 @@ pub fn matches_exclude_pattern(rel_path: &Path, patterns: &[Pattern]) -> bool {
 ///
 /// # 示例
 /// ```
-/// format_bytes(1024)      // "1.00 KB"
-/// format_bytes(1048576)   // "1.00 MB"
-/// format_bytes(500)       // "500 B"
+/// use recall::utils::format_bytes;
+/// assert_eq!(format_bytes(1024), "1.00 KB");
+/// assert_eq!(format_bytes(1048576), "1.00 MB");
+/// assert_eq!(format_bytes(500), "500 B");
 /// ```
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -136,6 +641,104 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// 按 CSV 规则转义字段（包含逗号、引号或换行时加引号）
+///
+/// # 示例
+/// ```
+/// use recall::utils::csv_field;
+/// assert_eq!(csv_field("plain"), "plain");
+/// assert_eq!(csv_field("a,b"), "\"a,b\"");
+/// assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+/// ```
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 解析人类可读的大小字符串为字节数
+///
+/// 支持 `K`、`M`、`G` 后缀（按 1024 进制，可选 `B` 结尾，不区分大小写），
+/// 没有后缀时按字节处理。
+///
+/// # 参数
+/// * `s` - 大小字符串
+///
+/// # 返回
+/// * `Ok(usize)` - 字节数
+/// * `Err(String)` - 格式无效
+///
+/// # 示例
+/// ```
+/// use recall::utils::parse_size;
+/// assert_eq!(parse_size("4M"), Ok(4 * 1024 * 1024));
+/// assert_eq!(parse_size("512kb"), Ok(512 * 1024));
+/// assert_eq!(parse_size("1000"), Ok(1000));
+/// assert!(parse_size("abc").is_err());
+/// ```
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper.strip_suffix('B').unwrap_or(&upper);
+
+    let (number, multiplier) = if let Some(n) = digits.strip_suffix('K') {
+        (n, 1024)
+    } else if let Some(n) = digits.strip_suffix('M') {
+        (n, 1024 * 1024)
+    } else if let Some(n) = digits.strip_suffix('G') {
+        (n, 1024 * 1024 * 1024)
+    } else {
+        (digits, 1)
+    };
+
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}', expected e.g. 512K, 4M or 1G", s))
+}
+
+/// 解析时间长度字符串（如 `--older-than 1y`）
+///
+/// 支持 `h`（小时）、`d`（天）、`w`（周）、`m`（月，按 30 天）和 `y`（年，按 365 天）后缀，
+/// 不区分大小写。
+///
+/// # 参数
+/// * `s` - 时间长度字符串
+///
+/// # 返回
+/// * `Ok(u64)` - 秒数
+/// * `Err(String)` - 格式无效
+///
+/// # 示例
+/// ```
+/// use recall::utils::parse_age;
+/// assert_eq!(parse_age("36h"), Ok(36 * 3600));
+/// assert_eq!(parse_age("2w"), Ok(14 * 86400));
+/// assert_eq!(parse_age("1Y"), Ok(365 * 86400));
+/// assert!(parse_age("10").is_err());
+/// ```
+pub fn parse_age(s: &str) -> Result<u64, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    let unit = match lower.chars().last() {
+        Some('h') => 3600,
+        Some('d') => 86400,
+        Some('w') => 7 * 86400,
+        Some('m') => 30 * 86400,
+        Some('y') => 365 * 86400,
+        _ => return Err(format!("invalid age '{}', expected e.g. 90d, 6m or 1y", s)),
+    };
+
+    lower[..lower.len() - 1]
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| format!("invalid age '{}', expected e.g. 90d, 6m or 1y", s))
+}
+
 /// 格式化秒数为人类可读的时间长度
 ///
 /// 将秒数转换为 "Xh Ym Zs" 或 "Xm Ys" 或 "Xs" 格式。
@@ -148,9 +751,10 @@ pub fn format_bytes(bytes: u64) -> String {
 ///
 /// # 示例
 /// ```
-/// format_duration(3661)  // "1h 1m 1s"
-/// format_duration(125)   // "2m 5s"
-/// format_duration(45)    // "45s"
+/// use recall::utils::format_duration;
+/// assert_eq!(format_duration(3661), "1h 1m 1s");
+/// assert_eq!(format_duration(125), "2m 5s");
+/// assert_eq!(format_duration(45), "45s");
 /// ```
 pub fn format_duration(secs: u64) -> String {
     if secs >= 3600 {
@@ -0,0 +1,223 @@
+// Recall - 配置检查模块
+// 检查全局默认设置和每个配置文件是否可用（源路径、目标可写、排除模式、保留数量等）
+
+use crate::backend::is_remote_destination;
+use crate::config::{check_path_overlap, project_name};
+use crate::credentials::{credential_target, get_password};
+use crate::i18n::parse_language;
+use crate::naming::SnapshotNaming;
+use crate::schedule::CronSchedule;
+use crate::store::{AppConfig, Defaults, Profile};
+use crate::webdav::is_webdav_destination;
+use glob::Pattern;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// 单个配置文件（或全局默认设置）的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileCheck {
+    /// 配置文件名称（全局默认设置为 `[defaults]`）
+    pub profile: String,
+    /// 导致配置无法运行的问题
+    pub errors: Vec<String>,
+    /// 不影响运行但值得注意的问题
+    pub warnings: Vec<String>,
+}
+
+impl ProfileCheck {
+    /// 是否通过检查（没有错误）
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 检查全局默认设置和所有配置文件
+///
+/// # 参数
+/// * `config` - 已加载的应用配置
+///
+/// # 返回
+/// 检查结果列表，第一项为全局默认设置，其余按配置文件名称排序
+pub fn validate_config(config: &AppConfig) -> Vec<ProfileCheck> {
+    let mut checks = vec![validate_defaults(&config.defaults)];
+    for (name, profile) in &config.profiles {
+        checks.push(validate_profile(name, profile, &config.defaults));
+    }
+    checks
+}
+
+/// 检查全局默认设置
+fn validate_defaults(defaults: &Defaults) -> ProfileCheck {
+    let mut check = ProfileCheck {
+        profile: "[defaults]".to_string(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    check_excludes(&defaults.exclude, &mut check);
+    check_keep(defaults.keep, &mut check);
+    for url in &defaults.webhooks {
+        check_url("Webhook", url, &mut check);
+    }
+    if let Err(e) = SnapshotNaming::new(defaults.snapshot_format.as_deref(), defaults.utc) {
+        check.errors.push(format!("{:#}", e));
+    }
+    if let Some(ref color) = defaults.color {
+        if !matches!(color.as_str(), "auto" | "always" | "never") {
+            check.errors.push(format!("Invalid color setting {:?} (expected auto, always or never)", color));
+        }
+    }
+    if let Some(ref language) = defaults.language {
+        if language != "auto" && parse_language(language).is_none() {
+            check.errors.push(format!("Unknown language {:?} (expected en, zh or auto)", language));
+        }
+    }
+    check
+}
+
+/// 检查单个配置文件
+fn validate_profile(name: &str, profile: &Profile, defaults: &Defaults) -> ProfileCheck {
+    let mut check = ProfileCheck {
+        profile: name.to_string(),
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    // 源路径
+    if !profile.source.exists() {
+        check.errors.push(format!("Source {:?} does not exist", profile.source));
+    } else if !profile.source.is_dir() {
+        check.errors.push(format!("Source {:?} is not a directory", profile.source));
+    } else {
+        // 同时检查 .recall.toml 能否解析
+        match project_name(&profile.source) {
+            Ok(project) if !is_remote_destination(&profile.destination) => {
+                let destination = profile.destination.join(project);
+                let excludes: Vec<String> = defaults.exclude.iter().chain(&profile.exclude).cloned().collect();
+                if let Err(e) = check_path_overlap(&profile.source, &destination, &excludes) {
+                    check.errors.push(e.to_string());
+                }
+            }
+            Ok(_) => {}
+            Err(e) => check.errors.push(format!("{:#}", e)),
+        }
+        if let Some(ref ignore_file) = profile.ignore_file {
+            let path = if ignore_file.is_relative() {
+                AppConfig::get_config_path()
+                    .ok()
+                    .and_then(|p| p.parent().map(|dir| dir.join(ignore_file)))
+                    .unwrap_or_else(|| ignore_file.clone())
+            } else {
+                ignore_file.clone()
+            };
+            if !path.is_file() {
+                check.errors.push(format!("Ignore file {:?} does not exist", path));
+            }
+        }
+    }
+
+    // 目标路径（需要认证时先确认凭据管理器中保存了密码）
+    if let Some(ref user) = profile.username {
+        if let Err(e) = get_password(&credential_target(&profile.destination), user) {
+            check.errors.push(format!("{:#}", e));
+        }
+    }
+    let repository = profile.chunked || profile.encrypt;
+    if repository && is_webdav_destination(&profile.destination) {
+        check.errors.push("chunked and encrypt are not supported for WebDAV destinations".to_string());
+    }
+    if is_remote_destination(&profile.destination) || repository {
+        // 远程目标和分块仓库只提示不支持的选项
+        if profile.keep.is_some() {
+            check.warnings.push("keep is ignored for remote destinations and chunk repositories".to_string());
+        }
+        if profile.replicate.is_some() {
+            check.warnings.push("replicate is ignored for remote destinations and chunk repositories".to_string());
+        }
+    }
+    // 远程目标不检查连通性（需要网络访问）
+    if !is_remote_destination(&profile.destination) {
+        if let Err(e) = check_writable(&profile.destination) {
+            check.errors.push(e);
+        }
+    }
+
+    check_excludes(&profile.exclude, &mut check);
+    check_keep(profile.keep, &mut check);
+
+    let format = profile.snapshot_format.as_deref().or(defaults.snapshot_format.as_deref());
+    if let Err(e) = SnapshotNaming::new(format, profile.utc || defaults.utc) {
+        check.errors.push(format!("{:#}", e));
+    }
+
+    if let Some(ref schedule) = profile.schedule {
+        if let Err(e) = CronSchedule::parse(schedule) {
+            check.errors.push(format!("{:#}", e));
+        }
+    }
+
+    for url in &profile.webhooks {
+        check_url("Webhook", url, &mut check);
+    }
+    if let Some(ref url) = profile.ping_url {
+        check_url("Ping", url, &mut check);
+    }
+
+    if profile.workers > 256 {
+        check.warnings.push(format!("{} worker threads is unusually high", profile.workers));
+    }
+    if profile.dry_run {
+        check.warnings.push("Profile runs in dry-run mode by default".to_string());
+    }
+    check
+}
+
+/// 检查排除模式能否编译为 Glob 模式
+fn check_excludes(patterns: &[String], check: &mut ProfileCheck) {
+    for pattern in patterns {
+        if let Err(e) = Pattern::new(pattern) {
+            check.errors.push(format!("Invalid exclude pattern {:?}: {}", pattern, e));
+        }
+    }
+}
+
+/// 检查保留数量（`keep = 0` 会在每次备份后删除所有快照）
+fn check_keep(keep: Option<usize>, check: &mut ProfileCheck) {
+    if keep == Some(0) {
+        check.errors.push("keep = 0 would delete every snapshot, use at least 1".to_string());
+    }
+}
+
+/// 检查通知 URL 是否为 HTTP(S) 地址（URL 中可能包含令牌，错误信息中不显示）
+fn check_url(kind: &str, url: &str, check: &mut ProfileCheck) {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        check.errors.push(format!("{} URL must start with http:// or https://", kind));
+    }
+}
+
+/// 检查目标目录是否可访问且可写
+///
+/// 目标目录不存在时检查最近的已存在上级目录（备份时会自动创建目标目录），
+/// 通过创建并删除一个临时文件确认可写。
+fn check_writable(destination: &Path) -> Result<(), String> {
+    let mut dir = destination;
+    while !dir.exists() {
+        dir = match dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => return Err(format!("Destination {:?} is not reachable", destination)),
+        };
+    }
+    if !dir.is_dir() {
+        return Err(format!("Destination {:?} is not a directory", dir));
+    }
+
+    let probe = dir.join(format!(".recall-write-test-{}", std::process::id()));
+    match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            fs::remove_file(&probe).ok();
+            Ok(())
+        }
+        Err(e) => Err(format!("Destination {:?} is not writable: {}", dir, e)),
+    }
+}
@@ -0,0 +1,234 @@
+// Recall - 快照校验模块
+// 按快照清单检查快照中的文件是否缺失、大小不符或内容损坏，供命令行和其他程序调用
+
+use crate::error::{RecallError, Result};
+use crate::events::EventSink;
+use crate::hasher::calculate_snapshot_hash;
+use crate::manifest::{Integrity, Manifest, ManifestEntry};
+use crate::summary::{MANIFEST_FILE, SUMMARY_FILE};
+use crate::utils::to_verbatim_path;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// 校验选项
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// 快速校验：只比较文件大小，不读取文件内容
+    pub quick: bool,
+
+    /// 清单签名密钥，提供时清单必须带有与之匹配的 HMAC 签名
+    pub key: Option<Vec<u8>>,
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerifyProblem {
+    /// 清单中的文件在快照中不存在
+    Missing,
+
+    /// 文件大小与清单不符
+    SizeMismatch {
+        /// 清单记录的大小
+        expected: u64,
+
+        /// 实际大小
+        actual: u64,
+    },
+
+    /// 文件内容的哈希值与清单不符
+    HashMismatch,
+
+    /// 文件无法读取
+    Unreadable {
+        /// 错误信息
+        error: String,
+    },
+
+    /// 快照中的文件不在清单中
+    NotInManifest,
+
+    /// 清单本身被截断、修改或签名不符（此时只检查文件能否读取）
+    InvalidManifest {
+        /// 错误信息
+        error: String,
+    },
+}
+
+impl VerifyProblem {
+    /// 问题的说明（按当前界面语言）
+    pub fn message(&self) -> String {
+        match self {
+            VerifyProblem::Missing => crate::i18n::tr("missing", "缺失").to_string(),
+            VerifyProblem::SizeMismatch { expected, actual } => crate::trf!(
+                "size is {} bytes, expected {}",
+                "大小为 {} 字节，应为 {} 字节",
+                actual,
+                expected
+            ),
+            VerifyProblem::HashMismatch => crate::i18n::tr("content does not match", "内容不一致").to_string(),
+            VerifyProblem::Unreadable { error } => crate::trf!("unreadable: {}", "无法读取：{}", error),
+            VerifyProblem::NotInManifest => crate::i18n::tr("not in manifest", "不在清单中").to_string(),
+            VerifyProblem::InvalidManifest { error } => {
+                crate::trf!("manifest cannot be trusted: {}", "清单不可信：{}", error)
+            }
+        }
+    }
+}
+
+/// 单个文件的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifyIssue {
+    /// 相对于快照目录的路径（`/` 分隔）
+    pub path: String,
+
+    /// 问题
+    #[serde(flatten)]
+    pub problem: VerifyProblem,
+}
+
+/// 一次校验的结果
+#[derive(Debug, Default, Serialize)]
+pub struct VerifyReport {
+    /// 快照中是否有可信的清单（没有清单或清单不可信时只检查文件能否读取）
+    pub manifest: bool,
+
+    /// 清单的完整性检查结果（没有可信的清单时为 `None`）
+    pub integrity: Option<Integrity>,
+
+    /// 检查的文件数量
+    pub files: u64,
+
+    /// 检查的文件总大小（字节）
+    pub bytes: u64,
+
+    /// 发现的问题（按路径排序，缺失的文件排在最后）
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    /// 是否没有发现任何问题
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// 校验快照目录
+///
+/// 有清单（[`MANIFEST_FILE`]，导入或归档时生成）的快照逐个文件核对大小和 XXH3 哈希值，
+/// 并报告缺失和多出的文件；没有清单的快照只检查每个文件能否完整读取。
+/// 清单的校验和或签名不符时记为清单本身的问题，其余文件同样只检查能否读取。
+/// 单个文件的问题记入结果而不中断校验。
+///
+/// # 参数
+/// * `snapshot` - 快照目录
+/// * `options` - 校验选项
+/// * `events` - 每校验一个文件调用一次 [`EventSink::file_verified`]
+///
+/// # 返回
+/// * `Ok(VerifyReport)` - 校验结果
+/// * `Err(RecallError)` - 快照目录不存在
+///
+/// # 示例
+/// ```
+/// use recall::events::NullSink;
+/// use recall::manifest::Manifest;
+/// use recall::verify::{verify, VerifyOptions, VerifyProblem};
+///
+/// let snapshot = tempfile::tempdir().unwrap();
+/// std::fs::write(snapshot.path().join("a.txt"), "hello").unwrap();
+/// Manifest::build(snapshot.path(), &mut Default::default()).unwrap().save(snapshot.path(), None).unwrap();
+///
+/// std::fs::write(snapshot.path().join("a.txt"), "jello").unwrap();
+/// let report = verify(snapshot.path(), &VerifyOptions::default(), &NullSink).unwrap();
+/// assert_eq!(report.issues[0].problem, VerifyProblem::HashMismatch);
+/// ```
+pub fn verify(snapshot: &Path, options: &VerifyOptions, events: &dyn EventSink) -> Result<VerifyReport> {
+    if !snapshot.is_dir() {
+        return Err(RecallError::Destination(anyhow::anyhow!("{:?} is not a snapshot directory", snapshot)));
+    }
+    let mut report = VerifyReport::default();
+    let manifest = match Manifest::load_with_key(snapshot, options.key.as_deref()) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let problem = VerifyProblem::InvalidManifest { error: format!("{:#}", e.root_cause()) };
+            record(&mut report, events, MANIFEST_FILE.to_string(), Some(problem));
+            None
+        }
+    };
+    let mut expected: Option<HashMap<&str, &ManifestEntry>> =
+        manifest.as_ref().map(|(m, _)| m.entries.iter().map(|e| (e.path.as_str(), e)).collect());
+    report.manifest = manifest.is_some();
+    report.integrity = manifest.as_ref().map(|(_, integrity)| *integrity);
+
+    // 使用逐字路径遍历，很深的目录树也能读取（Windows 路径长度限制）
+    let snapshot = &to_verbatim_path(snapshot);
+    for entry in WalkDir::new(snapshot).follow_links(false).min_depth(1).sort_by_file_name() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().and_then(|p| p.strip_prefix(snapshot).ok()).unwrap_or(Path::new(""));
+                let problem = VerifyProblem::Unreadable { error: e.to_string() };
+                record(&mut report, events, path.to_string_lossy().replace('\\', "/"), Some(problem));
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(snapshot).unwrap_or(entry.path());
+        if rel == Path::new(SUMMARY_FILE) || rel == Path::new(MANIFEST_FILE) {
+            continue;
+        }
+        let path = rel.to_string_lossy().replace('\\', "/");
+
+        let size = match entry.metadata() {
+            Ok(meta) => meta.len(),
+            Err(e) => {
+                record(&mut report, events, path, Some(VerifyProblem::Unreadable { error: e.to_string() }));
+                continue;
+            }
+        };
+        report.files += 1;
+        report.bytes += size;
+
+        let problem = match expected.as_mut().map(|m| m.remove(path.as_str())) {
+            // 有清单，但文件不在其中
+            Some(None) => Some(VerifyProblem::NotInManifest),
+            Some(Some(e)) if e.size != size => Some(VerifyProblem::SizeMismatch { expected: e.size, actual: size }),
+            Some(Some(_)) | None if options.quick => None,
+            Some(Some(e)) => match calculate_snapshot_hash(entry.path()) {
+                Ok(hash) if hash == e.hash => None,
+                Ok(_) => Some(VerifyProblem::HashMismatch),
+                Err(err) => Some(VerifyProblem::Unreadable { error: format!("{:#}", err) }),
+            },
+            // 没有清单时只检查文件能否完整读取
+            None => calculate_snapshot_hash(entry.path())
+                .err()
+                .map(|err| VerifyProblem::Unreadable { error: format!("{:#}", err) }),
+        };
+        record(&mut report, events, path, problem);
+    }
+
+    // 清单中剩下的文件在快照中不存在
+    if let (Some((manifest, _)), Some(expected)) = (&manifest, &expected) {
+        for entry in manifest.entries.iter().filter(|e| expected.contains_key(e.path.as_str())) {
+            record(&mut report, events, entry.path.clone(), Some(VerifyProblem::Missing));
+        }
+    }
+    Ok(report)
+}
+
+/// 报告一个文件的校验结果，有问题时记入校验结果
+fn record(report: &mut VerifyReport, events: &dyn EventSink, path: String, problem: Option<VerifyProblem>) {
+    match problem {
+        Some(problem) => {
+            let issue = VerifyIssue { path, problem };
+            events.file_verified(&issue.path, Some(&issue));
+            report.issues.push(issue);
+        }
+        None => events.file_verified(&path, None),
+    }
+}
@@ -1,22 +1,32 @@
 // Recall - 卷影复制服务（VSS）模块（仅 Windows）
 // 负责创建和管理 Windows 卷影副本，用于备份被锁定的文件
 
-use anyhow::{bail, Result};
+use crate::error::{Classify, RecallError};
+use crate::store::AppConfig;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
 use std::ffi::OsStr;
+use std::fs;
 use std::os::windows::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 use std::ptr::{null_mut, NonNull};
 use winapi::shared::winerror::{FAILED, RPC_E_CHANGED_MODE};
 use winapi::um::combaseapi::{CoInitializeEx, CoUninitialize};
-use winapi::um::fileapi::GetVolumePathNameW;
+use winapi::um::fileapi::{GetVolumeNameForVolumeMountPointW, GetVolumePathNameW};
 use winapi::um::handleapi::CloseHandle;
 use winapi::um::objbase::COINIT_MULTITHREADED;
 use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
 use winapi::um::securitybaseapi::GetTokenInformation;
-use winapi::um::vsbackup::{CreateVssBackupComponents, IVssBackupComponents, VssFreeSnapshotProperties};
+use winapi::um::oleauto::SysFreeString;
+use winapi::um::vsbackup::{
+    CreateVssBackupComponents, IVssBackupComponents, IVssExamineWriterMetadata, IVssWMComponent,
+    VssFreeSnapshotProperties, PVSSCOMPONENTINFO,
+};
 use winapi::um::vss::{
-    IVssAsync, VSS_BT_COPY, VSS_CTX_BACKUP, VSS_ID, VSS_OBJECT_SNAPSHOT, VSS_SNAPSHOT_PROP,
+    IVssAsync, IVssEnumObject, VSS_BT_COPY, VSS_CTX_ALL, VSS_CTX_APP_ROLLBACK, VSS_CTX_BACKUP, VSS_ID, VSS_OBJECT_NONE, VSS_OBJECT_PROP, VSS_OBJECT_SNAPSHOT,
+    VSS_SNAPSHOT_PROP,
 };
+use winapi::um::vswriter::{VSS_COMPONENT_TYPE, VSS_SOURCE_TYPE, VSS_USAGE_TYPE};
 use winapi::um::winnt::{TokenElevation, HRESULT, TOKEN_ELEVATION, TOKEN_QUERY};
 
 /// 卷影副本（Shadow Copy）包装器
@@ -31,18 +41,61 @@ pub struct ShadowCopy {
     snapshot_id: VSS_ID,
     device_path: PathBuf,
     com_initialized: bool,
+    selected: Vec<SelectedComponent>,
+    backup_succeeded: bool,
+    /// 快照是否由本进程创建（外部快照不执行 BackupComplete）
+    owned: bool,
+    /// 释放时是否删除快照
+    delete_on_drop: bool,
+}
+
+/// 已存在的卷影副本信息
+#[derive(Debug, Clone)]
+pub struct SnapshotInfo {
+    /// 快照 ID（GUID 字符串）
+    pub id: String,
+
+    /// 原始卷的 GUID 卷名
+    pub volume: String,
+
+    /// 快照设备路径
+    pub device_path: String,
+
+    /// 创建时间
+    pub created: Option<DateTime<Local>>,
+
+    /// 是否由 recall 创建且尚未被清理
+    pub created_by_recall: bool,
+}
+
+/// 已加入备份的 VSS 写入器组件
+///
+/// 备份结束时需要逐个向写入器报告备份结果。
+struct SelectedComponent {
+    instance_id: VSS_ID,
+    writer_id: VSS_ID,
+    component_type: VSS_COMPONENT_TYPE,
+    logical_path: Vec<u16>,
+    name: Vec<u16>,
 }
 
 impl ShadowCopy {
     /// 创建新的卷影副本
     ///
+    /// 指定写入器时，会将这些写入器的顶层组件加入备份，
+    /// 使 SQL Server、Exchange、Hyper-V 等应用在快照前完成静默，
+    /// 得到应用一致的快照；否则只得到崩溃一致的快照。
+    ///
     /// # 参数
     /// * `source_path` - 需要创建快照的源路径
+    /// * `writers` - 参与备份的写入器名称（不区分大小写，`all` 表示全部写入器）
+    /// * `persistent` - 是否创建持久快照（备份结束后保留，不随进程退出删除）
     ///
     /// # 返回
     /// * `Ok(ShadowCopy)` - 卷影副本对象
-    /// * `Err(anyhow::Error)` - 创建失败
-    pub fn new(source_path: &Path) -> Result<Self> {
+    /// * `Err(RecallError)` - 创建失败
+    pub fn new(source_path: &Path, writers: &[String], persistent: bool) -> crate::error::Result<Self> {
+        let _span = tracing::info_span!("vss", source = %source_path.display()).entered();
         unsafe {
             let mut com_initialized = false;
             let mut components_ptr: *mut IVssBackupComponents = null_mut();
@@ -67,11 +120,20 @@ impl ShadowCopy {
                     "InitializeForBackup",
                 )?;
                 check_hr(
-                    (*components.as_ptr()).SetBackupState(false, false, VSS_BT_COPY, false),
+                    (*components.as_ptr()).SetBackupState(
+                        !writers.is_empty(),
+                        false,
+                        VSS_BT_COPY,
+                        false,
+                    ),
                     "SetBackupState",
                 )?;
                 check_hr(
-                    (*components.as_ptr()).SetContext(VSS_CTX_BACKUP as i32),
+                    (*components.as_ptr()).SetContext(if persistent {
+                        VSS_CTX_APP_ROLLBACK as i32
+                    } else {
+                        VSS_CTX_BACKUP as i32
+                    }),
                     "SetContext",
                 )?;
 
@@ -81,6 +143,12 @@ impl ShadowCopy {
                     "GatherWriterMetadata",
                 )?;
                 wait_async(async_ptr, "GatherWriterMetadata")?;
+
+                let selected = if writers.is_empty() {
+                    Vec::new()
+                } else {
+                    select_components(components.as_ptr(), writers)?
+                };
                 check_hr((*components.as_ptr()).FreeWriterMetadata(), "FreeWriterMetadata")?;
 
                 let mut snapshot_set_id = zero_guid();
@@ -123,12 +191,26 @@ impl ShadowCopy {
                 )?;
                 let device_path = wide_ptr_to_string(props.m_pwszSnapshotDeviceObject)?;
                 VssFreeSnapshotProperties(&mut props);
+                tracing::info!(
+                    id = %format_guid(&snapshot_id),
+                    volume = %volume_root,
+                    device = %device_path,
+                    components = selected.len(),
+                    "shadow copy created"
+                );
+
+                // 记录快照 ID，进程崩溃后可通过 `recall snapshots --clean` 清理
+                let _ = record_snapshot(&format_guid(&snapshot_id));
 
                 Ok(Self {
                     components,
                     snapshot_id,
                     device_path: PathBuf::from(device_path),
                     com_initialized,
+                    selected,
+                    backup_succeeded: false,
+                    owned: true,
+                    delete_on_drop: !persistent,
                 })
             })();
 
@@ -141,24 +223,130 @@ impl ShadowCopy {
                 }
             }
 
-            result
+            result.classify(RecallError::Vss)
         }
     }
 
+    /// 打开一个已存在的卷影副本
+    ///
+    /// 用于复用外部（如计划任务的包装脚本）创建的快照。
+    /// 打开的快照在释放时不会被删除。
+    ///
+    /// # 参数
+    /// * `snapshot_id` - 快照 ID（GUID，如 `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}`）
+    /// * `source_path` - 源路径，用于校验快照是否属于源路径所在的卷
+    ///
+    /// # 返回
+    /// * `Ok(ShadowCopy)` - 卷影副本对象
+    /// * `Err(RecallError)` - 快照 ID 无效、快照不存在或不属于源卷
+    pub fn open(snapshot_id: &str, source_path: &Path) -> crate::error::Result<Self> {
+        let snapshot_id = parse_guid(snapshot_id).classify(RecallError::Vss)?;
+
+        unsafe {
+            let mut com_initialized = false;
+            let mut components_ptr: *mut IVssBackupComponents = null_mut();
+
+            let result = (|| -> Result<Self> {
+                let hr = CoInitializeEx(null_mut(), COINIT_MULTITHREADED);
+                if hr == RPC_E_CHANGED_MODE {
+                    bail!("COM already initialized with a different threading model");
+                }
+                check_hr(hr, "CoInitializeEx")?;
+                com_initialized = true;
+
+                check_hr(
+                    CreateVssBackupComponents(&mut components_ptr),
+                    "CreateVssBackupComponents",
+                )?;
+                let components = NonNull::new(components_ptr)
+                    .ok_or_else(|| anyhow::anyhow!("CreateVssBackupComponents returned null"))?;
+
+                check_hr(
+                    (*components.as_ptr()).InitializeForBackup(null_mut()),
+                    "InitializeForBackup",
+                )?;
+                check_hr(
+                    (*components.as_ptr()).SetContext(VSS_CTX_ALL as i32),
+                    "SetContext",
+                )?;
+
+                let mut props: VSS_SNAPSHOT_PROP = std::mem::zeroed();
+                check_hr(
+                    (*components.as_ptr()).GetSnapshotProperties(snapshot_id, &mut props),
+                    "GetSnapshotProperties",
+                )?;
+                let device_path = wide_ptr_to_string(props.m_pwszSnapshotDeviceObject);
+                let original_volume = wide_ptr_to_string(props.m_pwszOriginalVolumeName);
+                VssFreeSnapshotProperties(&mut props);
+                let device_path = device_path?;
+
+                // 快照必须来自源路径所在的卷，否则映射出的路径没有意义
+                let source_volume = get_volume_name(source_path)?;
+                if !original_volume?.eq_ignore_ascii_case(&source_volume) {
+                    bail!(
+                        "Snapshot {} does not belong to the volume of {:?}",
+                        format_guid(&snapshot_id),
+                        source_path
+                    );
+                }
+
+                Ok(Self {
+                    components,
+                    snapshot_id,
+                    device_path: PathBuf::from(device_path),
+                    com_initialized,
+                    selected: Vec::new(),
+                    backup_succeeded: false,
+                    owned: false,
+                    delete_on_drop: false,
+                })
+            })();
+
+            if result.is_err() {
+                if !components_ptr.is_null() {
+                    let _ = (*components_ptr).Release();
+                }
+                if com_initialized {
+                    CoUninitialize();
+                }
+            }
+
+            result.classify(RecallError::Vss)
+        }
+    }
+
+    /// 获取快照 ID（GUID 字符串），可通过 `--snapshot-id` 复用
+    pub fn snapshot_id(&self) -> String {
+        format_guid(&self.snapshot_id)
+    }
+
     /// 获取快照的路径
     ///
     /// # 返回
     /// * `Ok(PathBuf)` - 快照的访问路径
-    pub fn get_snapshot_path(&self) -> Result<PathBuf> {
+    pub fn get_snapshot_path(&self) -> crate::error::Result<PathBuf> {
         Ok(self.device_path.clone())
     }
 
+    /// 获取加入备份的写入器组件数量
+    pub fn selected_component_count(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// 标记备份是否成功
+    ///
+    /// 释放快照时会将此结果报告给参与备份的写入器，
+    /// 写入器据此决定是否执行备份后的操作（如截断日志）。
+    pub fn set_backup_succeeded(&mut self, succeeded: bool) {
+        self.backup_succeeded = succeeded;
+    }
+
     /// 判断当前进程是否以管理员权限运行
-    pub fn is_running_as_admin() -> Result<bool> {
+    pub fn is_running_as_admin() -> crate::error::Result<bool> {
         unsafe {
             let mut token = null_mut();
             if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
-                bail!("Failed to open process token for admin check");
+                return Err(RecallError::Vss(anyhow::anyhow!("Failed to open process token for admin check")));
             }
 
             let mut elevation: TOKEN_ELEVATION = std::mem::zeroed();
@@ -173,7 +361,7 @@ impl ShadowCopy {
             CloseHandle(token);
 
             if ok == 0 {
-                bail!("Failed to query token elevation status");
+                return Err(RecallError::Vss(anyhow::anyhow!("Failed to query token elevation status")));
             }
 
             Ok(elevation.TokenIsElevated != 0)
@@ -181,6 +369,158 @@ impl ShadowCopy {
     }
 }
 
+/// 列出系统中已存在的卷影副本
+///
+/// # 参数
+/// * `volume` - 只列出此路径所在卷的快照；为 `None` 时列出所有卷的快照
+///
+/// # 返回
+/// * `Ok(Vec<SnapshotInfo>)` - 快照列表，按创建时间排序
+/// * `Err(RecallError)` - 查询失败
+pub fn list_snapshots(volume: Option<&Path>) -> crate::error::Result<Vec<SnapshotInfo>> {
+    let volume_name = volume.map(get_volume_name).transpose().classify(RecallError::Vss)?;
+    let recorded = recorded_snapshots();
+
+    let mut snapshots = with_components(|components| unsafe {
+        let mut enum_ptr: *mut IVssEnumObject = null_mut();
+        let hr = (*components).Query(zero_guid(), VSS_OBJECT_NONE, VSS_OBJECT_SNAPSHOT, &mut enum_ptr);
+        check_hr(hr, "Query")?;
+        // 没有任何快照时 Query 返回 S_FALSE 且不返回枚举器
+        if enum_ptr.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshots = Vec::new();
+        loop {
+            let mut prop: VSS_OBJECT_PROP = std::mem::zeroed();
+            let mut fetched = 0u32;
+            let hr = (*enum_ptr).Next(1, &mut prop, &mut fetched);
+            if FAILED(hr) || fetched == 0 {
+                break;
+            }
+
+            let snap = prop.Obj.Snap_mut();
+            let id = format_guid(&snap.m_SnapshotId);
+            snapshots.push(SnapshotInfo {
+                created_by_recall: recorded.iter().any(|r| r.eq_ignore_ascii_case(&id)),
+                id,
+                volume: wide_ptr_to_string(snap.m_pwszOriginalVolumeName).unwrap_or_default(),
+                device_path: wide_ptr_to_string(snap.m_pwszSnapshotDeviceObject).unwrap_or_default(),
+                created: filetime_to_local(snap.m_tsCreationTimestamp),
+            });
+            VssFreeSnapshotProperties(snap);
+        }
+
+        let _ = (*enum_ptr).Release();
+        Ok(snapshots)
+    })
+    .classify(RecallError::Vss)?;
+
+    if let Some(ref name) = volume_name {
+        snapshots.retain(|s| s.volume.eq_ignore_ascii_case(name));
+    }
+    snapshots.sort_by_key(|s| s.created);
+    Ok(snapshots)
+}
+
+/// 删除指定的卷影副本
+///
+/// # 参数
+/// * `snapshot_id` - 快照 ID（GUID 字符串）
+///
+/// # 返回
+/// * `Ok(())` - 删除成功
+/// * `Err(RecallError)` - 快照 ID 无效或删除失败
+pub fn delete_snapshot(snapshot_id: &str) -> crate::error::Result<()> {
+    let guid = parse_guid(snapshot_id).classify(RecallError::Vss)?;
+
+    with_components(|components| unsafe {
+        let mut deleted = 0;
+        let mut non_deleted = zero_guid();
+        check_hr(
+            (*components).DeleteSnapshots(guid, VSS_OBJECT_SNAPSHOT, 1, &mut deleted, &mut non_deleted),
+            "DeleteSnapshots",
+        )
+    })
+    .classify(RecallError::Vss)?;
+
+    tracing::info!(id = %format_guid(&guid), "shadow copy deleted");
+    let _ = forget_snapshot(&format_guid(&guid));
+    Ok(())
+}
+
+/// 创建可查询所有快照的 VSS 备份组件，执行操作后释放
+fn with_components<T>(f: impl FnOnce(*mut IVssBackupComponents) -> Result<T>) -> Result<T> {
+    unsafe {
+        let hr = CoInitializeEx(null_mut(), COINIT_MULTITHREADED);
+        if hr == RPC_E_CHANGED_MODE {
+            bail!("COM already initialized with a different threading model");
+        }
+        check_hr(hr, "CoInitializeEx")?;
+
+        let mut components_ptr: *mut IVssBackupComponents = null_mut();
+        let result = (|| -> Result<T> {
+            check_hr(
+                CreateVssBackupComponents(&mut components_ptr),
+                "CreateVssBackupComponents",
+            )?;
+            if components_ptr.is_null() {
+                bail!("CreateVssBackupComponents returned null");
+            }
+            check_hr((*components_ptr).InitializeForBackup(null_mut()), "InitializeForBackup")?;
+            check_hr((*components_ptr).SetContext(VSS_CTX_ALL as i32), "SetContext")?;
+            f(components_ptr)
+        })();
+
+        if !components_ptr.is_null() {
+            let _ = (*components_ptr).Release();
+        }
+        CoUninitialize();
+        result
+    }
+}
+
+/// 记录由 recall 创建的快照 ID 的文件（位于配置目录中）
+fn registry_path() -> Result<PathBuf> {
+    Ok(AppConfig::get_config_path()?.with_file_name("vss_snapshots.txt"))
+}
+
+/// 读取由 recall 创建且尚未清理的快照 ID
+fn recorded_snapshots() -> Vec<String> {
+    registry_path()
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .map(|content| content.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// 记录新创建的快照 ID
+fn record_snapshot(id: &str) -> Result<()> {
+    let path = registry_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut ids = recorded_snapshots();
+    ids.push(id.to_string());
+    fs::write(&path, ids.join("\n") + "\n").context("Failed to record snapshot id")
+}
+
+/// 从记录中移除已删除的快照 ID
+fn forget_snapshot(id: &str) -> Result<()> {
+    let ids: Vec<String> = recorded_snapshots()
+        .into_iter()
+        .filter(|r| !r.eq_ignore_ascii_case(id))
+        .collect();
+    let content = if ids.is_empty() { String::new() } else { ids.join("\n") + "\n" };
+    fs::write(registry_path()?, content).context("Failed to update snapshot records")
+}
+
+/// 将 VSS 时间戳（FILETIME，自 1601 年起的 100 纳秒数）转换为本地时间
+fn filetime_to_local(timestamp: i64) -> Option<DateTime<Local>> {
+    const UNIX_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+    let secs = timestamp / 10_000_000 - UNIX_EPOCH_OFFSET_SECS;
+    DateTime::from_timestamp(secs, 0).map(|t| t.with_timezone(&Local))
+}
+
 /// 自动释放卷影副本
 ///
 /// 当 ShadowCopy 对象被销毁时，自动清理 VSS 资源。
@@ -188,7 +528,18 @@ impl Drop for ShadowCopy {
     fn drop(&mut self) {
         unsafe {
             let components_ptr = self.components.as_ptr();
-            if !components_ptr.is_null() {
+            if !components_ptr.is_null() && self.owned {
+                for component in &self.selected {
+                    let _ = (*components_ptr).SetBackupSucceeded(
+                        component.instance_id,
+                        component.writer_id,
+                        component.component_type,
+                        optional_wide_ptr(&component.logical_path),
+                        component.name.as_ptr(),
+                        self.backup_succeeded,
+                    );
+                }
+
                 let mut async_ptr: *mut IVssAsync = null_mut();
                 let hr = (*components_ptr).BackupComplete(&mut async_ptr);
                 if !async_ptr.is_null() && !FAILED(hr) {
@@ -197,16 +548,26 @@ impl Drop for ShadowCopy {
                     let _ = (*async_ptr).Release();
                 }
 
-                let mut deleted = 0;
-                let mut non_deleted = zero_guid();
-                let _ = (*components_ptr).DeleteSnapshots(
-                    self.snapshot_id,
-                    VSS_OBJECT_SNAPSHOT,
-                    0,
-                    &mut deleted,
-                    &mut non_deleted,
-                );
+                if self.delete_on_drop {
+                    let mut deleted = 0;
+                    let mut non_deleted = zero_guid();
+                    let hr = (*components_ptr).DeleteSnapshots(
+                        self.snapshot_id,
+                        VSS_OBJECT_SNAPSHOT,
+                        0,
+                        &mut deleted,
+                        &mut non_deleted,
+                    );
+                    if !FAILED(hr) {
+                        tracing::info!(id = %format_guid(&self.snapshot_id), "shadow copy deleted");
+                        let _ = forget_snapshot(&format_guid(&self.snapshot_id));
+                    } else {
+                        tracing::warn!(id = %format_guid(&self.snapshot_id), hr, "failed to delete shadow copy");
+                    }
+                }
+            }
 
+            if !components_ptr.is_null() {
                 let _ = (*components_ptr).Release();
             }
 
@@ -217,6 +578,162 @@ impl Drop for ShadowCopy {
     }
 }
 
+/// 将指定写入器的顶层组件加入备份
+///
+/// 顶层组件是指逻辑路径不属于其他组件的组件，
+/// 加入顶层组件会隐式包含其所有子组件。
+///
+/// # 参数
+/// * `components` - VSS 备份组件接口（已完成 GatherWriterMetadata）
+/// * `writers` - 参与备份的写入器名称
+///
+/// # 返回
+/// * `Ok(Vec<SelectedComponent>)` - 已加入备份的组件
+/// * `Err(anyhow::Error)` - 读取元数据或加入组件失败
+unsafe fn select_components(
+    components: *mut IVssBackupComponents,
+    writers: &[String],
+) -> Result<Vec<SelectedComponent>> {
+    let select_all = writers.iter().any(|w| w.eq_ignore_ascii_case("all"));
+    let mut selected = Vec::new();
+
+    let mut writer_count = 0u32;
+    check_hr(
+        (*components).GetWriterMetadataCount(&mut writer_count),
+        "GetWriterMetadataCount",
+    )?;
+
+    for i in 0..writer_count {
+        let mut instance_id = zero_guid();
+        let mut metadata: *mut IVssExamineWriterMetadata = null_mut();
+        check_hr(
+            (*components).GetWriterMetadata(i, &mut instance_id, &mut metadata),
+            "GetWriterMetadata",
+        )?;
+        if metadata.is_null() {
+            continue;
+        }
+
+        let result = (|| -> Result<()> {
+            let mut identity_instance = zero_guid();
+            let mut writer_id = zero_guid();
+            let mut writer_name_bstr = null_mut();
+            let mut usage: VSS_USAGE_TYPE = 0;
+            let mut source: VSS_SOURCE_TYPE = 0;
+            check_hr(
+                (*metadata).GetIdentity(
+                    &mut identity_instance,
+                    &mut writer_id,
+                    &mut writer_name_bstr,
+                    &mut usage,
+                    &mut source,
+                ),
+                "GetIdentity",
+            )?;
+            let writer_name = bstr_to_string(writer_name_bstr);
+            SysFreeString(writer_name_bstr);
+
+            if !select_all && !writers.iter().any(|w| w.eq_ignore_ascii_case(&writer_name)) {
+                return Ok(());
+            }
+
+            let (mut include_count, mut exclude_count, mut component_count) = (0u32, 0u32, 0u32);
+            check_hr(
+                (*metadata).GetFileCounts(&mut include_count, &mut exclude_count, &mut component_count),
+                "GetFileCounts",
+            )?;
+
+            // 读取写入器的全部组件：(类型, 逻辑路径, 名称)
+            let mut infos = Vec::new();
+            for j in 0..component_count {
+                let mut component: *mut IVssWMComponent = null_mut();
+                check_hr((*metadata).GetComponent(j, &mut component), "GetComponent")?;
+                if component.is_null() {
+                    continue;
+                }
+
+                let mut info: PVSSCOMPONENTINFO = null_mut();
+                let hr = (*component).GetComponentInfo(&mut info);
+                if !FAILED(hr) && !info.is_null() {
+                    infos.push((
+                        (*info).type_,
+                        bstr_to_string((*info).bstrLogicalPath),
+                        bstr_to_string((*info).bstrComponentName),
+                    ));
+                    (*component).FreeComponentInfo(info);
+                }
+                let _ = (*component).Release();
+                check_hr(hr, "GetComponentInfo")?;
+            }
+
+            // 组件的完整路径为“逻辑路径\名称”
+            let full_paths: Vec<String> = infos
+                .iter()
+                .map(|(_, path, name)| {
+                    if path.is_empty() {
+                        name.to_lowercase()
+                    } else {
+                        format!("{}\\{}", path, name).to_lowercase()
+                    }
+                })
+                .collect();
+
+            for (component_type, logical_path, name) in infos {
+                if !logical_path.is_empty() && full_paths.contains(&logical_path.to_lowercase()) {
+                    continue;
+                }
+
+                let logical_path = if logical_path.is_empty() {
+                    Vec::new()
+                } else {
+                    to_wide_null(&logical_path)
+                };
+                let name = to_wide_null(&name);
+                check_hr(
+                    (*components).AddComponent(
+                        instance_id,
+                        writer_id,
+                        component_type,
+                        optional_wide_ptr(&logical_path),
+                        name.as_ptr(),
+                    ),
+                    "AddComponent",
+                )?;
+                selected.push(SelectedComponent {
+                    instance_id,
+                    writer_id,
+                    component_type,
+                    logical_path,
+                    name,
+                });
+            }
+
+            Ok(())
+        })();
+
+        let _ = (*metadata).Release();
+        result?;
+    }
+
+    Ok(selected)
+}
+
+/// 空的宽字符串表示“无逻辑路径”，VSS 要求此时传入空指针
+fn optional_wide_ptr(wide: &[u16]) -> *const u16 {
+    if wide.is_empty() {
+        std::ptr::null()
+    } else {
+        wide.as_ptr()
+    }
+}
+
+unsafe fn bstr_to_string(bstr: *const u16) -> String {
+    if bstr.is_null() {
+        return String::new();
+    }
+    wide_ptr_to_string(bstr).unwrap_or_default()
+}
+
 fn to_wide_null(value: impl AsRef<OsStr>) -> Vec<u16> {
     let mut wide: Vec<u16> = value.as_ref().encode_wide().collect();
     wide.push(0);
@@ -247,6 +764,55 @@ fn get_volume_root(path: &Path) -> Result<String> {
     }
 }
 
+/// 获取路径所在卷的 GUID 卷名（如 `\\?\Volume{...}\`）
+fn get_volume_name(path: &Path) -> Result<String> {
+    let root = to_wide_null(get_volume_root(path)?);
+    let mut buffer = [0u16; 64];
+    let ok = unsafe {
+        GetVolumeNameForVolumeMountPointW(root.as_ptr(), buffer.as_mut_ptr(), buffer.len() as u32)
+    };
+    if ok == 0 {
+        bail!("Failed to get volume name of {:?}", path);
+    }
+    let len = buffer.iter().position(|&ch| ch == 0).unwrap_or(0);
+    Ok(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// 解析 GUID 字符串（可带或不带花括号）
+fn parse_guid(value: &str) -> Result<VSS_ID> {
+    let trimmed = value.trim().trim_start_matches('{').trim_end_matches('}');
+    let parts: Vec<&str> = trimmed.split('-').collect();
+    let lengths = [8, 4, 4, 4, 12];
+    if parts.len() != 5
+        || parts.iter().zip(lengths).any(|(p, len)| p.len() != len)
+        || !parts.iter().all(|p| p.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        bail!("Invalid snapshot id: {}", value);
+    }
+
+    let tail = format!("{}{}", parts[3], parts[4]);
+    let mut data4 = [0u8; 8];
+    for (i, byte) in data4.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&tail[i * 2..i * 2 + 2], 16)?;
+    }
+
+    Ok(VSS_ID {
+        Data1: u32::from_str_radix(parts[0], 16)?,
+        Data2: u16::from_str_radix(parts[1], 16)?,
+        Data3: u16::from_str_radix(parts[2], 16)?,
+        Data4: data4,
+    })
+}
+
+/// 将 GUID 格式化为 `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}`
+fn format_guid(guid: &VSS_ID) -> String {
+    let d = &guid.Data4;
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        guid.Data1, guid.Data2, guid.Data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+    )
+}
+
 fn zero_guid() -> VSS_ID {
     VSS_ID {
         Data1: 0,
@@ -0,0 +1,516 @@
+// Recall - WebDAV 模块
+// 备份到 WebDAV 服务（Nextcloud、NAS 等）：每个快照一个目录，未变化的文件由服务端复制，快照中的清单记录文件元数据用于增量判断
+
+use crate::actions::{FileTask, SyncAction};
+use crate::backend::{RemoteSnapshots, RestoreStats, SnapshotWriter, StorageBackend, TaskOutcome};
+use crate::chunks::{restore_index, EntryKind, IndexEntry, SnapshotIndex, INDEX_VERSION};
+use crate::config::BackupConfig;
+use crate::copy::open_for_backup;
+use crate::credentials::{credential_target, get_password};
+use crate::events::EventSink;
+use crate::hasher::calculate_hash;
+use crate::http::{self, percent_decode, percent_encode, xml_values};
+use crate::naming::SnapshotNaming;
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use xxhash_rust::xxh3::Xxh3;
+
+/// 快照清单文件名（位于快照目录根部）
+pub const INDEX_FILE: &str = ".recall-index.json";
+
+/// 列出目录内容的 PROPFIND 请求体
+const PROPFIND_BODY: &str =
+    r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#;
+
+/// 检查备份目标是否为 WebDAV 地址（`http://` 或 `https://`）
+pub fn is_webdav_destination(destination: &Path) -> bool {
+    let dest = destination.to_string_lossy();
+    dest.starts_with("http://") || dest.starts_with("https://")
+}
+
+/// WebDAV 客户端
+pub struct WebDav {
+    /// 备份目标的地址（不含末尾的 `/`）
+    base: String,
+
+    /// `Authorization` 请求头（无需认证时为 `None`）
+    auth: Option<String>,
+
+    /// HTTP 客户端
+    agent: ureq::Agent,
+}
+
+impl WebDav {
+    /// 根据备份目标打开 WebDAV 客户端
+    ///
+    /// 设置了用户名时从系统凭据管理器读取密码（`recall credential set`）；
+    /// 否则使用环境变量 `RECALL_WEBDAV_USER` 和 `RECALL_WEBDAV_PASSWORD`，都未设置时不认证。
+    ///
+    /// # 参数
+    /// * `destination` - 备份目标（如 `https://cloud.example.com/remote.php/dav/files/me/Backups`）
+    /// * `username` - 用户名
+    ///
+    /// # 返回
+    /// * `Ok(WebDav)` - 客户端
+    /// * `Err(anyhow::Error)` - 读取密码失败
+    pub fn open(destination: &Path, username: Option<&str>) -> Result<Self> {
+        let base = destination.to_string_lossy().replace('\\', "/").trim_end_matches('/').to_string();
+
+        let credentials = match username {
+            Some(user) => Some((user.to_string(), get_password(&credential_target(destination), user)?)),
+            None => match std::env::var("RECALL_WEBDAV_USER") {
+                Ok(user) => Some((user, std::env::var("RECALL_WEBDAV_PASSWORD").unwrap_or_default())),
+                Err(_) => None,
+            },
+        };
+        let auth = credentials.map(|(user, password)| {
+            use base64::Engine;
+            let token = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+            format!("Basic {}", token)
+        });
+
+        Ok(Self { base, auth, agent: http::agent() })
+    }
+
+    /// 获取相对路径（`/` 分隔，可为空）对应的 URL
+    fn url(&self, rel: &str) -> String {
+        if rel.is_empty() {
+            format!("{}/", self.base)
+        } else {
+            format!("{}/{}", self.base, percent_encode(rel, false))
+        }
+    }
+
+    /// 创建带认证信息的请求
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        match self.auth {
+            Some(ref auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+
+    /// 创建目录（已存在时不报错）
+    fn mkcol(&self, rel: &str) -> Result<()> {
+        let url = self.url(rel);
+        match http::with_retry(|| self.request("MKCOL", &url).call().map_err(Box::new)).map_err(|e| *e) {
+            Ok(_) => Ok(()),
+            // 405：目录已存在
+            Err(ureq::Error::Status(405, _)) => Ok(()),
+            Err(e) => Err(request_error("MKCOL", &url, e)),
+        }
+    }
+
+    /// 上传本地文件，同时计算内容的 XXH3 哈希值
+    ///
+    /// # 返回
+    /// * `Ok((u64, String))` - 上传的字节数和哈希值（十六进制）
+    /// * `Err(anyhow::Error)` - 读取或上传失败
+    fn put_file(&self, rel: &str, path: &Path) -> Result<(u64, String)> {
+        let url = self.url(rel);
+        let size = fs::metadata(path)?.len();
+        let hash = Cell::new(None);
+        let result = http::with_retry(|| {
+            let file = open_for_backup(path).map_err(|e| Box::new(e.into()))?;
+            let reader = HashingReader { inner: file, hasher: Xxh3::new(), result: &hash };
+            self.request("PUT", &url)
+                .set("Content-Length", &size.to_string())
+                .send(reader)
+                .map_err(Box::new)
+        });
+        result.map_err(|e| request_error("PUT", &url, *e))?;
+        let hash = hash.get().with_context(|| format!("Upload of {:?} ended early", path))?;
+        Ok((size, format!("{:016x}", hash)))
+    }
+
+    /// 上传内存中的数据
+    fn put_bytes(&self, rel: &str, data: &[u8]) -> Result<()> {
+        let url = self.url(rel);
+        http::with_retry(|| self.request("PUT", &url).send_bytes(data).map_err(Box::new))
+            .map_err(|e| request_error("PUT", &url, *e))?;
+        Ok(())
+    }
+
+    /// 在服务端复制文件（不经过本机传输数据）
+    ///
+    /// # 返回
+    /// * `Ok(true)` - 已复制
+    /// * `Ok(false)` - 源文件不存在
+    /// * `Err(anyhow::Error)` - 复制失败
+    fn copy(&self, from: &str, to: &str) -> Result<bool> {
+        let url = self.url(from);
+        let destination = self.url(to);
+        let result = http::with_retry(|| {
+            self.request("COPY", &url)
+                .set("Destination", &destination)
+                .set("Overwrite", "T")
+                .call()
+                .map_err(Box::new)
+        });
+        match result.map_err(|e| *e) {
+            Ok(_) => Ok(true),
+            Err(ureq::Error::Status(404, _)) => Ok(false),
+            Err(e) => Err(request_error("COPY", &url, e)),
+        }
+    }
+
+    /// 在服务端移动（重命名）文件或目录
+    fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let url = self.url(from);
+        let destination = self.url(to);
+        http::with_retry(|| {
+            self.request("MOVE", &url)
+                .set("Destination", &destination)
+                .set("Overwrite", "F")
+                .call()
+                .map_err(Box::new)
+        })
+        .map_err(|e| request_error("MOVE", &url, *e))?;
+        Ok(())
+    }
+
+    /// 下载文件
+    ///
+    /// # 返回
+    /// * `Ok(Some(Response))` - 响应（从中读取文件内容）
+    /// * `Ok(None)` - 文件不存在
+    /// * `Err(anyhow::Error)` - 下载失败
+    fn get(&self, rel: &str) -> Result<Option<ureq::Response>> {
+        let url = self.url(rel);
+        match http::with_retry(|| self.request("GET", &url).call().map_err(Box::new)).map_err(|e| *e) {
+            Ok(resp) => Ok(Some(resp)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(request_error("GET", &url, e)),
+        }
+    }
+
+    /// 列出备份目标下的所有条目名称（目标不存在时为空）
+    fn children(&self) -> Result<Vec<String>> {
+        let url = self.url("");
+        let result = http::with_retry(|| {
+            self.request("PROPFIND", &url)
+                .set("Depth", "1")
+                .set("Content-Type", "application/xml")
+                .send_string(PROPFIND_BODY)
+                .map_err(Box::new)
+        });
+        let body = match result.map_err(|e| *e) {
+            Ok(resp) => resp.into_string().context("Failed to read WebDAV listing")?,
+            Err(ureq::Error::Status(404, _)) => return Ok(Vec::new()),
+            Err(e) => return Err(request_error("PROPFIND", &url, e)),
+        };
+
+        // href 可能是完整 URL 或绝对路径；第一项是目标目录本身
+        let base_path = percent_decode(url_path(&self.base)).trim_end_matches('/').to_string();
+        Ok(xml_values(&body, "href")
+            .iter()
+            .map(|href| percent_decode(url_path(href)).trim_end_matches('/').to_string())
+            .filter(|path| !path.ends_with(&base_path))
+            .filter_map(|path| path.rsplit('/').next().map(str::to_string))
+            .filter(|name| !name.is_empty())
+            .collect())
+    }
+
+    /// 读取快照清单
+    ///
+    /// # 返回
+    /// * `Ok(Some(SnapshotIndex))` - 清单
+    /// * `Ok(None)` - 快照中没有清单（不是由 recall 写入的目录）
+    /// * `Err(anyhow::Error)` - 下载或解析失败
+    fn load_index(&self, name: &str) -> Result<Option<SnapshotIndex>> {
+        let Some(resp) = self.get(&format!("{}/{}", name, INDEX_FILE))? else {
+            return Ok(None);
+        };
+        let mut data = Vec::new();
+        resp.into_reader().read_to_end(&mut data)?;
+        let index: SnapshotIndex = serde_json::from_slice(&data)
+            .with_context(|| format!("Snapshot index of {} is corrupt", name))?;
+        if index.version > INDEX_VERSION {
+            anyhow::bail!(
+                "Snapshot {} was written by a newer version of recall (index version {})",
+                name,
+                index.version
+            );
+        }
+        Ok(Some(index))
+    }
+
+    /// 读取快照清单，快照不存在或没有清单时报错
+    fn require_index(&self, name: &str) -> Result<SnapshotIndex> {
+        self.load_index(name)?
+            .with_context(|| format!("Snapshot {} not found in {}", name, self.base))
+    }
+}
+
+impl RemoteSnapshots for WebDav {
+    fn describe(&self) -> String {
+        self.base.clone()
+    }
+
+    fn snapshots(&self, naming: &SnapshotNaming) -> Result<Vec<(NaiveDateTime, String)>> {
+        // 未完成的 .partial 目录无法按命名格式解析，会被忽略
+        let mut snapshots: Vec<(NaiveDateTime, String)> = self
+            .children()?
+            .into_iter()
+            .filter_map(|name| Some((naming.parse(&name)?, name)))
+            .collect();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    fn totals(&self, name: &str) -> Result<(u64, u64)> {
+        let index = self.require_index(name)?;
+        let files = index.entries.iter().filter(|e| e.kind == EntryKind::File);
+        Ok(files.fold((0, 0), |(count, size), e| (count + 1, size + e.size)))
+    }
+
+    fn restore(&self, name: &str, target: &Path, events: &dyn EventSink) -> Result<RestoreStats> {
+        let index = self.require_index(name)?;
+        restore_index(&index, target, events, |entry, file| {
+            let resp = self
+                .get(&format!("{}/{}", name, entry.path))?
+                .with_context(|| format!("{} is missing from snapshot {}", entry.path, name))?;
+            Ok(std::io::copy(&mut resp.into_reader(), file)?)
+        })
+    }
+}
+
+/// WebDAV 写入后端
+///
+/// 快照先写入 `<名称>.partial` 目录，提交时写入清单并重命名为最终名称。
+/// 与上一个快照相比未变化的文件（大小和修改时间相同；启用内容检查时比较哈希值）
+/// 由服务端从上一个快照复制，只有新增和修改的文件需要上传。
+pub struct WebDavWriter {
+    /// WebDAV 客户端
+    dav: WebDav,
+
+    /// 备份源路径
+    source: PathBuf,
+
+    /// 是否为试运行模式（不上传任何数据）
+    dry_run: bool,
+
+    /// 是否比较文件内容的哈希值
+    check_content: bool,
+
+    /// 本次快照的临时目录名称
+    partial: String,
+
+    /// 上一个快照的名称
+    previous_name: Option<String>,
+
+    /// 上一个快照的清单条目（按路径索引）
+    previous: HashMap<String, IndexEntry>,
+
+    /// 已创建的目录（相对于临时目录）
+    created_dirs: Mutex<HashSet<String>>,
+
+    /// 本次快照的条目
+    entries: Mutex<Vec<IndexEntry>>,
+}
+
+impl WebDavWriter {
+    /// 打开 WebDAV 写入后端，创建快照的临时目录并读取上一个快照的清单
+    ///
+    /// # 参数
+    /// * `config` - 备份配置
+    /// * `snapshot` - 本次快照名称
+    ///
+    /// # 返回
+    /// * `Ok(WebDavWriter)` - 写入后端
+    /// * `Err(anyhow::Error)` - 连接或创建目录失败
+    pub fn open(config: &BackupConfig, snapshot: &str) -> Result<Self> {
+        let dav = WebDav::open(&config.destination, config.username.as_deref())?;
+        let partial = format!("{}.partial", snapshot);
+
+        let previous_name = dav.snapshots(&config.naming)?.pop().map(|(_, name)| name);
+        let previous = match previous_name {
+            Some(ref name) => dav
+                .load_index(name)?
+                .map(|index| index.entries.into_iter().map(|e| (e.path.clone(), e)).collect())
+                .unwrap_or_default(),
+            None => HashMap::new(),
+        };
+
+        if !config.dry_run {
+            dav.mkcol("")?;
+            dav.mkcol(&partial)?;
+        }
+
+        Ok(Self {
+            dav,
+            source: config.source.clone(),
+            dry_run: config.dry_run,
+            check_content: config.check_content,
+            partial,
+            previous_name,
+            previous,
+            created_dirs: Mutex::new(HashSet::new()),
+            entries: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 确保临时目录下的目录（及其所有上级目录）已创建
+    ///
+    /// 执行器并行处理任务，子项可能先于其所在目录被处理，因此每次写入前都需要检查。
+    fn ensure_dir(&self, rel: &str) -> Result<()> {
+        let mut created = self.created_dirs.lock().unwrap();
+        let mut path = String::new();
+        for part in rel.split('/').filter(|p| !p.is_empty()) {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(part);
+            if !created.contains(&path) {
+                self.dav.mkcol(&format!("{}/{}", self.partial, path))?;
+                created.insert(path.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查文件能否沿用上一个快照中的版本
+    fn unchanged(&self, entry: &IndexEntry) -> Option<&IndexEntry> {
+        let old = self.previous.get(&entry.path)?;
+        let same = if self.check_content {
+            old.kind == EntryKind::File && old.size == entry.size && old.hash.is_some() && old.hash == entry.hash
+        } else {
+            old.same_metadata(entry)
+        };
+        same.then_some(old)
+    }
+}
+
+impl SnapshotWriter for WebDavWriter {
+    fn previous(&self) -> Option<&str> {
+        self.previous_name.as_deref()
+    }
+
+    fn commit(&self, name: &str) -> Result<()> {
+        let mut entries = std::mem::take(&mut *self.entries.lock().unwrap());
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let index = SnapshotIndex {
+            version: INDEX_VERSION,
+            created_at: Local::now().to_rfc3339(),
+            source: self.source.clone(),
+            entries,
+        };
+        self.dav
+            .put_bytes(&format!("{}/{}", self.partial, INDEX_FILE), &serde_json::to_vec(&index)?)?;
+        self.dav.rename(&self.partial, name)
+    }
+}
+
+impl StorageBackend for WebDavWriter {
+    fn apply(&self, task: &FileTask, action: &SyncAction) -> Result<TaskOutcome> {
+        if matches!(action, SyncAction::Skip | SyncAction::Resumed) {
+            return Ok(TaskOutcome::Done(0));
+        }
+
+        let meta = fs::symlink_metadata(&task.src_path)
+            .with_context(|| format!("Failed to read metadata of {:?}", task.src_path))?;
+        let mut entry = IndexEntry::from_metadata(&task.rel_path, &meta);
+        if entry.path.is_empty() {
+            return Ok(TaskOutcome::Done(0)); // 源目录本身对应快照目录
+        }
+
+        let outcome = match action {
+            SyncAction::CreateDir => {
+                entry.kind = EntryKind::Dir;
+                if !self.dry_run {
+                    self.ensure_dir(&entry.path)?;
+                }
+                TaskOutcome::Done(0)
+            }
+            SyncAction::MakeSymlink(target) => {
+                // WebDAV 没有符号链接，只记录在清单中，恢复时重建
+                entry.kind = EntryKind::Symlink;
+                entry.target = Some(target.clone());
+                TaskOutcome::Done(0)
+            }
+            _ => {
+                entry.size = meta.len();
+                if self.check_content {
+                    entry.hash = Some(format!("{:016x}", calculate_hash(&task.src_path)?));
+                }
+                let dest = format!("{}/{}", self.partial, entry.path);
+                if !self.dry_run {
+                    if let Some((parent, _)) = entry.path.rsplit_once('/') {
+                        self.ensure_dir(parent)?;
+                    }
+                }
+
+                let reused = match (self.unchanged(&entry), &self.previous_name) {
+                    (Some(old), Some(previous)) => {
+                        entry.hash = old.hash.clone();
+                        self.dry_run || self.dav.copy(&format!("{}/{}", previous, entry.path), &dest)?
+                    }
+                    _ => false,
+                };
+
+                if reused {
+                    TaskOutcome::Reused
+                } else if self.dry_run {
+                    TaskOutcome::Done(entry.size)
+                } else {
+                    let (bytes, hash) = self.dav.put_file(&dest, &task.src_path)?;
+                    entry.hash = Some(hash);
+                    TaskOutcome::Done(bytes)
+                }
+            }
+        };
+
+        self.entries.lock().unwrap().push(entry);
+        Ok(outcome)
+    }
+}
+
+/// 读取时同时计算 XXH3 哈希值，读到末尾时写入结果
+struct HashingReader<'a> {
+    /// 数据来源
+    inner: File,
+
+    /// 哈希计算器
+    hasher: Xxh3,
+
+    /// 读完后的哈希值
+    result: &'a Cell<Option<u64>>,
+}
+
+impl Read for HashingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 {
+            self.result.set(Some(self.hasher.digest()));
+        } else {
+            self.hasher.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// 获取 URL 的路径部分（`https://host/a/b` → `/a/b`；已经是路径时原样返回）
+fn url_path(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |i| &rest[i..]),
+        None => url,
+    }
+}
+
+/// 将请求错误转换为包含方法、地址和状态码的错误
+fn request_error(method: &str, url: &str, err: ureq::Error) -> anyhow::Error {
+    match err {
+        ureq::Error::Status(code, resp) => {
+            let status = resp.status_text().to_string();
+            anyhow::anyhow!("WebDAV {} {} failed with HTTP {} {}", method, url, code, status)
+        }
+        ureq::Error::Transport(t) => anyhow::anyhow!("WebDAV {} {} failed: {}", method, url, t),
+    }
+}
+
@@ -0,0 +1,178 @@
+// Recall - Webhook 通知模块
+// 每次备份结束后把结果以 JSON 发送到配置的 URL，供 Slack、Discord、Gotify、ntfy 等监控和消息服务接收
+
+use crate::actions::BackupStats;
+use crate::http::{agent, with_retry};
+use crate::utils::{format_bytes, format_duration};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// 发送 Webhook 的时机
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookOn {
+    /// 每次运行后都发送（默认）
+    #[default]
+    Always,
+
+    /// 只在运行失败或部分失败时发送
+    Failure,
+}
+
+impl WebhookOn {
+    /// 是否为默认的 `always`（保存配置时省略）
+    pub fn is_always(&self) -> bool {
+        *self == WebhookOn::Always
+    }
+
+    /// 指定状态的运行结果是否需要发送
+    ///
+    /// # 示例
+    /// ```
+    /// use recall::webhook::WebhookOn;
+    /// assert!(WebhookOn::Always.should_send("ok"));
+    /// assert!(!WebhookOn::Failure.should_send("ok"));
+    /// assert!(WebhookOn::Failure.should_send("partial"));
+    /// ```
+    pub fn should_send(&self, status: &str) -> bool {
+        *self == WebhookOn::Always || status != "ok"
+    }
+}
+
+/// Webhook 的 JSON 内容
+///
+/// 除结构化字段外，同时提供 Slack（`text`）、Discord（`content`）和 Gotify（`title`、`message`）
+/// 直接显示的文本字段，无需中间转换即可发送到这些服务。
+#[derive(Serialize, Debug)]
+pub struct WebhookPayload<'a> {
+    /// 事件类型（固定为 `backup`）
+    pub event: &'static str,
+
+    /// 配置文件名称（命令行运行时为项目名称）
+    pub profile: &'a str,
+
+    /// 运行结果：`ok`、`partial` 或 `failed`
+    pub status: &'a str,
+
+    /// 创建的快照名称（失败时为空）
+    pub snapshot: Option<&'a str>,
+
+    /// 开始时间（`%Y-%m-%d %H:%M:%S`）
+    pub started_at: String,
+
+    /// 耗时（秒）
+    pub duration_secs: u64,
+
+    /// 备份统计（失败时为空）
+    pub stats: Option<&'a BackupStats>,
+
+    /// 失败原因
+    pub error: Option<&'a str>,
+
+    /// 一行文本摘要（Slack）
+    pub text: String,
+
+    /// 一行文本摘要（Discord）
+    pub content: String,
+
+    /// 标题（Gotify）
+    pub title: String,
+
+    /// 一行文本摘要（Gotify）
+    pub message: String,
+}
+
+impl<'a> WebhookPayload<'a> {
+    /// 根据一次运行的结果生成 Webhook 内容
+    ///
+    /// # 参数
+    /// * `profile` - 配置文件名称
+    /// * `status` - 运行结果
+    /// * `snapshot` - 快照名称
+    /// * `started_at` - 开始时间
+    /// * `duration_secs` - 耗时（秒）
+    /// * `stats` - 备份统计
+    /// * `error` - 失败原因
+    pub fn new(
+        profile: &'a str,
+        status: &'a str,
+        snapshot: Option<&'a str>,
+        started_at: String,
+        duration_secs: u64,
+        stats: Option<&'a BackupStats>,
+        error: Option<&'a str>,
+    ) -> Self {
+        let title = match status {
+            "ok" => format!("recall: backup of '{}' succeeded", profile),
+            "partial" => format!("recall: backup of '{}' completed with failures", profile),
+            _ => format!("recall: backup of '{}' failed", profile),
+        };
+        let mut details = Vec::new();
+        if let Some(snapshot) = snapshot {
+            details.push(format!("snapshot {}", snapshot));
+        }
+        if let Some(stats) = stats {
+            details.push(format!("{} files, {} transferred", stats.total_files, format_bytes(stats.bytes_copied)));
+            if stats.failed > 0 {
+                details.push(format!("{} failed", stats.failed));
+            }
+        }
+        details.push(format!("took {}", format_duration(duration_secs)));
+        if let Some(error) = error {
+            details.push(error.to_string());
+        }
+        let message = details.join(", ");
+        let text = format!("{}: {}", title, message);
+
+        Self {
+            event: "backup",
+            profile,
+            status,
+            snapshot,
+            started_at,
+            duration_secs,
+            stats,
+            error,
+            content: text.clone(),
+            text,
+            title,
+            message,
+        }
+    }
+}
+
+/// 将 Webhook 内容以 JSON 发送（POST）到指定 URL
+///
+/// 网络错误和服务端错误（5xx）时按 [`with_retry`] 的规则重试。
+///
+/// # 返回
+/// * `Ok(())` - 服务端返回 2xx
+/// * `Err(anyhow::Error)` - 请求失败或服务端返回错误状态
+pub fn send_webhook(url: &str, payload: &WebhookPayload) -> Result<()> {
+    post(url, "application/json", &serde_json::to_string(payload)?)
+        .map_err(|reason| anyhow::anyhow!("Webhook to {} failed: {}", host(url), reason))
+}
+
+/// 发送 POST 请求，网络错误和服务端错误（5xx）时重试
+///
+/// # 返回
+/// * `Ok(())` - 服务端返回 2xx
+/// * `Err(String)` - 失败原因（HTTP 状态码或错误类型）
+pub(crate) fn post(url: &str, content_type: &str, body: &str) -> Result<(), String> {
+    let agent = agent();
+    with_retry(|| agent.post(url).set("Content-Type", content_type).send_string(body).map_err(Box::new))
+        .map(|_| ())
+        .map_err(|e| {
+            // ureq 的错误信息包含完整 URL，只保留状态码或错误类型
+            match *e {
+                ureq::Error::Status(code, _) => format!("HTTP {}", code),
+                ureq::Error::Transport(ref transport) => transport.kind().to_string(),
+            }
+        })
+}
+
+/// URL 中的主机部分（输出错误时不显示路径中可能包含的令牌）
+pub(crate) fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?']).next().unwrap_or(rest)
+}